@@ -6,6 +6,16 @@ use serde::Serialize;
 use super::types::*;
 use super::auth;
 
+/// Returned when a document changed on the server since the expected revision
+#[derive(Debug, thiserror::Error)]
+#[error("Document {} was modified since revision {expected} (now at revision {})", current.id, current.revision)]
+pub struct RevisionConflict {
+    /// Revision the caller based its changes on
+    pub expected: u32,
+    /// Current version of the document on the server
+    pub current: Box<Document>,
+}
+
 /// API client for Outline
 pub struct OutlineClient {
     client: reqwest::Client,
@@ -39,6 +49,11 @@ impl OutlineClient {
         Self::new(base_url)
     }
 
+    /// The API base URL the client talks to
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
     /// Get the authorization token (from explicit token, OAuth2, or API token)
     async fn get_auth_token(&self) -> Result<Option<String>> {
         if let Some(token) = &self.api_token {
@@ -127,6 +142,29 @@ impl OutlineClient {
         response.data.ok_or_else(|| anyhow!("Failed to update document"))
     }
 
+    /// Update a document only if it is still at the expected revision
+    ///
+    /// The document is re-fetched first; if someone else changed it in the
+    /// meantime a [`RevisionConflict`] carrying the current version is returned
+    /// instead of overwriting their changes. The API has no conditional update,
+    /// so a change landing between the check and the update can still slip through.
+    pub async fn update_document_if_revision(
+        &self,
+        request: UpdateDocumentRequest,
+        expected_revision: u32,
+    ) -> Result<Document> {
+        let current = self.get_document(request.id.clone()).await?;
+        if current.revision != expected_revision {
+            return Err(RevisionConflict {
+                expected: expected_revision,
+                current: Box::new(current),
+            }
+            .into());
+        }
+
+        self.update_document(request).await
+    }
+
     /// Delete a document
     pub async fn delete_document(&self, id: String, permanent: bool) -> Result<()> {
         let request = DeleteDocumentRequest::new(id).permanent(permanent);
@@ -193,6 +231,12 @@ impl OutlineClient {
         response.data.ok_or_else(|| anyhow!("Failed to move document"))
     }
 
+    /// Duplicate a document, returning the new documents (the copy first)
+    pub async fn duplicate_document(&self, request: DuplicateDocumentRequest) -> Result<Vec<Document>> {
+        let response: ApiResponse<DuplicateDocumentData> = self.post("documents.duplicate", &request).await?;
+        response.data.map(|d| d.documents).ok_or_else(|| anyhow!("Failed to duplicate document"))
+    }
+
     /// Restore a document from trash or to a previous revision
     pub async fn restore_document(&self, request: RestoreDocumentRequest) -> Result<Document> {
         let response: ApiResponse<Document> = self.post("documents.restore", &request).await?;
@@ -360,8 +404,15 @@ impl OutlineClient {
         response.data.ok_or_else(|| anyhow!("Failed to move collection"))
     }
 
-    /// List documents in a collection
-    pub async fn list_collection_documents(&self, request: CollectionDocumentsRequest) -> Result<ListDocumentsResponse> {
+    /// Archive a collection and all of its documents
+    pub async fn archive_collection(&self, id: String) -> Result<Collection> {
+        let request = ArchiveCollectionRequest::new(id);
+        let response: ApiResponse<Collection> = self.post("collections.archive", &request).await?;
+        response.data.ok_or_else(|| anyhow!("Failed to archive collection"))
+    }
+
+    /// Get a collection's published documents as a navigation tree
+    pub async fn list_collection_documents(&self, request: CollectionDocumentsRequest) -> Result<CollectionDocumentsResponse> {
         self.post("collections.documents", &request).await
     }
 
@@ -673,6 +724,15 @@ impl OutlineClient {
         self.post("groups.memberships", &request).await
     }
 
+    // ========================================================================
+    // Star Operations
+    // ========================================================================
+
+    /// List the current user's starred documents and collections
+    pub async fn list_stars(&self, request: ListStarsRequest) -> Result<ListStarsResponse> {
+        self.post("stars.list", &request).await
+    }
+
     // ========================================================================
     // Share Operations
     // ========================================================================
@@ -774,6 +834,85 @@ impl OutlineClient {
             .map(|r| r.url)
     }
 
+    /// Download an attachment's contents, refusing files larger than `max_bytes`
+    ///
+    /// `attachments.redirect` either answers with a redirect to the file, which
+    /// is followed, or with JSON naming the file's URL, which is then fetched.
+    pub async fn download_attachment(&self, id: String, max_bytes: u64) -> Result<AttachmentContent> {
+        let url = format!("{}/{}", self.base_url, "attachments.redirect");
+
+        let mut headers = self.build_auth_headers().await?;
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        let mut response = self
+            .client
+            .post(&url)
+            .headers(headers)
+            .json(&RedirectAttachmentRequest::new(id))
+            .send()
+            .await
+            .context("Failed to send attachment request")?;
+
+        let is_json = |response: &reqwest::Response| {
+            response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|ct| ct.starts_with("application/json"))
+        };
+
+        if response.status().is_success() && is_json(&response) {
+            let body = response.text().await.context("Failed to read response")?;
+            let redirect: ApiResponse<RedirectAttachmentResponse> =
+                serde_json::from_str(&body).context("Failed to parse response JSON")?;
+            let file_url = redirect.data.ok_or_else(|| anyhow!("Failed to get attachment URL"))?.url;
+
+            // Relative URLs point back at the API server, which needs the token
+            let request = match file_url.strip_prefix('/') {
+                Some(path) => {
+                    let origin = url::Url::parse(&self.base_url)
+                        .context("Invalid API base URL")?
+                        .join(&format!("/{}", path))
+                        .context("Invalid attachment URL")?;
+                    self.client.get(origin).headers(self.build_auth_headers().await?)
+                }
+                None => self.client.get(&file_url),
+            };
+            response = request.send().await.context("Failed to download attachment")?;
+        }
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.context("Failed to read error response")?;
+            return Err(anyhow!(
+                "Attachment download failed with status {}: {}",
+                status,
+                body
+            ));
+        }
+
+        if let Some(length) = response.content_length().filter(|&length| length > max_bytes) {
+            return Err(anyhow!("Attachment is {} bytes, more than the {} byte limit", length, max_bytes));
+        }
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|ct| ct.split(';').next().unwrap_or(ct).trim().to_string())
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+
+        let mut data = Vec::new();
+        while let Some(chunk) = response.chunk().await.context("Failed to read attachment data")? {
+            data.extend_from_slice(&chunk);
+            if data.len() as u64 > max_bytes {
+                return Err(anyhow!("Attachment is more than the {} byte limit", max_bytes));
+            }
+        }
+
+        Ok(AttachmentContent { content_type, data })
+    }
+
     /// List attachments
     pub async fn list_attachments(&self, request: ListAttachmentsRequest) -> Result<ListAttachmentsResponse> {
         self.post("attachments.list", &request).await
@@ -0,0 +1,502 @@
+//! Protocol conformance tests
+//!
+//! The first half checks our framing against golden Hocuspocus frames (see
+//! `fixtures/README.md`). The second half runs [`CollaborationClient`] end to
+//! end against [`TestServer`], an in-process WebSocket server that speaks the
+//! same framing and relays updates between connections like Hocuspocus does.
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::{sink::SinkExt, stream::StreamExt};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc};
+use tokio_tungstenite::{accept_async, tungstenite::Message as WsMessage};
+use yrs::updates::decoder::Decode;
+use yrs::updates::encoder::Encode;
+use yrs::{Doc, GetString, Options, ReadTxn, StateVector, Text, Transact};
+
+use super::protocol::{AuthMessage, Message, MessageType};
+use super::{CollaborationClient, CollaborationEvent, ConnectionStatus, DocumentStore, DocumentSync};
+
+const DOCUMENT_ID: &str = "7f3c2a1e-0b4d-4e55-9a61-2f0c8d9e4b10";
+const DOCUMENT_NAME: &str = "document.7f3c2a1e-0b4d-4e55-9a61-2f0c8d9e4b10";
+const VALID_TOKEN: &str = "valid-token";
+
+/// `Hello` inserted into `content` by client 1, as a v1 update
+const HELLO_UPDATE: &[u8] = &[
+    1, 1, 1, 0, 4, 1, 7, 99, 111, 110, 116, 101, 110, 116, 5, 72, 101, 108, 108, 111, 0,
+];
+/// ` world` appended to [`HELLO_UPDATE`] by client 1, as a v1 update
+const WORLD_UPDATE: &[u8] = &[1, 1, 1, 5, 132, 1, 4, 6, 32, 119, 111, 114, 108, 100, 0];
+
+fn decode_fixture(data: &[u8]) -> Message {
+    let (name, message) = Message::decode_with_document(data).unwrap();
+    assert_eq!(name, DOCUMENT_NAME);
+    message
+}
+
+#[test]
+fn test_fixture_sync_step1() {
+    let fixture = include_bytes!("fixtures/sync_step1.bin");
+
+    let empty = DocumentSync::new();
+    let message = empty.create_sync_step1();
+    assert_eq!(message.encode_with_document(DOCUMENT_NAME), fixture);
+
+    let decoded = decode_fixture(fixture);
+    assert_eq!(decoded.message_type, MessageType::SyncStep1);
+    assert_eq!(decoded.payload, empty.get_state_vector());
+}
+
+#[test]
+fn test_fixture_sync_step2() {
+    let fixture = include_bytes!("fixtures/sync_step2.bin");
+
+    let message = Message::sync_step2(HELLO_UPDATE.to_vec());
+    assert_eq!(message.encode_with_document(DOCUMENT_NAME), fixture);
+
+    let decoded = decode_fixture(fixture);
+    assert_eq!(decoded.message_type, MessageType::SyncStep2);
+
+    let sync = DocumentSync::new();
+    sync.apply_update(&decoded.payload).unwrap();
+    assert_eq!(sync.get_text().unwrap(), "Hello");
+}
+
+#[test]
+fn test_fixture_sync_reply() {
+    // Sync Reply carries the same body as Sync and decodes to the same message
+    let decoded = decode_fixture(include_bytes!("fixtures/sync_reply.bin"));
+    assert_eq!(decoded.message_type, MessageType::SyncStep2);
+    assert_eq!(decoded.payload, HELLO_UPDATE);
+}
+
+#[test]
+fn test_fixture_update() {
+    let fixture = include_bytes!("fixtures/update.bin");
+
+    let message = Message::update(WORLD_UPDATE.to_vec());
+    assert_eq!(message.encode_with_document(DOCUMENT_NAME), fixture);
+
+    let decoded = decode_fixture(fixture);
+    assert!(decoded.is_update());
+
+    let sync = DocumentSync::new();
+    sync.apply_update(HELLO_UPDATE).unwrap();
+    sync.apply_update(&decoded.payload).unwrap();
+    assert_eq!(sync.get_text().unwrap(), "Hello world");
+}
+
+#[test]
+fn test_fixture_awareness() {
+    let fixture = include_bytes!("fixtures/awareness.bin");
+
+    let decoded = decode_fixture(fixture);
+    assert!(decoded.is_awareness());
+
+    // Awareness update: [client count][client id][clock][state JSON]
+    let state = br##"{"user":{"name":"Ada","color":"#3b82f6"}}"##;
+    assert_eq!(&decoded.payload[..4], &[1, 1, 3, state.len() as u8]);
+    assert_eq!(&decoded.payload[4..], state);
+
+    let message = Message::awareness(decoded.payload.clone());
+    assert_eq!(message.encode_with_document(DOCUMENT_NAME), fixture);
+}
+
+#[test]
+fn test_fixture_auth() {
+    let token = include_bytes!("fixtures/auth_token.bin");
+    let message = Message::auth("eyJhbGciOiJIUzI1NiJ9.test".to_string());
+    assert_eq!(message.encode_with_document(DOCUMENT_NAME), token);
+
+    let authenticated = decode_fixture(include_bytes!("fixtures/auth_authenticated.bin"));
+    assert_eq!(
+        authenticated.auth_message().unwrap(),
+        AuthMessage::Authenticated("read-write".to_string())
+    );
+
+    let denied = decode_fixture(include_bytes!("fixtures/auth_permission_denied.bin"));
+    assert_eq!(
+        denied.auth_message().unwrap(),
+        AuthMessage::PermissionDenied("permission-denied".to_string())
+    );
+    assert_eq!(
+        Message::permission_denied("permission-denied").encode_with_document(DOCUMENT_NAME),
+        include_bytes!("fixtures/auth_permission_denied.bin")
+    );
+}
+
+#[test]
+fn test_fixture_query_awareness_and_sync_status() {
+    let query = include_bytes!("fixtures/query_awareness.bin");
+    assert_eq!(decode_fixture(query).message_type, MessageType::QueryAwareness);
+    assert_eq!(
+        Message::new(MessageType::QueryAwareness, Vec::new()).encode_with_document(DOCUMENT_NAME),
+        query
+    );
+
+    let status = include_bytes!("fixtures/sync_status.bin");
+    assert_eq!(decode_fixture(status).message_type, MessageType::SyncStatus);
+    assert!(decode_fixture(status).sync_status_synced().unwrap());
+    assert!(!Message::sync_status(false).sync_status_synced().unwrap());
+    assert_eq!(Message::sync_status(true).encode_with_document(DOCUMENT_NAME), status);
+}
+
+#[test]
+fn test_decode_rejects_truncated_frames() {
+    let fixture = include_bytes!("fixtures/sync_step2.bin");
+    assert!(Message::decode_with_document(&fixture[..fixture.len() - 1]).is_err());
+    assert!(Message::decode_with_document(&fixture[..10]).is_err());
+}
+
+/// Frames relayed between server connections: (sender connection, frame)
+type Relay = (usize, Vec<u8>);
+
+/// In-process stand-in for Hocuspocus serving a single document
+struct TestServer {
+    addr: SocketAddr,
+    doc: Arc<Doc>,
+    relay: broadcast::Sender<Relay>,
+    kick: broadcast::Sender<()>,
+}
+
+impl TestServer {
+    /// Start a server whose document initially contains `text`
+    async fn start(text: &str) -> Self {
+        let doc = Arc::new(Doc::with_options(Options {
+            client_id: 1,
+            ..Default::default()
+        }));
+        if !text.is_empty() {
+            let content = doc.get_or_insert_text("content");
+            let mut txn = doc.transact_mut();
+            content.insert(&mut txn, 0, text);
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (relay, _) = broadcast::channel(100);
+        let (kick, _) = broadcast::channel(1);
+
+        let next_id = Arc::new(AtomicUsize::new(1));
+        let server_doc = Arc::clone(&doc);
+        let server_relay = relay.clone();
+        let server_kick = kick.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let id = next_id.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(Self::serve(
+                    stream,
+                    id,
+                    Arc::clone(&server_doc),
+                    server_relay.clone(),
+                    server_kick.subscribe(),
+                ));
+            }
+        });
+
+        Self { addr, doc, relay, kick }
+    }
+
+    async fn serve(
+        stream: tokio::net::TcpStream,
+        id: usize,
+        doc: Arc<Doc>,
+        relay: broadcast::Sender<Relay>,
+        mut kick: broadcast::Receiver<()>,
+    ) {
+        let ws = accept_async(stream).await.unwrap();
+        let (mut write, mut read) = ws.split();
+        let mut relayed = relay.subscribe();
+
+        loop {
+            tokio::select! {
+                frame = read.next() => {
+                    let data = match frame {
+                        Some(Ok(WsMessage::Binary(data))) => data,
+                        Some(Ok(_)) => continue,
+                        _ => break,
+                    };
+
+                    let (name, message) = Message::decode_with_document(&data).unwrap();
+                    for reply in Self::respond(&doc, &relay, id, &data, message) {
+                        let denied = reply.message_type == MessageType::Auth
+                            && matches!(reply.auth_message(), Ok(AuthMessage::PermissionDenied(_)));
+                        write
+                            .send(WsMessage::Binary(reply.encode_with_document(&name)))
+                            .await
+                            .unwrap();
+                        if denied {
+                            let _ = write.close().await;
+                            return;
+                        }
+                    }
+                }
+                frame = relayed.recv() => {
+                    if let Ok((sender, data)) = frame {
+                        if sender != id && write.send(WsMessage::Binary(data)).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                _ = kick.recv() => {
+                    let _ = write.close().await;
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Handle one client message, returning the direct replies
+    fn respond(
+        doc: &Doc,
+        relay: &broadcast::Sender<Relay>,
+        id: usize,
+        frame: &[u8],
+        message: Message,
+    ) -> Vec<Message> {
+        match message.message_type {
+            MessageType::Auth => match message.auth_message().unwrap() {
+                AuthMessage::Token(token) if token == VALID_TOKEN => {
+                    vec![Message::authenticated("read-write")]
+                }
+                _ => vec![Message::permission_denied("permission-denied")],
+            },
+            MessageType::SyncStep1 => {
+                // Answer with what the client is missing, then ask for what we're missing
+                let txn = doc.transact();
+                let remote = StateVector::decode_v1(&message.payload).unwrap();
+                vec![
+                    Message::sync_step2(txn.encode_diff_v1(&remote)),
+                    Message::sync_step1(txn.state_vector().encode_v1()),
+                ]
+            }
+            MessageType::SyncStep2 | MessageType::Update => {
+                {
+                    let mut txn = doc.transact_mut();
+                    txn.apply_update(yrs::Update::decode_v1(&message.payload).unwrap())
+                        .unwrap();
+                }
+                let update = Message::update(message.payload);
+                let _ = relay.send((id, update.encode_with_document(DOCUMENT_NAME)));
+                vec![Message::sync_status(true)]
+            }
+            MessageType::Awareness => {
+                let _ = relay.send((id, frame.to_vec()));
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Current text of the server's document
+    fn text(&self) -> String {
+        let content = self.doc.get_or_insert_text("content");
+        let txn = self.doc.transact();
+        content.get_string(&txn)
+    }
+
+    /// Make an edit on the server and broadcast it to every client
+    fn append(&self, suffix: &str) {
+        let content = self.doc.get_or_insert_text("content");
+        let update = {
+            let mut txn = self.doc.transact_mut();
+            let len = content.len(&txn);
+            content.insert(&mut txn, len, suffix);
+            txn.encode_update_v1()
+        };
+        let frame = Message::update(update).encode_with_document(DOCUMENT_NAME);
+        let _ = self.relay.send((0, frame));
+    }
+
+    /// Close every open connection
+    fn kick_all(&self) {
+        let _ = self.kick.send(());
+    }
+
+    fn client(&self, token: &str) -> (CollaborationClient, mpsc::Receiver<CollaborationEvent>) {
+        let (tx, rx) = mpsc::channel(100);
+        let client = CollaborationClient::new(
+            format!("http://{}/api", self.addr),
+            token.to_string(),
+            DOCUMENT_ID.to_string(),
+            tx,
+            Arc::new(DocumentSync::new()),
+        )
+        .unwrap();
+        (client, rx)
+    }
+}
+
+/// Wait for an event matching the predicate, failing after a timeout
+async fn wait_for<F>(rx: &mut mpsc::Receiver<CollaborationEvent>, mut predicate: F)
+where
+    F: FnMut(&CollaborationEvent) -> bool,
+{
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(event) = rx.recv().await {
+            if predicate(&event) {
+                return;
+            }
+        }
+        panic!("Event channel closed");
+    })
+    .await
+    .expect("Timed out waiting for collaboration event");
+}
+
+async fn wait_for_synced(rx: &mut mpsc::Receiver<CollaborationEvent>) {
+    wait_for(rx, |e| {
+        matches!(e, CollaborationEvent::StatusChanged(ConnectionStatus::Synced))
+    })
+    .await;
+}
+
+async fn wait_for_text(rx: &mut mpsc::Receiver<CollaborationEvent>, expected: &str) {
+    wait_for(rx, |e| {
+        matches!(e, CollaborationEvent::DocumentUpdated(text) if text == expected)
+    })
+    .await;
+}
+
+/// Poll until the server document has the expected text
+async fn wait_for_server_text(server: &TestServer, expected: &str) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while server.text() != expected {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("Server text {:?} never became {:?}", server.text(), expected));
+}
+
+#[tokio::test]
+async fn test_connect_and_sync() {
+    let server = TestServer::start("Hello").await;
+    let (mut client, mut rx) = server.client(VALID_TOKEN);
+
+    client.connect().await.unwrap();
+    wait_for_synced(&mut rx).await;
+    assert_eq!(client.doc_sync().get_text().unwrap(), "Hello");
+    assert!(client.is_connected());
+
+    // Remote edits arrive as document updates
+    server.append(" world");
+    wait_for_text(&mut rx, "Hello world").await;
+
+    client.disconnect().await.unwrap();
+    assert!(!client.is_connected());
+}
+
+#[tokio::test]
+async fn test_permission_denied() {
+    let server = TestServer::start("Hello").await;
+    let (mut client, mut rx) = server.client("wrong-token");
+
+    client.connect().await.unwrap();
+    wait_for(&mut rx, |e| {
+        matches!(
+            e,
+            CollaborationEvent::StatusChanged(ConnectionStatus::Error(msg))
+                if msg.contains("permission-denied")
+        )
+    })
+    .await;
+    assert_eq!(client.doc_sync().get_text().unwrap(), "");
+}
+
+#[tokio::test]
+async fn test_concurrent_edits_converge() {
+    let server = TestServer::start("middle").await;
+    let (mut alice, mut alice_rx) = server.client(VALID_TOKEN);
+    let (mut bob, mut bob_rx) = server.client(VALID_TOKEN);
+
+    alice.connect().await.unwrap();
+    bob.connect().await.unwrap();
+    wait_for_synced(&mut alice_rx).await;
+    wait_for_synced(&mut bob_rx).await;
+
+    // Both edit the same base text before seeing each other's change
+    let alice_update = alice.doc_sync().apply_local_text("start middle").unwrap().unwrap();
+    let bob_update = bob.doc_sync().apply_local_text("middle end").unwrap().unwrap();
+    alice.send_update(alice_update).await.unwrap();
+    bob.send_update(bob_update).await.unwrap();
+
+    let expected = "start middle end";
+    wait_for_text(&mut alice_rx, expected).await;
+    wait_for_text(&mut bob_rx, expected).await;
+    wait_for_server_text(&server, expected).await;
+
+    alice.disconnect().await.unwrap();
+    bob.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_reconnect_delivers_offline_edits() {
+    let server = TestServer::start("Hello").await;
+    let (mut client, mut rx) = server.client(VALID_TOKEN);
+
+    client.connect().await.unwrap();
+    wait_for_synced(&mut rx).await;
+
+    // Server drops the connection; the client notices
+    server.kick_all();
+    wait_for(&mut rx, |e| {
+        matches!(e, CollaborationEvent::StatusChanged(ConnectionStatus::Disconnected))
+    })
+    .await;
+
+    // Edits on both sides while disconnected
+    client.doc_sync().apply_local_text("Oh, Hello").unwrap();
+    server.append(", world");
+
+    // Reconnecting exchanges both sides' missing updates
+    client.disconnect().await.unwrap();
+    client.connect().await.unwrap();
+    wait_for_synced(&mut rx).await;
+
+    let expected = "Oh, Hello, world";
+    wait_for_server_text(&server, expected).await;
+    assert_eq!(client.doc_sync().get_text().unwrap(), expected);
+}
+
+#[tokio::test]
+async fn test_pending_updates_clear_on_acknowledgement() {
+    let dir = std::env::temp_dir()
+        .join(format!("outline-conformance-ack-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let server = TestServer::start("Hello").await;
+    let doc_sync = Arc::new(DocumentSync::open(DocumentStore::new(&dir), DOCUMENT_ID).unwrap());
+    let (tx, mut rx) = mpsc::channel(100);
+    let mut client = CollaborationClient::new(
+        format!("http://{}/api", server.addr),
+        VALID_TOKEN.to_string(),
+        DOCUMENT_ID.to_string(),
+        tx,
+        Arc::clone(&doc_sync),
+    )
+    .unwrap();
+
+    client.connect().await.unwrap();
+    wait_for_synced(&mut rx).await;
+
+    let update = doc_sync.apply_local_text("Hello world").unwrap().unwrap();
+    client.send_update(update).await.unwrap();
+
+    // The edit stays pending until the server's Sync Status arrives
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !doc_sync.pending_updates().unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Pending update was never acknowledged");
+    assert_eq!(server.text(), "Hello world");
+
+    client.disconnect().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
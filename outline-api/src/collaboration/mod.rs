@@ -1,9 +1,9 @@
-/// Collaboration module for real-time document editing using CRDT (Yjs)
-///
-/// This module provides WebSocket-based collaboration functionality that integrates
-/// with Outline's Hocuspocus backend for real-time, conflict-free document editing.
-///
-/// This module is only available when the `collaboration` feature is enabled.
+//! Collaboration module for real-time document editing using CRDT (Yjs)
+//!
+//! This module provides WebSocket-based collaboration functionality that integrates
+//! with Outline's Hocuspocus backend for real-time, conflict-free document editing.
+//!
+//! This module is only available when the `collaboration` feature is enabled.
 
 #[cfg(feature = "collaboration")]
 mod websocket;
@@ -11,13 +11,19 @@ mod websocket;
 mod sync;
 #[cfg(feature = "collaboration")]
 mod protocol;
+#[cfg(feature = "collaboration")]
+mod store;
+#[cfg(all(test, feature = "collaboration"))]
+mod conformance;
 
 #[cfg(feature = "collaboration")]
 pub use websocket::{CollaborationClient, ConnectionStatus};
 #[cfg(feature = "collaboration")]
 pub use sync::{DocumentSync, SyncState};
 #[cfg(feature = "collaboration")]
-pub use protocol::{Message, MessageType};
+pub use protocol::{AuthMessage, Message, MessageType};
+#[cfg(feature = "collaboration")]
+pub use store::DocumentStore;
 
 #[cfg(feature = "collaboration")]
 use anyhow::Result;
@@ -41,6 +47,11 @@ pub enum CollaborationEvent {
 }
 
 /// Initialize a collaboration session for a document
+///
+/// The document state is loaded from (and saved to) the local
+/// [`DocumentStore`], so previously synced content and offline edits are
+/// available immediately. If the store can't be opened the session falls back
+/// to an in-memory document.
 #[cfg(feature = "collaboration")]
 pub async fn start_collaboration(
     api_base_url: String,
@@ -51,8 +62,17 @@ pub async fn start_collaboration(
 
     let (tx, rx) = mpsc::channel(100);
 
-    // Create a shared DocumentSync instance
-    let doc_sync = Arc::new(DocumentSync::new());
+    // Create a shared DocumentSync instance backed by the local store
+    let doc_sync = match DocumentStore::open_default(&api_base_url)
+        .and_then(|store| DocumentSync::open(store, &document_id))
+    {
+        Ok(sync) => sync,
+        Err(e) => {
+            tracing::warn!("Failed to load local document state: {}", e);
+            DocumentSync::new()
+        }
+    };
+    let doc_sync = Arc::new(doc_sync);
 
     let client = CollaborationClient::new(
         api_base_url,
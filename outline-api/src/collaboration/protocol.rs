@@ -1,16 +1,44 @@
-/// Hocuspocus/Yjs WebSocket protocol implementation
-///
-/// This module implements the binary protocol used by Hocuspocus and Yjs for
-/// collaborative editing over WebSockets.
-///
-/// Protocol message types:
-/// - 0: Sync Step 1 (send state vector)
-/// - 1: Sync Step 2 (send missing updates)
-/// - 2: Update (incremental changes)
-/// - 3+: Awareness and other extensions
+//! Hocuspocus/Yjs WebSocket protocol implementation
+//!
+//! This module implements the binary protocol used by Hocuspocus and Yjs for
+//! collaborative editing over WebSockets.
+//!
+//! Every Hocuspocus frame starts with the document name, followed by a
+//! y-protocols message:
+//!
+//! ```text
+//! [document name: varString][message type: varUint][body]
+//! ```
+//!
+//! Message types and bodies:
+//! - 0: Sync, `[sync type: varUint][data: varUint8Array]` where the sync type is
+//!   0 (Step 1, state vector), 1 (Step 2, missing updates) or 2 (Update)
+//! - 1: Awareness, `[awareness update: varUint8Array]`
+//! - 2: Auth, `[auth type: varUint][token, scope or reason: varString]`
+//! - 3: Query Awareness, no body
+//! - 4: Sync Reply, same body as Sync but must not be answered
+//! - 8: Sync Status, `[synced: varUint]`
 
 use anyhow::{anyhow, Result};
 
+/// Outer message type tags on the wire
+const WIRE_SYNC: u64 = 0;
+const WIRE_AWARENESS: u64 = 1;
+const WIRE_AUTH: u64 = 2;
+const WIRE_QUERY_AWARENESS: u64 = 3;
+const WIRE_SYNC_REPLY: u64 = 4;
+const WIRE_SYNC_STATUS: u64 = 8;
+
+/// Sync sub-message type tags on the wire
+const SYNC_STEP1: u64 = 0;
+const SYNC_STEP2: u64 = 1;
+const SYNC_UPDATE: u64 = 2;
+
+/// Auth sub-message type tags on the wire
+const AUTH_TOKEN: u64 = 0;
+const AUTH_PERMISSION_DENIED: u64 = 1;
+const AUTH_AUTHENTICATED: u64 = 2;
+
 /// Message types in the Yjs sync protocol
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(u8)]
@@ -27,6 +55,8 @@ pub enum MessageType {
     Auth = 4,
     /// Query Awareness: Request awareness state
     QueryAwareness = 5,
+    /// Sync Status: Server acknowledgement that an update was stored
+    SyncStatus = 6,
 }
 
 impl MessageType {
@@ -39,12 +69,28 @@ impl MessageType {
             3 => Ok(MessageType::Awareness),
             4 => Ok(MessageType::Auth),
             5 => Ok(MessageType::QueryAwareness),
+            6 => Ok(MessageType::SyncStatus),
             _ => Err(anyhow!("Unknown message type: {}", value)),
         }
     }
 }
 
+/// Decoded body of an Auth message
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthMessage {
+    /// Client to server: authentication token
+    Token(String),
+    /// Server to client: authentication failed, with a reason
+    PermissionDenied(String),
+    /// Server to client: authentication succeeded, with the granted scope
+    Authenticated(String),
+}
+
 /// A message in the Hocuspocus/Yjs protocol
+///
+/// For sync and awareness messages the payload is the Yjs data itself. For auth
+/// messages it is the encoded auth body (see [`Message::auth_message`]), and for
+/// sync status messages it is a single var uint.
 #[derive(Debug, Clone)]
 pub struct Message {
     /// Type of message
@@ -62,28 +108,83 @@ impl Message {
         }
     }
 
-    /// Encode message to binary format
-    /// Format: [message_type: u8][payload: Vec<u8>]
+    /// Encode message to the y-protocols binary format (without a document name)
     pub fn encode(&self) -> Vec<u8> {
-        let mut encoded = Vec::with_capacity(1 + self.payload.len());
-        encoded.push(self.message_type as u8);
-        encoded.extend_from_slice(&self.payload);
+        let mut encoded = Vec::with_capacity(4 + self.payload.len());
+
+        match self.message_type {
+            MessageType::SyncStep1 | MessageType::SyncStep2 | MessageType::Update => {
+                let sync_type = match self.message_type {
+                    MessageType::SyncStep1 => SYNC_STEP1,
+                    MessageType::SyncStep2 => SYNC_STEP2,
+                    _ => SYNC_UPDATE,
+                };
+                encoded.extend(encode_var_uint(WIRE_SYNC));
+                encoded.extend(encode_var_uint(sync_type));
+                encoded.extend(encode_var_uint8_array(&self.payload));
+            }
+            MessageType::Awareness => {
+                encoded.extend(encode_var_uint(WIRE_AWARENESS));
+                encoded.extend(encode_var_uint8_array(&self.payload));
+            }
+            MessageType::Auth => {
+                encoded.extend(encode_var_uint(WIRE_AUTH));
+                encoded.extend_from_slice(&self.payload);
+            }
+            MessageType::QueryAwareness => {
+                encoded.extend(encode_var_uint(WIRE_QUERY_AWARENESS));
+            }
+            MessageType::SyncStatus => {
+                encoded.extend(encode_var_uint(WIRE_SYNC_STATUS));
+                encoded.extend_from_slice(&self.payload);
+            }
+        }
+
         encoded
     }
 
-    /// Decode message from binary format
+    /// Decode message from the y-protocols binary format (without a document name)
     pub fn decode(data: &[u8]) -> Result<Self> {
         if data.is_empty() {
             return Err(anyhow!("Empty message data"));
         }
 
-        let message_type = MessageType::from_u8(data[0])?;
-        let payload = data[1..].to_vec();
+        let mut reader = Reader::new(data);
+        let message = match reader.read_var_uint()? {
+            WIRE_SYNC | WIRE_SYNC_REPLY => {
+                let message_type = match reader.read_var_uint()? {
+                    SYNC_STEP1 => MessageType::SyncStep1,
+                    SYNC_STEP2 => MessageType::SyncStep2,
+                    SYNC_UPDATE => MessageType::Update,
+                    other => return Err(anyhow!("Unknown sync message type: {}", other)),
+                };
+                Self::new(message_type, reader.read_var_uint8_array()?.to_vec())
+            }
+            WIRE_AWARENESS => {
+                Self::new(MessageType::Awareness, reader.read_var_uint8_array()?.to_vec())
+            }
+            WIRE_AUTH => Self::new(MessageType::Auth, reader.rest().to_vec()),
+            WIRE_QUERY_AWARENESS => Self::new(MessageType::QueryAwareness, Vec::new()),
+            WIRE_SYNC_STATUS => Self::new(MessageType::SyncStatus, reader.rest().to_vec()),
+            other => return Err(anyhow!("Unknown message type: {}", other)),
+        };
+
+        Ok(message)
+    }
 
-        Ok(Self {
-            message_type,
-            payload,
-        })
+    /// Encode message as a Hocuspocus frame addressed to a document
+    pub fn encode_with_document(&self, document_name: &str) -> Vec<u8> {
+        let mut encoded = encode_var_string(document_name);
+        encoded.extend(self.encode());
+        encoded
+    }
+
+    /// Decode a Hocuspocus frame into the document name and message
+    pub fn decode_with_document(data: &[u8]) -> Result<(String, Self)> {
+        let mut reader = Reader::new(data);
+        let document_name = reader.read_var_string()?;
+        let message = Self::decode(reader.rest())?;
+        Ok((document_name, message))
     }
 
     /// Create a Sync Step 1 message with a state vector
@@ -106,16 +207,59 @@ impl Message {
         Self::new(MessageType::Awareness, awareness_update)
     }
 
-    /// Create an Auth message
+    /// Create an Auth message carrying a token
     pub fn auth(token: String) -> Self {
-        // Auth payload is JSON: {"token": "..."}
-        let json = serde_json::json!({
-            "token": token
-        });
-        let payload = serde_json::to_vec(&json).unwrap_or_default();
+        Self::auth_with(AUTH_TOKEN, &token)
+    }
+
+    /// Create an Auth message reporting successful authentication
+    pub fn authenticated(scope: &str) -> Self {
+        Self::auth_with(AUTH_AUTHENTICATED, scope)
+    }
+
+    /// Create an Auth message reporting failed authentication
+    pub fn permission_denied(reason: &str) -> Self {
+        Self::auth_with(AUTH_PERMISSION_DENIED, reason)
+    }
+
+    fn auth_with(auth_type: u64, value: &str) -> Self {
+        let mut payload = encode_var_uint(auth_type);
+        payload.extend(encode_var_string(value));
         Self::new(MessageType::Auth, payload)
     }
 
+    /// Create a Sync Status message
+    pub fn sync_status(synced: bool) -> Self {
+        Self::new(MessageType::SyncStatus, encode_var_uint(synced as u64))
+    }
+
+    /// Decode the body of an Auth message
+    pub fn auth_message(&self) -> Result<AuthMessage> {
+        if self.message_type != MessageType::Auth {
+            return Err(anyhow!("Not an auth message: {:?}", self.message_type));
+        }
+
+        let mut reader = Reader::new(&self.payload);
+        let auth_type = reader.read_var_uint()?;
+        let value = reader.read_var_string()?;
+        match auth_type {
+            AUTH_TOKEN => Ok(AuthMessage::Token(value)),
+            AUTH_PERMISSION_DENIED => Ok(AuthMessage::PermissionDenied(value)),
+            AUTH_AUTHENTICATED => Ok(AuthMessage::Authenticated(value)),
+            other => Err(anyhow!("Unknown auth message type: {}", other)),
+        }
+    }
+
+    /// Decode the body of a Sync Status message
+    ///
+    /// Returns `true` if the server stored the update it acknowledges.
+    pub fn sync_status_synced(&self) -> Result<bool> {
+        if self.message_type != MessageType::SyncStatus {
+            return Err(anyhow!("Not a sync status message: {:?}", self.message_type));
+        }
+        Ok(Reader::new(&self.payload).read_var_uint()? != 0)
+    }
+
     /// Check if this is a sync message (Step 1 or Step 2)
     pub fn is_sync(&self) -> bool {
         matches!(
@@ -135,6 +279,55 @@ impl Message {
     }
 }
 
+/// Cursor over an encoded message
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn read_var_uint(&mut self) -> Result<u64> {
+        let (value, read) = decode_var_uint(&self.data[self.pos..])?;
+        self.pos += read;
+        Ok(value)
+    }
+
+    fn read_var_uint8_array(&mut self) -> Result<&'a [u8]> {
+        let len = self.read_var_uint()? as usize;
+        let end = self.pos.checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| anyhow!("Byte array length {} exceeds message size", len))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_var_string(&mut self) -> Result<String> {
+        let bytes = self.read_var_uint8_array()?;
+        String::from_utf8(bytes.to_vec()).map_err(|e| anyhow!("Invalid UTF-8 string: {}", e))
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        &self.data[self.pos..]
+    }
+}
+
+/// Encode a length-prefixed byte array (used in Yjs encoding)
+pub fn encode_var_uint8_array(data: &[u8]) -> Vec<u8> {
+    let mut result = encode_var_uint(data.len() as u64);
+    result.extend_from_slice(data);
+    result
+}
+
+/// Encode a length-prefixed UTF-8 string (used in Yjs encoding)
+pub fn encode_var_string(value: &str) -> Vec<u8> {
+    encode_var_uint8_array(value.as_bytes())
+}
+
 /// Encode a variable-length integer (used in Yjs encoding)
 pub fn encode_var_uint(mut num: u64) -> Vec<u8> {
     let mut result = Vec::new();
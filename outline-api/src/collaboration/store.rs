@@ -0,0 +1,381 @@
+//! On-disk persistence for collaborative documents
+//!
+//! The default store keeps one directory per Outline instance, so documents
+//! from different servers never share files. Each document is stored under
+//! its ID as three files:
+//! - `<id>.state`: the full Yjs document state encoded as a v1 update
+//! - `<id>.pending`: local updates not yet delivered to the server
+//! - `<id>.json`: the last known document metadata from the REST API
+//!
+//! Pending updates are stored as a sequence of length-prefixed (var uint)
+//! v1 updates so new edits can be appended without rewriting the file.
+
+use anyhow::{Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use super::protocol::{decode_var_uint, encode_var_uint};
+use crate::types::Document;
+
+/// Serializes changes to pending update files
+///
+/// Edits are appended from the UI while the socket tasks remove acknowledged
+/// ones, so a rewrite must never interleave with an append. Shared by every
+/// store in the process, since each may point at the same files.
+static PENDING_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_pending() -> MutexGuard<'static, ()> {
+    PENDING_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Persistent store for Yjs document state, keyed by document ID
+#[derive(Debug, Clone)]
+pub struct DocumentStore {
+    dir: PathBuf,
+}
+
+impl DocumentStore {
+    /// Create a store rooted at the given directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Open the store for an Outline instance in the user's cache directory
+    ///
+    /// e.g. `~/.cache/outline-cli/collaboration/app.getoutline.com` for
+    /// `https://app.getoutline.com/api`.
+    pub fn open_default(api_base_url: &str) -> Result<Self> {
+        let cache_dir = dirs::cache_dir()
+            .context("Unable to determine cache directory")?;
+        Ok(Self::new(
+            cache_dir
+                .join("outline-cli")
+                .join("collaboration")
+                .join(instance_dir_name(api_base_url)?),
+        ))
+    }
+
+    /// Get the root directory of the store
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of one of a document's files, rejecting IDs that aren't a plain file name
+    fn file_path(&self, document_id: &str, extension: &str) -> Result<PathBuf> {
+        let valid = !document_id.is_empty()
+            && document_id.len() <= 128
+            && document_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            anyhow::bail!("Invalid document ID for local storage: {:?}", document_id);
+        }
+        Ok(self.dir.join(format!("{}.{}", document_id, extension)))
+    }
+
+    fn state_path(&self, document_id: &str) -> Result<PathBuf> {
+        self.file_path(document_id, "state")
+    }
+
+    fn pending_path(&self, document_id: &str) -> Result<PathBuf> {
+        self.file_path(document_id, "pending")
+    }
+
+    fn document_path(&self, document_id: &str) -> Result<PathBuf> {
+        self.file_path(document_id, "json")
+    }
+
+    fn ensure_dir(&self) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .context(format!("Failed to create collaboration cache directory: {:?}", self.dir))
+    }
+
+    /// Write a file atomically by writing to a temporary file and renaming it
+    fn write_atomic(&self, path: &Path, data: &[u8]) -> Result<()> {
+        self.ensure_dir()?;
+        // Keep the original extension so the state and pending files never share a temporary
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        fs::write(&tmp_path, data)
+            .context(format!("Failed to write {:?}", tmp_path))?;
+        fs::rename(&tmp_path, path)
+            .context(format!("Failed to replace {:?}", path))
+    }
+
+    /// Load the encoded document state, if any has been saved
+    pub fn load_state(&self, document_id: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.state_path(document_id)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read(&path).context(format!("Failed to read {:?}", path))?;
+        Ok(Some(data))
+    }
+
+    /// Save the encoded document state, replacing any previous state
+    pub fn save_state(&self, document_id: &str, state: &[u8]) -> Result<()> {
+        self.write_atomic(&self.state_path(document_id)?, state)
+    }
+
+    /// Load all pending local updates in the order they were recorded
+    pub fn load_pending(&self, document_id: &str) -> Result<Vec<Vec<u8>>> {
+        let _guard = lock_pending();
+        self.read_pending(document_id)
+    }
+
+    fn read_pending(&self, document_id: &str) -> Result<Vec<Vec<u8>>> {
+        let path = self.pending_path(document_id)?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let data = fs::read(&path).context(format!("Failed to read {:?}", path))?;
+        let mut updates = Vec::new();
+        let mut pos = 0;
+
+        while pos < data.len() {
+            let (len, read) = decode_var_uint(&data[pos..])
+                .context("Corrupt pending update file")?;
+            pos += read;
+
+            let end = pos + len as usize;
+            if end > data.len() {
+                // A partially written trailing update is dropped; earlier ones are intact
+                tracing::warn!("Truncated pending update in {:?}", path);
+                break;
+            }
+            updates.push(data[pos..end].to_vec());
+            pos = end;
+        }
+
+        Ok(updates)
+    }
+
+    /// Append a local update to the pending queue
+    pub fn append_pending(&self, document_id: &str, update: &[u8]) -> Result<()> {
+        let _guard = lock_pending();
+        self.ensure_dir()?;
+        let path = self.pending_path(document_id)?;
+
+        let mut record = encode_var_uint(update.len() as u64);
+        record.extend_from_slice(update);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .context(format!("Failed to open {:?}", path))?;
+        file.write_all(&record)
+            .context(format!("Failed to write {:?}", path))
+    }
+
+    /// Remove the given updates from the pending queue, keeping any others
+    ///
+    /// Each delivered update removes one matching entry, so updates recorded while
+    /// these were being sent stay queued.
+    pub fn remove_pending(&self, document_id: &str, delivered: &[Vec<u8>]) -> Result<()> {
+        let _guard = lock_pending();
+        let mut pending = self.read_pending(document_id)?;
+        for update in delivered {
+            if let Some(index) = pending.iter().position(|p| p == update) {
+                pending.remove(index);
+            }
+        }
+        if pending.is_empty() {
+            return self.remove_pending_file(document_id);
+        }
+
+        let mut data = Vec::new();
+        for update in &pending {
+            data.extend(encode_var_uint(update.len() as u64));
+            data.extend_from_slice(update);
+        }
+        self.write_atomic(&self.pending_path(document_id)?, &data)
+    }
+
+    /// Remove all pending updates (after they have been delivered)
+    pub fn clear_pending(&self, document_id: &str) -> Result<()> {
+        let _guard = lock_pending();
+        self.remove_pending_file(document_id)
+    }
+
+    fn remove_pending_file(&self, document_id: &str) -> Result<()> {
+        let path = self.pending_path(document_id)?;
+        if path.exists() {
+            fs::remove_file(&path).context(format!("Failed to remove {:?}", path))?;
+        }
+        Ok(())
+    }
+
+    /// Load the cached document metadata
+    pub fn load_document(&self, document_id: &str) -> Result<Option<Document>> {
+        let path = self.document_path(document_id)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read(&path).context(format!("Failed to read {:?}", path))?;
+        let document = serde_json::from_slice(&data)
+            .context("Failed to parse cached document")?;
+        Ok(Some(document))
+    }
+
+    /// Cache document metadata so the document can be opened offline
+    pub fn save_document(&self, document: &Document) -> Result<()> {
+        let data = serde_json::to_vec(document)
+            .context("Failed to serialize document")?;
+        self.write_atomic(&self.document_path(&document.id)?, &data)
+    }
+
+    /// Remove everything stored for a document
+    pub fn remove(&self, document_id: &str) -> Result<()> {
+        let _guard = lock_pending();
+        for path in [
+            self.state_path(document_id)?,
+            self.pending_path(document_id)?,
+            self.document_path(document_id)?,
+        ] {
+            if path.exists() {
+                fs::remove_file(&path).context(format!("Failed to remove {:?}", path))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Directory name for an Outline instance, derived from its API URL
+///
+/// Uses the host, port and any path before `/api`, with anything other than
+/// letters, digits, `.` and `-` replaced by `_`.
+fn instance_dir_name(api_base_url: &str) -> Result<String> {
+    let url = url::Url::parse(api_base_url)
+        .context(format!("Invalid API URL: {}", api_base_url))?;
+    let host = url.host_str()
+        .context(format!("API URL has no host: {}", api_base_url))?;
+
+    let mut name = host.to_string();
+    if let Some(port) = url.port() {
+        name.push_str(&format!("_{}", port));
+    }
+    let path = url.path().trim_end_matches('/');
+    let path = path.strip_suffix("/api").unwrap_or(path).trim_matches('/');
+    if !path.is_empty() {
+        name.push('_');
+        name.push_str(path);
+    }
+
+    Ok(name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(name: &str) -> DocumentStore {
+        let dir = std::env::temp_dir()
+            .join(format!("outline-store-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        DocumentStore::new(dir)
+    }
+
+    #[test]
+    fn test_state_roundtrip() {
+        let store = temp_store("state");
+        assert!(store.load_state("doc").unwrap().is_none());
+
+        store.save_state("doc", &[1, 2, 3]).unwrap();
+        assert_eq!(store.load_state("doc").unwrap(), Some(vec![1, 2, 3]));
+
+        store.remove("doc").unwrap();
+        assert!(store.load_state("doc").unwrap().is_none());
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_pending_append_and_clear() {
+        let store = temp_store("pending");
+        assert!(store.load_pending("doc").unwrap().is_empty());
+
+        let large = vec![7u8; 300];
+        store.append_pending("doc", &[1, 2]).unwrap();
+        store.append_pending("doc", &large).unwrap();
+
+        let pending = store.load_pending("doc").unwrap();
+        assert_eq!(pending, vec![vec![1, 2], large]);
+
+        store.clear_pending("doc").unwrap();
+        assert!(store.load_pending("doc").unwrap().is_empty());
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_remove_pending_keeps_undelivered() {
+        let store = temp_store("remove-pending");
+        store.append_pending("doc", &[1]).unwrap();
+        store.append_pending("doc", &[2, 2]).unwrap();
+        store.append_pending("doc", &[3]).unwrap();
+
+        store.remove_pending("doc", &[vec![1], vec![3], vec![9]]).unwrap();
+        assert_eq!(store.load_pending("doc").unwrap(), vec![vec![2, 2]]);
+
+        store.remove_pending("doc", &[vec![2, 2]]).unwrap();
+        assert!(store.load_pending("doc").unwrap().is_empty());
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_rejects_ids_outside_the_store() {
+        let store = temp_store("ids");
+        for id in ["", "../escape", "a/b", "..", "doc.state", "C:\\doc"] {
+            assert!(store.save_state(id, &[1]).is_err(), "{:?}", id);
+            assert!(store.load_pending(id).is_err(), "{:?}", id);
+        }
+        store.save_state("7f3c2a1e-0b4d-4e55-9a61-2f0c8d9e4b10", &[1]).unwrap();
+        store.save_state("hDYep1TPAM", &[1]).unwrap();
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_instance_dir_name() {
+        assert_eq!(instance_dir_name("https://app.getoutline.com/api").unwrap(), "app.getoutline.com");
+        assert_eq!(instance_dir_name("http://localhost:3000/api/").unwrap(), "localhost_3000");
+        assert_eq!(instance_dir_name("https://example.com/wiki/api").unwrap(), "example.com_wiki");
+        assert_ne!(
+            instance_dir_name("https://a.example.com/api").unwrap(),
+            instance_dir_name("https://b.example.com/api").unwrap()
+        );
+        assert!(instance_dir_name("not a url").is_err());
+    }
+
+    #[test]
+    fn test_concurrent_append_and_remove_loses_nothing() {
+        let store = temp_store("concurrent");
+        let delivered: Vec<Vec<u8>> = (0..100u8).map(|i| vec![0, i]).collect();
+        for update in &delivered {
+            store.append_pending("doc", update).unwrap();
+        }
+
+        // Edits keep arriving while acknowledged updates are removed one by one
+        let appender = {
+            let store = store.clone();
+            std::thread::spawn(move || {
+                for i in 0..100u8 {
+                    store.append_pending("doc", &[1, i]).unwrap();
+                }
+            })
+        };
+        for update in &delivered {
+            store.remove_pending("doc", std::slice::from_ref(update)).unwrap();
+        }
+        appender.join().unwrap();
+
+        let expected: Vec<Vec<u8>> = (0..100u8).map(|i| vec![1, i]).collect();
+        assert_eq!(store.load_pending("doc").unwrap(), expected);
+        let _ = fs::remove_dir_all(store.dir());
+    }
+}
@@ -1,9 +1,16 @@
 use anyhow::Result;
-use yrs::{Doc, Text, Transact, ReadTxn, GetString, UpdateEvent, StateVector};
+use yrs::{Doc, Text, Transact, ReadTxn, GetString, UpdateEvent, StateVector, Origin, UndoManager};
 use yrs::updates::decoder::Decode;
 use yrs::updates::encoder::Encode;
 use std::sync::{Arc, Mutex};
 use super::protocol::Message;
+use super::store::DocumentStore;
+
+/// Transaction origin for edits made by the local user
+///
+/// Only transactions with this origin are tracked for undo/redo, so undoing
+/// never reverts changes merged in from other collaborators.
+const LOCAL_ORIGIN: &str = "outline-local";
 
 /// Synchronization state for the document
 #[derive(Debug, Clone, PartialEq)]
@@ -24,17 +31,66 @@ pub struct DocumentSync {
     doc: Arc<Doc>,
     /// Current sync state
     state: Arc<Mutex<SyncState>>,
+    /// Local persistence (store and document ID), if enabled
+    persistence: Option<(DocumentStore, String)>,
+    /// Undo/redo history of local edits
+    undo_manager: Mutex<UndoManager>,
 }
 
 impl DocumentSync {
     /// Create a new document sync handler
     pub fn new() -> Self {
         let doc = Doc::new();
+        let text = doc.get_or_insert_text("content");
+
+        // Each local edit is its own undo step rather than grouping by time
+        let mut options = yrs::undo::Options {
+            capture_timeout_millis: 0,
+            ..Default::default()
+        };
+        options.tracked_origins.insert(Origin::from(LOCAL_ORIGIN));
+        let undo_manager = UndoManager::with_scope_and_options(&doc, &text, options);
 
         Self {
             doc: Arc::new(doc),
             state: Arc::new(Mutex::new(SyncState::NotSynced)),
+            persistence: None,
+            undo_manager: Mutex::new(undo_manager),
+        }
+    }
+
+    /// Open a persisted document sync handler
+    ///
+    /// Loads the saved state and any pending local updates for the document,
+    /// so the content is available before connecting to the server.
+    pub fn open(store: DocumentStore, document_id: &str) -> Result<Self> {
+        let mut sync = Self::new();
+
+        if let Some(state) = store.load_state(document_id)? {
+            sync.apply_encoded(&state)?;
         }
+        for update in store.load_pending(document_id)? {
+            sync.apply_encoded(&update)?;
+        }
+
+        sync.persistence = Some((store, document_id.to_string()));
+        Ok(sync)
+    }
+
+    /// Get the document ID this handler persists to, if persistence is enabled
+    pub fn document_id(&self) -> Option<&str> {
+        self.persistence.as_ref().map(|(_, id)| id.as_str())
+    }
+
+    /// Get the store this handler persists to, if persistence is enabled
+    pub fn store(&self) -> Option<&DocumentStore> {
+        self.persistence.as_ref().map(|(store, _)| store)
+    }
+
+    /// Check whether the document has any content from a previous sync or local edit
+    pub fn has_state(&self) -> bool {
+        let txn = self.doc.transact();
+        !txn.state_vector().is_empty()
     }
 
     /// Get the Yjs document
@@ -54,15 +110,16 @@ impl DocumentSync {
 
     /// Get the document text content
     pub fn get_text(&self) -> Result<String> {
-        let txn = self.doc.transact();
+        // The root type must be resolved before opening a transaction
         let text = self.doc.get_or_insert_text("content");
+        let txn = self.doc.transact();
         Ok(text.get_string(&txn))
     }
 
     /// Set the document text content
     pub fn set_text(&self, content: &str) -> Result<()> {
-        let mut txn = self.doc.transact_mut();
         let text = self.doc.get_or_insert_text("content");
+        let mut txn = self.doc.transact_mut();
 
         // Clear existing content
         let len = text.len(&txn);
@@ -79,14 +136,143 @@ impl DocumentSync {
     /// Apply a binary update from the server
     pub fn apply_update(&self, update: &[u8]) -> Result<()> {
         self.set_state(SyncState::Syncing);
+        self.apply_encoded(update)?;
+        self.persist()?;
+        self.set_state(SyncState::Synced);
+        Ok(())
+    }
 
+    /// Decode and apply a v1 update without touching the sync state
+    fn apply_encoded(&self, update: &[u8]) -> Result<()> {
         let mut txn = self.doc.transact_mut();
         let decoded_update = yrs::Update::decode_v1(update)
             .map_err(|e| anyhow::anyhow!("Failed to decode update: {:?}", e))?;
         txn.apply_update(decoded_update)
             .map_err(|e| anyhow::anyhow!("Failed to apply update: {:?}", e))?;
+        Ok(())
+    }
 
-        self.set_state(SyncState::Synced);
+    /// Replace the document text with a local edit
+    ///
+    /// Only the changed region between the current and new text is rewritten, so
+    /// concurrent remote edits outside that region merge cleanly. The resulting
+    /// update is recorded as pending until [`DocumentSync::mark_delivered`] is
+    /// called. Returns `None` if the text is unchanged.
+    pub fn apply_local_text(&self, new_text: &str) -> Result<Option<Vec<u8>>> {
+        let update = {
+            let text = self.doc.get_or_insert_text("content");
+            let mut txn = self.doc.transact_mut_with(LOCAL_ORIGIN);
+            let old_text = text.get_string(&txn);
+
+            let (start, old_end, new_end) = changed_range(&old_text, new_text);
+            if start == old_end && start == new_end {
+                return Ok(None);
+            }
+
+            // Offsets are UTF-8 byte offsets, matching the document's default OffsetKind
+            if old_end > start {
+                text.remove_range(&mut txn, start as u32, (old_end - start) as u32);
+            }
+            if new_end > start {
+                text.insert(&mut txn, start as u32, &new_text[start..new_end]);
+            }
+
+            txn.encode_update_v1()
+        };
+
+        self.record_local_update(&update)?;
+        Ok(Some(update))
+    }
+
+    /// Undo the most recent local edit
+    ///
+    /// Edits merged in from other collaborators are never undone. Returns the
+    /// update to send to the server, or `None` if there was nothing to undo.
+    pub fn undo(&self) -> Result<Option<Vec<u8>>> {
+        self.apply_history(|manager| manager.try_undo())
+    }
+
+    /// Redo the most recently undone local edit
+    ///
+    /// Returns the update to send to the server, or `None` if there was nothing to redo.
+    pub fn redo(&self) -> Result<Option<Vec<u8>>> {
+        self.apply_history(|manager| manager.try_redo())
+    }
+
+    /// Check whether there are local edits to undo
+    pub fn can_undo(&self) -> bool {
+        self.undo_manager.lock().unwrap().can_undo()
+    }
+
+    /// Check whether there are undone edits to redo
+    pub fn can_redo(&self) -> bool {
+        self.undo_manager.lock().unwrap().can_redo()
+    }
+
+    /// Run an undo or redo step and encode the resulting change as an update
+    fn apply_history<F>(&self, step: F) -> Result<Option<Vec<u8>>>
+    where
+        F: FnOnce(&mut UndoManager) -> Result<bool, yrs::TransactionAcqError>,
+    {
+        let before = self.doc.transact().state_vector();
+
+        let changed = {
+            let mut manager = self.undo_manager.lock().unwrap();
+            step(&mut manager)
+                .map_err(|e| anyhow::anyhow!("Document is busy: {:?}", e))?
+        };
+        if !changed {
+            return Ok(None);
+        }
+
+        // The diff carries the new items plus the document's delete set,
+        // which covers anything the step removed
+        let update = self.doc.transact().encode_diff_v1(&before);
+        self.record_local_update(&update)?;
+        Ok(Some(update))
+    }
+
+    /// Record a local update as pending and save the document state
+    fn record_local_update(&self, update: &[u8]) -> Result<()> {
+        if let Some((store, id)) = &self.persistence {
+            store.append_pending(id, update)?;
+        }
+        self.persist()
+    }
+
+    /// Get local updates that have not yet been delivered to the server
+    pub fn pending_updates(&self) -> Result<Vec<Vec<u8>>> {
+        match &self.persistence {
+            Some((store, id)) => store.load_pending(id),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Mark these pending local updates as delivered, keeping any others
+    pub fn mark_delivered(&self, updates: &[Vec<u8>]) -> Result<()> {
+        match &self.persistence {
+            Some((store, id)) => store.remove_pending(id, updates),
+            None => Ok(()),
+        }
+    }
+
+    /// Mark all pending local updates as delivered
+    pub fn clear_pending(&self) -> Result<()> {
+        match &self.persistence {
+            Some((store, id)) => store.clear_pending(id),
+            None => Ok(()),
+        }
+    }
+
+    /// Save the full document state to the store, if persistence is enabled
+    pub fn persist(&self) -> Result<()> {
+        if let Some((store, id)) = &self.persistence {
+            let state = {
+                let txn = self.doc.transact();
+                txn.encode_state_as_update_v1(&StateVector::default())
+            };
+            store.save_state(id, &state)?;
+        }
         Ok(())
     }
 
@@ -128,12 +314,35 @@ impl DocumentSync {
     /// Subscribe to document changes
     pub fn subscribe<F>(&self, callback: F) -> yrs::Subscription
     where
-        F: Fn(&yrs::TransactionMut, &UpdateEvent) + 'static,
+        F: Fn(&yrs::TransactionMut, &UpdateEvent) + Send + Sync + 'static,
     {
         self.doc.observe_update_v1(callback).unwrap()
     }
 }
 
+/// Find the byte range that differs between two strings
+///
+/// Returns `(start, old_end, new_end)` where `old[start..old_end]` was replaced by
+/// `new[start..new_end]`. All offsets fall on character boundaries.
+fn changed_range(old: &str, new: &str) -> (usize, usize, usize) {
+    let start = old
+        .char_indices()
+        .zip(new.chars())
+        .find(|((_, a), b)| a != b)
+        .map(|((i, _), _)| i)
+        .unwrap_or_else(|| old.len().min(new.len()));
+
+    let suffix = old[start..]
+        .chars()
+        .rev()
+        .zip(new[start..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum::<usize>();
+
+    (start, old.len() - suffix, new.len() - suffix)
+}
+
 impl Default for DocumentSync {
     fn default() -> Self {
         Self::new()
@@ -165,4 +374,111 @@ mod tests {
         sync.set_state(SyncState::Synced);
         assert_eq!(sync.state(), SyncState::Synced);
     }
+
+    #[test]
+    fn test_undo_only_reverts_local_edits() {
+        let local = DocumentSync::new();
+        let remote = DocumentSync::new();
+
+        let update = local.apply_local_text("hello").unwrap().unwrap();
+        remote.apply_update(&update).unwrap();
+
+        // A collaborator appends while we make a second edit
+        let theirs = remote.apply_local_text("hello world").unwrap().unwrap();
+        let ours = local.apply_local_text("Hello").unwrap().unwrap();
+        local.apply_update(&theirs).unwrap();
+        remote.apply_update(&ours).unwrap();
+        assert_eq!(local.get_text().unwrap(), "Hello world");
+
+        // Undo walks back our edits only
+        let undo = local.undo().unwrap().unwrap();
+        assert_eq!(local.get_text().unwrap(), "hello world");
+        local.undo().unwrap();
+        assert_eq!(local.get_text().unwrap(), " world");
+        assert!(!local.can_undo());
+        assert!(local.undo().unwrap().is_none());
+
+        // Undo updates reach other clients like any other edit
+        remote.apply_update(&undo).unwrap();
+        assert_eq!(remote.get_text().unwrap(), "hello world");
+
+        local.redo().unwrap();
+        assert_eq!(local.get_text().unwrap(), "hello world");
+        local.redo().unwrap();
+        assert_eq!(local.get_text().unwrap(), "Hello world");
+        assert!(!local.can_redo());
+    }
+
+    #[test]
+    fn test_changed_range() {
+        assert_eq!(changed_range("hello world", "hello world"), (11, 11, 11));
+        assert_eq!(changed_range("hello world", "hello there world"), (6, 6, 12));
+        assert_eq!(changed_range("héllo", "hallo"), (1, 3, 2));
+        assert_eq!(changed_range("abc", ""), (0, 3, 0));
+    }
+
+    #[test]
+    fn test_offline_edits_merge_with_remote() {
+        let dir = std::env::temp_dir()
+            .join(format!("outline-sync-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // Server and client start from the same synced state
+        let server = DocumentSync::new();
+        server.set_text("line one\nline two\n").unwrap();
+        let initial = server.create_sync_step2(&[0]).unwrap().payload;
+
+        let client = DocumentSync::open(DocumentStore::new(&dir), "doc").unwrap();
+        client.apply_update(&initial).unwrap();
+
+        // Client edits offline, then restarts
+        client.apply_local_text("line one\nline two\nline three\n").unwrap();
+        drop(client);
+        let client = DocumentSync::open(DocumentStore::new(&dir), "doc").unwrap();
+        assert_eq!(client.get_text().unwrap(), "line one\nline two\nline three\n");
+        assert_eq!(client.pending_updates().unwrap().len(), 1);
+
+        // Meanwhile someone else edited the first line
+        let server_doc = server.doc();
+        let remote = {
+            let text = server_doc.get_or_insert_text("content");
+            let mut txn = server_doc.transact_mut();
+            text.insert(&mut txn, 0, "first ");
+            txn.encode_update_v1()
+        };
+
+        // Reconnect: exchange diffs in both directions
+        client.apply_update(&remote).unwrap();
+        let client_diff = client
+            .create_sync_step2(&server.get_state_vector())
+            .unwrap()
+            .payload;
+        server.apply_update(&client_diff).unwrap();
+        client.clear_pending().unwrap();
+
+        let expected = "first line one\nline two\nline three\n";
+        assert_eq!(client.get_text().unwrap(), expected);
+        assert_eq!(server.get_text().unwrap(), expected);
+        assert!(client.pending_updates().unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_mark_delivered_keeps_later_edits() {
+        let dir = std::env::temp_dir()
+            .join(format!("outline-sync-delivered-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let client = DocumentSync::open(DocumentStore::new(&dir), "doc").unwrap();
+        let sent = client.apply_local_text("one\n").unwrap().unwrap();
+        // A second edit recorded while the first is still on its way
+        let queued = client.apply_local_text("one\ntwo\n").unwrap().unwrap();
+
+        client.mark_delivered(std::slice::from_ref(&sent)).unwrap();
+        assert_eq!(client.pending_updates().unwrap(), vec![queued.clone()]);
+
+        client.mark_delivered(&[queued]).unwrap();
+        assert!(client.pending_updates().unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
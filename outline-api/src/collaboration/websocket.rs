@@ -1,11 +1,15 @@
 use anyhow::{Context, Result};
 use futures_util::{sink::SinkExt, stream::StreamExt};
 use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::{Message as WsMessage, http::Request}};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{client::IntoClientRequest, http::HeaderValue, Message as WsMessage},
+};
 use url::Url;
-use std::sync::Arc;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 
-use super::{CollaborationEvent, DocumentSync, protocol::{Message, MessageType}};
+use super::{CollaborationEvent, DocumentSync, protocol::{AuthMessage, Message, MessageType}};
 
 /// Connection status for the WebSocket client
 #[derive(Debug, Clone, PartialEq)]
@@ -17,6 +21,25 @@ pub enum ConnectionStatus {
     Error(String),
 }
 
+/// A message queued for the socket writer
+struct Outgoing {
+    message: Message,
+    /// Pending local updates this message carries, cleared once the server acknowledges it
+    delivers: Vec<Vec<u8>>,
+}
+
+/// Pending updates carried by each sent message, oldest first, awaiting a Sync Status
+///
+/// Hocuspocus answers every Sync Step 2 and Update it receives with one Sync
+/// Status, in order, so each acknowledgement matches the oldest entry.
+type AwaitingAck = Arc<Mutex<VecDeque<Vec<Vec<u8>>>>>;
+
+impl From<Message> for Outgoing {
+    fn from(message: Message) -> Self {
+        Self { message, delivers: Vec::new() }
+    }
+}
+
 /// WebSocket client for Outline collaboration
 pub struct CollaborationClient {
     api_base_url: String,
@@ -24,7 +47,8 @@ pub struct CollaborationClient {
     document_id: String,
     event_tx: mpsc::Sender<CollaborationEvent>,
     doc_sync: Arc<DocumentSync>,
-    message_tx: Option<mpsc::Sender<Message>>,
+    message_tx: Option<mpsc::Sender<Outgoing>>,
+    writer: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl CollaborationClient {
@@ -43,6 +67,7 @@ impl CollaborationClient {
             event_tx,
             doc_sync,
             message_tx: None,
+            writer: None,
         })
     }
 
@@ -59,6 +84,11 @@ impl CollaborationClient {
         Url::parse(&ws_url).context("Failed to parse WebSocket URL")
     }
 
+    /// Name Hocuspocus uses to address this document in every frame
+    fn document_name(&self) -> String {
+        format!("document.{}", self.document_id)
+    }
+
     /// Connect to the WebSocket server and start the message loop
     pub async fn connect(&mut self) -> Result<()> {
         let ws_url = self.build_ws_url()?;
@@ -70,11 +100,16 @@ impl CollaborationClient {
             .ok();
 
         // Build WebSocket request with authentication header
-        let request = Request::builder()
-            .uri(ws_url.as_str())
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .body(())
+        // (starting from the URL so the handshake headers are filled in)
+        let mut request = ws_url
+            .as_str()
+            .into_client_request()
             .context("Failed to build WebSocket request")?;
+        request.headers_mut().insert(
+            "Authorization",
+            HeaderValue::from_str(&format!("Bearer {}", self.api_token))
+                .context("Invalid API token")?,
+        );
 
         // Connect to WebSocket with timeout
         let ws_stream = match tokio::time::timeout(
@@ -113,18 +148,30 @@ impl CollaborationClient {
         let (mut ws_write, mut ws_read) = ws_stream.split();
 
         // Create channel for sending messages
-        let (msg_tx, mut msg_rx) = mpsc::channel::<Message>(100);
+        let (msg_tx, mut msg_rx) = mpsc::channel::<Outgoing>(100);
+        // The reader holds a weak handle for replies, so dropping the client's
+        // sender is enough to close the connection
+        let reply_tx = msg_tx.downgrade();
         self.message_tx = Some(msg_tx);
 
         // Clone references for async tasks
         let event_tx = self.event_tx.clone();
         let doc_sync = Arc::clone(&self.doc_sync);
         let event_tx_send = self.event_tx.clone();
+        let document_name = self.document_name();
+        let reader_document_name = document_name.clone();
+        let awaiting_ack = AwaitingAck::default();
+        let reader_awaiting_ack = Arc::clone(&awaiting_ack);
 
         // Spawn task to handle outgoing messages
-        tokio::spawn(async move {
-            while let Some(message) = msg_rx.recv().await {
-                let encoded = message.encode();
+        self.writer = Some(tokio::spawn(async move {
+            while let Some(outgoing) = msg_rx.recv().await {
+                // Queued before sending so a fast acknowledgement always finds it
+                if matches!(outgoing.message.message_type, MessageType::SyncStep2 | MessageType::Update) {
+                    awaiting_ack.lock().unwrap().push_back(outgoing.delivers);
+                }
+
+                let encoded = outgoing.message.encode_with_document(&document_name);
                 if let Err(e) = ws_write.send(WsMessage::Binary(encoded)).await {
                     let _ = event_tx_send
                         .send(CollaborationEvent::Error(format!(
@@ -132,20 +179,29 @@ impl CollaborationClient {
                             e
                         )))
                         .await;
-                    break;
+                    return;
                 }
             }
-        });
+
+            // All senders dropped: close the socket gracefully
+            let _ = ws_write.close().await;
+        }));
 
         // Spawn task to handle incoming messages
         tokio::spawn(async move {
             while let Some(msg_result) = ws_read.next().await {
                 match msg_result {
                     Ok(WsMessage::Binary(data)) => {
+                        let Some(reply_tx) = reply_tx.upgrade() else {
+                            break;
+                        };
                         if let Err(e) = Self::handle_binary_message(
                             data,
+                            &reader_document_name,
                             &doc_sync,
-                            &event_tx
+                            &reader_awaiting_ack,
+                            &event_tx,
+                            &reply_tx,
                         ).await {
                             let _ = event_tx
                                 .send(CollaborationEvent::Error(format!(
@@ -186,7 +242,9 @@ impl CollaborationClient {
             }
         });
 
-        // Send initial Sync Step 1 to request document state
+        // Authenticate, then send initial Sync Step 1 to request document state.
+        // Hocuspocus queues messages received before authentication completes.
+        self.send_message(Message::auth(self.api_token.clone())).await?;
         let sync_step1 = self.doc_sync.create_sync_step1();
         self.send_message(sync_step1).await?;
 
@@ -196,24 +254,47 @@ impl CollaborationClient {
     /// Handle binary messages (Yjs protocol)
     async fn handle_binary_message(
         data: Vec<u8>,
+        document_name: &str,
         doc_sync: &Arc<DocumentSync>,
+        awaiting_ack: &AwaitingAck,
         event_tx: &mpsc::Sender<CollaborationEvent>,
+        reply_tx: &mpsc::Sender<Outgoing>,
     ) -> Result<()> {
         // Decode the protocol message
-        let message = Message::decode(&data)?;
+        let (name, message) = Message::decode_with_document(&data)?;
+        if name != document_name {
+            tracing::debug!("Ignoring message for other document: {}", name);
+            return Ok(());
+        }
 
         match message.message_type {
             MessageType::SyncStep1 => {
-                // Server is requesting our state - send Sync Step 2 with updates
-                // This usually doesn't happen in client-server model, but handle it
-                let _sync_step2 = doc_sync.create_sync_step2(&message.payload)?;
-                // We would need to send this back, but we don't have access to message_tx here
-                // This is handled by the server sending SyncStep2 to us instead
-                tracing::debug!("Received SyncStep1 from server (unusual)");
+                // Server is requesting our state - reply with only the updates it's missing,
+                // which includes any edits made while offline
+                let sync_step2 = doc_sync.create_sync_step2(&message.payload)?;
+                reply_tx
+                    .send(sync_step2.into())
+                    .await
+                    .context("Failed to queue Sync Step 2 reply")?;
             }
             MessageType::SyncStep2 => {
                 // Server is sending us the document state
                 doc_sync.apply_update(&message.payload)?;
+
+                // Deliver edits made while offline. Re-sending an update the server
+                // already has is harmless, so this also covers servers that never
+                // send their own Sync Step 1.
+                let pending = doc_sync.pending_updates()?;
+                if !pending.is_empty() {
+                    tracing::debug!("Sending {} pending offline updates", pending.len());
+                    let merged = yrs::merge_updates_v1(&pending)
+                        .map_err(|e| anyhow::anyhow!("Failed to merge pending updates: {:?}", e))?;
+                    reply_tx
+                        .send(Outgoing { message: Message::update(merged), delivers: pending })
+                        .await
+                        .context("Failed to queue pending updates")?;
+                }
+
                 event_tx
                     .send(CollaborationEvent::StatusChanged(ConnectionStatus::Synced))
                     .await
@@ -242,12 +323,41 @@ impl CollaborationClient {
             }
             MessageType::Auth => {
                 // Authentication response
-                tracing::debug!("Received auth response");
+                match message.auth_message()? {
+                    AuthMessage::Authenticated(scope) => {
+                        tracing::debug!("Authenticated with scope: {}", scope);
+                    }
+                    AuthMessage::PermissionDenied(reason) => {
+                        event_tx
+                            .send(CollaborationEvent::StatusChanged(ConnectionStatus::Error(
+                                format!("Permission denied: {}", reason),
+                            )))
+                            .await
+                            .ok();
+                    }
+                    AuthMessage::Token(_) => {
+                        tracing::debug!("Ignoring auth token sent by server");
+                    }
+                }
             }
             MessageType::QueryAwareness => {
                 // Server is querying awareness state
                 tracing::debug!("Received query awareness");
             }
+            MessageType::SyncStatus => {
+                // Server acknowledged the oldest unacknowledged update. Updates it
+                // didn't store stay pending and are resent on the next connection.
+                let synced = message.sync_status_synced()?;
+                let delivered = awaiting_ack.lock().unwrap().pop_front();
+                match delivered {
+                    Some(updates) if synced && !updates.is_empty() => {
+                        doc_sync.mark_delivered(&updates)?;
+                    }
+                    Some(_) if !synced => tracing::warn!("Server did not store an update"),
+                    Some(_) => {}
+                    None => tracing::debug!("Received sync status with nothing awaiting it"),
+                }
+            }
         }
 
         Ok(())
@@ -291,8 +401,12 @@ impl CollaborationClient {
 
     /// Send a message to the WebSocket server
     pub async fn send_message(&self, message: Message) -> Result<()> {
+        self.queue(message.into()).await
+    }
+
+    async fn queue(&self, outgoing: Outgoing) -> Result<()> {
         if let Some(tx) = &self.message_tx {
-            tx.send(message)
+            tx.send(outgoing)
                 .await
                 .context("Failed to send message to WebSocket")?;
             Ok(())
@@ -302,9 +416,12 @@ impl CollaborationClient {
     }
 
     /// Send a document update to the server
+    ///
+    /// If the update is pending (see [`DocumentSync::apply_local_text`]) it is marked
+    /// delivered once the server acknowledges storing it.
     pub async fn send_update(&self, update: Vec<u8>) -> Result<()> {
-        let message = Message::update(update);
-        self.send_message(message).await
+        let message = Message::update(update.clone());
+        self.queue(Outgoing { message, delivers: vec![update] }).await
     }
 
     /// Get the document sync instance
@@ -312,8 +429,21 @@ impl CollaborationClient {
         Arc::clone(&self.doc_sync)
     }
 
+    /// Check whether the client has an open connection
+    pub fn is_connected(&self) -> bool {
+        self.message_tx.as_ref().is_some_and(|tx| !tx.is_closed())
+    }
+
     /// Disconnect from the WebSocket server
-    pub async fn disconnect(&self) -> Result<()> {
+    ///
+    /// Messages already queued are sent before the socket is closed.
+    pub async fn disconnect(&mut self) -> Result<()> {
+        // Dropping the sender ends the writer loop once the queue is drained
+        self.message_tx = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = tokio::time::timeout(std::time::Duration::from_secs(5), writer).await;
+        }
+
         self.event_tx
             .send(CollaborationEvent::StatusChanged(
                 ConnectionStatus::Disconnected,
@@ -272,7 +272,7 @@ pub fn has_nerd_fonts() -> bool {
 ///
 /// # Arguments
 /// * `icon_name` - The icon name from Outline (e.g., "codepen", "windows", "database")
-///                 Can also be an actual emoji character (e.g., "🔥", "💻")
+///   Can also be an actual emoji character (e.g., "🔥", "💻")
 ///
 /// # Returns
 /// The corresponding icon as a string (Font Awesome glyph if supported, emoji otherwise)
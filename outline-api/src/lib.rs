@@ -2,10 +2,12 @@ pub mod client;
 pub mod types;
 pub mod auth;
 pub mod icon;
+pub mod markdown;
+pub mod merge;
 
 #[cfg(feature = "collaboration")]
 pub mod collaboration;
 
-pub use client::OutlineClient;
+pub use client::{OutlineClient, RevisionConflict};
 pub use types::*;
 pub use icon::*;
@@ -0,0 +1,561 @@
+//! Markdown structure helpers
+//!
+//! Lightweight parsing of the heading structure of Outline's Markdown, used to
+//! address sections of a document by heading. Only ATX headings (`# Title`) are
+//! recognised, since that's what Outline produces; headings inside fenced code
+//! blocks are ignored.
+//!
+//! Inline links are extracted the same way, so clients can follow links to other
+//! documents and mentions without a full Markdown parser.
+
+/// A heading and the extent of the section it introduces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// Heading level (1-6)
+    pub level: usize,
+    /// Heading text without the leading `#` markers
+    pub title: String,
+    /// Byte offset of the start of the heading line
+    pub start: usize,
+    /// Byte offset just after the heading line (start of the section body)
+    pub body_start: usize,
+    /// Byte offset of the end of the section (next heading of the same or higher level)
+    pub end: usize,
+}
+
+impl Heading {
+    /// Byte range of the section body, excluding the heading line
+    pub fn body_range(&self) -> std::ops::Range<usize> {
+        self.body_start..self.end
+    }
+}
+
+/// Parse an ATX heading line, returning its level and title
+fn parse_heading_line(line: &str) -> Option<(usize, String)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+
+    let rest = &line[indent..];
+    let level = rest.len() - rest.trim_start_matches('#').len();
+    if level == 0 || level > 6 {
+        return None;
+    }
+
+    let after = &rest[level..];
+    if !after.is_empty() && !after.starts_with([' ', '\t']) {
+        return None;
+    }
+
+    // Strip an optional closing sequence of #s
+    let title = after.trim();
+    let title = match title.trim_end_matches('#') {
+        stripped if stripped.is_empty() || stripped.ends_with([' ', '\t']) => stripped.trim_end(),
+        _ => title,
+    };
+
+    Some((level, title.to_string()))
+}
+
+/// Extract all headings from Markdown text, in document order
+pub fn headings(text: &str) -> Vec<Heading> {
+    let mut headings: Vec<Heading> = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let content = line.trim_end_matches(['\n', '\r']);
+        let trimmed = content.trim_start();
+
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") {
+            fence = Some("```");
+            continue;
+        }
+        if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+            continue;
+        }
+
+        if let Some((level, title)) = parse_heading_line(content) {
+            headings.push(Heading {
+                level,
+                title,
+                start,
+                body_start: offset,
+                end: text.len(),
+            });
+        }
+    }
+
+    // A section ends where the next heading of the same or higher level begins
+    for i in 0..headings.len() {
+        let level = headings[i].level;
+        if let Some(next) = headings[i + 1..].iter().find(|h| h.level <= level) {
+            headings[i].end = next.start;
+        }
+    }
+
+    headings
+}
+
+/// A heading together with its place in the document outline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// Outline number, e.g. `2.1` for the first subsection of the second top-level section
+    pub id: String,
+    /// Titles from the outermost enclosing heading down to this one
+    pub path: Vec<String>,
+    /// The heading and its extent
+    pub heading: Heading,
+}
+
+/// Extract the outline of a document: every heading with its number and path
+///
+/// A heading is nested under the closest preceding heading of a lower level,
+/// so skipped levels (`#` then `###`) still nest.
+pub fn sections(text: &str) -> Vec<Section> {
+    let mut sections = Vec::new();
+    // (level, id, number of children so far) for each enclosing heading
+    let mut stack: Vec<(usize, String, usize)> = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    let mut top_level = 0;
+
+    for heading in headings(text) {
+        while stack.last().is_some_and(|(level, _, _)| *level >= heading.level) {
+            stack.pop();
+            path.pop();
+        }
+
+        let id = match stack.last_mut() {
+            Some((_, parent, children)) => {
+                *children += 1;
+                format!("{}.{}", parent, children)
+            }
+            None => {
+                top_level += 1;
+                top_level.to_string()
+            }
+        };
+
+        path.push(heading.title.clone());
+        stack.push((heading.level, id.clone(), 0));
+        sections.push(Section {
+            id,
+            path: path.clone(),
+            heading,
+        });
+    }
+
+    sections
+}
+
+/// Find the first section whose heading path ends with the given titles
+///
+/// Titles compare like [`find_heading`], so `["Install"]` finds the first
+/// `Install` heading anywhere and `["Setup", "Install"]` only one directly
+/// under `Setup`.
+pub fn find_section<S: AsRef<str>>(text: &str, path: &[S]) -> Option<Section> {
+    find_sections(text, path).into_iter().next()
+}
+
+/// Find every section whose heading path ends with the given titles, in document order
+pub fn find_sections<S: AsRef<str>>(text: &str, path: &[S]) -> Vec<Section> {
+    if path.is_empty() {
+        return Vec::new();
+    }
+    let wanted: Vec<String> = path.iter().map(|t| normalize_title(t.as_ref())).collect();
+
+    sections(text)
+        .into_iter()
+        .filter(|section| {
+            section.path.len() >= wanted.len()
+                && section.path[section.path.len() - wanted.len()..]
+                    .iter()
+                    .zip(&wanted)
+                    .all(|(title, wanted)| title.to_lowercase() == *wanted)
+        })
+        .collect()
+}
+
+fn normalize_title(title: &str) -> String {
+    title.trim().trim_start_matches('#').trim().to_lowercase()
+}
+
+/// Find the first heading whose title matches (case-insensitive, ignoring surrounding whitespace)
+pub fn find_heading(text: &str, title: &str) -> Option<Heading> {
+    let wanted = normalize_title(title);
+    headings(text)
+        .into_iter()
+        .find(|h| h.title.to_lowercase() == wanted)
+}
+
+/// Ensure a block of text ends with a newline so it can be spliced between lines
+fn as_block(content: &str) -> String {
+    let mut block = content.to_string();
+    if !block.ends_with('\n') {
+        block.push('\n');
+    }
+    block
+}
+
+/// Insert content at the start of the section under a heading
+///
+/// Returns `None` if no heading with that title exists.
+pub fn insert_after_heading(text: &str, title: &str, content: &str) -> Option<String> {
+    let heading = find_heading(text, title)?;
+    let mut result = String::with_capacity(text.len() + content.len() + 1);
+    result.push_str(&text[..heading.body_start]);
+    if !result.ends_with('\n') {
+        result.push('\n');
+    }
+    result.push_str(&as_block(content));
+    result.push_str(&text[heading.body_start..]);
+    Some(result)
+}
+
+/// Replace the body of the section under a heading, keeping the heading itself
+///
+/// Subsections are part of the body and are replaced too. Returns `None` if no
+/// heading with that title exists.
+pub fn replace_section(text: &str, title: &str, content: &str) -> Option<String> {
+    let heading = find_heading(text, title)?;
+    Some(replace_section_body(text, &heading, content))
+}
+
+/// Replace the body of an already located section, keeping its heading
+pub fn replace_section_body(text: &str, heading: &Heading, content: &str) -> String {
+    let mut result = String::with_capacity(text.len() + content.len() + 2);
+    result.push_str(&text[..heading.body_start]);
+    if !result.ends_with('\n') {
+        result.push('\n');
+    }
+    result.push_str(&as_block(content));
+    if heading.end < text.len() {
+        // Keep a blank line before the next heading
+        if !result.ends_with("\n\n") {
+            result.push('\n');
+        }
+        result.push_str(&text[heading.end..]);
+    }
+    result
+}
+
+/// Append content to the end of the document as a new block
+pub fn append(text: &str, content: &str) -> String {
+    let mut result = text.to_string();
+    if !result.is_empty() && !result.ends_with('\n') {
+        result.push('\n');
+    }
+    result.push_str(&as_block(content));
+    result
+}
+
+/// An inline link (`[text](url)`) in Markdown text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// Link text, as written
+    pub text: String,
+    pub url: String,
+    /// Zero-based line the link is on
+    pub line: usize,
+    /// Byte range of the whole link
+    pub range: std::ops::Range<usize>,
+}
+
+/// What a link points at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    /// Another document, by ID or URL ID (`documents.info` accepts either)
+    Document(String),
+    /// A mentioned user, by ID
+    User(String),
+    /// A page outside the knowledge base
+    External(String),
+    /// Anything else, such as a heading anchor or another kind of mention
+    Other(String),
+}
+
+impl Link {
+    /// Work out what the link points at
+    ///
+    /// `base_url` is the URL of the Outline instance (its API URL works too); absolute
+    /// links to documents are only recognised on the same host.
+    pub fn target(&self, base_url: &str) -> LinkTarget {
+        // Outline writes mentions as `@[Name](mention://<id>/<kind>/<model id>)`
+        if let Some(mention) = self.url.strip_prefix("mention://") {
+            let mut parts = mention.split('/').skip(1);
+            return match (parts.next(), parts.next()) {
+                (Some("user"), Some(id)) if !id.is_empty() => LinkTarget::User(id.to_string()),
+                (Some("document"), Some(id)) if !id.is_empty() => LinkTarget::Document(id.to_string()),
+                _ => LinkTarget::Other(self.url.clone()),
+            };
+        }
+
+        if self.url.starts_with('/') {
+            return match document_url_id(&self.url) {
+                Some(id) => LinkTarget::Document(id.to_string()),
+                None => LinkTarget::Other(self.url.clone()),
+            };
+        }
+
+        match url::Url::parse(&self.url) {
+            Ok(link) => {
+                let same_host = url::Url::parse(base_url)
+                    .is_ok_and(|base| link.host_str().is_some() && link.host_str() == base.host_str());
+                match document_url_id(link.path()).filter(|_| same_host) {
+                    Some(id) => LinkTarget::Document(id.to_string()),
+                    None => LinkTarget::External(self.url.clone()),
+                }
+            }
+            Err(_) => LinkTarget::Other(self.url.clone()),
+        }
+    }
+}
+
+/// The URL ID in a document path like `/doc/<slug>-<url id>`
+fn document_url_id(path: &str) -> Option<&str> {
+    let slug = path.strip_prefix("/doc/")?.split(['/', '#', '?']).next()?;
+    slug.rsplit('-').next().filter(|id| !id.is_empty())
+}
+
+/// Extract inline links from Markdown text, in document order
+///
+/// Images and links inside code are skipped. Mentions are links with a `mention://`
+/// URL, so they are included too.
+pub fn links(text: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut offset = 0;
+
+    for (number, line) in text.split_inclusive('\n').enumerate() {
+        let start = offset;
+        offset += line.len();
+        let trimmed = line.trim_start();
+
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") {
+            fence = Some("```");
+            continue;
+        }
+        if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+            continue;
+        }
+
+        line_links(line, start, number, &mut links);
+    }
+
+    links
+}
+
+/// Collect the links on one line, skipping code spans and images
+fn line_links(line: &str, start: usize, number: usize, links: &mut Vec<Link>) {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+
+    // Only ASCII positions are sliced at, so indexing stays on char boundaries
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => {
+                let ticks = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+                let marker = &line[i..i + ticks];
+                i += ticks + line[i + ticks..].find(marker).map_or(0, |end| end + ticks);
+            }
+            b'[' if i == 0 || bytes[i - 1] != b'!' => match parse_link(&line[i..]) {
+                Some((text, url, len)) => {
+                    links.push(Link {
+                        text,
+                        url,
+                        line: number,
+                        range: start + i..start + i + len,
+                    });
+                    i += len;
+                }
+                None => i += 1,
+            },
+            _ => i += 1,
+        }
+    }
+}
+
+/// Parse `[text](url "title")` at the start of `s`, returning the text, URL and length
+fn parse_link(s: &str) -> Option<(String, String, usize)> {
+    let mut depth = 0;
+    let close = s.bytes().position(|b| {
+        match b {
+            b'[' => depth += 1,
+            b']' => depth -= 1,
+            _ => {}
+        }
+        depth == 0
+    })?;
+
+    let rest = s[close + 1..].strip_prefix('(')?;
+    let mut depth = 1;
+    let end = rest.bytes().position(|b| {
+        match b {
+            b'(' => depth += 1,
+            b')' => depth -= 1,
+            _ => {}
+        }
+        depth == 0
+    })?;
+
+    let destination = rest[..end].trim();
+    let url = match destination.strip_prefix('<') {
+        Some(bracketed) => bracketed.split('>').next().unwrap_or_default(),
+        None => destination.split_whitespace().next().unwrap_or_default(),
+    };
+    if url.is_empty() {
+        return None;
+    }
+
+    Some((s[1..close].to_string(), url.to_string(), close + end + 3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "Intro\n# One\nalpha\n## Sub\nbeta\n```\n# not a heading\n```\n# Two ##\ngamma\n";
+
+    #[test]
+    fn test_headings() {
+        let hs = headings(DOC);
+        let titles: Vec<_> = hs.iter().map(|h| (h.level, h.title.as_str())).collect();
+        assert_eq!(titles, vec![(1, "One"), (2, "Sub"), (1, "Two")]);
+
+        assert_eq!(&DOC[hs[0].body_range()], "alpha\n## Sub\nbeta\n```\n# not a heading\n```\n");
+        assert_eq!(&DOC[hs[1].body_range()], "beta\n```\n# not a heading\n```\n");
+        assert_eq!(&DOC[hs[2].body_range()], "gamma\n");
+    }
+
+    #[test]
+    fn test_parse_heading_line() {
+        assert_eq!(parse_heading_line("### Title"), Some((3, "Title".to_string())));
+        assert_eq!(parse_heading_line("#"), Some((1, String::new())));
+        assert_eq!(parse_heading_line("#hashtag"), None);
+        assert_eq!(parse_heading_line("####### Too deep"), None);
+        assert_eq!(parse_heading_line("# C#"), Some((1, "C#".to_string())));
+    }
+
+    #[test]
+    fn test_find_heading() {
+        let h = find_heading(DOC, "  sub ").unwrap();
+        assert_eq!(h.level, 2);
+        assert!(find_heading(DOC, "Missing").is_none());
+    }
+
+    #[test]
+    fn test_sections() {
+        let doc = "# One\n## A\n### Deep\n## B\n# Two\n### Skipped\n## Install\n# Setup\n## Install\n";
+        let outline: Vec<_> = sections(doc)
+            .into_iter()
+            .map(|s| (s.id, s.path.join(" > ")))
+            .collect();
+        assert_eq!(
+            outline,
+            vec![
+                ("1".to_string(), "One".to_string()),
+                ("1.1".to_string(), "One > A".to_string()),
+                ("1.1.1".to_string(), "One > A > Deep".to_string()),
+                ("1.2".to_string(), "One > B".to_string()),
+                ("2".to_string(), "Two".to_string()),
+                ("2.1".to_string(), "Two > Skipped".to_string()),
+                ("2.2".to_string(), "Two > Install".to_string()),
+                ("3".to_string(), "Setup".to_string()),
+                ("3.1".to_string(), "Setup > Install".to_string()),
+            ]
+        );
+
+        assert_eq!(find_section(doc, &["install"]).unwrap().id, "2.2");
+        assert_eq!(find_section(doc, &["Setup", "Install"]).unwrap().id, "3.1");
+        assert_eq!(find_section(doc, &["One", "Deep"]), None);
+        assert_eq!(find_section::<&str>(doc, &[]), None);
+    }
+
+    #[test]
+    fn test_section_edits() {
+        let doc = "# A\nold\n\n# B\nkeep";
+
+        assert_eq!(
+            insert_after_heading(doc, "a", "new").unwrap(),
+            "# A\nnew\nold\n\n# B\nkeep"
+        );
+        assert_eq!(
+            replace_section(doc, "A", "new").unwrap(),
+            "# A\nnew\n\n# B\nkeep"
+        );
+        assert_eq!(replace_section(doc, "B", "x\n").unwrap(), "# A\nold\n\n# B\nx\n");
+        assert_eq!(insert_after_heading("# Last", "Last", "x").unwrap(), "# Last\nx\n");
+        assert!(replace_section(doc, "C", "x").is_none());
+
+        assert_eq!(append("one", "two"), "one\ntwo\n");
+        assert_eq!(append("", "two"), "two\n");
+    }
+
+    #[test]
+    fn test_links() {
+        let doc = "See [Setup](/doc/setup-guide-Ab12Cd34Ef) and ![img](/pic.png).\n\
+                   Ask @[Jane](mention://m1/user/u1), `[not](a link)`\n\
+                   ```\n[skipped](/doc/x-1)\n```\n\
+                   [nested [brackets]](https://example.com/a_(b) \"Title\")\n";
+        let found = links(doc);
+        let summary: Vec<_> = found.iter().map(|l| (l.text.as_str(), l.url.as_str(), l.line)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Setup", "/doc/setup-guide-Ab12Cd34Ef", 0),
+                ("Jane", "mention://m1/user/u1", 1),
+                ("nested [brackets]", "https://example.com/a_(b)", 5),
+            ]
+        );
+        assert_eq!(&doc[found[0].range.clone()], "[Setup](/doc/setup-guide-Ab12Cd34Ef)");
+
+        assert!(links("[no url]() and [text] alone").is_empty());
+    }
+
+    #[test]
+    fn test_link_targets() {
+        let base = "https://team.getoutline.com/api";
+        let target = |url: &str| {
+            Link {
+                text: String::new(),
+                url: url.to_string(),
+                line: 0,
+                range: 0..0,
+            }
+            .target(base)
+        };
+
+        assert_eq!(target("/doc/setup-guide-Ab12Cd34Ef"), LinkTarget::Document("Ab12Cd34Ef".to_string()));
+        assert_eq!(
+            target("https://team.getoutline.com/doc/setup-Ab12Cd34Ef#install"),
+            LinkTarget::Document("Ab12Cd34Ef".to_string())
+        );
+        assert_eq!(
+            target("https://example.com/doc/setup-Ab12Cd34Ef"),
+            LinkTarget::External("https://example.com/doc/setup-Ab12Cd34Ef".to_string())
+        );
+        assert_eq!(target("mention://m1/user/u1"), LinkTarget::User("u1".to_string()));
+        assert_eq!(target("mention://m2/document/d1"), LinkTarget::Document("d1".to_string()));
+        assert_eq!(target("mailto:jane@example.com"), LinkTarget::External("mailto:jane@example.com".to_string()));
+        assert_eq!(target("#install"), LinkTarget::Other("#install".to_string()));
+        assert_eq!(target("/collection/eng-xyz"), LinkTarget::Other("/collection/eng-xyz".to_string()));
+    }
+}
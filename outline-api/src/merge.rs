@@ -0,0 +1,401 @@
+//! Line-based three-way merge
+//!
+//! Used when a document changed on the server after it was loaded: the text
+//! at load time (base), the local edit (mine) and the server's current text
+//! (theirs) are split into chunks. A chunk changed on only one side (or the
+//! same way on both) merges cleanly; a chunk changed differently on both sides
+//! is a conflict.
+
+/// How a chunk of the merge came about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkKind {
+    /// No side changed these lines
+    Unchanged,
+    /// Only the local side changed these lines
+    Mine,
+    /// Only the server side changed these lines
+    Theirs,
+    /// Both sides made the same change
+    Both,
+    /// Both sides changed these lines differently
+    Conflict,
+}
+
+/// A run of lines as they appear in each version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeChunk {
+    pub kind: ChunkKind,
+    pub base: Vec<String>,
+    pub mine: Vec<String>,
+    pub theirs: Vec<String>,
+}
+
+impl MergeChunk {
+    /// The merged lines, or `None` for a conflict
+    pub fn resolved(&self) -> Option<&[String]> {
+        match self.kind {
+            ChunkKind::Unchanged | ChunkKind::Theirs => Some(&self.theirs),
+            ChunkKind::Mine | ChunkKind::Both => Some(&self.mine),
+            ChunkKind::Conflict => None,
+        }
+    }
+}
+
+/// Result of a three-way merge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merge {
+    pub chunks: Vec<MergeChunk>,
+    /// Whether the merged text ends with a newline
+    pub trailing_newline: bool,
+}
+
+impl Merge {
+    /// Check whether any chunk conflicts
+    pub fn has_conflicts(&self) -> bool {
+        self.conflict_count() > 0
+    }
+
+    /// Number of conflicting chunks
+    pub fn conflict_count(&self) -> usize {
+        self.chunks
+            .iter()
+            .filter(|c| c.kind == ChunkKind::Conflict)
+            .count()
+    }
+
+    /// Merged text, or `None` if there are conflicts
+    pub fn resolved_text(&self) -> Option<String> {
+        let mut lines: Vec<&str> = Vec::new();
+        for chunk in &self.chunks {
+            lines.extend(chunk.resolved()?.iter().map(String::as_str));
+        }
+        Some(self.join(&lines))
+    }
+
+    /// Merged text with git-style conflict markers around each conflict
+    pub fn text_with_markers(&self) -> String {
+        let mut lines: Vec<&str> = Vec::new();
+        for chunk in &self.chunks {
+            match chunk.resolved() {
+                Some(resolved) => lines.extend(resolved.iter().map(String::as_str)),
+                None => {
+                    lines.push("<<<<<<< mine");
+                    lines.extend(chunk.mine.iter().map(String::as_str));
+                    lines.push("||||||| base");
+                    lines.extend(chunk.base.iter().map(String::as_str));
+                    lines.push("=======");
+                    lines.extend(chunk.theirs.iter().map(String::as_str));
+                    lines.push(">>>>>>> theirs");
+                }
+            }
+        }
+        self.join(&lines)
+    }
+
+    fn join(&self, lines: &[&str]) -> String {
+        let mut text = lines.join("\n");
+        if self.trailing_newline && !lines.is_empty() {
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Merge two edits of the same base text, line by line
+pub fn merge3(base: &str, mine: &str, theirs: &str) -> Merge {
+    // The final newline merges like a line of its own
+    let trailing_newline = match (base.ends_with('\n'), mine.ends_with('\n'), theirs.ends_with('\n')) {
+        (base, mine, theirs) if mine == base => theirs,
+        (_, mine, _) => mine,
+    };
+
+    let base: Vec<&str> = base.lines().collect();
+    let mine: Vec<&str> = mine.lines().collect();
+    let theirs: Vec<&str> = theirs.lines().collect();
+
+    let to_mine = match_lines(&base, &mine);
+    let to_theirs = match_lines(&base, &theirs);
+
+    let mut chunks = Vec::new();
+    let (mut i, mut j, mut k) = (0, 0, 0);
+
+    loop {
+        // Next base line kept by both sides anchors the end of this chunk
+        let anchor = (i..base.len())
+            .find_map(|b| Some((b, to_mine[b]?, to_theirs[b]?)));
+        let (bi, mj, tk) = anchor.unwrap_or((base.len(), mine.len(), theirs.len()));
+
+        if bi > i || mj > j || tk > k {
+            chunks.push(classify(&base[i..bi], &mine[j..mj], &theirs[k..tk]));
+        }
+
+        if anchor.is_none() {
+            break;
+        }
+        push_unchanged(&mut chunks, base[bi]);
+        (i, j, k) = (bi + 1, mj + 1, tk + 1);
+    }
+
+    Merge { chunks, trailing_newline }
+}
+
+fn to_lines(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|s| s.to_string()).collect()
+}
+
+fn classify(base: &[&str], mine: &[&str], theirs: &[&str]) -> MergeChunk {
+    let kind = if mine == theirs {
+        ChunkKind::Both
+    } else if mine == base {
+        ChunkKind::Theirs
+    } else if theirs == base {
+        ChunkKind::Mine
+    } else {
+        ChunkKind::Conflict
+    };
+
+    MergeChunk {
+        kind,
+        base: to_lines(base),
+        mine: to_lines(mine),
+        theirs: to_lines(theirs),
+    }
+}
+
+/// Append an unchanged line, extending the previous unchanged chunk if there is one
+fn push_unchanged(chunks: &mut Vec<MergeChunk>, line: &str) {
+    if let Some(last) = chunks.last_mut().filter(|c| c.kind == ChunkKind::Unchanged) {
+        last.base.push(line.to_string());
+        last.mine.push(line.to_string());
+        last.theirs.push(line.to_string());
+        return;
+    }
+
+    let lines = vec![line.to_string()];
+    chunks.push(MergeChunk {
+        kind: ChunkKind::Unchanged,
+        base: lines.clone(),
+        mine: lines.clone(),
+        theirs: lines,
+    });
+}
+
+/// For each line of `a`, the index of the matching line in `b` (longest common subsequence)
+///
+/// Uses Myers' linear-space diff, so memory stays linear in the length of the
+/// documents and time grows with the number of changed lines.
+fn match_lines(a: &[&str], b: &[&str]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; a.len()];
+    match_range(a, b, 0, 0, &mut matches);
+    matches
+}
+
+/// Match `a` against `b`, which start at `a_start` and `b_start` in the full documents
+fn match_range(a: &[&str], b: &[&str], a_start: usize, b_start: usize, matches: &mut [Option<usize>]) {
+    // Common prefix and suffix are matched directly
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    for i in 0..prefix {
+        matches[a_start + i] = Some(b_start + i);
+    }
+    for s in 1..=suffix {
+        matches[a_start + a.len() - s] = Some(b_start + b.len() - s);
+    }
+
+    let a = &a[prefix..a.len() - suffix];
+    let b = &b[prefix..b.len() - suffix];
+    let (a_start, b_start) = (a_start + prefix, b_start + prefix);
+    if a.is_empty() || b.is_empty() {
+        return;
+    }
+
+    // Both ends now differ, so each side of the middle snake holds at least one edit
+    let (x, y, u, v) = middle_snake(a, b);
+    for i in 0..u - x {
+        matches[a_start + x + i] = Some(b_start + y + i);
+    }
+    match_range(&a[..x], &b[..y], a_start, b_start, matches);
+    match_range(&a[u..], &b[v..], a_start + u, b_start + v, matches);
+}
+
+/// Find the middle snake of a shortest edit script from `a` to `b`
+///
+/// Searches from both ends at once until the paths meet, and returns the
+/// diagonal run of matching lines where they do as `(x, y, u, v)`: lines
+/// `a[x..u]` match `b[y..v]`.
+fn middle_snake(a: &[&str], b: &[&str]) -> (usize, usize, usize, usize) {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    let offset = max + 1;
+
+    // Furthest x reached on each diagonal k = x - y; backwards, x counts from the end
+    let mut forward = vec![0isize; (2 * max + 3) as usize];
+    let mut backward = vec![0isize; (2 * max + 3) as usize];
+    let at = |k: isize| (k + offset) as usize;
+
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let (x0, y0) = (x, x - k);
+            let mut y = y0;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[at(k)] = x;
+
+            let reverse = delta - k;
+            if odd && reverse.abs() < d && x + backward[at(reverse)] >= n {
+                return (x0 as usize, y0 as usize, x as usize, y as usize);
+            }
+        }
+
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let (x0, y0) = (x, x - k);
+            let mut y = y0;
+            while x < n && y < m && a[(n - 1 - x) as usize] == b[(m - 1 - y) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[at(k)] = x;
+
+            let ahead = delta - k;
+            if !odd && ahead.abs() <= d && x + forward[at(ahead)] >= n {
+                return ((n - x) as usize, (m - y) as usize, (n - x0) as usize, (m - y0) as usize);
+            }
+        }
+    }
+
+    unreachable!("the forward and backward searches always meet")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_overlapping_edits_merge() {
+        let base = "one\ntwo\nthree\nfour";
+        let mine = "one\nTWO\nthree\nfour";
+        let theirs = "one\ntwo\nthree\nfour\nfive";
+
+        let merge = merge3(base, mine, theirs);
+        assert!(!merge.has_conflicts());
+        assert_eq!(merge.resolved_text().unwrap(), "one\nTWO\nthree\nfour\nfive");
+    }
+
+    #[test]
+    fn test_same_change_on_both_sides() {
+        let merge = merge3("a\nb", "a\nB", "a\nB");
+        assert_eq!(merge.resolved_text().unwrap(), "a\nB");
+        assert!(merge.chunks.iter().any(|c| c.kind == ChunkKind::Both));
+    }
+
+    #[test]
+    fn test_conflicting_edits() {
+        let base = "title\nstatus: draft\nend";
+        let mine = "title\nstatus: review\nend";
+        let theirs = "title\nstatus: done\nend";
+
+        let merge = merge3(base, mine, theirs);
+        assert_eq!(merge.conflict_count(), 1);
+        assert!(merge.resolved_text().is_none());
+        assert_eq!(
+            merge.text_with_markers(),
+            "title\n<<<<<<< mine\nstatus: review\n||||||| base\nstatus: draft\n=======\nstatus: done\n>>>>>>> theirs\nend"
+        );
+    }
+
+    #[test]
+    fn test_trailing_newline_is_kept() {
+        let merge = merge3("one\ntwo\n", "ONE\ntwo\n", "one\ntwo\nthree\n");
+        assert_eq!(merge.resolved_text().unwrap(), "ONE\ntwo\nthree\n");
+
+        // Removing it on one side is a change like any other
+        let merge = merge3("one\n", "one", "one\n");
+        assert_eq!(merge.resolved_text().unwrap(), "one");
+
+        let merge = merge3("a\nx\n", "a\ny\n", "a\nz\n");
+        assert!(merge.text_with_markers().ends_with(">>>>>>> theirs\n"));
+    }
+
+    #[test]
+    fn test_match_lines_large_documents() {
+        // Far too big for a full n*m table, with changes spread through it
+        let a: Vec<String> = (0..50_000).map(|i| format!("line {}", i)).collect();
+        let b: Vec<String> = a
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| i % 1000 != 0)
+            .map(|(i, line)| if i % 1000 == 500 { format!("changed {}", i) } else { line.clone() })
+            .collect();
+        let a: Vec<&str> = a.iter().map(String::as_str).collect();
+        let b: Vec<&str> = b.iter().map(String::as_str).collect();
+
+        let matches = match_lines(&a, &b);
+        assert_eq!(matches.iter().filter(|m| m.is_none()).count(), 100);
+        let mut previous = None;
+        for (i, j) in matches.iter().enumerate().filter_map(|(i, m)| Some((i, (*m)?))) {
+            assert_eq!(a[i], b[j]);
+            assert!(previous < Some(j));
+            previous = Some(j);
+        }
+    }
+
+    #[test]
+    fn test_match_lines_finds_longest_match() {
+        // Compare against a plain LCS table on many small pseudo-random inputs
+        let mut seed = 7u32;
+        let mut next = |limit: u32| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) % limit
+        };
+        let words = ["a", "b", "c"];
+        for _ in 0..500 {
+            let a: Vec<&str> = (0..next(12)).map(|_| words[next(3) as usize]).collect();
+            let b: Vec<&str> = (0..next(12)).map(|_| words[next(3) as usize]).collect();
+
+            let mut lengths = vec![vec![0; b.len() + 1]; a.len() + 1];
+            for i in 0..a.len() {
+                for j in 0..b.len() {
+                    lengths[i + 1][j + 1] = if a[i] == b[j] {
+                        lengths[i][j] + 1
+                    } else {
+                        lengths[i][j + 1].max(lengths[i + 1][j])
+                    };
+                }
+            }
+
+            let matches = match_lines(&a, &b);
+            let pairs: Vec<(usize, usize)> =
+                matches.iter().enumerate().filter_map(|(i, m)| Some((i, (*m)?))).collect();
+            assert_eq!(pairs.len(), lengths[a.len()][b.len()], "{:?} {:?}", a, b);
+            assert!(pairs.iter().all(|&(i, j)| a[i] == b[j]));
+            assert!(pairs.windows(2).all(|w| w[0].1 < w[1].1));
+        }
+    }
+
+    #[test]
+    fn test_match_lines() {
+        let a = ["a", "b", "c", "d"];
+        let b = ["a", "x", "c", "d", "e"];
+        assert_eq!(match_lines(&a, &b), vec![Some(0), None, Some(2), Some(3)]);
+    }
+}
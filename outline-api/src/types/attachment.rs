@@ -90,6 +90,37 @@ pub struct RedirectAttachmentResponse {
     pub url: String,
 }
 
+/// The contents of a downloaded attachment
+#[derive(Debug, Clone)]
+pub struct AttachmentContent {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+impl AttachmentContent {
+    /// Whether the content is an image
+    pub fn is_image(&self) -> bool {
+        self.content_type.starts_with("image/")
+    }
+}
+
+/// Extract the attachment ID from a link as Outline embeds it in documents
+///
+/// Accepts `/api/attachments.redirect?id=<id>` links, relative or absolute,
+/// and bare IDs.
+pub fn attachment_id(link: &str) -> Option<&str> {
+    let link = link.trim();
+    let Some((_, query)) = link.split_once("attachments.redirect?") else {
+        let bare = !link.is_empty() && link.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        return bare.then_some(link);
+    };
+
+    query
+        .split(['&', '#', ' ', ')'])
+        .find_map(|pair| pair.strip_prefix("id="))
+        .filter(|id| !id.is_empty())
+}
+
 // ============================================================================
 // List Attachments
 // ============================================================================
@@ -209,6 +240,35 @@ mod tests {
         assert_eq!(json["limit"], 10);
     }
 
+    #[test]
+    fn test_attachment_id() {
+        let id = "9bcd64b4-6a4e-4bcc-b3d3-3c6d4c3c6f2e";
+        assert_eq!(attachment_id(id), Some(id));
+        assert_eq!(attachment_id(&format!("/api/attachments.redirect?id={}", id)), Some(id));
+        assert_eq!(
+            attachment_id(&format!("https://wiki.example.com/api/attachments.redirect?id={}&download=1", id)),
+            Some(id)
+        );
+        assert_eq!(attachment_id("/api/attachments.redirect?download=1"), None);
+        assert_eq!(attachment_id("https://example.com/image.png"), None);
+        assert_eq!(attachment_id(""), None);
+    }
+
+    #[test]
+    fn test_attachment_content_is_image() {
+        let content = AttachmentContent {
+            content_type: "image/png".to_string(),
+            data: Vec::new(),
+        };
+        assert!(content.is_image());
+
+        let content = AttachmentContent {
+            content_type: "application/pdf".to_string(),
+            data: Vec::new(),
+        };
+        assert!(!content.is_image());
+    }
+
     #[test]
     fn test_attachment_size_calculation() {
         let data = vec![0u8; 1024]; // 1KB
@@ -18,6 +18,8 @@ pub struct Collection {
     pub updated_at: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub deleted_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub permission: Option<CollectionPermission>,
 }
@@ -257,6 +259,18 @@ impl DeleteCollectionRequest {
     }
 }
 
+/// Request to archive a collection
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveCollectionRequest {
+    pub id: String,
+}
+
+impl ArchiveCollectionRequest {
+    pub fn new(id: String) -> Self {
+        Self { id }
+    }
+}
+
 // ============================================================================
 // Response Types
 // ============================================================================
@@ -269,6 +283,41 @@ pub struct ListCollectionsResponse {
     pub pagination: Option<PaginationResponse>,
 }
 
+/// A document in a collection's navigation tree, with its nested documents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationNode {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    /// Icon name or emoji (sent as `emoji` by older servers)
+    #[serde(default, alias = "emoji", skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub children: Vec<NavigationNode>,
+}
+
+impl NavigationNode {
+    /// Get the icon for this document, mapping icon names to appropriate glyphs/emojis
+    pub fn icon(&self) -> &str {
+        self.icon
+            .as_deref()
+            .map(crate::icon::icon_to_string)
+            .unwrap_or("📄")
+    }
+
+    /// Number of documents in this subtree, including this one
+    pub fn count(&self) -> usize {
+        1 + self.children.iter().map(NavigationNode::count).sum::<usize>()
+    }
+}
+
+/// Response from listing a collection's document tree
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CollectionDocumentsResponse {
+    pub data: Vec<NavigationNode>,
+}
+
 // ============================================================================
 // Organization Operations Request Types
 // ============================================================================
@@ -1026,7 +1075,7 @@ mod tests {
 
         let json = serde_json::to_string(&req).unwrap();
         // Verify it serializes without errors
-        assert!(json.len() > 0);
+        assert!(!json.is_empty());
     }
 
     // ========================================================================
@@ -1318,7 +1367,7 @@ mod tests {
         let req = ExportCollectionRequest::new("📚col".to_string(), ExportFormat::Html);
         assert_eq!(req.id, "📚col");
         let json = serde_json::to_string(&req).unwrap();
-        assert!(json.len() > 0);
+        assert!(!json.is_empty());
     }
 
     #[test]
@@ -1329,7 +1378,7 @@ mod tests {
         for format in [ExportFormat::Markdown, ExportFormat::Html, ExportFormat::Pdf] {
             let req = ExportAllCollectionsRequest::new(format);
             let json = serde_json::to_string(&req).unwrap();
-            assert!(json.len() > 0);
+            assert!(!json.is_empty());
         }
     }
 
@@ -1362,6 +1411,39 @@ mod tests {
         assert_eq!(String::from_utf8(req.file).unwrap(), "# Hello World\n\nTest document");
     }
 
+    #[test]
+    fn test_collection_documents_response_deserialization() {
+        let json = r#"{
+            "data": [
+                {
+                    "id": "doc1",
+                    "title": "Guides",
+                    "url": "/doc/guides-abc",
+                    "icon": "🚀",
+                    "children": [
+                        {"id": "doc2", "title": "Setup", "url": "/doc/setup-def", "emoji": "🔧", "children": []},
+                        {"id": "doc3", "title": "Deploy", "url": "/doc/deploy-ghi"}
+                    ]
+                }
+            ]
+        }"#;
+        let response: CollectionDocumentsResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.data.len(), 1);
+        assert_eq!(response.data[0].children[1].title, "Deploy");
+        assert!(response.data[0].children[1].children.is_empty());
+        assert_eq!(response.data[0].count(), 3);
+        assert_eq!(response.data[0].icon.as_deref(), Some("🚀"));
+        assert_eq!(response.data[0].children[0].icon.as_deref(), Some("🔧"));
+        assert_eq!(response.data[0].children[1].icon(), "📄");
+    }
+
+    #[test]
+    fn test_archive_collection_request_serialization() {
+        let req = ArchiveCollectionRequest::new("col123".to_string());
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(json, r#"{"id":"col123"}"#);
+    }
+
     #[test]
     fn test_all_export_import_types_are_cloneable() {
         use crate::types::common::{ExportFormat, ImportFormat};
@@ -1,4 +1,5 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
 use super::User;
 
 // ============================================================================
@@ -18,10 +19,16 @@ pub struct Comment {
     pub data: CommentData,
     pub resolved_at: Option<String>,
     pub resolved_by: Option<User>,
+    /// Document text the comment is attached to, when requested with `include_anchor_text`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor_text: Option<String>,
 }
 
 /// Comment data structure containing text and optional position
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The server stores comment bodies as ProseMirror documents; those are
+/// flattened to plain text (mentions as `@Name`) when deserializing.
+#[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CommentData {
     pub text: String,
@@ -29,6 +36,70 @@ pub struct CommentData {
     pub position: Option<CommentPosition>,
 }
 
+impl<'de> Deserialize<'de> for CommentData {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Plain {
+                text: String,
+                #[serde(default)]
+                position: Option<CommentPosition>,
+            },
+            ProseMirror(Value),
+        }
+
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::Plain { text, position } => Self { text, position },
+            Raw::ProseMirror(node) => Self::new(prosemirror_text(&node)),
+        })
+    }
+}
+
+/// Flatten a ProseMirror node to plain text, one line per block
+fn prosemirror_text(node: &Value) -> String {
+    match node["type"].as_str().unwrap_or_default() {
+        "text" => return node["text"].as_str().unwrap_or_default().to_string(),
+        "mention" => {
+            let label = node["attrs"]["label"].as_str().unwrap_or("someone");
+            return format!("@{}", label);
+        }
+        "hard_break" | "br" => return "\n".to_string(),
+        _ => {}
+    }
+
+    let Some(children) = node["content"].as_array() else {
+        return String::new();
+    };
+
+    let inline = children
+        .iter()
+        .all(|c| matches!(c["type"].as_str(), Some("text" | "mention" | "hard_break" | "br")));
+    if inline {
+        return children.iter().map(prosemirror_text).collect();
+    }
+
+    let bullet = match node["type"].as_str() {
+        Some("bullet_list" | "checkbox_list") => Some("- "),
+        Some("ordered_list") => Some("1. "),
+        _ => None,
+    };
+    children
+        .iter()
+        .map(|child| {
+            let text = prosemirror_text(child);
+            match bullet {
+                Some(marker) => format!("{}{}", marker, text.replace('\n', "\n   ")),
+                None => text,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 impl CommentData {
     pub fn new(text: String) -> Self {
         Self {
@@ -116,6 +187,9 @@ pub struct ListCommentsRequest {
     pub offset: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit: Option<u32>,
+    /// Include the document text each comment is attached to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_anchor_text: Option<bool>,
 }
 
 impl ListCommentsRequest {
@@ -124,9 +198,15 @@ impl ListCommentsRequest {
             document_id,
             offset: None,
             limit: None,
+            include_anchor_text: None,
         }
     }
 
+    pub fn include_anchor_text(mut self, include: bool) -> Self {
+        self.include_anchor_text = Some(include);
+        self
+    }
+
     pub fn offset(mut self, offset: u32) -> Self {
         self.offset = Some(offset);
         self
@@ -276,6 +356,37 @@ mod tests {
         assert_eq!(json["data"]["text"], "Test comment");
     }
 
+    #[test]
+    fn test_deserialize_prosemirror_comment_data() {
+        let json = serde_json::json!({
+            "type": "doc",
+            "content": [
+                {
+                    "type": "paragraph",
+                    "content": [
+                        { "type": "mention", "attrs": { "label": "Ada" } },
+                        { "type": "text", "text": " can you check this?" }
+                    ]
+                },
+                {
+                    "type": "bullet_list",
+                    "content": [
+                        { "type": "list_item", "content": [
+                            { "type": "paragraph", "content": [{ "type": "text", "text": "typo" }] }
+                        ]}
+                    ]
+                }
+            ]
+        });
+
+        let data: CommentData = serde_json::from_value(json).unwrap();
+        assert_eq!(data.text, "@Ada can you check this?\n- typo");
+        assert!(data.position.is_none());
+
+        let plain: CommentData = serde_json::from_value(serde_json::json!({ "text": "Plain" })).unwrap();
+        assert_eq!(plain.text, "Plain");
+    }
+
     #[test]
     fn test_serialize_list_comments_request() {
         let request = ListCommentsRequest::new("doc-123".to_string())
@@ -286,5 +397,33 @@ mod tests {
         assert_eq!(json["documentId"], "doc-123");
         assert_eq!(json["offset"], 5);
         assert_eq!(json["limit"], 10);
+        assert!(json.get("includeAnchorText").is_none());
+
+        let json = serde_json::to_value(request.include_anchor_text(true)).unwrap();
+        assert_eq!(json["includeAnchorText"], true);
+    }
+
+    #[test]
+    fn test_deserialize_comment_anchor_text() {
+        let json = serde_json::json!({
+            "id": "c1",
+            "documentId": "doc-123",
+            "parentCommentId": null,
+            "createdBy": { "id": "u1", "name": "Ada" },
+            "createdAt": "2024-05-01T10:00:00.000Z",
+            "updatedAt": "2024-05-01T10:00:00.000Z",
+            "data": { "text": "Check this" },
+            "resolvedAt": null,
+            "resolvedBy": null,
+            "anchorText": "the quarterly numbers"
+        });
+
+        let comment: Comment = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(comment.anchor_text.as_deref(), Some("the quarterly numbers"));
+
+        let mut without = json;
+        without.as_object_mut().unwrap().remove("anchorText");
+        let comment: Comment = serde_json::from_value(without).unwrap();
+        assert!(comment.anchor_text.is_none());
     }
 }
@@ -33,19 +33,14 @@ pub struct PaginationResponse {
 }
 
 /// Sort direction
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum SortDirection {
     Asc,
+    #[default]
     Desc,
 }
 
-impl Default for SortDirection {
-    fn default() -> Self {
-        Self::Desc
-    }
-}
-
 /// Export format for documents and collections
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -75,19 +70,14 @@ pub enum Permission {
 }
 
 /// Collection permission level
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum CollectionPermission {
     Read,
+    #[default]
     ReadWrite,
 }
 
-impl Default for CollectionPermission {
-    fn default() -> Self {
-        Self::ReadWrite
-    }
-}
-
 /// User role in the team
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -45,27 +45,22 @@ impl Document {
     pub fn icon(&self) -> &str {
         self.emoji
             .as_deref()
-            .map(|e| crate::icon::icon_to_string(e))
+            .map(crate::icon::icon_to_string)
             .unwrap_or("📄")
     }
 }
 
 /// Document sort options
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub enum DocumentSort {
     Title,
     Index,
+    #[default]
     UpdatedAt,
     CreatedAt,
 }
 
-impl Default for DocumentSort {
-    fn default() -> Self {
-        Self::UpdatedAt
-    }
-}
-
 // ============================================================================
 // Request Types
 // ============================================================================
@@ -652,6 +647,42 @@ impl TemplatizeDocumentRequest {
     }
 }
 
+/// Request to duplicate a document
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateDocumentRequest {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recursive: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_document_id: Option<String>,
+}
+
+impl DuplicateDocumentRequest {
+    pub fn new(id: String) -> Self {
+        Self {
+            id,
+            title: None,
+            recursive: None,
+            publish: None,
+            collection_id: None,
+            parent_document_id: None,
+        }
+    }
+}
+
+/// Documents created by duplicating a document (the copy first, then any copied children)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateDocumentData {
+    pub documents: Vec<Document>,
+}
+
 /// Request to move a document
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -1298,6 +1329,24 @@ mod tests {
         assert!(json.contains("\"id\":\"doc678\""));
     }
 
+    #[test]
+    fn test_duplicate_document_request_serialization() {
+        let req = DuplicateDocumentRequest::new("doc123".to_string());
+        assert_eq!(serde_json::to_string(&req).unwrap(), r#"{"id":"doc123"}"#);
+
+        let req = DuplicateDocumentRequest {
+            title: Some("Copy".to_string()),
+            recursive: Some(true),
+            parent_document_id: Some("parent456".to_string()),
+            ..DuplicateDocumentRequest::new("doc123".to_string())
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"title\":\"Copy\""));
+        assert!(json.contains("\"recursive\":true"));
+        assert!(json.contains("\"parentDocumentId\":\"parent456\""));
+        assert!(!json.contains("collectionId"));
+    }
+
     #[test]
     fn test_move_document_request_new() {
         let req = MoveDocumentRequest::new("doc123".to_string());
@@ -6,6 +6,7 @@ pub mod user;
 pub mod comment;
 pub mod group;
 pub mod share;
+pub mod star;
 pub mod attachment;
 pub mod notification;
 pub mod event;
@@ -19,6 +20,7 @@ pub use user::*;
 pub use comment::*;
 pub use group::*;
 pub use share::*;
+pub use star::*;
 pub use attachment::*;
 pub use notification::*;
 pub use event::*;
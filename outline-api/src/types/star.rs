@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use super::{Document, Pagination};
+
+// ============================================================================
+// Star Type
+// ============================================================================
+
+/// A document or collection starred by the current user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Star {
+    pub id: String,
+    pub document_id: Option<String>,
+    pub collection_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+// ============================================================================
+// List Stars
+// ============================================================================
+
+/// Request to list the current user's stars
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ListStarsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl ListStarsRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_pagination(offset: u32, limit: u32) -> Self {
+        Self {
+            offset: Some(offset),
+            limit: Some(limit),
+        }
+    }
+}
+
+/// Stars together with the documents they point at
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StarsData {
+    pub stars: Vec<Star>,
+    #[serde(default)]
+    pub documents: Vec<Document>,
+}
+
+/// Response from listing stars
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListStarsResponse {
+    pub data: StarsData,
+    #[serde(default)]
+    pub pagination: Pagination,
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_list_stars_response() {
+        let json = serde_json::json!({
+            "data": {
+                "stars": [
+                    { "id": "star-1", "documentId": "doc-1", "collectionId": null, "index": "P" },
+                    { "id": "star-2", "documentId": null, "collectionId": "col-1" }
+                ],
+                "documents": [
+                    { "id": "doc-1", "title": "Roadmap", "collectionId": "col-1",
+                      "parentDocumentId": null, "publishedAt": null, "archivedAt": null,
+                      "deletedAt": null, "revision": 3 }
+                ]
+            },
+            "pagination": { "offset": 0, "limit": 25 }
+        });
+
+        let response: ListStarsResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(response.data.stars.len(), 2);
+        assert_eq!(response.data.stars[0].document_id.as_deref(), Some("doc-1"));
+        assert_eq!(response.data.documents[0].title, "Roadmap");
+    }
+}
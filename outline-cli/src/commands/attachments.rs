@@ -4,7 +4,6 @@ use std::fs;
 
 use outline_api::{
     OutlineClient,
-    auth,
     CreateAttachmentRequest,
     ListAttachmentsRequest,
 };
@@ -63,7 +62,7 @@ impl AttachmentsCommands {
         match self {
             AttachmentsCommands::Create { document_id, file, name, content_type } => {
                 // Read file data
-                let file_data = fs::read(&file)
+                let file_data = fs::read(file)
                     .context(format!("Failed to read file: {}", file))?;
 
                 let ct = content_type.clone().unwrap_or_else(|| "application/octet-stream".to_string());
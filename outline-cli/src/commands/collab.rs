@@ -0,0 +1,344 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use std::io::Read;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use outline_api::{
+    auth,
+    collaboration::{start_collaboration, CollaborationClient, CollaborationEvent, ConnectionStatus},
+    markdown,
+};
+use crate::config::Config;
+
+/// How long to wait for the initial document sync before giving up
+const SYNC_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Subcommand)]
+pub enum CollabCommands {
+    /// Append text to the end of a document
+    Append {
+        /// Document ID
+        id: String,
+
+        /// Text to append (reads from stdin if omitted)
+        #[arg(long)]
+        text: Option<String>,
+    },
+
+    /// Insert text directly below a heading
+    InsertAfterHeading {
+        /// Document ID
+        id: String,
+
+        /// Heading title (case-insensitive)
+        #[arg(long)]
+        heading: String,
+
+        /// Text to insert (reads from stdin if omitted)
+        #[arg(long)]
+        text: Option<String>,
+    },
+
+    /// Replace the contents of the section under a heading
+    ReplaceSection {
+        /// Document ID
+        id: String,
+
+        /// Heading title (case-insensitive)
+        #[arg(long)]
+        heading: String,
+
+        /// New section contents (reads from stdin if omitted)
+        #[arg(long)]
+        text: Option<String>,
+    },
+
+    /// Stream changes to a document as they happen
+    Tail {
+        /// Document ID
+        id: String,
+    },
+}
+
+impl CollabCommands {
+    pub async fn execute(&self) -> Result<()> {
+        match self {
+            CollabCommands::Append { id, text } => {
+                let content = read_text(text)?;
+                edit_document(id, |current| self.edited_text(current, &content)).await?;
+                println!("✓ Appended to document: {}", id);
+            }
+
+            CollabCommands::InsertAfterHeading { id, heading, text } => {
+                let content = read_text(text)?;
+                edit_document(id, |current| self.edited_text(current, &content)).await?;
+                println!("✓ Inserted below \"{}\" in document: {}", heading, id);
+            }
+
+            CollabCommands::ReplaceSection { id, heading, text } => {
+                let content = read_text(text)?;
+                edit_document(id, |current| self.edited_text(current, &content)).await?;
+                println!("✓ Replaced section \"{}\" in document: {}", heading, id);
+            }
+
+            CollabCommands::Tail { id } => {
+                tail_document(id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply this command's edit to the current document text
+    fn edited_text(&self, current: &str, content: &str) -> Result<String> {
+        match self {
+            CollabCommands::Append { .. } => Ok(markdown::append(current, content)),
+            CollabCommands::InsertAfterHeading { heading, .. } => {
+                markdown::insert_after_heading(current, heading, content)
+                    .with_context(|| format!("Heading not found: {}", heading))
+            }
+            CollabCommands::ReplaceSection { heading, .. } => {
+                markdown::replace_section(current, heading, content)
+                    .with_context(|| format!("Heading not found: {}", heading))
+            }
+            CollabCommands::Tail { .. } => anyhow::bail!("Tail does not edit the document"),
+        }
+    }
+}
+
+/// Use the given text, or read it from stdin
+fn read_text(text: &Option<String>) -> Result<String> {
+    if let Some(text) = text {
+        return Ok(text.clone());
+    }
+
+    let mut buffer = String::new();
+    std::io::stdin()
+        .read_to_string(&mut buffer)
+        .context("Failed to read text from stdin")?;
+    Ok(buffer)
+}
+
+/// Connect to a document's collaboration session and wait for the initial sync
+async fn connect(id: &str) -> Result<(CollaborationClient, mpsc::Receiver<CollaborationEvent>)> {
+    let config = Config::load()?;
+    let api_base_url = config.get_api_base_url()?;
+    let api_token = auth::get_access_token().await?;
+
+    let (mut client, mut rx) = start_collaboration(api_base_url, api_token, id.to_string()).await?;
+    client.connect().await?;
+
+    let synced = tokio::time::timeout(SYNC_TIMEOUT, async {
+        while let Some(event) = rx.recv().await {
+            match event {
+                CollaborationEvent::StatusChanged(ConnectionStatus::Synced) => return Ok(()),
+                CollaborationEvent::StatusChanged(ConnectionStatus::Error(e)) => {
+                    anyhow::bail!("Connection failed: {}", e)
+                }
+                CollaborationEvent::StatusChanged(ConnectionStatus::Disconnected) => {
+                    anyhow::bail!("Disconnected before the document was synced")
+                }
+                _ => {}
+            }
+        }
+        anyhow::bail!("Connection closed before the document was synced")
+    })
+    .await
+    .context("Timed out waiting for the document to sync")?;
+
+    if let Err(e) = synced {
+        client.disconnect().await.ok();
+        return Err(e);
+    }
+
+    Ok((client, rx))
+}
+
+/// Apply an edit to the live document as a minimal CRDT update
+async fn edit_document<F>(id: &str, edit: F) -> Result<()>
+where
+    F: FnOnce(&str) -> Result<String>,
+{
+    let (mut client, _rx) = connect(id).await?;
+    let doc_sync = client.doc_sync();
+
+    let result = async {
+        let current = doc_sync.get_text()?;
+        let updated = edit(&current)?;
+
+        if let Some(update) = doc_sync.apply_local_text(&updated)? {
+            // Stays pending until the server acknowledges storing it
+            client.send_update(update).await?;
+            tokio::time::timeout(SYNC_TIMEOUT, async {
+                while !doc_sync.pending_updates()?.is_empty() {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                Ok::<_, anyhow::Error>(())
+            })
+            .await
+            .context("Timed out waiting for the server to store the edit")??;
+        }
+        Ok(())
+    }
+    .await;
+
+    // Flushes the queued update before closing the socket
+    client.disconnect().await?;
+    result
+}
+
+/// Print the document, then a line diff for every remote change until interrupted
+async fn tail_document(id: &str) -> Result<()> {
+    let (mut client, mut rx) = connect(id).await?;
+
+    let mut current = client.doc_sync().get_text()?;
+    print!("{}", format_snapshot(&current));
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Some(CollaborationEvent::DocumentUpdated(text)) => {
+                    if text != current {
+                        print!("{}", format_change(&current, &text));
+                        current = text;
+                    }
+                }
+                Some(CollaborationEvent::StatusChanged(ConnectionStatus::Disconnected)) | None => {
+                    eprintln!("Connection closed");
+                    break;
+                }
+                Some(CollaborationEvent::StatusChanged(ConnectionStatus::Error(e))) => {
+                    anyhow::bail!("Connection error: {}", e);
+                }
+                Some(CollaborationEvent::Error(e)) => {
+                    eprintln!("Error: {}", e);
+                }
+                Some(_) => {}
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    client.disconnect().await.ok();
+    Ok(())
+}
+
+/// Format the full document for the start of a tail, ending with a newline
+fn format_snapshot(text: &str) -> String {
+    let mut snapshot = text.to_string();
+    if !snapshot.is_empty() && !snapshot.ends_with('\n') {
+        snapshot.push('\n');
+    }
+    snapshot
+}
+
+/// Format one remote change as an `@@` marker followed by its line diff
+fn format_change(old: &str, new: &str) -> String {
+    let mut change = String::from("@@\n");
+    for line in line_diff(old, new) {
+        change.push_str(&line);
+        change.push('\n');
+    }
+    change
+}
+
+/// The lines that changed between two versions, trimming the common prefix and suffix
+///
+/// Removed lines are prefixed with `- ` and come before the added lines, prefixed with `+ `.
+fn line_diff(old: &str, new: &str) -> Vec<String> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let removed = old_lines[prefix..old_lines.len() - suffix]
+        .iter()
+        .map(|line| format!("- {}", line));
+    let added = new_lines[prefix..new_lines.len() - suffix]
+        .iter()
+        .map(|line| format!("+ {}", line));
+    removed.chain(added).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(kind: &str, heading: &str) -> CollabCommands {
+        let id = "doc".to_string();
+        let heading = heading.to_string();
+        match kind {
+            "append" => CollabCommands::Append { id, text: None },
+            "insert" => CollabCommands::InsertAfterHeading { id, heading, text: None },
+            "replace" => CollabCommands::ReplaceSection { id, heading, text: None },
+            _ => CollabCommands::Tail { id },
+        }
+    }
+
+    #[test]
+    fn test_line_diff() {
+        assert!(line_diff("a\nb\n", "a\nb\n").is_empty());
+        assert_eq!(line_diff("a\nb\nc\n", "a\nB\nc\n"), vec!["- b", "+ B"]);
+        assert_eq!(line_diff("a\nc\n", "a\nb\nc\n"), vec!["+ b"]);
+        assert_eq!(line_diff("a\nb\nc", "a\nc"), vec!["- b"]);
+        assert_eq!(line_diff("", "new"), vec!["+ new"]);
+        assert_eq!(line_diff("a\nb", "x\ny"), vec!["- a", "- b", "+ x", "+ y"]);
+    }
+
+    #[test]
+    fn test_line_diff_repeated_lines() {
+        // The common suffix never overlaps the common prefix
+        assert_eq!(line_diff("a\na\n", "a\na\na\n"), vec!["+ a"]);
+        assert_eq!(line_diff("a\na\na\n", "a\n"), vec!["- a", "- a"]);
+    }
+
+    #[test]
+    fn test_tail_formatting() {
+        assert_eq!(format_snapshot(""), "");
+        assert_eq!(format_snapshot("# Title"), "# Title\n");
+        assert_eq!(format_snapshot("# Title\n"), "# Title\n");
+
+        assert_eq!(format_change("one\ntwo\n", "one\n2\n"), "@@\n- two\n+ 2\n");
+        // A change to trailing whitespace only still gets a marker
+        assert_eq!(format_change("one", "one\n"), "@@\n");
+    }
+
+    #[test]
+    fn test_edited_text() {
+        let doc = "# Intro\nhello\n\n# Notes\nold\n";
+
+        assert_eq!(
+            command("append", "").edited_text(doc, "bye").unwrap(),
+            "# Intro\nhello\n\n# Notes\nold\nbye\n"
+        );
+        assert_eq!(
+            command("insert", "notes").edited_text(doc, "first").unwrap(),
+            "# Intro\nhello\n\n# Notes\nfirst\nold\n"
+        );
+        assert_eq!(
+            command("replace", "Intro").edited_text(doc, "hi").unwrap(),
+            "# Intro\nhi\n\n# Notes\nold\n"
+        );
+    }
+
+    #[test]
+    fn test_edited_text_missing_heading() {
+        let doc = "# Intro\nhello\n";
+        for kind in ["insert", "replace"] {
+            let err = command(kind, "Missing").edited_text(doc, "x").unwrap_err();
+            assert_eq!(err.to_string(), "Heading not found: Missing");
+        }
+        assert!(command("tail", "").edited_text(doc, "x").is_err());
+    }
+}
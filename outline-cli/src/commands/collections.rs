@@ -4,7 +4,6 @@ use std::fs;
 
 use outline_api::{
     OutlineClient,
-    auth,
     ExportFormat,
     ImportFormat,
     Permission,
@@ -297,7 +296,7 @@ impl CollectionsCommands {
                 let request = ExportCollectionRequest::new(id.clone(), export_format);
                 let data = client.export_collection(request).await?;
 
-                fs::write(&output, data)
+                fs::write(output, data)
                     .context(format!("Failed to write export to {}", output))?;
 
                 println!("✓ Collection exported to: {}", output);
@@ -314,7 +313,7 @@ impl CollectionsCommands {
                 let request = ExportAllCollectionsRequest::new(export_format);
                 let data = client.export_all_collections(request).await?;
 
-                fs::write(&output, data)
+                fs::write(output, data)
                     .context(format!("Failed to write export to {}", output))?;
 
                 println!("✓ All collections exported to: {}", output);
@@ -330,7 +329,7 @@ impl CollectionsCommands {
                     _ => anyhow::bail!("Invalid format '{}'. Use: markdown, html, docx, notion, or confluence", format),
                 };
 
-                let file_data = fs::read(&file)
+                let file_data = fs::read(file)
                     .context(format!("Failed to read file: {}", file))?;
 
                 let request = ImportFileToCollectionRequest::new(
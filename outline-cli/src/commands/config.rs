@@ -32,7 +32,7 @@ impl ConfigCommands {
 
                 println!("Current configuration:");
                 println!();
-                println!("Instance URL: {}", config.instance_url.as_ref().map(|s| s.as_str()).unwrap_or("Not set"));
+                println!("Instance URL: {}", config.instance_url.as_deref().unwrap_or("Not set"));
                 println!("API Base URL: {}", config.get_api_base_url().unwrap_or_else(|_| "Not set".to_string()));
                 println!("Output format: {}", config.output_format);
 
@@ -16,8 +16,7 @@ use outline_api::{
     ViewedDocumentsRequest,
     DraftsRequest,
     TemplatesRequest,
-    ExportDocumentRequest,
-    auth
+    ExportDocumentRequest
 };
 use crate::config::Config;
 
@@ -119,6 +118,10 @@ pub enum DocumentsCommands {
         /// Publish the document
         #[arg(long)]
         publish: Option<bool>,
+
+        /// Only update if the document is still at this revision
+        #[arg(long)]
+        if_revision: Option<u32>,
     },
 
     /// Delete a document
@@ -360,6 +363,7 @@ impl DocumentsCommands {
                     println!("Collection: {}", doc.collection_id.as_deref().unwrap_or("None"));
                     println!("Created: {}", doc.created_at);
                     println!("Updated: {}", doc.updated_at);
+                    println!("Revision: {}", doc.revision);
                     println!("\n--- Content ---\n");
                     println!("{}", doc.text);
                 }
@@ -401,6 +405,7 @@ impl DocumentsCommands {
                 text,
                 emoji,
                 publish,
+                if_revision,
             } => {
                 let request = UpdateDocumentRequest {
                     id: id.clone(),
@@ -412,7 +417,10 @@ impl DocumentsCommands {
                     done: None,
                 };
 
-                let doc = client.update_document(request).await?;
+                let doc = match if_revision {
+                    Some(revision) => client.update_document_if_revision(request, *revision).await?,
+                    None => client.update_document(request).await?,
+                };
 
                 if output_format.is_json() {
                     crate::output::output_json(&doc)?;
@@ -420,6 +428,7 @@ impl DocumentsCommands {
                     println!("Document updated successfully!");
                     println!("ID: {}", doc.id);
                     println!("Title: {}", doc.title);
+                    println!("Revision: {}", doc.revision);
                 }
             }
 
@@ -531,7 +540,7 @@ impl DocumentsCommands {
                 let mut request = MoveDocumentRequest::new(id.clone());
                 request.collection_id = collection_id.clone();
                 request.parent_document_id = parent_id.clone();
-                request.index = index.clone();
+                request.index = *index;
                 let doc = client.move_document(request).await?;
                 println!("✓ Document moved: {}", doc.title);
             }
@@ -602,7 +611,7 @@ impl DocumentsCommands {
                 let request = ExportDocumentRequest::new(id.clone(), export_format);
                 let data = client.export_document(request).await?;
 
-                fs::write(&output, data)
+                fs::write(output, data)
                     .context(format!("Failed to write to file: {}", output))?;
 
                 println!("✓ Document exported to: {}", output);
@@ -621,7 +630,7 @@ fn display_document_tree(documents: &[Document]) {
     for doc in documents {
         by_collection
             .entry(doc.collection_id.clone())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(doc);
     }
 
@@ -642,7 +651,7 @@ fn display_document_tree(documents: &[Document]) {
             all_doc_ids.insert(doc.id.clone(), doc);
             children
                 .entry(doc.parent_document_id.clone())
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(doc);
         }
 
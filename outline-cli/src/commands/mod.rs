@@ -1,5 +1,6 @@
 pub mod attachments;
 pub mod auth;
+pub mod collab;
 pub mod collections;
 pub mod comments;
 pub mod config;
@@ -10,6 +11,7 @@ pub mod users;
 
 pub use attachments::AttachmentsCommands;
 pub use auth::AuthCommands;
+pub use collab::CollabCommands;
 pub use collections::CollectionsCommands;
 pub use comments::CommentsCommands;
 pub use config::ConfigCommands;
@@ -3,7 +3,6 @@ use clap::Subcommand;
 
 use outline_api::{
     OutlineClient,
-    auth,
     CreateShareRequest,
     UpdateShareRequest,
     ListSharesRequest,
@@ -11,6 +11,11 @@ pub struct Config {
     /// Default output format
     #[serde(default = "default_output_format")]
     pub output_format: String,
+
+    /// Settings owned by other tools sharing this file (e.g. the MCP server's
+    /// `[policy]`), kept so saving doesn't drop them
+    #[serde(flatten)]
+    pub other: toml::Table,
 }
 
 fn default_output_format() -> String {
@@ -22,6 +27,7 @@ impl Default for Config {
         Self {
             instance_url: None,
             output_format: default_output_format(),
+            other: toml::Table::new(),
         }
     }
 }
@@ -113,6 +119,25 @@ mod tests {
         assert_eq!(api_url, "https://outline.example.com/api");
     }
 
+    #[test]
+    fn test_unknown_settings_survive_round_trip() {
+        let contents = r#"
+instance_url = "https://outline.example.com"
+prompts_dir = "/srv/prompts"
+
+[policy]
+read_only = true
+deny_collections = ["hr"]
+"#;
+        let config: Config = toml::from_str(contents).unwrap();
+        let saved = toml::to_string_pretty(&config).unwrap();
+        let reloaded: toml::Table = toml::from_str(&saved).unwrap();
+
+        assert_eq!(reloaded["prompts_dir"].as_str(), Some("/srv/prompts"));
+        assert_eq!(reloaded["policy"]["read_only"].as_bool(), Some(true));
+        assert_eq!(reloaded["policy"]["deny_collections"][0].as_str(), Some("hr"));
+    }
+
     #[test]
     fn test_api_base_url_trailing_slash() {
         let mut config = Config::default();
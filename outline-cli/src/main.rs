@@ -5,7 +5,7 @@ mod output;
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use commands::{
-    AttachmentsCommands, AuthCommands, CollectionsCommands, CommentsCommands,
+    AttachmentsCommands, AuthCommands, CollabCommands, CollectionsCommands, CommentsCommands,
     ConfigCommands, DocumentsCommands, GroupsCommands, SharesCommands, UsersCommands
 };
 
@@ -77,6 +77,12 @@ enum Commands {
         #[command(subcommand)]
         command: AttachmentsCommands,
     },
+
+    /// Make live edits through the collaboration server
+    Collab {
+        #[command(subcommand)]
+        command: CollabCommands,
+    },
 }
 
 #[tokio::main]
@@ -97,5 +103,6 @@ async fn main() -> Result<()> {
         Commands::Groups { command } => command.execute().await,
         Commands::Shares { command } => command.execute().await,
         Commands::Attachments { command } => command.execute().await,
+        Commands::Collab { command } => command.execute().await,
     }
 }
@@ -0,0 +1,229 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use outline_api::Attachment;
+use std::path::Path;
+
+/// Largest file `outline_attachments_upload` accepts
+pub const MAX_UPLOAD_BYTES: u64 = 25 * 1024 * 1024;
+
+/// Largest file `outline_attachments_get` downloads
+pub const MAX_DOWNLOAD_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Image types MCP clients can be expected to display
+const INLINE_IMAGE_TYPES: [&str; 4] = ["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// Decode base64 file data, accepting a `data:` URL prefix
+///
+/// Returns the data and the content type named in the prefix, if any.
+pub fn decode_base64(data: &str) -> Result<(Vec<u8>, Option<String>)> {
+    let (content_type, payload) = match data.strip_prefix("data:").and_then(|rest| rest.split_once(',')) {
+        Some((header, payload)) => {
+            let content_type = header.strip_suffix(";base64").unwrap_or(header);
+            (Some(content_type.to_string()).filter(|ct| !ct.is_empty()), payload)
+        }
+        None => (None, data),
+    };
+
+    // Base64 is a third larger than the data; refuse before decoding anything huge.
+    // Line breaks don't count, so wrapped data at the limit is still accepted.
+    let encoded = payload.bytes().filter(|b| !b.is_ascii_whitespace()).count();
+    if encoded as u64 > MAX_UPLOAD_BYTES.div_ceil(3) * 4 {
+        anyhow::bail!("File is larger than the {} upload limit", size(MAX_UPLOAD_BYTES));
+    }
+
+    let compact: String = payload.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(compact)
+        .context("Invalid base64 data")?;
+    check_upload_size(bytes.len() as u64)?;
+    Ok((bytes, content_type))
+}
+
+/// Read a local file for upload, checking its size first
+pub fn read_local(path: &Path) -> Result<Vec<u8>> {
+    let metadata = std::fs::metadata(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if !metadata.is_file() {
+        anyhow::bail!("{} is not a file", path.display());
+    }
+    check_upload_size(metadata.len())?;
+    std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+fn check_upload_size(bytes: u64) -> Result<()> {
+    if bytes > MAX_UPLOAD_BYTES {
+        anyhow::bail!("File is {}, more than the {} upload limit", size(bytes), size(MAX_UPLOAD_BYTES));
+    }
+    if bytes == 0 {
+        anyhow::bail!("File is empty");
+    }
+    Ok(())
+}
+
+/// Content type from a file name's extension
+pub fn guess_content_type(name: &str) -> String {
+    mime_guess::from_path(name)
+        .first_or_octet_stream()
+        .essence_str()
+        .to_string()
+}
+
+/// Whether an MCP client can be sent this content type as image content
+pub fn is_inline_image(content_type: &str) -> bool {
+    INLINE_IMAGE_TYPES.contains(&content_type)
+}
+
+/// Markdown that embeds an attachment: an image for images, a link otherwise
+pub fn embed_markdown(attachment: &Attachment, alt: Option<&str>) -> String {
+    let label = alt.unwrap_or(&attachment.name).replace(['[', ']'], "");
+    if attachment.content_type.starts_with("image/") {
+        format!("![{}]({})", label, attachment.url)
+    } else {
+        format!("[{}]({})", label, attachment.url)
+    }
+}
+
+/// Human-readable file size
+pub fn size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::header, response::Response, routing::post, Router};
+    use outline_api::OutlineClient;
+
+    fn encode(data: &[u8]) -> String {
+        base64::engine::general_purpose::STANDARD.encode(data)
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(decode_base64("aGVsbG8=").unwrap(), (b"hello".to_vec(), None));
+        // Line-wrapped data, as base64 tools often produce
+        assert_eq!(decode_base64("aGVs\nbG8=\n").unwrap().0, b"hello");
+    }
+
+    #[test]
+    fn test_decode_base64_data_urls() {
+        assert_eq!(
+            decode_base64("data:image/png;base64,aGVsbG8=").unwrap(),
+            (b"hello".to_vec(), Some("image/png".to_string()))
+        );
+        assert_eq!(
+            decode_base64("data:text/plain,aGVsbG8=").unwrap(),
+            (b"hello".to_vec(), Some("text/plain".to_string()))
+        );
+        // No content type in the prefix
+        assert_eq!(decode_base64("data:;base64,aGVsbG8=").unwrap(), (b"hello".to_vec(), None));
+    }
+
+    #[test]
+    fn test_decode_base64_rejects_invalid_data() {
+        let err = decode_base64("not base64!").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid base64 data");
+        assert!(decode_base64("data:image/png;base64,@@@").is_err());
+        assert_eq!(decode_base64("").unwrap_err().to_string(), "File is empty");
+    }
+
+    #[test]
+    fn test_decode_base64_upload_limit() {
+        let at_limit = vec![7u8; MAX_UPLOAD_BYTES as usize];
+        let encoded = encode(&at_limit);
+        assert_eq!(decode_base64(&encoded).unwrap().0.len(), at_limit.len());
+
+        // Wrapping doesn't push data at the limit over it
+        let wrapped: String = encoded
+            .as_bytes()
+            .chunks(76)
+            .map(|line| format!("{}\r\n", std::str::from_utf8(line).unwrap()))
+            .collect();
+        assert_eq!(decode_base64(&wrapped).unwrap().0.len(), at_limit.len());
+
+        let over = encode(&vec![7u8; MAX_UPLOAD_BYTES as usize + 1]);
+        let err = decode_base64(&over).unwrap_err();
+        assert_eq!(err.to_string(), "File is 25.0 MB, more than the 25.0 MB upload limit");
+
+        // Far over the limit is refused without decoding
+        let huge = "A".repeat((MAX_UPLOAD_BYTES as usize + 3) / 3 * 4 + 4);
+        let err = decode_base64(&huge).unwrap_err();
+        assert_eq!(err.to_string(), "File is larger than the 25.0 MB upload limit");
+    }
+
+    #[test]
+    fn test_read_local_limits() {
+        let dir = std::env::temp_dir().join(format!("outline-mcp-attachments-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let empty = dir.join("empty.txt");
+        std::fs::write(&empty, b"").unwrap();
+        assert_eq!(read_local(&empty).unwrap_err().to_string(), "File is empty");
+
+        let over = dir.join("over.bin");
+        let file = std::fs::File::create(&over).unwrap();
+        file.set_len(MAX_UPLOAD_BYTES + 1).unwrap();
+        assert!(read_local(&over).unwrap_err().to_string().contains("upload limit"));
+
+        let at_limit = dir.join("at_limit.bin");
+        let file = std::fs::File::create(&at_limit).unwrap();
+        file.set_len(MAX_UPLOAD_BYTES).unwrap();
+        assert_eq!(read_local(&at_limit).unwrap().len() as u64, MAX_UPLOAD_BYTES);
+
+        assert!(read_local(&dir).unwrap_err().to_string().ends_with("is not a file"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// Serve `attachments.redirect` with the given body, optionally without a length
+    async fn serve_attachment(size: usize, chunked: bool) -> OutlineClient {
+        let app = Router::new().route(
+            "/api/attachments.redirect",
+            post(move || async move {
+                let body = if chunked {
+                    let chunks = vec![Ok::<_, std::io::Error>(vec![1u8; size / 2]), Ok(vec![1u8; size - size / 2])];
+                    Body::from_stream(futures::stream::iter(chunks))
+                } else {
+                    Body::from(vec![1u8; size])
+                };
+                Response::builder()
+                    .header(header::CONTENT_TYPE, "image/png")
+                    .body(body)
+                    .unwrap()
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        OutlineClient::new(format!("http://{}/api", addr)).unwrap().with_token("token".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_download_limit() {
+        const LIMIT: u64 = 1024;
+
+        for chunked in [false, true] {
+            let client = serve_attachment(LIMIT as usize, chunked).await;
+            let content = client.download_attachment("a".to_string(), LIMIT).await.unwrap();
+            assert_eq!(content.data.len() as u64, LIMIT);
+            assert_eq!(content.content_type, "image/png");
+
+            let client = serve_attachment(LIMIT as usize + 1, chunked).await;
+            let err = client.download_attachment("a".to_string(), LIMIT).await.unwrap_err();
+            assert!(err.to_string().contains("1024 byte limit"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_size() {
+        assert_eq!(size(0), "0 B");
+        assert_eq!(size(1023), "1023 B");
+        assert_eq!(size(1536), "1.5 KB");
+        assert_eq!(size(MAX_DOWNLOAD_BYTES), "10.0 MB");
+    }
+}
@@ -0,0 +1,583 @@
+use anyhow::{Context, Result};
+use axum::http::request::Parts;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rmcp::model::{CallToolResult, JsonObject, RawContent};
+use rmcp::ErrorData;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// String parameters longer than this are replaced by their length
+const MAX_LOGGED_STRING: usize = 200;
+
+/// Most recent calls counted by the `outline://audit/summary` resource
+pub const MAX_SUMMARY_ENTRIES: usize = 10_000;
+
+/// Bytes read at a time when reading the log from the end
+const TAIL_CHUNK: usize = 64 * 1024;
+
+/// Parameter names whose values are never logged
+const REDACTED_KEYS: [&str; 4] = ["token", "password", "secret", "data"];
+
+/// Whether a tool call succeeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Ok,
+    Error,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    /// When the call finished (RFC 3339, UTC)
+    pub timestamp: String,
+    pub tool: String,
+    /// Client name from the MCP handshake
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    /// MCP session ID (HTTP transport)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    /// Fingerprint of the bearer token the call was made with (HTTP transport)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Arguments with long text shortened and secrets removed
+    #[serde(default)]
+    pub params: Value,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub document_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collection_ids: Vec<String>,
+    pub outcome: Outcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Set when the server ran with the dry-run policy
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+    pub latency_ms: u64,
+}
+
+/// Who made a tool call
+#[derive(Debug, Clone, Default)]
+pub struct Caller {
+    /// Client name from the MCP handshake
+    pub client: Option<String>,
+    /// MCP session ID (HTTP transport)
+    pub session: Option<String>,
+    /// Fingerprint of the bearer token (HTTP transport)
+    pub token: Option<String>,
+}
+
+impl Caller {
+    /// Identify a caller by client name and, over HTTP, by the request's session and token
+    pub fn new(client: Option<String>, parts: Option<&Parts>) -> Self {
+        let Some(parts) = parts else {
+            return Self { client, ..Self::default() };
+        };
+        Self {
+            client,
+            session: parts
+                .headers
+                .get(crate::http::SESSION_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+            token: crate::http::bearer_token(&parts.headers).map(token_fingerprint),
+        }
+    }
+}
+
+/// Short fingerprint of a bearer token: tells tokens apart without revealing them
+pub fn token_fingerprint(token: &str) -> String {
+    Sha256::digest(token.as_bytes())[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Per-tool totals over an audit log
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCounters {
+    pub calls: u64,
+    pub errors: u64,
+    pub average_latency_ms: u64,
+    pub max_latency_ms: u64,
+    pub last_call: String,
+    #[serde(skip)]
+    total_latency_ms: u64,
+}
+
+/// Append-only JSONL log of tool calls, shared by every session
+pub struct AuditLog {
+    path: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            file: Mutex::new(None),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record a finished tool call; failures to write are logged, never returned
+    pub fn record(
+        &self,
+        tool: &str,
+        arguments: Option<&JsonObject>,
+        caller: Caller,
+        result: &Result<CallToolResult, ErrorData>,
+        latency: Duration,
+        dry_run: bool,
+    ) {
+        let params = arguments.map_or(Value::Null, |args| sanitize(&Value::Object(args.clone()), None));
+        let (mut document_ids, mut collection_ids) = affected_ids(tool, arguments);
+        if let Ok(result) = result {
+            created_ids(tool, result, &mut document_ids, &mut collection_ids);
+        }
+
+        let (outcome, error) = match result {
+            Ok(result) if result.is_error == Some(true) => (Outcome::Error, Some(result_text(result))),
+            Ok(_) => (Outcome::Ok, None),
+            Err(e) => (Outcome::Error, Some(e.message.to_string())),
+        };
+
+        let entry = AuditEntry {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            tool: tool.to_string(),
+            client: caller.client,
+            session: caller.session,
+            token: caller.token,
+            params,
+            document_ids,
+            collection_ids,
+            outcome,
+            error,
+            dry_run,
+            latency_ms: latency.as_millis() as u64,
+        };
+
+        if let Err(e) = self.append(&entry) {
+            tracing::warn!("Failed to write audit log {}: {:#}", self.path.display(), e);
+        }
+    }
+
+    fn append(&self, entry: &AuditEntry) -> Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let mut file = self.file.lock().unwrap();
+        if file.is_none() {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir).context("Failed to create audit log directory")?;
+            }
+            let opened = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .context("Failed to open audit log")?;
+            *file = Some(opened);
+        }
+
+        // One write per line keeps lines whole even with several writers
+        let file = file.as_mut().expect("audit log opened above");
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Read every entry of an audit log, skipping lines that don't parse
+pub fn read_entries(path: &Path) -> Result<Vec<AuditEntry>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to open {}", path.display())),
+    };
+
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => tracing::debug!("Skipping malformed audit line: {}", e),
+        }
+    }
+    Ok(entries)
+}
+
+/// Read the last `max` entries of an audit log, oldest first
+///
+/// The log is read backwards from the end, so the cost depends on `max`
+/// rather than on how large the log has grown.
+pub fn read_recent_entries(path: &Path, max: usize) -> Result<Vec<AuditEntry>> {
+    read_recent_entries_in_chunks(path, max, TAIL_CHUNK)
+}
+
+fn read_recent_entries_in_chunks(path: &Path, max: usize, chunk_size: usize) -> Result<Vec<AuditEntry>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to open {}", path.display())),
+    };
+
+    let mut entries = Vec::new();
+    let mut pos = file.seek(SeekFrom::End(0))?;
+    // Start of the earliest line read so far, which may continue in the previous chunk
+    let mut partial: Vec<u8> = Vec::new();
+
+    while entries.len() < max {
+        let mut lines = if pos == 0 {
+            // Whatever is left is the first line of the file
+            let first = std::mem::take(&mut partial);
+            vec![first]
+        } else {
+            let size = (chunk_size as u64).min(pos) as usize;
+            pos -= size as u64;
+            let mut chunk = vec![0; size];
+            file.seek(SeekFrom::Start(pos))?;
+            file.read_exact(&mut chunk)?;
+            chunk.extend_from_slice(&partial);
+
+            let mut lines: Vec<Vec<u8>> = chunk.split(|&b| b == b'\n').map(<[u8]>::to_vec).collect();
+            partial = lines.remove(0);
+            lines
+        };
+
+        while let Some(line) = lines.pop() {
+            if entries.len() == max {
+                break;
+            }
+            let line = String::from_utf8_lossy(&line);
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => tracing::debug!("Skipping malformed audit line: {}", e),
+            }
+        }
+
+        if pos == 0 && partial.is_empty() {
+            break;
+        }
+    }
+
+    entries.reverse();
+    Ok(entries)
+}
+
+/// Parse a date (`YYYY-MM-DD`, midnight UTC) or an RFC 3339 timestamp
+pub fn parse_since(since: &str) -> Result<DateTime<Utc>> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(since) {
+        return Ok(at.with_timezone(&Utc));
+    }
+
+    let date = chrono::NaiveDate::parse_from_str(since, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{}': use YYYY-MM-DD or RFC 3339", since))?;
+    Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+}
+
+/// Totals per tool, by tool name
+pub fn summarize(entries: &[AuditEntry]) -> BTreeMap<String, ToolCounters> {
+    let mut counters: BTreeMap<String, ToolCounters> = BTreeMap::new();
+
+    for entry in entries {
+        let c = counters.entry(entry.tool.clone()).or_default();
+        c.calls += 1;
+        if entry.outcome == Outcome::Error {
+            c.errors += 1;
+        }
+        c.total_latency_ms += entry.latency_ms;
+        c.max_latency_ms = c.max_latency_ms.max(entry.latency_ms);
+        if entry.timestamp > c.last_call {
+            c.last_call = entry.timestamp.clone();
+        }
+    }
+    for c in counters.values_mut() {
+        c.average_latency_ms = c.total_latency_ms / c.calls.max(1);
+    }
+
+    counters
+}
+
+/// Filters for `outline-mcp audit`
+#[derive(Debug, Default)]
+pub struct Query {
+    pub tool: Option<String>,
+    pub document: Option<String>,
+    pub collection: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub errors_only: bool,
+}
+
+impl Query {
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.tool.as_ref().is_none_or(|tool| entry.tool == *tool || entry.tool == format!("outline_{}", tool))
+            && self.document.as_ref().is_none_or(|id| entry.document_ids.contains(id))
+            && self.collection.as_ref().is_none_or(|id| entry.collection_ids.contains(id))
+            && self.since.is_none_or(|since| {
+                DateTime::parse_from_rfc3339(&entry.timestamp).is_ok_and(|at| at >= since)
+            })
+            && (!self.errors_only || entry.outcome == Outcome::Error)
+    }
+}
+
+/// Shorten long strings and drop secrets, recursively
+fn sanitize(value: &Value, key: Option<&str>) -> Value {
+    if key.is_some_and(|k| REDACTED_KEYS.iter().any(|r| k.to_lowercase().contains(r))) {
+        return Value::String("[redacted]".to_string());
+    }
+
+    match value {
+        Value::String(s) if s.chars().count() > MAX_LOGGED_STRING => {
+            Value::String(format!("[{} chars]", s.chars().count()))
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|v| sanitize(v, None)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), sanitize(v, Some(k))))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Document and collection IDs named in a tool's arguments
+fn affected_ids(tool: &str, arguments: Option<&JsonObject>) -> (Vec<String>, Vec<String>) {
+    let mut documents = Vec::new();
+    let mut collections = Vec::new();
+    let Some(args) = arguments else {
+        return (documents, collections);
+    };
+    let get = |key: &str| args.get(key).and_then(Value::as_str).map(str::to_string);
+
+    // `id` means a different kind of object depending on the tool
+    if let Some(id) = get("id") {
+        if tool.starts_with("outline_documents_") {
+            documents.push(id);
+        } else if tool.starts_with("outline_collections_") {
+            collections.push(id);
+        }
+    }
+    documents.extend(get("documentId"));
+    documents.extend(get("parentDocumentId"));
+    collections.extend(get("collectionId"));
+
+    (documents, collections)
+}
+
+/// IDs of objects a creating tool returned
+fn created_ids(tool: &str, result: &CallToolResult, documents: &mut Vec<String>, collections: &mut Vec<String>) {
+    let target = match tool {
+        "outline_documents_create" | "outline_documents_duplicate" | "outline_documents_templatize" => &mut *documents,
+        "outline_collections_create" => &mut *collections,
+        _ => return,
+    };
+
+    let Ok(value) = serde_json::from_str::<Value>(&result_text(result)) else {
+        return;
+    };
+    let objects = match &value {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    for object in objects {
+        if let Some(id) = object.get("id").and_then(Value::as_str) {
+            if !target.iter().any(|existing| existing == id) {
+                target.push(id.to_string());
+            }
+        }
+    }
+}
+
+fn result_text(result: &CallToolResult) -> String {
+    result
+        .content
+        .iter()
+        .filter_map(|c| match &c.raw {
+            RawContent::Text(text) => Some(text.text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Render entries as one line each for the terminal
+pub fn render_entries(entries: &[&AuditEntry]) -> String {
+    let mut out = String::new();
+    for entry in entries {
+        let status = match entry.outcome {
+            Outcome::Ok if entry.dry_run => "dry-run",
+            Outcome::Ok => "ok",
+            Outcome::Error => "ERROR",
+        };
+        out.push_str(&format!(
+            "{}  {:<32} {:<7} {:>6}ms",
+            entry.timestamp, entry.tool, status, entry.latency_ms
+        ));
+        for id in &entry.document_ids {
+            out.push_str(&format!("  doc:{}", id));
+        }
+        for id in &entry.collection_ids {
+            out.push_str(&format!("  col:{}", id));
+        }
+        if let Some(client) = &entry.client {
+            out.push_str(&format!("  [{}]", client));
+        }
+        if let Some(token) = &entry.token {
+            out.push_str(&format!("  token:{}", token));
+        }
+        out.push('\n');
+        if let Some(error) = &entry.error {
+            out.push_str(&format!("    {}\n", error.lines().next().unwrap_or_default()));
+        }
+    }
+    out
+}
+
+/// Render per-tool counters as a table
+pub fn render_summary(counters: &BTreeMap<String, ToolCounters>) -> String {
+    let mut out = format!(
+        "{:<32} {:>7} {:>7} {:>9} {:>9}  {}\n",
+        "TOOL", "CALLS", "ERRORS", "AVG MS", "MAX MS", "LAST CALL"
+    );
+    for (tool, c) in counters {
+        out.push_str(&format!(
+            "{:<32} {:>7} {:>7} {:>9} {:>9}  {}\n",
+            tool, c.calls, c.errors, c.average_latency_ms, c.max_latency_ms, c.last_call
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(headers: &[(&str, &str)]) -> Parts {
+        let mut request = axum::http::Request::builder();
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        request.body(()).unwrap().into_parts().0
+    }
+
+    #[test]
+    fn test_token_fingerprint() {
+        let fingerprint = token_fingerprint("ol_api_secret");
+        assert_eq!(fingerprint.len(), 16);
+        assert_eq!(fingerprint, token_fingerprint("ol_api_secret"));
+        assert_ne!(fingerprint, token_fingerprint("ol_api_other"));
+        assert!(!fingerprint.contains("secret"));
+    }
+
+    #[test]
+    fn test_caller_from_request() {
+        let parts = parts(&[("authorization", "Bearer ol_api_secret"), ("mcp-session-id", "session-1")]);
+        let caller = Caller::new(Some("client".to_string()), Some(&parts));
+        assert_eq!(caller.client.as_deref(), Some("client"));
+        assert_eq!(caller.session.as_deref(), Some("session-1"));
+        assert_eq!(caller.token, Some(token_fingerprint("ol_api_secret")));
+
+        // Over stdio there is no request to identify the caller by
+        let caller = Caller::new(Some("client".to_string()), None);
+        assert!(caller.session.is_none() && caller.token.is_none());
+    }
+
+    #[test]
+    fn test_record_includes_caller_without_token() {
+        let path = std::env::temp_dir().join(format!("outline-mcp-audit-test-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let log = AuditLog::new(path.clone());
+
+        let parts = parts(&[("authorization", "Bearer ol_api_secret"), ("mcp-session-id", "session-1")]);
+        let result = Ok(CallToolResult::success(Vec::new()));
+        log.record("outline_documents_get", None, Caller::new(None, Some(&parts)), &result, Duration::from_millis(5), false);
+        log.record("outline_documents_get", None, Caller::default(), &result, Duration::from_millis(5), false);
+
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("ol_api_secret"));
+        let entries = read_entries(&path).unwrap();
+        assert_eq!(entries[0].session.as_deref(), Some("session-1"));
+        assert_eq!(entries[0].token, Some(token_fingerprint("ol_api_secret")));
+        assert!(entries[1].session.is_none() && entries[1].token.is_none());
+        let _ = fs::remove_file(&path);
+    }
+
+    fn write_log(name: &str, lines: &[String]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("outline-mcp-audit-{}-{}.jsonl", name, std::process::id()));
+        fs::write(&path, lines.join("\n")).unwrap();
+        path
+    }
+
+    fn entry_line(tool: &str) -> String {
+        serde_json::json!({
+            "timestamp": "2024-01-01T00:00:00Z",
+            "tool": tool,
+            "outcome": "ok",
+            "latencyMs": 1,
+        })
+        .to_string()
+    }
+
+    fn tools(entries: &[AuditEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.tool.as_str()).collect()
+    }
+
+    #[test]
+    fn test_read_recent_entries() {
+        let mut lines: Vec<String> = (0..20).map(|i| entry_line(&format!("tool_{}", i))).collect();
+        lines.insert(5, "not json".to_string());
+        lines.insert(10, String::new());
+        let path = write_log("recent", &lines);
+
+        let all = read_entries(&path).unwrap();
+        assert_eq!(all.len(), 20);
+
+        // Small chunks put line breaks (and lines) across chunk boundaries
+        for chunk_size in [1, 7, 64, TAIL_CHUNK] {
+            for max in [0, 1, 3, 19, 20, 50] {
+                let recent = read_recent_entries_in_chunks(&path, max, chunk_size).unwrap();
+                assert_eq!(tools(&recent), tools(&all[all.len().saturating_sub(max)..]), "{} {}", chunk_size, max);
+            }
+        }
+
+        assert!(read_recent_entries(&path.with_extension("missing"), 10).unwrap().is_empty());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_read_recent_entries_with_trailing_newline() {
+        let path = write_log("trailing", &[entry_line("a"), entry_line("b"), String::new()]);
+        assert_eq!(tools(&read_recent_entries(&path, 1).unwrap()), ["b"]);
+        assert_eq!(tools(&read_recent_entries(&path, 5).unwrap()), ["a", "b"]);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("2024-03-01").unwrap().to_rfc3339(), "2024-03-01T00:00:00+00:00");
+        assert_eq!(
+            parse_since("2024-03-01T12:30:00+02:00").unwrap().to_rfc3339(),
+            "2024-03-01T10:30:00+00:00"
+        );
+        let err = parse_since("last week").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid date 'last week': use YYYY-MM-DD or RFC 3339");
+    }
+}
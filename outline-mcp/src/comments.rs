@@ -0,0 +1,166 @@
+use outline_api::Comment;
+use std::collections::HashMap;
+
+/// Render comments as a thread tree, oldest thread first
+///
+/// Replies are nested under their parent comment; comments whose parent is
+/// not in the list are treated as thread roots.
+pub fn render_threads(comments: &[Comment]) -> String {
+    let ids: Vec<&str> = comments.iter().map(|c| c.id.as_str()).collect();
+    let mut replies: HashMap<&str, Vec<&Comment>> = HashMap::new();
+    let mut roots = Vec::new();
+
+    for comment in comments {
+        match comment.parent_comment_id.as_deref() {
+            Some(parent) if ids.contains(&parent) => {
+                replies.entry(parent).or_default().push(comment)
+            }
+            _ => roots.push(comment),
+        }
+    }
+
+    roots.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    for children in replies.values_mut() {
+        children.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    }
+
+    let resolved = roots.iter().filter(|c| c.resolved_at.is_some()).count();
+    let mut out = format!(
+        "{} thread(s), {} open, {} resolved\n",
+        roots.len(),
+        roots.len() - resolved,
+        resolved
+    );
+
+    for root in roots {
+        out.push('\n');
+        render_comment(&mut out, root, &replies, 0);
+    }
+
+    out
+}
+
+/// Render a single comment (without replies)
+pub fn render_comment_summary(comment: &Comment) -> String {
+    let mut out = String::new();
+    render_comment(&mut out, comment, &HashMap::new(), 0);
+    out
+}
+
+fn render_comment(
+    out: &mut String,
+    comment: &Comment,
+    replies: &HashMap<&str, Vec<&Comment>>,
+    depth: usize,
+) {
+    let indent = "  ".repeat(depth);
+    let marker = if depth > 0 { "└─ " } else { "" };
+
+    let status = match (&comment.resolved_at, &comment.resolved_by) {
+        (Some(at), Some(by)) => format!(" [resolved by {} at {}]", by.name, at),
+        (Some(at), None) => format!(" [resolved at {}]", at),
+        _ if depth == 0 => " [open]".to_string(),
+        _ => String::new(),
+    };
+
+    out.push_str(&format!(
+        "{}{}{} ({}) {}{}\n",
+        indent, marker, comment.created_by.name, comment.created_at, comment.id, status
+    ));
+
+    let body_indent = format!("{}{}", indent, if depth > 0 { "   " } else { "  " });
+    for line in comment.data.text.lines() {
+        out.push_str(&format!("{}{}\n", body_indent, line));
+    }
+
+    if let Some(children) = replies.get(comment.id.as_str()) {
+        for child in children {
+            render_comment(out, child, replies, depth + 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(id: &str, parent: Option<&str>, created_at: &str, text: &str) -> Comment {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "documentId": "doc",
+            "parentCommentId": parent,
+            "createdBy": { "id": "u1", "name": "Ada" },
+            "createdAt": created_at,
+            "updatedAt": created_at,
+            "data": { "text": text },
+        }))
+        .unwrap()
+    }
+
+    fn resolved(mut comment: Comment, by: Option<&str>) -> Comment {
+        comment.resolved_at = Some("2024-01-05".to_string());
+        comment.resolved_by = by.map(|name| {
+            serde_json::from_value(serde_json::json!({ "id": "u2", "name": name })).unwrap()
+        });
+        comment
+    }
+
+    #[test]
+    fn test_replies_nest_in_order() {
+        // Listed out of order: threads and replies are sorted by creation time
+        let comments = vec![
+            comment("r2", Some("a"), "2024-01-03", "second reply"),
+            comment("b", None, "2024-01-02", "later thread"),
+            comment("r1", Some("a"), "2024-01-02", "first reply"),
+            comment("rr", Some("r1"), "2024-01-04", "nested\nover two lines"),
+            comment("a", None, "2024-01-01", "question"),
+        ];
+
+        assert_eq!(
+            render_threads(&comments),
+            "2 thread(s), 2 open, 0 resolved\n\
+             \n\
+             Ada (2024-01-01) a [open]\n  question\n\
+             \x20 └─ Ada (2024-01-02) r1\n     first reply\n\
+             \x20   └─ Ada (2024-01-04) rr\n       nested\n       over two lines\n\
+             \x20 └─ Ada (2024-01-03) r2\n     second reply\n\
+             \n\
+             Ada (2024-01-02) b [open]\n  later thread\n"
+        );
+    }
+
+    #[test]
+    fn test_resolved_threads() {
+        let comments = vec![
+            resolved(comment("a", None, "2024-01-01", "done"), Some("Grace")),
+            resolved(comment("b", None, "2024-01-02", "also done"), None),
+            comment("c", None, "2024-01-03", "still open"),
+        ];
+
+        let out = render_threads(&comments);
+        assert!(out.starts_with("3 thread(s), 1 open, 2 resolved\n"));
+        assert!(out.contains("a [resolved by Grace at 2024-01-05]\n"));
+        assert!(out.contains("b [resolved at 2024-01-05]\n"));
+        assert!(out.contains("c [open]\n"));
+    }
+
+    #[test]
+    fn test_missing_parent_becomes_a_thread() {
+        // The parent was deleted or is on another page of results
+        let comments = vec![
+            comment("a", None, "2024-01-01", "root"),
+            comment("orphan", Some("gone"), "2024-01-02", "reply to nothing"),
+        ];
+
+        let out = render_threads(&comments);
+        assert!(out.starts_with("2 thread(s), 2 open, 0 resolved\n"));
+        assert!(out.contains("\nAda (2024-01-02) orphan [open]\n  reply to nothing\n"));
+    }
+
+    #[test]
+    fn test_render_comment_summary() {
+        let reply = comment("r", Some("a"), "2024-01-02", "just this");
+        assert_eq!(render_comment_summary(&reply), "Ada (2024-01-02) r [open]\n  just this\n");
+        assert_eq!(render_threads(&[]), "0 thread(s), 0 open, 0 resolved\n");
+    }
+}
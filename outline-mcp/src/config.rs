@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::policy::Policy;
+
 /// Configuration for the Outline MCP server
 /// Reuses the CLI's config file for consistency
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +15,18 @@ pub struct Config {
     /// Default output format (not used by MCP, but kept for compatibility)
     #[serde(default = "default_output_format")]
     pub output_format: String,
+
+    /// Directory of user-defined prompt files (default: ~/.outline-cli/prompts)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompts_dir: Option<PathBuf>,
+
+    /// Append-only JSONL log of tool calls (default: ~/.outline-cli/mcp-audit.jsonl)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit_log: Option<PathBuf>,
+
+    /// Restrictions on what the MCP server may read and change
+    #[serde(default)]
+    pub policy: Policy,
 }
 
 fn default_output_format() -> String {
@@ -24,6 +38,9 @@ impl Default for Config {
         Self {
             instance_url: None,
             output_format: default_output_format(),
+            prompts_dir: None,
+            audit_log: None,
+            policy: Policy::default(),
         }
     }
 }
@@ -66,6 +83,22 @@ impl Config {
             .context("Instance URL not configured. Run 'outline-cli config set-instance <url>' first.")
     }
 
+    /// Get the directory user-defined prompts are loaded from
+    pub fn prompts_dir(&self) -> Result<PathBuf> {
+        match &self.prompts_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => Ok(Self::config_dir()?.join("prompts")),
+        }
+    }
+
+    /// Get the path of the tool call audit log
+    pub fn audit_log(&self) -> Result<PathBuf> {
+        match &self.audit_log {
+            Some(path) => Ok(path.clone()),
+            None => Ok(Self::config_dir()?.join("mcp-audit.jsonl")),
+        }
+    }
+
     /// Get the API base URL (instance_url + /api)
     pub fn get_api_base_url(&self) -> Result<String> {
         let instance_url = self.get_instance_url()?;
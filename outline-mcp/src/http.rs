@@ -0,0 +1,386 @@
+use anyhow::{Context, Result};
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use rmcp::transport::streamable_http_server::{
+    session::local::{LocalSessionManager, SessionConfig},
+    StreamableHttpServerConfig, StreamableHttpService,
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+use crate::audit::AuditLog;
+use crate::config::Config;
+use crate::server::{self, OutlineServer};
+
+/// Path the MCP endpoint is served on
+const MCP_PATH: &str = "/mcp";
+
+/// Header carrying the MCP session ID
+pub const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Sessions idle for this long are closed
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Interval between SSE keep-alive pings
+const SSE_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// How long open connections get to finish after a shutdown signal
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Options for the HTTP transport
+#[derive(Debug, Clone)]
+pub struct HttpOptions {
+    /// Address to listen on
+    pub bind: SocketAddr,
+    /// Maximum number of concurrent sessions
+    pub max_sessions: usize,
+}
+
+/// Shared state for the request gate
+struct Gate {
+    sessions: Arc<LocalSessionManager>,
+    /// Bearer token each session was opened with
+    owners: Mutex<HashMap<String, String>>,
+    max_sessions: usize,
+}
+
+/// Extract the token from an `Authorization: Bearer <token>` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Run the MCP server with the streamable HTTP transport
+pub async fn serve(config: Config, options: HttpOptions) -> Result<()> {
+    let audit = Arc::new(AuditLog::new(config.audit_log()?));
+
+    // Fail at startup rather than on the first session if the config is incomplete
+    OutlineServer::for_http_session(&config, audit.clone())?;
+
+    let sessions = Arc::new(LocalSessionManager {
+        sessions: Default::default(),
+        session_config: SessionConfig {
+            keep_alive: Some(SESSION_IDLE_TIMEOUT),
+            ..Default::default()
+        },
+    });
+
+    let session_config = config.clone();
+    let service = StreamableHttpService::new(
+        move || OutlineServer::for_http_session(&session_config, audit.clone()).map_err(std::io::Error::other),
+        sessions.clone(),
+        StreamableHttpServerConfig {
+            sse_keep_alive: Some(SSE_KEEP_ALIVE),
+            stateful_mode: true,
+        },
+    );
+
+    let gate = Arc::new(Gate {
+        sessions: sessions.clone(),
+        owners: Mutex::new(HashMap::new()),
+        max_sessions: options.max_sessions,
+    });
+
+    let router = Router::new()
+        .route_service(MCP_PATH, service)
+        .layer(middleware::from_fn_with_state(gate, gate_requests));
+
+    let listener = tokio::net::TcpListener::bind(options.bind)
+        .await
+        .with_context(|| format!("Failed to bind {}", options.bind))?;
+
+    server::log_capabilities(&config);
+    tracing::info!(
+        address = %options.bind,
+        path = MCP_PATH,
+        max_sessions = options.max_sessions,
+        "Starting MCP server on HTTP"
+    );
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        let _ = shutdown_tx.send(true);
+    });
+
+    let mut graceful = shutdown_rx.clone();
+    let server = axum::serve(listener, router).with_graceful_shutdown(async move {
+        let _ = graceful.wait_for(|stop| *stop).await;
+        tracing::info!("Shutdown requested, draining connections");
+    });
+
+    // Long-lived SSE streams would keep a graceful shutdown waiting forever
+    let mut drain = shutdown_rx;
+    tokio::select! {
+        result = server => result.context("HTTP server error")?,
+        _ = async {
+            let _ = drain.wait_for(|stop| *stop).await;
+            tokio::time::sleep(DRAIN_TIMEOUT).await;
+        } => tracing::warn!("Connections still open after {:?}, closing them", DRAIN_TIMEOUT),
+    }
+
+    let open: Vec<_> = sessions.sessions.read().await.keys().cloned().collect();
+    for id in open {
+        use rmcp::transport::streamable_http_server::SessionManager;
+        if let Err(e) = sessions.close_session(&id).await {
+            tracing::warn!(session = %id, "Failed to close session: {}", e);
+        }
+    }
+
+    tracing::info!("Server shutdown complete");
+    Ok(())
+}
+
+/// Authenticate, enforce session ownership and limits, and log every request
+async fn gate_requests(State(gate): State<Arc<Gate>>, request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let session = request
+        .headers()
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let response = match check_request(&gate, request.headers(), session.as_deref()).await {
+        Err(rejection) => rejection,
+        Ok(token) => {
+            let response = next.run(request).await;
+            track_session(&gate, &method, session.as_deref(), &response, token);
+            response
+        }
+    };
+
+    let new_session = response
+        .headers()
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok());
+    tracing::info!(
+        method = %method,
+        path = %path,
+        status = response.status().as_u16(),
+        session = session.as_deref().or(new_session).unwrap_or("-"),
+        latency_ms = started.elapsed().as_millis() as u64,
+        "HTTP request"
+    );
+
+    response
+}
+
+/// Check a request may proceed, returning its bearer token or the rejection to send
+async fn check_request(gate: &Gate, headers: &HeaderMap, session: Option<&str>) -> Result<String, Response> {
+    let Some(token) = bearer_token(headers) else {
+        let mut response = reject(StatusCode::UNAUTHORIZED, "Missing bearer token: pass your Outline API token as 'Authorization: Bearer <token>'");
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        return Err(response);
+    };
+
+    match session {
+        Some(session) => {
+            // Unknown sessions are refused too, so a session is only ever used with the token that opened it
+            let owners = gate.owners.lock().unwrap();
+            match owners.get(session) {
+                Some(owner) if owner == token => {}
+                Some(_) => return Err(reject(StatusCode::FORBIDDEN, "Session belongs to a different token")),
+                None => return Err(reject(StatusCode::NOT_FOUND, "Unknown or expired session, start a new one")),
+            }
+        }
+        None => {
+            let open = gate.sessions.sessions.read().await.len();
+            if open >= gate.max_sessions {
+                tracing::warn!(open, max_sessions = gate.max_sessions, "Session limit reached");
+                return Err(reject(StatusCode::SERVICE_UNAVAILABLE, "Session limit reached, try again later"));
+            }
+        }
+    }
+
+    Ok(token.to_string())
+}
+
+/// Remember which token opened a session, and forget sessions that are closed
+fn track_session(gate: &Gate, method: &Method, session: Option<&str>, response: &Response, token: String) {
+    let mut owners = gate.owners.lock().unwrap();
+
+    if let Some(created) = response.headers().get(SESSION_HEADER).and_then(|v| v.to_str().ok()) {
+        if session.is_none() {
+            owners.insert(created.to_string(), token);
+        }
+    }
+
+    if *method == Method::DELETE && response.status().is_success() {
+        if let Some(session) = session {
+            owners.remove(session);
+        }
+    }
+
+    // Sessions also end by idling out; drop owners the session manager no longer knows
+    if let Ok(open) = gate.sessions.sessions.try_read() {
+        owners.retain(|id, _| open.keys().any(|open_id| open_id.as_ref() == id.as_str()));
+    }
+}
+
+fn reject(status: StatusCode, message: &'static str) -> Response {
+    (status, Body::from(message)).into_response()
+}
+
+/// Wait for Ctrl-C or (on Unix) SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::transport::streamable_http_server::SessionManager;
+
+    fn headers(authorization: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = authorization {
+            headers.insert(header::AUTHORIZATION, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn gate(max_sessions: usize) -> Gate {
+        Gate {
+            sessions: Arc::new(LocalSessionManager::default()),
+            owners: Mutex::new(HashMap::new()),
+            max_sessions,
+        }
+    }
+
+    /// Open a session in the session manager, as the MCP service would
+    async fn open_session(gate: &Gate) -> String {
+        let (id, _transport) = gate.sessions.create_session().await.unwrap();
+        id.to_string()
+    }
+
+    fn response_for(session: Option<&str>, status: StatusCode) -> Response {
+        let mut response = reject(status, "");
+        if let Some(session) = session {
+            response.headers_mut().insert(SESSION_HEADER, HeaderValue::from_str(session).unwrap());
+        }
+        response
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        assert_eq!(bearer_token(&headers(Some("Bearer abc"))), Some("abc"));
+        assert_eq!(bearer_token(&headers(Some("Bearer  abc "))), Some("abc"));
+        assert_eq!(bearer_token(&headers(Some("Bearer "))), None);
+        assert_eq!(bearer_token(&headers(Some("Basic abc"))), None);
+        assert_eq!(bearer_token(&headers(Some("bearer abc"))), None);
+        assert_eq!(bearer_token(&headers(None)), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_request_requires_a_token() {
+        let gate = gate(4);
+        let response = check_request(&gate, &headers(None), None).await.unwrap_err();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+    }
+
+    #[tokio::test]
+    async fn test_check_request_session_ownership() {
+        let gate = gate(4);
+        let session = open_session(&gate).await;
+        gate.owners.lock().unwrap().insert(session.clone(), "alice".to_string());
+
+        let token = check_request(&gate, &headers(Some("Bearer alice")), Some(&session)).await.unwrap();
+        assert_eq!(token, "alice");
+
+        let wrong = check_request(&gate, &headers(Some("Bearer bob")), Some(&session)).await.unwrap_err();
+        assert_eq!(wrong.status(), StatusCode::FORBIDDEN);
+
+        let missing = check_request(&gate, &headers(Some("Bearer alice")), Some("no-such-session")).await.unwrap_err();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_check_request_session_limit() {
+        let gate = gate(1);
+        assert!(check_request(&gate, &headers(Some("Bearer alice")), None).await.is_ok());
+
+        let session = open_session(&gate).await;
+        let full = check_request(&gate, &headers(Some("Bearer bob")), None).await.unwrap_err();
+        assert_eq!(full.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // Requests within an existing session aren't counted against the limit
+        gate.owners.lock().unwrap().insert(session.clone(), "alice".to_string());
+        assert!(check_request(&gate, &headers(Some("Bearer alice")), Some(&session)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_track_session() {
+        let gate = gate(4);
+        let session = open_session(&gate).await;
+
+        // The response to an initialize request carries the new session ID
+        let created = response_for(Some(&session), StatusCode::OK);
+        track_session(&gate, &Method::POST, None, &created, "alice".to_string());
+        assert_eq!(gate.owners.lock().unwrap().get(&session).map(String::as_str), Some("alice"));
+
+        // Later responses in the session don't change the owner
+        track_session(&gate, &Method::POST, Some(&session), &created, "bob".to_string());
+        assert_eq!(gate.owners.lock().unwrap().get(&session).map(String::as_str), Some("alice"));
+
+        // A failed delete keeps the session, a successful one forgets it
+        let failed = response_for(None, StatusCode::FORBIDDEN);
+        track_session(&gate, &Method::DELETE, Some(&session), &failed, "alice".to_string());
+        assert!(gate.owners.lock().unwrap().contains_key(&session));
+        let deleted = response_for(None, StatusCode::OK);
+        track_session(&gate, &Method::DELETE, Some(&session), &deleted, "alice".to_string());
+        assert!(!gate.owners.lock().unwrap().contains_key(&session));
+    }
+
+    #[tokio::test]
+    async fn test_track_session_forgets_expired_sessions() {
+        let gate = gate(4);
+        let open = open_session(&gate).await;
+        gate.owners.lock().unwrap().insert(open.clone(), "alice".to_string());
+        gate.owners.lock().unwrap().insert("idled-out".to_string(), "alice".to_string());
+
+        let response = response_for(None, StatusCode::OK);
+        track_session(&gate, &Method::POST, Some(&open), &response, "alice".to_string());
+
+        let owners = gate.owners.lock().unwrap();
+        assert!(owners.contains_key(&open));
+        assert!(!owners.contains_key("idled-out"));
+    }
+}
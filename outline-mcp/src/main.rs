@@ -1,11 +1,109 @@
+mod attachments;
+mod audit;
+mod comments;
 mod config;
+mod http;
+mod policy;
+mod prompts;
+mod resources;
+mod search;
+mod sections;
 mod server;
 
 use anyhow::Result;
-use tracing_subscriber;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::net::SocketAddr;
+
+/// MCP server for Outline
+#[derive(Parser)]
+#[command(name = "outline-mcp")]
+#[command(version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Transport to serve MCP over
+    #[arg(long, value_enum, default_value_t = Transport::Stdio)]
+    transport: Transport,
+
+    /// Address to listen on (http transport)
+    #[arg(long, default_value = "127.0.0.1:3000")]
+    bind: SocketAddr,
+
+    /// Maximum number of concurrent sessions (http transport)
+    #[arg(long, default_value_t = 32)]
+    max_sessions: usize,
+
+    /// Only register tools that don't modify the workspace
+    #[arg(long)]
+    read_only: bool,
+
+    /// Never publish documents; created documents stay drafts
+    #[arg(long)]
+    drafts_only: bool,
+
+    /// Destructive tools report what they would do instead of doing it
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Query the tool call audit log
+    Audit(AuditArgs),
+}
+
+#[derive(Args)]
+struct AuditArgs {
+    /// Only calls to this tool (with or without the outline_ prefix)
+    #[arg(long)]
+    tool: Option<String>,
+
+    /// Only calls that touched this document
+    #[arg(long)]
+    document: Option<String>,
+
+    /// Only calls that touched this collection
+    #[arg(long)]
+    collection: Option<String>,
+
+    /// Only calls since this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long)]
+    since: Option<String>,
+
+    /// Only failed calls
+    #[arg(long)]
+    errors: bool,
+
+    /// Show the most recent N matching calls
+    #[arg(long, default_value_t = 50)]
+    limit: usize,
+
+    /// Print per-tool counters instead of individual calls
+    #[arg(long)]
+    summary: bool,
+
+    /// Print matching entries as JSON lines
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Transport {
+    /// Serve a single client over stdin/stdout
+    Stdio,
+    /// Serve many clients over streamable HTTP/SSE; each authenticates with its own bearer token
+    Http,
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    if let Some(Command::Audit(args)) = cli.command {
+        return show_audit(args);
+    }
+
     // Initialize logging
     tracing_subscriber::fmt()
         .with_max_level(tracing::Level::INFO)
@@ -14,6 +112,57 @@ async fn main() -> Result<()> {
 
     tracing::info!("Starting Outline MCP server...");
 
+    // Flags can only tighten the policy from the config file
+    let mut config = config::Config::load()?;
+    config.policy.read_only |= cli.read_only;
+    config.policy.drafts_only |= cli.drafts_only;
+    config.policy.dry_run |= cli.dry_run;
+
     // Create and run the server
-    server::run().await
+    match cli.transport {
+        Transport::Stdio => server::run(config).await,
+        Transport::Http => {
+            http::serve(config, http::HttpOptions {
+                bind: cli.bind,
+                max_sessions: cli.max_sessions,
+            })
+            .await
+        }
+    }
+}
+
+/// Print entries or counters from the audit log
+fn show_audit(args: AuditArgs) -> Result<()> {
+    let config = config::Config::load()?;
+    let path = config.audit_log()?;
+
+    let since = args.since.as_deref().map(audit::parse_since).transpose()?;
+    let query = audit::Query {
+        tool: args.tool,
+        document: args.document,
+        collection: args.collection,
+        since,
+        errors_only: args.errors,
+    };
+
+    let entries = audit::read_entries(&path)?;
+    let matching: Vec<&audit::AuditEntry> = entries.iter().filter(|e| query.matches(e)).collect();
+
+    if args.summary {
+        let owned: Vec<audit::AuditEntry> = matching.into_iter().cloned().collect();
+        print!("{}", audit::render_summary(&audit::summarize(&owned)));
+        return Ok(());
+    }
+
+    let recent = &matching[matching.len().saturating_sub(args.limit)..];
+    if args.json {
+        for entry in recent {
+            println!("{}", serde_json::to_string(entry)?);
+        }
+    } else if recent.is_empty() {
+        eprintln!("No matching calls in {}", path.display());
+    } else {
+        print!("{}", audit::render_entries(recent));
+    }
+    Ok(())
 }
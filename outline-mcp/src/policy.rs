@@ -0,0 +1,276 @@
+use rmcp::model::Tool;
+use serde::{Deserialize, Serialize};
+
+/// Tools that report what they would do instead of doing it under `dry_run`
+const DRY_RUN_TOOLS: &[&str] = &[
+    "outline_documents_delete",
+    "outline_documents_restore",
+    "outline_collections_archive",
+];
+
+/// Safety policy applied to every tool, resource and prompt
+///
+/// Read from the `[policy]` table of the config file; command-line flags can
+/// only tighten it.
+///
+/// ```toml
+/// [policy]
+/// read_only = false
+/// allow_collections = ["3f2e..."]   # only these collections (empty = all)
+/// deny_collections = ["9c1d..."]    # never these collections
+/// drafts_only = true
+/// dry_run = true
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Policy {
+    /// Only register tools that don't modify the workspace
+    #[serde(default)]
+    pub read_only: bool,
+
+    /// Collections the server may touch; empty means every collection
+    #[serde(default)]
+    pub allow_collections: Vec<String>,
+
+    /// Collections the server may never touch, even if allowed above
+    #[serde(default)]
+    pub deny_collections: Vec<String>,
+
+    /// Never publish: created documents stay drafts and publishing is refused
+    #[serde(default)]
+    pub drafts_only: bool,
+
+    /// Destructive tools report what they would do instead of doing it
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl Policy {
+    /// Whether any collection filter is configured
+    pub fn filters_collections(&self) -> bool {
+        !self.allow_collections.is_empty() || !self.deny_collections.is_empty()
+    }
+
+    /// Whether a tool is registered at all
+    ///
+    /// Read-only mode drops every tool not annotated as read-only.
+    pub fn registers(&self, tool: &Tool) -> bool {
+        !self.read_only || tool.annotations.as_ref().and_then(|a| a.read_only_hint) == Some(true)
+    }
+
+    /// Whether a call to the named tool only reports what it would do
+    pub fn dry_runs(&self, tool: &str) -> bool {
+        self.dry_run && DRY_RUN_TOOLS.contains(&tool)
+    }
+
+    /// Whether content in the given collection may be read or changed
+    ///
+    /// Documents outside any collection (e.g. private drafts) are only
+    /// reachable when there is no allowlist.
+    pub fn allows_collection(&self, collection_id: Option<&str>) -> bool {
+        match collection_id {
+            Some(id) => {
+                !self.deny_collections.iter().any(|d| d == id)
+                    && (self.allow_collections.is_empty() || self.allow_collections.iter().any(|a| a == id))
+            }
+            None => self.allow_collections.is_empty(),
+        }
+    }
+
+    /// Fail unless the given collection is allowed
+    pub fn check_collection(&self, collection_id: Option<&str>) -> anyhow::Result<()> {
+        if self.allows_collection(collection_id) {
+            return Ok(());
+        }
+        match collection_id {
+            Some(id) => anyhow::bail!("Blocked by policy: collection {} is not allowed", id),
+            None => anyhow::bail!("Blocked by policy: only documents in allowed collections can be accessed"),
+        }
+    }
+
+    /// Fail if new collections would fall outside the allowlist
+    pub fn check_new_collection(&self) -> anyhow::Result<()> {
+        if !self.allow_collections.is_empty() {
+            anyhow::bail!("Blocked by policy: collections can't be created while allow_collections is set");
+        }
+        Ok(())
+    }
+
+    /// Fail if the policy forbids publishing
+    pub fn check_publish(&self, publish: Option<bool>) -> anyhow::Result<()> {
+        if self.drafts_only && publish == Some(true) {
+            anyhow::bail!("Blocked by policy: drafts-only mode does not allow publishing");
+        }
+        Ok(())
+    }
+
+    /// One-line summary for the startup log
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.read_only {
+            parts.push("read-only".to_string());
+        }
+        if self.drafts_only {
+            parts.push("drafts only".to_string());
+        }
+        if self.dry_run {
+            parts.push("dry run".to_string());
+        }
+        if !self.allow_collections.is_empty() {
+            parts.push(format!("{} allowed collection(s)", self.allow_collections.len()));
+        }
+        if !self.deny_collections.is_empty() {
+            parts.push(format!("{} denied collection(s)", self.deny_collections.len()));
+        }
+
+        if parts.is_empty() {
+            "unrestricted".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::OutlineServer;
+
+    /// Every tool with what the policy does to it: registered in read-only
+    /// mode, covered by dry run, and takes a `publish` flag (refused under drafts-only)
+    const TOOLS: &[(&str, bool, bool, bool)] = &[
+        ("outline_attachments_get", true, false, false),
+        ("outline_attachments_upload", false, false, false),
+        ("outline_collections_archive", false, true, false),
+        ("outline_collections_create", false, false, false),
+        ("outline_collections_get", true, false, false),
+        ("outline_collections_list", true, false, false),
+        ("outline_collections_move", false, false, false),
+        ("outline_collections_tree", true, false, false),
+        ("outline_collections_update", false, false, false),
+        ("outline_comments_create", false, false, false),
+        ("outline_comments_list", true, false, false),
+        ("outline_comments_resolve", false, false, false),
+        ("outline_comments_unresolve", false, false, false),
+        ("outline_comments_update", false, false, false),
+        ("outline_documents_archive", false, false, false),
+        ("outline_documents_create", false, false, true),
+        ("outline_documents_delete", false, true, false),
+        ("outline_documents_duplicate", false, false, true),
+        ("outline_documents_get", true, false, false),
+        ("outline_documents_get_section", true, false, false),
+        ("outline_documents_list", true, false, false),
+        ("outline_documents_move", false, false, false),
+        ("outline_documents_outline", true, false, false),
+        ("outline_documents_patch", false, false, false),
+        ("outline_documents_restore", false, true, false),
+        ("outline_documents_search", true, false, false),
+        ("outline_documents_templatize", false, false, false),
+        ("outline_documents_unarchive", false, false, false),
+        ("outline_documents_update", false, false, true),
+    ];
+
+    fn tools() -> Vec<Tool> {
+        let mut tools = OutlineServer::tool_router().list_all();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        tools
+    }
+
+    #[test]
+    fn test_tool_table_covers_every_tool() {
+        let names: Vec<String> = tools().iter().map(|t| t.name.to_string()).collect();
+        let expected: Vec<&str> = TOOLS.iter().map(|(name, ..)| *name).collect();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn test_read_only_registers_only_read_only_tools() {
+        let unrestricted = Policy::default();
+        let read_only = Policy { read_only: true, ..Policy::default() };
+
+        for (tool, &(name, reads, _, _)) in tools().iter().zip(TOOLS) {
+            assert!(unrestricted.registers(tool), "{}", name);
+            assert_eq!(read_only.registers(tool), reads, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_policy_tool_router_drops_unregistered_tools() {
+        let served = |policy: &Policy| {
+            let mut names: Vec<String> = OutlineServer::policy_tool_router(policy)
+                .list_all()
+                .iter()
+                .map(|t| t.name.to_string())
+                .collect();
+            names.sort();
+            names
+        };
+
+        let all: Vec<&str> = TOOLS.iter().map(|(name, ..)| *name).collect();
+        let reads: Vec<&str> = TOOLS.iter().filter(|(_, reads, ..)| *reads).map(|(name, ..)| *name).collect();
+        assert_eq!(served(&Policy::default()), all);
+        assert_eq!(served(&Policy { read_only: true, ..Policy::default() }), reads);
+    }
+
+    #[test]
+    fn test_dry_run_covers_destructive_tools() {
+        let dry_run = Policy { dry_run: true, ..Policy::default() };
+
+        for (tool, &(name, _, dry_runs, _)) in tools().iter().zip(TOOLS) {
+            let destructive = tool.annotations.as_ref().and_then(|a| a.destructive_hint) == Some(true);
+            assert_eq!(destructive, dry_runs, "{}", name);
+            assert_eq!(dry_run.dry_runs(name), dry_runs, "{}", name);
+            assert!(!Policy::default().dry_runs(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_drafts_only_refuses_publishing() {
+        let drafts_only = Policy { drafts_only: true, ..Policy::default() };
+
+        for (tool, &(name, _, _, publishes)) in tools().iter().zip(TOOLS) {
+            let takes_publish = tool.input_schema
+                .get("properties")
+                .and_then(|p| p.get("publish"))
+                .is_some();
+            assert_eq!(takes_publish, publishes, "{}", name);
+        }
+
+        assert!(drafts_only.check_publish(Some(true)).is_err());
+        assert!(drafts_only.check_publish(Some(false)).is_ok());
+        assert!(drafts_only.check_publish(None).is_ok());
+        assert!(Policy::default().check_publish(Some(true)).is_ok());
+    }
+
+    #[test]
+    fn test_collection_allow_and_deny_lists() {
+        let policy = |allow: &[&str], deny: &[&str]| Policy {
+            allow_collections: allow.iter().map(|s| s.to_string()).collect(),
+            deny_collections: deny.iter().map(|s| s.to_string()).collect(),
+            ..Policy::default()
+        };
+
+        // (allow, deny, collection, allowed)
+        type Case<'a> = (&'a [&'a str], &'a [&'a str], Option<&'a str>, bool);
+        let cases: &[Case] = &[
+            (&[], &[], Some("a"), true),
+            (&[], &[], None, true),
+            (&["a"], &[], Some("a"), true),
+            (&["a"], &[], Some("b"), false),
+            (&["a"], &[], None, false),
+            (&[], &["a"], Some("a"), false),
+            (&[], &["a"], Some("b"), true),
+            (&[], &["a"], None, true),
+            (&["a", "b"], &["b"], Some("b"), false),
+            (&["a", "b"], &["b"], Some("a"), true),
+        ];
+        for &(allow, deny, collection, allowed) in cases {
+            let policy = policy(allow, deny);
+            assert_eq!(policy.allows_collection(collection), allowed, "{:?} {:?} {:?}", allow, deny, collection);
+            assert_eq!(policy.check_collection(collection).is_ok(), allowed);
+            assert_eq!(policy.filters_collections(), !allow.is_empty() || !deny.is_empty());
+        }
+
+        assert!(policy(&["a"], &[]).check_new_collection().is_err());
+        assert!(policy(&[], &["a"]).check_new_collection().is_ok());
+    }
+}
@@ -0,0 +1,538 @@
+use anyhow::{Context, Result};
+use outline_api::{markdown, Collection, Document, Event};
+use rmcp::model::{JsonObject, Prompt, PromptArgument};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::resources::ResourceUri;
+
+/// Documents whose outlines are included in `summarize_collection`
+pub const MAX_SUMMARY_DOCUMENTS: usize = 100;
+
+/// Built-in prompt names; user prompts may not reuse them
+pub const BUILTIN_PROMPTS: [&str; 3] = ["summarize_collection", "draft_from_template", "weekly_changes"];
+
+/// A prompt defined in a TOML file in the prompts directory
+///
+/// ```toml
+/// name = "review_document"
+/// description = "Review a document for clarity and accuracy"
+/// template = """
+/// Review this document and list concrete improvements:
+///
+/// {{outline://document/{{documentId}}}}
+/// """
+///
+/// [[arguments]]
+/// name = "documentId"
+/// description = "Document to review"
+/// required = true
+/// ```
+///
+/// `{{name}}` placeholders are replaced with argument values and
+/// `{{outline://...}}` placeholders written in the template are replaced with
+/// the resource's contents. Argument values are never expanded themselves.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PromptDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<ArgumentDefinition>,
+    pub template: String,
+}
+
+/// An argument of a user-defined prompt
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArgumentDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl PromptDefinition {
+    /// The prompt as advertised in `prompts/list`
+    pub fn to_prompt(&self) -> Prompt {
+        let arguments = self
+            .arguments
+            .iter()
+            .map(|a| argument(&a.name, a.description.as_deref().unwrap_or_default(), a.required))
+            .collect();
+        Prompt::new(&self.name, self.description.clone(), Some(arguments))
+    }
+
+    /// Fill in argument placeholders, checking required arguments are present
+    ///
+    /// Resource placeholders are found in the template before any argument is
+    /// substituted, so argument values are only ever text (or part of a URI
+    /// the template already references) and can't pull in other resources.
+    pub fn fill_arguments(&self, args: &HashMap<String, String>) -> Result<Vec<TemplatePart>> {
+        for definition in &self.arguments {
+            if definition.required && !args.contains_key(&definition.name) {
+                anyhow::bail!("Missing required argument: {}", definition.name);
+            }
+        }
+
+        let fill = |text: &str| {
+            let mut text = text.to_string();
+            for definition in &self.arguments {
+                let value = args.get(&definition.name).map(String::as_str).unwrap_or_default();
+                text = text.replace(&format!("{{{{{}}}}}", definition.name), value);
+            }
+            text
+        };
+
+        Ok(resource_placeholders(&self.template)
+            .into_iter()
+            .map(|part| match part {
+                TemplatePart::Text(text) => TemplatePart::Text(fill(&text)),
+                TemplatePart::Resource(uri) => {
+                    let uri = fill(&uri);
+                    if ResourceUri::parse(&uri).is_some() {
+                        TemplatePart::Resource(uri)
+                    } else {
+                        TemplatePart::Text(format!("{{{{{}}}}}", uri))
+                    }
+                }
+            })
+            .collect())
+    }
+}
+
+/// A piece of a prompt template
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplatePart {
+    /// Literal text
+    Text(String),
+    /// An `outline://` URI whose contents replace the placeholder
+    Resource(String),
+}
+
+/// Load every `*.toml` prompt in a directory, skipping (and logging) invalid files
+pub fn load_user_prompts(dir: &Path) -> Vec<PromptDefinition> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+
+    let mut prompts: Vec<PromptDefinition> = Vec::new();
+    for path in paths {
+        let definition = fs::read_to_string(&path)
+            .context("Failed to read prompt file")
+            .and_then(|contents| {
+                toml::from_str::<PromptDefinition>(&contents).context("Failed to parse prompt file")
+            });
+
+        match definition {
+            Ok(definition) if BUILTIN_PROMPTS.contains(&definition.name.as_str()) => {
+                tracing::warn!("Skipping {}: '{}' is a built-in prompt", path.display(), definition.name);
+            }
+            Ok(definition) if prompts.iter().any(|p| p.name == definition.name) => {
+                tracing::warn!("Skipping {}: duplicate prompt '{}'", path.display(), definition.name);
+            }
+            Ok(definition) => prompts.push(definition),
+            Err(e) => tracing::warn!("Skipping {}: {:#}", path.display(), e),
+        }
+    }
+
+    prompts
+}
+
+/// Split a template into text and `{{outline://...}}` placeholders, in order
+///
+/// A placeholder may contain argument placeholders, as in
+/// `{{outline://document/{{documentId}}}}`; an unterminated one is left as text.
+pub fn resource_placeholders(template: &str) -> Vec<TemplatePart> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{outline://") {
+        let body = &rest[start + 2..];
+        let Some(end) = placeholder_end(body) else {
+            break;
+        };
+        if start > 0 {
+            parts.push(TemplatePart::Text(rest[..start].to_string()));
+        }
+        parts.push(TemplatePart::Resource(body[..end].to_string()));
+        rest = &body[end + 2..];
+    }
+
+    if !rest.is_empty() {
+        parts.push(TemplatePart::Text(rest.to_string()));
+    }
+    parts
+}
+
+/// Offset of the `}}` closing a placeholder body, skipping nested `{{name}}` placeholders
+fn placeholder_end(body: &str) -> Option<usize> {
+    let mut pos = 0;
+    loop {
+        let close = pos + body[pos..].find("}}")?;
+        match body[pos..].find("{{").map(|open| pos + open) {
+            Some(open) if open < close => {
+                pos = open + 2 + body[open + 2..].find("}}")? + 2;
+            }
+            _ => return Some(close),
+        }
+    }
+}
+
+/// Prompt argument values as strings
+pub fn argument_values(arguments: Option<JsonObject>) -> HashMap<String, String> {
+    arguments
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(name, value)| match value {
+            serde_json::Value::Null => None,
+            serde_json::Value::String(s) => Some((name, s)),
+            other => Some((name, other.to_string())),
+        })
+        .collect()
+}
+
+/// Built-in prompts as advertised in `prompts/list`
+pub fn builtin_prompts() -> Vec<Prompt> {
+    vec![
+        Prompt::new(
+            "summarize_collection",
+            Some("Summarize a collection, pre-filled with its document titles and outlines"),
+            Some(vec![argument("collectionId", "Collection to summarize", true)]),
+        ),
+        Prompt::new(
+            "draft_from_template",
+            Some("Draft a new document from an Outline template"),
+            Some(vec![
+                argument("templateId", "Template ID or URL ID (see templates in Outline)", true),
+                argument("title", "Title for the new document", false),
+                argument("notes", "What the document should cover", false),
+            ]),
+        ),
+        Prompt::new(
+            "weekly_changes",
+            Some("Write release notes from recent document changes in the event log"),
+            Some(vec![
+                argument("since", "Start date (YYYY-MM-DD or RFC 3339). Default: 7 days ago", false),
+                argument("collectionId", "Only include changes in this collection", false),
+            ]),
+        ),
+    ]
+}
+
+fn argument(name: &str, description: &str, required: bool) -> PromptArgument {
+    PromptArgument {
+        name: name.to_string(),
+        title: None,
+        description: (!description.is_empty()).then(|| description.to_string()),
+        required: Some(required),
+    }
+}
+
+/// Prompt text for `summarize_collection`
+pub fn summarize_collection(collection: &Collection, documents: &[Document]) -> String {
+    let mut out = format!(
+        "Summarize the \"{}\" collection in Outline for someone new to it. Describe what it covers \
+         and how it is organised, then point out gaps, overlapping documents and areas that look \
+         stale. Refer to documents by title.\n\n",
+        collection.name
+    );
+
+    if let Some(description) = collection.description.as_deref().filter(|d| !d.is_empty()) {
+        out.push_str(&format!("Collection description:\n{}\n\n", description));
+    }
+
+    let mut documents: Vec<&Document> = documents.iter().collect();
+    documents.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+
+    out.push_str(&format!("Documents ({}):\n", documents.len()));
+    for document in documents.iter().take(MAX_SUMMARY_DOCUMENTS) {
+        out.push_str(&format!(
+            "\n## {}\n{} - last updated {}\n",
+            document.title,
+            ResourceUri::Document(document.id.clone()),
+            document.updated_at
+        ));
+        for heading in markdown::headings(&document.text) {
+            out.push_str(&format!("{}- {}\n", "  ".repeat(heading.level.saturating_sub(1)), heading.title));
+        }
+    }
+
+    if documents.len() > MAX_SUMMARY_DOCUMENTS {
+        out.push_str(&format!(
+            "\n({} older documents omitted; read {} for the full tree.)\n",
+            documents.len() - MAX_SUMMARY_DOCUMENTS,
+            ResourceUri::Collection(collection.id.clone())
+        ));
+    }
+
+    out
+}
+
+/// Prompt text for `draft_from_template`
+pub fn draft_from_template(template: &Document, title: Option<&str>, notes: Option<&str>) -> String {
+    let mut out = match title {
+        Some(title) => format!("Draft a new Outline document titled \"{}\"", title),
+        None => "Draft a new Outline document".to_string(),
+    };
+    out.push_str(&format!(
+        " using the \"{}\" template below. Keep the template's structure and headings, fill in every \
+         section with real content and remove placeholder text.\n",
+        template.title
+    ));
+
+    if let Some(notes) = notes {
+        out.push_str(&format!("\nThe document should cover:\n{}\n", notes));
+    }
+
+    out.push_str("\nWhen the draft is ready, create it with outline_documents_create");
+    if let Some(collection_id) = &template.collection_id {
+        out.push_str(&format!(" in collection {}", collection_id));
+    }
+    out.push_str(".\n\nTemplate:\n\n");
+    out.push_str(&template.text);
+    out
+}
+
+/// What happened to one document over the period
+struct DocumentChanges<'a> {
+    document_id: &'a str,
+    actions: Vec<&'a str>,
+    counts: HashMap<&'a str, usize>,
+    actors: Vec<&'a str>,
+    last_change: &'a str,
+}
+
+/// Prompt text for `weekly_changes`, from events newest first
+pub fn weekly_changes(since: &str, events: &[Event], titles: &HashMap<String, String>) -> String {
+    let mut changes: Vec<DocumentChanges> = Vec::new();
+
+    for event in events {
+        let Some(document_id) = event.document_id.as_deref() else {
+            continue;
+        };
+        let Some(action) = event.name.strip_prefix("documents.") else {
+            continue;
+        };
+
+        let index = match changes.iter().position(|c| c.document_id == document_id) {
+            Some(index) => index,
+            None => {
+                changes.push(DocumentChanges {
+                    document_id,
+                    actions: Vec::new(),
+                    counts: HashMap::new(),
+                    actors: Vec::new(),
+                    last_change: &event.created_at,
+                });
+                changes.len() - 1
+            }
+        };
+        let entry = &mut changes[index];
+
+        if !entry.actions.contains(&action) {
+            entry.actions.push(action);
+        }
+        *entry.counts.entry(action).or_default() += 1;
+        if let Some(actor) = event.actor.as_ref().map(|a| a.name.as_str()) {
+            if !entry.actors.contains(&actor) {
+                entry.actors.push(actor);
+            }
+        }
+    }
+
+    let mut out = format!(
+        "Write release notes summarising documentation changes in Outline since {}. Group related \
+         changes, lead with the most significant ones and say who made them. Link each document \
+         with its outline:// URI so it can be read for detail.\n\n",
+        since
+    );
+
+    if changes.is_empty() {
+        out.push_str("No documents changed in this period.\n");
+        return out;
+    }
+
+    out.push_str(&format!("Changed documents ({}), most recent first:\n", changes.len()));
+    for change in &changes {
+        let title = titles
+            .get(change.document_id)
+            .map(String::as_str)
+            .unwrap_or("(deleted document)");
+        let actions: Vec<String> = change
+            .actions
+            .iter()
+            .map(|action| match change.counts[action] {
+                1 => action.to_string(),
+                n => format!("{} x{}", action, n),
+            })
+            .collect();
+
+        out.push_str(&format!(
+            "- \"{}\" ({}): {}",
+            title,
+            ResourceUri::Document(change.document_id.to_string()),
+            actions.join(", ")
+        ));
+        if !change.actors.is_empty() {
+            out.push_str(&format!(" - by {}", change.actors.join(", ")));
+        }
+        out.push_str(&format!(" - last change {}\n", change.last_change));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition(template: &str) -> PromptDefinition {
+        toml::from_str(&format!(
+            "name = \"review\"\ntemplate = '''{}'''\n\
+             [[arguments]]\nname = \"documentId\"\nrequired = true\n\
+             [[arguments]]\nname = \"focus\"\n",
+            template
+        ))
+        .unwrap()
+    }
+
+    fn args(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn text(s: &str) -> TemplatePart {
+        TemplatePart::Text(s.to_string())
+    }
+
+    fn resource(s: &str) -> TemplatePart {
+        TemplatePart::Resource(s.to_string())
+    }
+
+    fn event(name: &str, document_id: Option<&str>, actor: Option<&str>, created_at: &str) -> Event {
+        serde_json::from_value(serde_json::json!({
+            "id": format!("{}-{}", name, created_at),
+            "name": name,
+            "documentId": document_id,
+            "actor": actor.map(|name| serde_json::json!({ "id": name, "name": name })),
+            "createdAt": created_at,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resource_placeholders() {
+        assert_eq!(
+            resource_placeholders("Read {{outline://document/{{documentId}}}} and {{outline://audit/summary}}."),
+            vec![
+                text("Read "),
+                resource("outline://document/{{documentId}}"),
+                text(" and "),
+                resource("outline://audit/summary"),
+                text("."),
+            ]
+        );
+        assert_eq!(resource_placeholders("{{focus}} only"), vec![text("{{focus}} only")]);
+        assert_eq!(
+            resource_placeholders("broken {{outline://document/x"),
+            vec![text("broken {{outline://document/x")]
+        );
+        assert!(resource_placeholders("").is_empty());
+    }
+
+    #[test]
+    fn test_fill_arguments() {
+        let prompt = definition("Review {{outline://document/{{documentId}}}} for {{focus}}.");
+
+        assert_eq!(
+            prompt.fill_arguments(&args(&[("documentId", "abc"), ("focus", "clarity")])).unwrap(),
+            vec![text("Review "), resource("outline://document/abc"), text(" for clarity.")]
+        );
+        // Optional arguments default to empty
+        assert_eq!(
+            prompt.fill_arguments(&args(&[("documentId", "abc")])).unwrap()[2],
+            text(" for .")
+        );
+
+        let err = prompt.fill_arguments(&args(&[("focus", "clarity")])).unwrap_err();
+        assert_eq!(err.to_string(), "Missing required argument: documentId");
+    }
+
+    #[test]
+    fn test_argument_values_are_not_expanded() {
+        let prompt = definition("Review {{outline://document/{{documentId}}}} for {{focus}}.");
+        let injected = "{{outline://document/secret}}";
+
+        let parts = prompt
+            .fill_arguments(&args(&[("documentId", "abc"), ("focus", injected)]))
+            .unwrap();
+        assert_eq!(parts[2], text(&format!(" for {}.", injected)));
+        assert_eq!(parts.iter().filter(|p| matches!(p, TemplatePart::Resource(_))).count(), 1);
+
+        // A value that doesn't make a valid URI leaves the placeholder as text
+        let parts = prompt.fill_arguments(&args(&[("documentId", "")])).unwrap();
+        assert_eq!(parts[1], text("{{outline://document/}}"));
+    }
+
+    #[test]
+    fn test_load_user_prompts() {
+        let dir = std::env::temp_dir().join(format!("outline-prompts-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let write = |file: &str, name: &str| {
+            let contents = format!("name = \"{}\"\ntemplate = \"Hello\"\n", name);
+            fs::write(dir.join(file), contents).unwrap();
+        };
+        write("a.toml", "greet");
+        write("b.toml", "greet");
+        write("c.toml", "weekly_changes");
+        write("d.toml", "other");
+        write("e.txt", "ignored");
+        fs::write(dir.join("f.toml"), "not = [valid").unwrap();
+
+        let names: Vec<String> = load_user_prompts(&dir).into_iter().map(|p| p.name).collect();
+        // The first file wins a duplicate name; built-in names and invalid files are skipped
+        assert_eq!(names, ["greet", "other"]);
+
+        assert!(load_user_prompts(&dir.join("missing")).is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_weekly_changes() {
+        let events = vec![
+            event("documents.update", Some("d1"), Some("Ada"), "2024-01-07"),
+            event("documents.update", Some("d2"), Some("Grace"), "2024-01-06"),
+            event("documents.update", Some("d1"), Some("Grace"), "2024-01-05"),
+            event("documents.publish", Some("d1"), Some("Ada"), "2024-01-04"),
+            event("collections.create", None, Some("Ada"), "2024-01-03"),
+            event("users.invite", Some("d2"), None, "2024-01-02"),
+            event("documents.delete", Some("d3"), None, "2024-01-01"),
+        ];
+        let titles = HashMap::from([
+            ("d1".to_string(), "Handbook".to_string()),
+            ("d2".to_string(), "Roadmap".to_string()),
+        ]);
+
+        let out = weekly_changes("2024-01-01", &events, &titles);
+        assert!(out.contains("since 2024-01-01."));
+        assert!(out.ends_with(
+            "Changed documents (3), most recent first:\n\
+             - \"Handbook\" (outline://document/d1): update x2, publish - by Ada, Grace - last change 2024-01-07\n\
+             - \"Roadmap\" (outline://document/d2): update - by Grace - last change 2024-01-06\n\
+             - \"(deleted document)\" (outline://document/d3): delete - last change 2024-01-01\n"
+        ));
+
+        let empty = weekly_changes("2024-01-01", &events[4..6], &titles);
+        assert!(empty.ends_with("No documents changed in this period.\n"));
+    }
+}
@@ -0,0 +1,509 @@
+use outline_api::{
+    Collection, Document, Event, ListEventsRequest, NavigationNode, OutlineClient, SortDirection,
+};
+use rmcp::model::{
+    AnnotateAble, RawResource, RawResourceTemplate, Resource, ResourceTemplate,
+    ResourceUpdatedNotificationParam,
+};
+use rmcp::service::{Peer, RoleServer};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// URI scheme for all Outline resources
+pub const SCHEME: &str = "outline://";
+
+/// How often the event log is polled for changes to subscribed resources
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Events fetched per page while polling
+const EVENTS_PAGE_SIZE: u32 = 100;
+
+/// Upper bound on pages fetched per poll, in case the server lags far behind
+const MAX_EVENT_PAGES: u32 = 5;
+
+/// A parsed `outline://` resource URI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceUri {
+    /// `outline://collection/{id}` - the collection's document tree
+    Collection(String),
+    /// `outline://document/{id}` - the document as markdown with front matter
+    Document(String),
+    /// `outline://document/{id}/comments` - the document's comment threads
+    DocumentComments(String),
+    /// `outline://audit/summary` - per-tool counters from the audit log
+    AuditSummary,
+}
+
+impl ResourceUri {
+    /// Parse an `outline://` URI, returning `None` for anything else
+    pub fn parse(uri: &str) -> Option<Self> {
+        let path = uri.strip_prefix(SCHEME)?;
+        let parts: Vec<&str> = path.split('/').collect();
+
+        match parts.as_slice() {
+            ["collection", id] if !id.is_empty() => Some(Self::Collection(id.to_string())),
+            ["document", id] if !id.is_empty() => Some(Self::Document(id.to_string())),
+            ["document", id, "comments"] if !id.is_empty() => {
+                Some(Self::DocumentComments(id.to_string()))
+            }
+            ["audit", "summary"] => Some(Self::AuditSummary),
+            _ => None,
+        }
+    }
+
+    /// MIME type of the resource's contents
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Collection(_) | Self::Document(_) => "text/markdown",
+            Self::DocumentComments(_) => "text/plain",
+            Self::AuditSummary => "application/json",
+        }
+    }
+}
+
+impl std::fmt::Display for ResourceUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Collection(id) => write!(f, "{}collection/{}", SCHEME, id),
+            Self::Document(id) => write!(f, "{}document/{}", SCHEME, id),
+            Self::DocumentComments(id) => write!(f, "{}document/{}/comments", SCHEME, id),
+            Self::AuditSummary => write!(f, "{}audit/summary", SCHEME),
+        }
+    }
+}
+
+/// Resource templates advertised to clients for browsing
+pub fn templates() -> Vec<ResourceTemplate> {
+    let template = |uri: &str, name: &str, description: &str, mime: &str| {
+        RawResourceTemplate {
+            uri_template: uri.to_string(),
+            name: name.to_string(),
+            title: None,
+            description: Some(description.to_string()),
+            mime_type: Some(mime.to_string()),
+        }
+        .no_annotation()
+    };
+
+    vec![
+        template(
+            "outline://collection/{id}",
+            "Collection",
+            "A collection's document tree, with links to each document resource",
+            "text/markdown",
+        ),
+        template(
+            "outline://document/{id}",
+            "Document",
+            "A document as markdown, with YAML front matter for its metadata",
+            "text/markdown",
+        ),
+        template(
+            "outline://document/{id}/comments",
+            "Document comments",
+            "A document's comment threads with authors and resolved state",
+            "text/plain",
+        ),
+    ]
+}
+
+/// List entry for a document resource
+pub fn document_resource(document: &Document) -> Resource {
+    let mut resource = RawResource::new(
+        ResourceUri::Document(document.id.clone()).to_string(),
+        document_title(document),
+    );
+    resource.description = Some(format!("Last updated {}", document.updated_at));
+    resource.mime_type = Some("text/markdown".to_string());
+    resource.no_annotation()
+}
+
+/// List entry for the audit summary
+pub fn audit_summary_resource() -> Resource {
+    let mut resource = RawResource::new(ResourceUri::AuditSummary.to_string(), "Tool call counters");
+    resource.description = Some("Calls, errors and latency per tool, from the most recent calls in the audit log".to_string());
+    resource.mime_type = Some(ResourceUri::AuditSummary.mime_type().to_string());
+    resource.no_annotation()
+}
+
+/// Render a document as markdown with YAML front matter
+pub fn render_document(document: &Document, instance_url: &str) -> String {
+    let mut out = String::from("---\n");
+    let mut field = |key: &str, value: &str| {
+        // JSON strings are valid YAML scalars, which keeps titles with colons or quotes intact
+        out.push_str(&format!("{}: {}\n", key, serde_json::Value::from(value)));
+    };
+
+    field("id", &document.id);
+    field("title", &document.title);
+    if !document.url_id.is_empty() {
+        field("url", &format!("{}/doc/{}", instance_url.trim_end_matches('/'), document.url_id));
+    }
+    if let Some(collection_id) = &document.collection_id {
+        field("collectionId", collection_id);
+    }
+    if let Some(parent) = &document.parent_document_id {
+        field("parentDocumentId", parent);
+    }
+    if let Some(author) = &document.created_by {
+        field("createdBy", &author.name);
+    }
+    if let Some(editor) = &document.updated_by {
+        field("updatedBy", &editor.name);
+    }
+    field("createdAt", &document.created_at);
+    field("updatedAt", &document.updated_at);
+    field("status", if document.published_at.is_some() { "published" } else { "draft" });
+    out.push_str(&format!("revision: {}\n", document.revision));
+    out.push_str("---\n\n");
+
+    out.push_str(&document.text);
+    if !document.text.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+/// Render a collection and its documents as a nested markdown list
+pub fn render_collection(collection: &Collection, documents: &[Document]) -> String {
+    let mut out = format!("# {}\n\n", collection.name);
+    if let Some(description) = collection.description.as_deref().filter(|d| !d.is_empty()) {
+        out.push_str(description);
+        out.push_str("\n\n");
+    }
+
+    let ids: HashSet<&str> = documents.iter().map(|d| d.id.as_str()).collect();
+    let mut children: HashMap<Option<&str>, Vec<&Document>> = HashMap::new();
+    for document in documents {
+        // Documents whose parent isn't listed (e.g. a draft parent) go at the top level
+        let parent = document.parent_document_id.as_deref().filter(|p| ids.contains(p));
+        children.entry(parent).or_default().push(document);
+    }
+    for list in children.values_mut() {
+        list.sort_by_key(|d| d.title.to_lowercase());
+    }
+
+    if documents.is_empty() {
+        out.push_str("_No documents_\n");
+    } else {
+        render_tree(&mut out, &children, None, 0);
+    }
+    out
+}
+
+fn render_tree(
+    out: &mut String,
+    children: &HashMap<Option<&str>, Vec<&Document>>,
+    parent: Option<&str>,
+    depth: usize,
+) {
+    let Some(list) = children.get(&parent) else {
+        return;
+    };
+
+    for document in list {
+        out.push_str(&format!(
+            "{}- [{}]({})\n",
+            "  ".repeat(depth),
+            document_title(document),
+            ResourceUri::Document(document.id.clone())
+        ));
+        render_tree(out, children, Some(document.id.as_str()), depth + 1);
+    }
+}
+
+/// Render a collection's navigation tree as a nested list of titles and document IDs
+pub fn render_navigation(collection: &Collection, nodes: &[NavigationNode]) -> String {
+    let count: usize = nodes.iter().map(NavigationNode::count).sum();
+    let mut out = format!("# {} ({}) - {} document(s)\n", collection.name, collection.id, count);
+
+    let mut stack: Vec<(usize, &NavigationNode)> = nodes.iter().rev().map(|n| (0, n)).collect();
+    while let Some((depth, node)) = stack.pop() {
+        let title = if node.title.is_empty() { "Untitled" } else { &node.title };
+        out.push_str(&format!("{}- {} ({})\n", "  ".repeat(depth), title, node.id));
+        stack.extend(node.children.iter().rev().map(|child| (depth + 1, child)));
+    }
+
+    if nodes.is_empty() {
+        out.push_str("_No documents_\n");
+    }
+    out
+}
+
+fn document_title(document: &Document) -> String {
+    if document.title.is_empty() {
+        "Untitled".to_string()
+    } else {
+        document.title.clone()
+    }
+}
+
+/// Resource URIs whose contents an event may have changed
+pub fn affected_uris(event: &Event) -> Vec<String> {
+    let mut uris = Vec::new();
+
+    if let Some(document_id) = &event.document_id {
+        if event.name.starts_with("comments.") {
+            uris.push(ResourceUri::DocumentComments(document_id.clone()).to_string());
+        } else if event.name.starts_with("documents.") || event.name.starts_with("revisions.") {
+            uris.push(ResourceUri::Document(document_id.clone()).to_string());
+        }
+    }
+
+    if let Some(collection_id) = &event.collection_id {
+        if event.name.starts_with("documents.") || event.name.starts_with("collections.") {
+            uris.push(ResourceUri::Collection(collection_id.clone()).to_string());
+        }
+    }
+
+    uris
+}
+
+/// URIs clients have subscribed to with `resources/subscribe`
+///
+/// The event log is only polled while at least one URI is subscribed: the
+/// poller starts with the first subscription and stops with the last.
+#[derive(Clone, Default)]
+pub struct Subscriptions {
+    state: Arc<Mutex<SubscriptionState>>,
+}
+
+#[derive(Default)]
+struct SubscriptionState {
+    uris: HashSet<String>,
+    watcher: Option<JoinHandle<()>>,
+}
+
+impl Subscriptions {
+    /// Subscribe to a URI, starting the poller if it isn't running
+    pub fn subscribe(&self, uri: String, client: Arc<OutlineClient>, peer: Peer<RoleServer>) {
+        let mut state = self.state.lock().unwrap();
+        state.uris.insert(uri);
+
+        // The poller also stops by itself once the client goes away
+        if state.watcher.as_ref().is_none_or(JoinHandle::is_finished) {
+            tracing::debug!("Watching events for resource updates");
+            state.watcher = Some(tokio::spawn(watch_events(client, self.clone(), peer)));
+        }
+    }
+
+    /// Unsubscribe from a URI, stopping the poller if nothing is left
+    pub fn unsubscribe(&self, uri: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.uris.remove(uri);
+
+        if state.uris.is_empty() {
+            if let Some(watcher) = state.watcher.take() {
+                tracing::debug!("No subscriptions left, no longer watching events");
+                watcher.abort();
+            }
+        }
+    }
+
+    /// The subscribed URIs among these
+    fn filter(&self, uris: impl Iterator<Item = String>) -> BTreeSet<String> {
+        let state = self.state.lock().unwrap();
+        uris.filter(|uri| state.uris.contains(uri)).collect()
+    }
+}
+
+/// How far the event log has been read
+///
+/// Events can share a timestamp, so besides the newest timestamp seen this
+/// keeps the IDs of every event seen with it.
+#[derive(Debug, Default)]
+struct EventCursor {
+    created_at: String,
+    ids: HashSet<String>,
+}
+
+impl EventCursor {
+    /// Whether an event comes before everything not yet seen (newest-first order)
+    fn is_older(&self, event: &Event) -> bool {
+        event.created_at < self.created_at
+    }
+
+    /// Whether an event was already seen
+    fn has_seen(&self, event: &Event) -> bool {
+        self.is_older(event) || (event.created_at == self.created_at && self.ids.contains(&event.id))
+    }
+
+    /// Move past newly seen events
+    fn advance(&mut self, events: &[Event]) {
+        for event in events {
+            if event.created_at > self.created_at {
+                self.created_at = event.created_at.clone();
+                self.ids.clear();
+            }
+            if event.created_at == self.created_at {
+                self.ids.insert(event.id.clone());
+            }
+        }
+    }
+}
+
+/// Poll the event log and send `resources/updated` for subscribed URIs until the client goes away
+async fn watch_events(client: Arc<OutlineClient>, subscriptions: Subscriptions, peer: Peer<RoleServer>) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    let mut cursor: Option<EventCursor> = None;
+
+    loop {
+        interval.tick().await;
+        if peer.is_transport_closed() {
+            break;
+        }
+
+        let events = match new_events(&client, cursor.as_ref()).await {
+            Ok(events) => events,
+            Err(e) => {
+                tracing::warn!("Failed to poll events: {}", e);
+                continue;
+            }
+        };
+
+        // The first poll only establishes where the log currently ends
+        let Some(cursor) = cursor.as_mut() else {
+            let mut start = EventCursor::default();
+            start.advance(&events);
+            cursor = Some(start);
+            continue;
+        };
+        cursor.advance(&events);
+
+        let updated = subscriptions.filter(events.iter().flat_map(affected_uris));
+        for uri in updated {
+            tracing::debug!("Resource updated: {}", uri);
+            if let Err(e) = peer
+                .notify_resource_updated(ResourceUpdatedNotificationParam { uri })
+                .await
+            {
+                tracing::warn!("Failed to send resource update: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// Events not yet seen, newest first
+async fn new_events(client: &OutlineClient, cursor: Option<&EventCursor>) -> anyhow::Result<Vec<Event>> {
+    let mut events = Vec::new();
+
+    for page in 0..MAX_EVENT_PAGES {
+        let request = ListEventsRequest::new()
+            .direction(SortDirection::Desc)
+            .offset(page * EVENTS_PAGE_SIZE)
+            .limit(EVENTS_PAGE_SIZE);
+        let response = client.list_events(request).await?;
+
+        let count = response.data.len() as u32;
+        let reached_seen = unseen_events(response.data, cursor, &mut events);
+
+        // Without a starting point a single page is enough to find the newest event
+        if reached_seen || cursor.is_none() || count < EVENTS_PAGE_SIZE {
+            break;
+        }
+    }
+
+    Ok(events)
+}
+
+/// Collect the events of a newest-first page not yet seen, returning whether
+/// the page reached events older than the cursor
+fn unseen_events(page: Vec<Event>, cursor: Option<&EventCursor>, events: &mut Vec<Event>) -> bool {
+    let Some(cursor) = cursor else {
+        events.extend(page);
+        return false;
+    };
+
+    for event in page {
+        if cursor.is_older(&event) {
+            return true;
+        }
+        if !cursor.has_seen(&event) {
+            events.push(event);
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, created_at: &str) -> Event {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": "documents.update",
+            "documentId": "doc",
+            "createdAt": created_at,
+        }))
+        .unwrap()
+    }
+
+    fn ids(events: &[Event]) -> Vec<&str> {
+        events.iter().map(|e| e.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_events_sharing_a_timestamp_are_not_dropped() {
+        let mut cursor = EventCursor::default();
+        cursor.advance(&[event("a", "2024-01-01T00:00:01Z"), event("b", "2024-01-01T00:00:00Z")]);
+
+        // "c" landed in the same second as "a" after the previous poll
+        let page = vec![
+            event("d", "2024-01-01T00:00:02Z"),
+            event("c", "2024-01-01T00:00:01Z"),
+            event("a", "2024-01-01T00:00:01Z"),
+            event("b", "2024-01-01T00:00:00Z"),
+        ];
+        let mut events = Vec::new();
+        assert!(unseen_events(page, Some(&cursor), &mut events));
+        assert_eq!(ids(&events), ["d", "c"]);
+
+        cursor.advance(&events);
+        assert_eq!(cursor.created_at, "2024-01-01T00:00:02Z");
+        assert_eq!(cursor.ids, HashSet::from(["d".to_string()]));
+    }
+
+    #[test]
+    fn test_cursor_keeps_all_ids_at_the_newest_timestamp() {
+        let mut cursor = EventCursor::default();
+        cursor.advance(&[event("a", "2024-01-01T00:00:01Z")]);
+        cursor.advance(&[event("b", "2024-01-01T00:00:01Z")]);
+
+        let page = vec![event("b", "2024-01-01T00:00:01Z"), event("a", "2024-01-01T00:00:01Z")];
+        let mut events = Vec::new();
+        assert!(!unseen_events(page, Some(&cursor), &mut events));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_affected_uris() {
+        let uris = affected_uris(&event("a", "2024-01-01T00:00:00Z"));
+        assert_eq!(uris, ["outline://document/doc"]);
+    }
+
+    #[tokio::test]
+    async fn test_poller_stops_with_the_last_subscription() {
+        let subscriptions = Subscriptions::default();
+        let watcher = tokio::spawn(std::future::pending::<()>());
+        let running = watcher.abort_handle();
+        {
+            let mut state = subscriptions.state.lock().unwrap();
+            state.uris.insert("outline://document/a".to_string());
+            state.uris.insert("outline://document/b".to_string());
+            state.watcher = Some(watcher);
+        }
+
+        subscriptions.unsubscribe("outline://document/a");
+        tokio::task::yield_now().await;
+        assert!(!running.is_finished());
+
+        subscriptions.unsubscribe("outline://document/b");
+        tokio::task::yield_now().await;
+        assert!(running.is_finished());
+        assert!(subscriptions.state.lock().unwrap().watcher.is_none());
+    }
+}
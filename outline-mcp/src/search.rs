@@ -0,0 +1,242 @@
+use outline_api::markdown;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Terms Outline highlighted in a search result's `context` (wrapped in `<b>` tags)
+pub fn highlighted_terms(context: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut rest = context;
+
+    while let Some(start) = rest.find("<b>") {
+        let after = &rest[start + 3..];
+        let Some(end) = after.find("</b>") else {
+            break;
+        };
+        let term = after[..end].trim();
+        if !term.is_empty() && !terms.iter().any(|t| t == term) {
+            terms.push(term.to_string());
+        }
+        rest = &after[end + 4..];
+    }
+
+    terms
+}
+
+/// Context with the highlight markup removed
+pub fn plain_context(context: &str) -> String {
+    context.replace("<b>", "").replace("</b>", "")
+}
+
+/// Byte offset in `text` of the match a search result's context points at
+///
+/// Looks for the highlighted terms first, then for the context itself.
+pub fn match_offset(text: &str, context: &str) -> Option<usize> {
+    let mut candidates = highlighted_terms(context);
+    let plain = plain_context(context);
+    let plain = plain.trim();
+    if !plain.is_empty() {
+        candidates.push(plain.to_string());
+    }
+
+    candidates
+        .iter()
+        .filter_map(|term| find_ignore_case(text, term))
+        .min()
+}
+
+/// Find `needle` in `text`, ignoring case
+///
+/// Characters are compared lowercased one at a time, so the offset stays
+/// valid even where lowercasing changes a character's length (e.g. `İ`).
+fn find_ignore_case(text: &str, needle: &str) -> Option<usize> {
+    if let Some(offset) = text.find(needle) {
+        return Some(offset);
+    }
+
+    let needle: Vec<char> = needle.chars().flat_map(char::to_lowercase).collect();
+    if needle.is_empty() {
+        return None;
+    }
+    text.char_indices().map(|(i, _)| i).find(|&i| {
+        let mut folded = text[i..].chars().flat_map(char::to_lowercase);
+        needle.iter().all(|&c| folded.next() == Some(c))
+    })
+}
+
+/// The first `max` grapheme clusters of the text, followed by `...` if it was cut
+pub fn preview(text: &str, max: usize) -> String {
+    match text.grapheme_indices(true).nth(max) {
+        Some((cut, _)) => format!("{}...", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+/// A snippet of at most `max` grapheme clusters around `offset`, with whitespace collapsed
+///
+/// About a third of the window comes before the match. Cut ends are marked with `...`.
+pub fn snippet(text: &str, offset: usize, max: usize) -> String {
+    let graphemes: Vec<(usize, &str)> = text.grapheme_indices(true).collect();
+    if graphemes.is_empty() {
+        return String::new();
+    }
+
+    let at = graphemes.partition_point(|(i, _)| *i < offset);
+    let mut start = at.saturating_sub(max / 3);
+    let mut end = (start + max).min(graphemes.len());
+    start = end.saturating_sub(max).min(start);
+
+    // Start on a word where one begins shortly after the cut
+    if start > 0 {
+        if let Some(space) = graphemes[start..at.min(end)]
+            .iter()
+            .take(max / 6)
+            .position(|(_, g)| g.chars().all(char::is_whitespace))
+        {
+            start += space + 1;
+        }
+    }
+
+    // Likewise end after the last whole word
+    if end < graphemes.len() {
+        if let Some(space) = graphemes[at.max(start)..end]
+            .iter()
+            .rev()
+            .take(max / 6)
+            .position(|(_, g)| g.chars().all(char::is_whitespace))
+        {
+            end -= space + 1;
+        }
+    }
+
+    let byte_start = graphemes[start].0;
+    let byte_end = graphemes.get(end).map_or(text.len(), |(i, _)| *i);
+    let body = text[byte_start..byte_end].split_whitespace().collect::<Vec<_>>().join(" ");
+
+    format!(
+        "{}{}{}",
+        if start > 0 { "..." } else { "" },
+        body,
+        if end < graphemes.len() { "..." } else { "" }
+    )
+}
+
+/// Headings enclosing a byte offset, outermost first
+pub fn heading_path(text: &str, offset: usize) -> Vec<String> {
+    markdown::sections(text)
+        .into_iter()
+        .rev()
+        .find(|s| s.heading.start <= offset && offset < s.heading.end)
+        .map(|s| s.path)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILLER: &str = "lorem ipsum dolor sit amet, consectetur adipiscing elit sed do eiusmod ";
+
+    /// Documents with `word` once at the start, in the middle and at the end, with its offset
+    fn placements(word: &str) -> [(String, usize); 3] {
+        let filler = FILLER.repeat(4);
+        [
+            (format!("{word} {filler}"), 0),
+            (format!("{filler}{word} {filler}"), filler.len()),
+            (format!("{filler}{word}"), filler.len()),
+        ]
+    }
+
+    #[test]
+    fn test_find_ignore_case() {
+        assert_eq!(find_ignore_case("Hello World", "world"), Some(6));
+        assert_eq!(find_ignore_case("Hello World", "planet"), None);
+        assert_eq!(find_ignore_case("Hello World", ""), Some(0));
+
+        // Lowercasing "İ" adds a byte; offsets after it must still point at the match
+        let text = "İzmir and Paris";
+        assert_eq!(find_ignore_case(text, "paris"), Some(text.find("Paris").unwrap()));
+        assert_eq!(find_ignore_case(text, "i̇zmir"), Some(0));
+
+        // "ẞ" lowercases to the shorter "ß"
+        let text = "GROẞE STRAẞE";
+        assert_eq!(find_ignore_case(text, "straße"), Some(text.find("STRAẞE").unwrap()));
+    }
+
+    #[test]
+    fn test_match_offset_at_start_middle_and_end() {
+        // (as written in the document, as highlighted by the search)
+        let words = [
+            ("Größe", "GRÖSSE"),
+            ("İstanbul", "i̇stanbul"),
+            ("STRAẞE", "straße"),
+            ("Café", "CAFÉ"),
+            ("Cafe\u{301}", "cafe\u{301}"),
+            ("👨‍👩‍👧 family", "👨‍👩‍👧 FAMILY"),
+            ("日本語", "日本語"),
+        ];
+        for (word, highlighted) in words {
+            for (text, offset) in placements(word) {
+                let context = format!("<b>{}</b>", highlighted);
+                // Only simple lowercasing is done, not full case folding, so "SS" doesn't match "ß"
+                let expected = (highlighted != "GRÖSSE").then_some(offset);
+                assert_eq!(match_offset(&text, &context), expected, "{:?} in {:?}", highlighted, text);
+            }
+        }
+    }
+
+    #[test]
+    fn test_snippet_keeps_graphemes_whole() {
+        for word in ["Größe", "İstanbul", "cafe\u{301}", "👨‍👩‍👧", "🇯🇵", "日本語"] {
+            for (text, offset) in placements(word) {
+                let snippet = snippet(&text, offset, 40);
+                assert!(snippet.contains(word), "{:?} at {}: {:?}", word, offset, snippet);
+                let body = snippet.trim_start_matches("...").trim_end_matches("...");
+                assert!(body.graphemes(true).count() <= 40, "{:?}", snippet);
+            }
+        }
+
+        // A combining mark stays with its letter even when the window cuts right there
+        let text = "e\u{301}".repeat(100);
+        for offset in [0, 1, 2, 101, text.len() - 1] {
+            let snippet = snippet(&text, offset, 10);
+            assert!(!snippet.trim_start_matches("...").starts_with('\u{301}'), "{:?}", snippet);
+            assert!(!snippet.trim_end_matches("...").ends_with('e'), "{:?}", snippet);
+        }
+    }
+
+    #[test]
+    fn test_preview_cuts_on_grapheme_boundaries() {
+        assert_eq!(preview("short", 200), "short");
+        assert_eq!(preview("exactly", 7), "exactly");
+        assert_eq!(preview("Größe", 3), "Grö...");
+        assert_eq!(preview("🇯🇵🇯🇵", 1), "🇯🇵...");
+        assert_eq!(preview(&"e\u{301}".repeat(300), 200), format!("{}...", "e\u{301}".repeat(200)));
+        assert_eq!(preview("", 200), "");
+    }
+
+    #[test]
+    fn test_snippet_marks_cut_ends() {
+        let [(start_text, start), (middle_text, middle), (end_text, end)] = placements("🇯🇵");
+        let at_start = snippet(&start_text, start, 40);
+        let in_middle = snippet(&middle_text, middle, 40);
+        let at_end = snippet(&end_text, end, 40);
+
+        assert!(at_start.starts_with("🇯🇵") && at_start.ends_with("..."), "{:?}", at_start);
+        assert!(in_middle.starts_with("...") && in_middle.ends_with("..."), "{:?}", in_middle);
+        assert!(at_end.starts_with("...") && at_end.ends_with("🇯🇵"), "{:?}", at_end);
+        assert_eq!(snippet("", 0, 40), "");
+    }
+
+    #[test]
+    fn test_heading_path() {
+        let text = "# Ünïcode\n\n## Émoji 👍\n\nbody 👍\n\n## Other\n\ntext\n";
+        assert_eq!(heading_path(text, text.find("body").unwrap()), ["Ünïcode", "Émoji 👍"]);
+        assert_eq!(heading_path(text, text.find("text").unwrap()), ["Ünïcode", "Other"]);
+        assert!(heading_path("no headings", 3).is_empty());
+    }
+
+    #[test]
+    fn test_highlighted_terms() {
+        assert_eq!(highlighted_terms("a <b>Größe</b> b <b>größe</b> <b>Größe</b>"), ["Größe", "größe"]);
+        assert_eq!(plain_context("a <b>Größe</b>"), "a Größe");
+    }
+}
@@ -0,0 +1,329 @@
+use anyhow::Result;
+use outline_api::markdown::{self, Section};
+use std::ops::Range;
+
+/// Default page size for `outline_documents_get_section`
+pub const DEFAULT_PAGE_BYTES: usize = 16_000;
+
+/// Largest page `outline_documents_get_section` returns
+pub const MAX_PAGE_BYTES: usize = 100_000;
+
+/// ID of the text before the first heading
+pub const PREAMBLE_ID: &str = "0";
+
+/// Render a document's heading tree with section IDs, byte ranges and sizes
+pub fn render_outline(title: &str, id: &str, text: &str) -> String {
+    let sections = markdown::sections(text);
+    let mut out = format!(
+        "\"{}\" ({}): {} bytes, {} section(s)\n\n",
+        title,
+        id,
+        text.len(),
+        sections.len()
+    );
+
+    let preamble = preamble_range(text, &sections);
+    if !text[preamble.clone()].trim().is_empty() {
+        out.push_str(&format!(
+            "- [{}] (text before the first heading) bytes {}-{}, {}\n",
+            PREAMBLE_ID,
+            preamble.start,
+            preamble.end,
+            size(preamble.len())
+        ));
+    }
+
+    for section in &sections {
+        let heading = &section.heading;
+        out.push_str(&format!(
+            "{}- [{}] {} bytes {}-{}, {}\n",
+            "  ".repeat(section.path.len() - 1),
+            section.id,
+            if heading.title.is_empty() { "(untitled)" } else { &heading.title },
+            heading.start,
+            heading.end,
+            size(heading.end - heading.start)
+        ));
+    }
+
+    if sections.is_empty() {
+        out.push_str("No headings; use a byte range to read the document in parts.\n");
+    }
+    out
+}
+
+fn preamble_range(text: &str, sections: &[Section]) -> Range<usize> {
+    0..sections.first().map_or(text.len(), |s| s.heading.start)
+}
+
+fn size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+/// Byte range of a section (heading line included) by outline ID
+pub fn range_by_id(text: &str, id: &str) -> Result<Range<usize>> {
+    let sections = markdown::sections(text);
+    if id == PREAMBLE_ID {
+        return Ok(preamble_range(text, &sections));
+    }
+    sections
+        .iter()
+        .find(|s| s.id == id)
+        .map(|s| s.heading.start..s.heading.end)
+        .ok_or_else(|| anyhow::anyhow!("No section {}; call outline_documents_outline for current IDs", id))
+}
+
+/// Byte range of a section (heading line included) by heading path
+pub fn range_by_path(text: &str, path: &[String]) -> Result<Range<usize>> {
+    markdown::find_section(text, path)
+        .map(|s| s.heading.start..s.heading.end)
+        .ok_or_else(|| anyhow::anyhow!("No section matching heading path \"{}\"", path.join(" > ")))
+}
+
+/// Validate a caller-supplied byte range, widening it to whole characters
+pub fn checked_range(text: &str, start: usize, end: Option<usize>) -> Result<Range<usize>> {
+    let end = end.unwrap_or(text.len());
+    if start > end || end > text.len() {
+        anyhow::bail!("Invalid byte range {}-{} for a document of {} bytes", start, end, text.len());
+    }
+    Ok(floor_char_boundary(text, start)..ceil_char_boundary(text, end))
+}
+
+fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// One page of a range: at most `max_bytes` starting `offset` bytes in
+///
+/// Pages end on a line break when one falls in the second half of the page,
+/// and always on a character boundary.
+pub fn page(text: &str, range: Range<usize>, offset: usize, max_bytes: usize) -> Range<usize> {
+    let start = floor_char_boundary(text, (range.start + offset).min(range.end));
+    let limit = start + max_bytes.max(1);
+    if limit >= range.end {
+        return start..range.end;
+    }
+
+    let mut end = floor_char_boundary(text, limit);
+    if let Some(newline) = text[start..end].rfind('\n') {
+        if newline + 1 >= max_bytes / 2 {
+            end = start + newline + 1;
+        }
+    }
+    if end == start {
+        // A single character wider than the page; return it whole rather than nothing
+        end = start + text[start..].chars().next().map_or(0, char::len_utf8);
+    }
+    start..end
+}
+
+/// A single edit for `outline_documents_patch`
+#[derive(Debug, Clone)]
+pub enum Edit {
+    /// Replace exact text, which must occur once unless `all` is set
+    Replace { search: String, replace: String, all: bool },
+    /// Replace the body of the section at a heading path, keeping the heading
+    ReplaceSection {
+        path: Vec<String>,
+        content: String,
+        expect: Option<String>,
+    },
+}
+
+/// Apply edits in order, failing without changes if any anchor doesn't match
+///
+/// Returns the new text and a one-line description of each edit.
+pub fn apply_edits(text: &str, edits: &[Edit]) -> Result<(String, Vec<String>)> {
+    let mut text = text.to_string();
+    let mut applied = Vec::new();
+
+    for (i, edit) in edits.iter().enumerate() {
+        let n = i + 1;
+        match edit {
+            Edit::Replace { search, replace, all } => {
+                if search.is_empty() {
+                    anyhow::bail!("Edit {}: search text is empty", n);
+                }
+                let count = text.matches(search.as_str()).count();
+                match count {
+                    0 => anyhow::bail!(
+                        "Edit {}: search text not found; the document may have changed, re-read it and retry",
+                        n
+                    ),
+                    1 => {}
+                    _ if *all => {}
+                    _ => anyhow::bail!(
+                        "Edit {}: search text matches {} places; include more surrounding text or set replaceAll",
+                        n,
+                        count
+                    ),
+                }
+                text = text.replace(search.as_str(), replace);
+                applied.push(format!("Edit {}: replaced {} occurrence(s)", n, count));
+            }
+            Edit::ReplaceSection { path, content, expect } => {
+                let mut matches = markdown::find_sections(&text, path);
+                let section = match matches.len() {
+                    0 => anyhow::bail!(
+                        "Edit {}: no section matching heading path \"{}\"; the document may have changed",
+                        n,
+                        path.join(" > ")
+                    ),
+                    1 => matches.remove(0),
+                    count => anyhow::bail!(
+                        "Edit {}: heading path \"{}\" matches {} sections; include parent headings to pick one",
+                        n,
+                        path.join(" > "),
+                        count
+                    ),
+                };
+                let body = &text[section.heading.body_range()];
+                if let Some(expect) = expect {
+                    if !body.contains(expect.as_str()) {
+                        anyhow::bail!(
+                            "Edit {}: section \"{}\" no longer contains the expected text; re-read it and retry",
+                            n,
+                            section.path.join(" > ")
+                        );
+                    }
+                }
+                let removed = body.len();
+                text = markdown::replace_section_body(&text, &section.heading, content);
+                applied.push(format!(
+                    "Edit {}: replaced section \"{}\" ({} bytes -> {} bytes)",
+                    n,
+                    section.path.join(" > "),
+                    removed,
+                    content.len()
+                ));
+            }
+        }
+    }
+
+    Ok((text, applied))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "# Guide\n\nIntro\n\n## Setup\n\nInstall it.\n\n### Notes\n\nSetup notes.\n\n## Usage\n\nRun it.\n\n### Notes\n\nUsage notes.\n";
+
+    fn replace(search: &str, replace: &str) -> Edit {
+        Edit::Replace { search: search.to_string(), replace: replace.to_string(), all: false }
+    }
+
+    fn replace_section(path: &[&str], content: &str, expect: Option<&str>) -> Edit {
+        Edit::ReplaceSection {
+            path: path.iter().map(|s| s.to_string()).collect(),
+            content: content.to_string(),
+            expect: expect.map(str::to_string),
+        }
+    }
+
+    fn error(edits: &[Edit]) -> String {
+        apply_edits(DOC, edits).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_apply_edits() {
+        let (text, applied) = apply_edits(DOC, &[
+            replace("Install it.", "Install it with cargo."),
+            replace_section(&["Usage"], "Run `outline`.\n", Some("Run it.")),
+        ])
+        .unwrap();
+        assert!(text.contains("Install it with cargo."));
+        assert!(text.contains("## Usage\nRun `outline`.\n"));
+        assert!(!text.contains("Usage notes."));
+        assert_eq!(applied.len(), 2);
+    }
+
+    #[test]
+    fn test_missing_heading() {
+        let message = error(&[replace_section(&["Deploy"], "Ship it.\n", None)]);
+        assert!(message.contains("no section matching heading path \"Deploy\""), "{}", message);
+    }
+
+    #[test]
+    fn test_duplicate_heading_must_be_qualified() {
+        let message = error(&[replace_section(&["Notes"], "New notes.\n", None)]);
+        assert!(message.contains("matches 2 sections"), "{}", message);
+
+        let (text, _) = apply_edits(DOC, &[replace_section(&["Usage", "Notes"], "New notes.\n", None)]).unwrap();
+        assert!(text.contains("Setup notes."));
+        assert!(!text.contains("Usage notes."));
+        assert!(text.contains("New notes."));
+    }
+
+    #[test]
+    fn test_stale_anchor() {
+        let message = error(&[replace("Install it now.", "Install it.")]);
+        assert!(message.contains("search text not found"), "{}", message);
+
+        let message = error(&[replace_section(&["Setup"], "x\n", Some("Compile it."))]);
+        assert!(message.contains("no longer contains the expected text"), "{}", message);
+
+        let message = error(&[replace("notes.", "remarks.")]);
+        assert!(message.contains("matches 2 places"), "{}", message);
+    }
+
+    #[test]
+    fn test_overlapping_edits_fail_as_a_whole() {
+        // The second edit's anchor was rewritten by the first
+        let message = error(&[
+            replace("Run it.", "Run it daily."),
+            replace_section(&["Usage"], "Stop.\n", Some("Run it.\n")),
+        ]);
+        assert!(message.starts_with("Edit 2:"), "{}", message);
+
+        let message = error(&[
+            replace_section(&["Setup"], "Nothing to install.\n", None),
+            replace("Setup notes.", "Old notes."),
+        ]);
+        assert!(message.starts_with("Edit 2: search text not found"), "{}", message);
+    }
+
+    #[test]
+    fn test_checked_range() {
+        let text = "aé b";
+        assert_eq!(checked_range(text, 0, None).unwrap(), 0..text.len());
+        // Inside "é" widens to the whole character
+        assert_eq!(checked_range(text, 2, Some(2)).unwrap(), 1..3);
+        assert!(checked_range(text, 3, Some(2)).is_err());
+        assert!(checked_range(text, 0, Some(text.len() + 1)).is_err());
+    }
+
+    #[test]
+    fn test_page() {
+        let text = "line one\nline two\nline three\n";
+        let all = 0..text.len();
+
+        // Ends on the last line break in the page
+        assert_eq!(&text[page(text, all.clone(), 0, 15)], "line one\n");
+        assert_eq!(&text[page(text, all.clone(), 9, 100)], "line two\nline three\n");
+
+        // Paging past the end returns an empty page at the end
+        assert_eq!(page(text, all.clone(), text.len(), 10), text.len()..text.len());
+        assert_eq!(page(text, all.clone(), text.len() + 50, 10), text.len()..text.len());
+        assert_eq!(page(text, 5..9, 10, 10), 9..9);
+
+        // Never splits a character, even when it's wider than the page
+        let wide = "日本語";
+        assert_eq!(&wide[page(wide, 0..wide.len(), 0, 1)], "日");
+        assert_eq!(&wide[page(wide, 0..wide.len(), 4, 4)], "本");
+    }
+}
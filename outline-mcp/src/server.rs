@@ -1,25 +1,35 @@
 use anyhow::{Context, Result};
 use outline_api::{
-    OutlineClient,
+    OutlineClient, RevisionConflict,
     CreateDocumentRequest, UpdateDocumentRequest, SearchDocumentsRequest,
     ListDocumentsRequest, ListCollectionsRequest,
+    MoveDocumentRequest, RestoreDocumentRequest, DuplicateDocumentRequest,
+    CreateCollectionRequest, UpdateCollectionRequest, MoveCollectionRequest, CollectionDocumentsRequest,
+    CommentData, CreateCommentRequest, ListCommentsRequest, UpdateCommentRequest,
+    Comment, ListStarsRequest, ViewedDocumentsRequest,
+    Event, ListEventsRequest, SortDirection, TemplatesRequest,
+    DateFilter, ListUsersRequest, CreateAttachmentRequest,
 };
 use rmcp::{
     model::*,
-    tool_router, tool, tool_handler,
-    handler::server::{ServerHandler, tool::ToolRouter, wrapper::Parameters},
+    tool_router, tool,
+    handler::server::{ServerHandler, tool::{ToolCallContext, ToolRouter}, wrapper::Parameters},
+    service::{RequestContext, RoleServer},
     ServiceExt,
 };
 use rmcp::model::{ErrorCode, ServerInfo, ServerCapabilities, ProtocolVersion, Implementation};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
 
 // ============================================================================
 // Search Response Types (optimized for context limits)
 // ============================================================================
 
-/// Maximum length for document text snippets in search results
+/// Maximum length, in characters, of snippets in search results
 const SNIPPET_MAX_LENGTH: usize = 300;
 
 /// A summarized search result optimized for LLM context limits.
@@ -34,14 +44,19 @@ pub struct SearchResultSummary {
     pub ranking: f32,
     /// Text snippet showing where the query matched
     pub context: String,
-    /// Truncated preview of document content (first 300 chars)
+    /// Up to 300 characters of the document around the match
     pub snippet: String,
+    /// Headings enclosing the match, outermost first (only if includeHeadingPath=true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading_path: Option<Vec<String>>,
     /// Full document text (only included if include_content=true)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<String>,
     /// Collection containing this document
     #[serde(skip_serializing_if = "Option::is_none")]
     pub collection_id: Option<String>,
+    /// Published, draft or archived
+    pub status: &'static str,
     /// Last updated timestamp
     pub updated_at: String,
 }
@@ -61,12 +76,51 @@ pub struct SearchResponse {
     pub next_offset: Option<u32>,
 }
 
+use crate::attachments;
+use crate::audit::{self, AuditLog, Caller};
+use crate::comments::{render_comment_summary, render_threads};
 use crate::config::Config;
+use crate::policy::Policy;
+use crate::prompts;
+use crate::resources::{self, ResourceUri, Subscriptions};
+use crate::search;
+use crate::sections::{self, Edit};
+
+/// Page size used when fetching every comment on a document
+const COMMENTS_PAGE_SIZE: u32 = 100;
+
+/// Documents per `resources/list` page
+const RESOURCES_PAGE_SIZE: u32 = 25;
+
+/// Page size used when fetching every document in a collection
+const COLLECTION_PAGE_SIZE: u32 = 100;
+
+/// Page size used when paging through templates or events for prompts
+const PROMPT_PAGE_SIZE: u32 = 100;
+
+/// Upper bound on pages fetched while assembling a prompt
+const MAX_PROMPT_PAGES: u32 = 10;
+
+/// Upper bound on pages fetched to fill one page of filtered search results
+const MAX_SEARCH_PAGES: u32 = 5;
+
+/// Documents whose titles are looked up for `weekly_changes`
+const MAX_CHANGED_DOCUMENTS: usize = 50;
 
 /// Main MCP server for Outline operations
 #[derive(Clone)]
 pub struct OutlineServer {
-    client: Arc<OutlineClient>,
+    client: Arc<RwLock<Arc<OutlineClient>>>,
+    api_base_url: String,
+    /// Whether each session authenticates with its own bearer token (HTTP transport)
+    session_auth: bool,
+    /// Fingerprint of the session's bearer token (HTTP transport)
+    token_fingerprint: Arc<RwLock<Option<String>>>,
+    instance_url: String,
+    prompts_dir: PathBuf,
+    policy: Arc<Policy>,
+    audit: Arc<AuditLog>,
+    subscriptions: Subscriptions,
     tool_router: ToolRouter<Self>,
 }
 
@@ -93,6 +147,74 @@ pub struct GetDocumentParams {
     pub id: String,
 }
 
+/// Parameters for reading one section of a document
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetSectionParams {
+    /// The document's unique identifier (UUID)
+    pub id: String,
+    /// Section ID from outline_documents_outline, e.g. "2.1" ("0" is the text before the first heading)
+    #[serde(rename = "sectionId")]
+    pub section_id: Option<String>,
+    /// Heading titles from outer to inner, e.g. ["Setup", "Install"]. A suffix is enough.
+    #[serde(rename = "headingPath")]
+    pub heading_path: Option<Vec<String>>,
+    /// Start of a byte range to read instead of a section
+    pub start: Option<usize>,
+    /// End of the byte range (exclusive, default: end of document)
+    pub end: Option<usize>,
+    /// Bytes into the section to start this page at (use nextOffset from the previous page)
+    pub offset: Option<usize>,
+    /// Maximum bytes to return (default: 16000, max: 100000)
+    #[serde(rename = "maxBytes")]
+    pub max_bytes: Option<usize>,
+}
+
+/// A single edit applied by outline_documents_patch
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PatchEditParams {
+    /// Search/replace: exact text to find. Must match exactly once unless replaceAll is set.
+    pub search: Option<String>,
+    /// Search/replace: text to put in its place
+    pub replace: Option<String>,
+    /// Search/replace: replace every occurrence instead of requiring a unique match
+    #[serde(default, rename = "replaceAll")]
+    pub replace_all: bool,
+    /// Replace-section: heading titles from outer to inner, e.g. ["Setup", "Install"]
+    #[serde(rename = "headingPath")]
+    pub heading_path: Option<Vec<String>>,
+    /// Replace-section: new body for the section. The heading line is kept; subsections are replaced.
+    pub content: Option<String>,
+    /// Replace-section: text the section must still contain, or the edit is refused
+    pub expect: Option<String>,
+}
+
+impl PatchEditParams {
+    fn into_edit(self, n: usize) -> Result<Edit> {
+        match (self.search, self.heading_path) {
+            (Some(search), None) => Ok(Edit::Replace {
+                search,
+                replace: self.replace.unwrap_or_default(),
+                all: self.replace_all,
+            }),
+            (None, Some(path)) => Ok(Edit::ReplaceSection {
+                path,
+                content: self.content.with_context(|| format!("Edit {}: replace-section needs content", n))?,
+                expect: self.expect,
+            }),
+            _ => anyhow::bail!("Edit {}: give either search (with replace) or headingPath (with content)", n),
+        }
+    }
+}
+
+/// Parameters for patching a document in place
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PatchDocumentParams {
+    /// The document's unique identifier (UUID)
+    pub id: String,
+    /// Edits to apply in order. If any anchor doesn't match, nothing is saved.
+    pub edits: Vec<PatchEditParams>,
+}
+
 /// Parameters for creating a new document
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct CreateDocumentParams {
@@ -149,6 +271,78 @@ pub struct SearchDocumentsParams {
     /// Use outline_documents_get to retrieve full content for specific documents.
     #[serde(default, rename = "includeContent")]
     pub include_content: bool,
+    /// Only documents by this author: a user ID, name or email
+    pub author: Option<String>,
+    /// Only documents updated within the last day, week, month or year
+    #[serde(rename = "dateFilter")]
+    pub date_filter: Option<SearchDateFilter>,
+    /// Only documents updated on or after this date (YYYY-MM-DD or RFC 3339)
+    #[serde(rename = "updatedAfter")]
+    pub updated_after: Option<String>,
+    /// Only documents updated before this date (YYYY-MM-DD or RFC 3339)
+    #[serde(rename = "updatedBefore")]
+    pub updated_before: Option<String>,
+    /// Only documents with this status
+    pub status: Option<SearchStatus>,
+    /// Include drafts in results (default: false)
+    #[serde(rename = "includeDrafts")]
+    pub include_drafts: Option<bool>,
+    /// Include archived documents in results (default: false)
+    #[serde(rename = "includeArchived")]
+    pub include_archived: Option<bool>,
+    /// Add the heading path of the matched section to each result (default: false)
+    #[serde(default, rename = "includeHeadingPath")]
+    pub include_heading_path: bool,
+}
+
+/// Relative date ranges supported by Outline's search
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchDateFilter {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl From<SearchDateFilter> for DateFilter {
+    fn from(filter: SearchDateFilter) -> Self {
+        match filter {
+            SearchDateFilter::Day => DateFilter::Day,
+            SearchDateFilter::Week => DateFilter::Week,
+            SearchDateFilter::Month => DateFilter::Month,
+            SearchDateFilter::Year => DateFilter::Year,
+        }
+    }
+}
+
+/// Document status filter for search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchStatus {
+    Published,
+    Draft,
+    Archived,
+}
+
+impl SearchStatus {
+    fn of(document: &outline_api::Document) -> Self {
+        if document.archived_at.is_some() {
+            Self::Archived
+        } else if document.published_at.is_none() {
+            Self::Draft
+        } else {
+            Self::Published
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Published => "published",
+            Self::Draft => "draft",
+            Self::Archived => "archived",
+        }
+    }
 }
 
 /// Parameters for listing collections
@@ -167,25 +361,182 @@ pub struct GetCollectionParams {
     pub id: String,
 }
 
+/// Parameters for getting collection document trees
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CollectionTreeParams {
+    /// Collection to show. Omit for every collection you can access.
+    pub id: Option<String>,
+}
+
+/// Parameters for creating a collection
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CreateCollectionParams {
+    /// Collection name
+    pub name: String,
+    /// Collection description (Markdown)
+    pub description: Option<String>,
+    /// Hex color, e.g. "#4E5C6E"
+    pub color: Option<String>,
+    /// Set true to make the collection private to its members
+    pub private: Option<bool>,
+}
+
+/// Parameters for updating a collection
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct UpdateCollectionParams {
+    /// Collection identifier to update
+    pub id: String,
+    /// New name
+    pub name: Option<String>,
+    /// New description (Markdown)
+    pub description: Option<String>,
+    /// New hex color
+    pub color: Option<String>,
+}
+
+/// Parameters for moving a collection in the sidebar
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MoveCollectionParams {
+    /// Collection identifier to move
+    pub id: String,
+    /// New position in the sidebar (0 = top)
+    pub index: u32,
+}
+
+/// Parameters for moving a document
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MoveDocumentParams {
+    /// Document identifier to move
+    pub id: String,
+    /// Collection to move into. Omit to stay in the current collection.
+    #[serde(rename = "collectionId")]
+    pub collection_id: Option<String>,
+    /// Document to nest under. Omit to move to the top level of the collection.
+    #[serde(rename = "parentDocumentId")]
+    pub parent_document_id: Option<String>,
+    /// Position among its new siblings (0 = first)
+    pub index: Option<u32>,
+}
+
+/// Parameters for restoring a document
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RestoreDocumentParams {
+    /// Document identifier to restore
+    pub id: String,
+    /// Revision to roll the document back to. Omit to restore from trash.
+    #[serde(rename = "revisionId")]
+    pub revision_id: Option<String>,
+    /// Collection to restore into, if the original collection no longer exists
+    #[serde(rename = "collectionId")]
+    pub collection_id: Option<String>,
+}
+
+/// Parameters for duplicating a document
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DuplicateDocumentParams {
+    /// Document identifier to copy
+    pub id: String,
+    /// Title for the copy (default: "Copy of ...")
+    pub title: Option<String>,
+    /// Also copy nested documents (default: false)
+    #[serde(default)]
+    pub recursive: bool,
+    /// Publish the copy (default: false)
+    pub publish: Option<bool>,
+    /// Collection to put the copy in (default: same collection)
+    #[serde(rename = "collectionId")]
+    pub collection_id: Option<String>,
+    /// Document to nest the copy under
+    #[serde(rename = "parentDocumentId")]
+    pub parent_document_id: Option<String>,
+}
+
+/// Parameters for listing a document's comment threads
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListCommentsParams {
+    /// Document whose comments to list
+    #[serde(rename = "documentId")]
+    pub document_id: String,
+    /// Include resolved threads (default: true)
+    #[serde(rename = "includeResolved")]
+    pub include_resolved: Option<bool>,
+}
+
+/// Parameters for creating a comment or reply
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CreateCommentParams {
+    /// Document to comment on
+    #[serde(rename = "documentId")]
+    pub document_id: String,
+    /// Comment text
+    pub text: String,
+    /// Reply to this comment instead of starting a new thread
+    #[serde(rename = "parentCommentId")]
+    pub parent_comment_id: Option<String>,
+}
+
+/// Parameters for editing a comment
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct UpdateCommentParams {
+    /// Comment identifier to edit
+    pub id: String,
+    /// New comment text (replaces the existing text)
+    pub text: String,
+}
+
+/// Parameters for resolving or reopening a comment thread
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CommentThreadParams {
+    /// Identifier of the thread's top-level comment
+    pub id: String,
+}
+
+/// Parameters for uploading an attachment
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct UploadAttachmentParams {
+    /// File name, e.g. "diagram.png"; also used to guess the content type
+    pub name: Option<String>,
+    /// File contents as base64, optionally as a data: URL
+    pub data: Option<String>,
+    /// Path of a local file to upload (stdio transport only)
+    pub path: Option<String>,
+    /// MIME type (default: guessed from the name or data: URL)
+    #[serde(rename = "contentType")]
+    pub content_type: Option<String>,
+    /// Document the attachment belongs to
+    #[serde(rename = "documentId")]
+    pub document_id: Option<String>,
+    /// Alt text for the embed snippet (default: the file name)
+    pub alt: Option<String>,
+}
+
+/// Parameters for fetching an attachment
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetAttachmentParams {
+    /// Attachment ID, or a link as found in documents (/api/attachments.redirect?id=...)
+    pub id: String,
+    /// Document that embeds the attachment
+    #[serde(rename = "documentId")]
+    pub document_id: Option<String>,
+}
+
 // ============================================================================
 // Server Implementation
 // ============================================================================
 
-#[tool_router]
+#[tool_router(vis = "pub(crate)")]
 impl OutlineServer {
     /// Create a new Outline MCP server
-    pub async fn new() -> Result<Self> {
+    pub async fn new(config: &Config, audit: Arc<AuditLog>) -> Result<Self> {
         tracing::info!("Initializing Outline MCP server");
 
-        // Load config to get instance URL
-        let config = Config::load()?;
         let api_base_url = config.get_api_base_url()
             .context("Failed to get API base URL from config")?;
 
         tracing::info!("Using API base URL: {}", api_base_url);
 
         // Create client with auto auth (uses keyring)
-        let client = OutlineClient::new(api_base_url)?;
+        let client = OutlineClient::new(api_base_url.clone())?;
 
         // Test authentication
         match outline_api::auth::get_access_token().await {
@@ -195,9 +546,397 @@ impl OutlineServer {
             }
         }
 
+        Self::with_client(config, api_base_url, client, false, audit)
+    }
+
+    /// Create a server for one HTTP session
+    ///
+    /// The session's client is created from the bearer token of its
+    /// `initialize` request; until then requests carry an empty token rather
+    /// than falling back to the keyring.
+    pub fn for_http_session(config: &Config, audit: Arc<AuditLog>) -> Result<Self> {
+        let api_base_url = config.get_api_base_url()
+            .context("Failed to get API base URL from config")?;
+        let client = OutlineClient::new(api_base_url.clone())?.with_token(String::new());
+
+        Self::with_client(config, api_base_url, client, true, audit)
+    }
+
+    fn with_client(
+        config: &Config,
+        api_base_url: String,
+        client: OutlineClient,
+        session_auth: bool,
+        audit: Arc<AuditLog>,
+    ) -> Result<Self> {
+        let tool_router = Self::policy_tool_router(&config.policy);
+
         Ok(Self {
-            client: Arc::new(client),
-            tool_router: Self::tool_router(),
+            client: Arc::new(RwLock::new(Arc::new(client))),
+            api_base_url,
+            session_auth,
+            token_fingerprint: Arc::new(RwLock::new(None)),
+            instance_url: config.get_instance_url()?,
+            prompts_dir: config.prompts_dir()?,
+            policy: Arc::new(config.policy.clone()),
+            audit,
+            subscriptions: Subscriptions::default(),
+            tool_router,
+        })
+    }
+
+    /// The tool router without the tools the policy doesn't register
+    ///
+    /// In read-only mode write tools aren't registered at all, so clients never see them.
+    pub(crate) fn policy_tool_router(policy: &Policy) -> ToolRouter<Self> {
+        let mut tool_router = Self::tool_router();
+        let dropped: Vec<String> = tool_router.list_all()
+            .into_iter()
+            .filter(|tool| !policy.registers(tool))
+            .map(|tool| tool.name.to_string())
+            .collect();
+        for name in dropped {
+            tool_router.remove_route(&name);
+        }
+        tool_router
+    }
+
+    /// Fetch a document, failing if the policy doesn't allow its collection
+    async fn allowed_document(&self, id: &str) -> Result<outline_api::Document> {
+        let document = self.client().get_document(id.to_string()).await?;
+        self.policy.check_collection(document.collection_id.as_deref())?;
+        Ok(document)
+    }
+
+    /// Fetch the document a comment belongs to, failing if the policy doesn't allow it
+    async fn check_comment(&self, id: &str) -> Result<()> {
+        let comment = self.client().get_comment(id.to_string()).await?;
+        self.allowed_document(&comment.document_id).await?;
+        Ok(())
+    }
+
+    /// The Outline client for this session
+    fn client(&self) -> Arc<OutlineClient> {
+        self.client.read().unwrap().clone()
+    }
+
+    /// Fetch every comment on a document, following pagination
+    async fn list_all_comments(&self, document_id: &str) -> Result<Vec<Comment>> {
+        let mut comments = Vec::new();
+        loop {
+            let request = ListCommentsRequest::new(document_id.to_string())
+                .offset(comments.len() as u32)
+                .limit(COMMENTS_PAGE_SIZE);
+
+            let response = self.client().list_comments(request).await?;
+            let count = response.data.len() as u32;
+            comments.extend(response.data);
+            if count < COMMENTS_PAGE_SIZE {
+                return Ok(comments);
+            }
+        }
+    }
+
+    /// Fetch every document in a collection, following pagination
+    async fn list_all_collection_documents(&self, collection_id: &str) -> Result<Vec<outline_api::Document>> {
+        let mut documents = Vec::new();
+        loop {
+            let request = ListDocumentsRequest {
+                backlink_document_id: None,
+                collection_id: Some(collection_id.to_string()),
+                direction: None,
+                limit: Some(COLLECTION_PAGE_SIZE),
+                offset: Some(documents.len() as u32),
+                parent_document_id: None,
+                sort: None,
+                template: None,
+                user_id: None,
+            };
+
+            let response = self.client().list_documents(request).await?;
+            let count = response.data.len() as u32;
+            documents.extend(response.data);
+            if count < COLLECTION_PAGE_SIZE {
+                return Ok(documents);
+            }
+        }
+    }
+
+    /// Every collection, following pagination
+    async fn list_all_collections(&self) -> Result<Vec<outline_api::Collection>> {
+        let mut collections = Vec::new();
+        loop {
+            let request = ListCollectionsRequest::with_pagination(collections.len() as u32, COLLECTION_PAGE_SIZE);
+            let response = self.client().list_collections(request).await?;
+            let count = response.data.len() as u32;
+            collections.extend(response.data);
+            if count < COLLECTION_PAGE_SIZE {
+                return Ok(collections);
+            }
+        }
+    }
+
+    /// Resolve a user given as an ID, name or email to their ID
+    async fn resolve_user(&self, user: &str) -> Result<String> {
+        // Outline IDs are UUIDs; anything else is looked up
+        if user.len() == 36 && user.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+            return Ok(user.to_string());
+        }
+
+        let request = ListUsersRequest::builder().query(user.to_string()).limit(25).build();
+        let users = self.client().list_users(request).await?.data;
+
+        let exact: Vec<_> = users
+            .iter()
+            .filter(|u| {
+                u.name.eq_ignore_ascii_case(user)
+                    || u.email.as_deref().is_some_and(|e| e.eq_ignore_ascii_case(user))
+            })
+            .collect();
+        let all: Vec<_> = users.iter().collect();
+        match (exact.as_slice(), all.as_slice()) {
+            ([only], _) | ([], [only]) => Ok(only.id.clone()),
+            ([], []) => anyhow::bail!("No user matching '{}'", user),
+            _ => {
+                let names: Vec<&str> = users.iter().map(|u| u.name.as_str()).collect();
+                anyhow::bail!("'{}' matches several users ({}); use a full name, email or ID", user, names.join(", "))
+            }
+        }
+    }
+
+    /// Fail unless a document may be placed at the given destination
+    ///
+    /// Without a collection the destination is the parent's collection, or the
+    /// document's current one.
+    async fn check_destination(
+        &self,
+        collection_id: Option<&str>,
+        parent_document_id: Option<&str>,
+        current_collection_id: Option<&str>,
+    ) -> Result<()> {
+        match (collection_id, parent_document_id) {
+            (Some(collection_id), _) => self.policy.check_collection(Some(collection_id)),
+            (None, Some(parent_id)) => self.allowed_document(parent_id).await.map(|_| ()),
+            (None, None) => self.policy.check_collection(current_collection_id),
+        }
+    }
+
+    /// Read the contents of an `outline://` resource
+    async fn read_resource_uri(&self, uri: &ResourceUri) -> Result<String> {
+        match uri {
+            ResourceUri::Collection(id) => {
+                self.policy.check_collection(Some(id))?;
+                let collection = self.client().get_collection(id.clone()).await?;
+                let documents = self.list_all_collection_documents(id).await?;
+                Ok(resources::render_collection(&collection, &documents))
+            }
+            ResourceUri::Document(id) => {
+                let document = self.allowed_document(id).await?;
+                Ok(resources::render_document(&document, &self.instance_url))
+            }
+            ResourceUri::DocumentComments(id) => {
+                self.allowed_document(id).await?;
+                let comments = self.list_all_comments(id).await?;
+                Ok(render_threads(&comments))
+            }
+            ResourceUri::AuditSummary => {
+                let mut entries = audit::read_recent_entries(self.audit.path(), audit::MAX_SUMMARY_ENTRIES)?;
+                // The log is shared by every HTTP session; each caller only sees calls made with its own token
+                if self.session_auth {
+                    let token = self.token_fingerprint.read().unwrap().clone();
+                    entries.retain(|entry| token.is_some() && entry.token == token);
+                }
+                Ok(serde_json::to_string_pretty(&audit::summarize(&entries))?)
+            }
+        }
+    }
+
+    /// Find a template by ID or URL ID
+    async fn find_template(&self, id: &str) -> Result<outline_api::Document> {
+        for page in 0..MAX_PROMPT_PAGES {
+            let request = TemplatesRequest {
+                collection_id: None,
+                offset: Some(page * PROMPT_PAGE_SIZE),
+                limit: Some(PROMPT_PAGE_SIZE),
+            };
+            let templates = self.client().list_templates(request).await?.data;
+            let count = templates.len() as u32;
+
+            if let Some(template) = templates.into_iter().find(|t| t.id == id || t.url_id == id) {
+                return Ok(template);
+            }
+            if count < PROMPT_PAGE_SIZE {
+                break;
+            }
+        }
+        anyhow::bail!("Template not found: {}", id)
+    }
+
+    /// Events since the given time, newest first
+    async fn events_since(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+        collection_id: Option<&str>,
+    ) -> Result<Vec<Event>> {
+        let mut events = Vec::new();
+
+        for page in 0..MAX_PROMPT_PAGES {
+            let mut request = ListEventsRequest::new()
+                .direction(SortDirection::Desc)
+                .offset(page * PROMPT_PAGE_SIZE)
+                .limit(PROMPT_PAGE_SIZE);
+            if let Some(collection_id) = collection_id {
+                request = request.collection_id(collection_id.to_string());
+            }
+
+            let response = self.client().list_events(request).await?;
+            let count = response.data.len() as u32;
+            for event in response.data {
+                let at = chrono::DateTime::parse_from_rfc3339(&event.created_at);
+                if at.is_ok_and(|at| at < since) {
+                    return Ok(events);
+                }
+                events.push(event);
+            }
+            if count < PROMPT_PAGE_SIZE {
+                break;
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Assemble one of the built-in prompts
+    async fn builtin_prompt(&self, name: &str, args: &HashMap<String, String>) -> Result<String> {
+        let required = |key: &str| {
+            args.get(key)
+                .map(String::as_str)
+                .with_context(|| format!("Missing required argument: {}", key))
+        };
+
+        match name {
+            "summarize_collection" => {
+                let id = required("collectionId")?;
+                self.policy.check_collection(Some(id))?;
+                let collection = self.client().get_collection(id.to_string()).await?;
+                let documents = self.list_all_collection_documents(id).await?;
+                Ok(prompts::summarize_collection(&collection, &documents))
+            }
+            "draft_from_template" => {
+                let template = self.find_template(required("templateId")?).await?;
+                self.policy.check_collection(template.collection_id.as_deref())?;
+                Ok(prompts::draft_from_template(
+                    &template,
+                    args.get("title").map(String::as_str),
+                    args.get("notes").map(String::as_str),
+                ))
+            }
+            "weekly_changes" => {
+                let since = match args.get("since") {
+                    Some(since) => audit::parse_since(since)?,
+                    None => chrono::Utc::now() - chrono::Duration::days(7),
+                };
+                let collection_id = args.get("collectionId").map(String::as_str);
+                if collection_id.is_some() {
+                    self.policy.check_collection(collection_id)?;
+                }
+                let mut events = self.events_since(since, collection_id).await?;
+                if self.policy.filters_collections() {
+                    events.retain(|e| self.policy.allows_collection(e.collection_id.as_deref()));
+                }
+
+                let mut titles = HashMap::new();
+                for event in &events {
+                    let Some(id) = event.document_id.as_ref() else {
+                        continue;
+                    };
+                    if titles.contains_key(id) || titles.len() >= MAX_CHANGED_DOCUMENTS {
+                        continue;
+                    }
+                    // Deleted documents can't be fetched; the event may still carry the title
+                    let title = match self.client().get_document(id.clone()).await {
+                        Ok(document) => Some(document.title),
+                        Err(_) => event.data.as_ref()
+                            .and_then(|d| d["title"].as_str())
+                            .map(str::to_string),
+                    };
+                    if let Some(title) = title {
+                        titles.insert(id.clone(), title);
+                    }
+                }
+
+                Ok(prompts::weekly_changes(&since.format("%Y-%m-%d %H:%M UTC").to_string(), &events, &titles))
+            }
+            _ => anyhow::bail!("Unknown prompt: {}", name),
+        }
+    }
+
+    /// Assemble a user-defined prompt, expanding `{{outline://...}}` placeholders
+    async fn user_prompt(&self, definition: &prompts::PromptDefinition, args: &HashMap<String, String>) -> Result<String> {
+        let mut text = String::new();
+        let mut read: HashMap<String, String> = HashMap::new();
+
+        for part in definition.fill_arguments(args)? {
+            match part {
+                prompts::TemplatePart::Text(part) => text.push_str(&part),
+                prompts::TemplatePart::Resource(uri) => {
+                    if !read.contains_key(&uri) {
+                        let resource = ResourceUri::parse(&uri).context("Invalid resource URI")?;
+                        let contents = self.read_resource_uri(&resource).await
+                            .with_context(|| format!("Failed to read {}", uri))?;
+                        read.insert(uri.clone(), contents);
+                    }
+                    text.push_str(&read[&uri]);
+                }
+            }
+        }
+
+        Ok(text)
+    }
+
+    /// One page of the resource list: recently viewed documents, then starred ones
+    async fn list_resources_page(&self, cursor: Option<&str>) -> Result<ListResourcesResult> {
+        let (source, offset) = match cursor {
+            None => ("viewed", 0),
+            Some(cursor) => {
+                let (source, offset) = cursor.split_once(':')
+                    .context("Invalid cursor")?;
+                (source, offset.parse::<u32>().context("Invalid cursor")?)
+            }
+        };
+
+        let (documents, next_cursor) = match source {
+            "viewed" => {
+                let request = ViewedDocumentsRequest::with_pagination(offset, RESOURCES_PAGE_SIZE);
+                let documents = self.client().list_viewed_documents(request).await?.data;
+                let next = if documents.len() as u32 >= RESOURCES_PAGE_SIZE {
+                    format!("viewed:{}", offset + RESOURCES_PAGE_SIZE)
+                } else {
+                    "starred:0".to_string()
+                };
+                (documents, Some(next))
+            }
+            "starred" => {
+                let request = ListStarsRequest::with_pagination(offset, RESOURCES_PAGE_SIZE);
+                let response = self.client().list_stars(request).await?;
+                let next = (response.data.stars.len() as u32 >= RESOURCES_PAGE_SIZE)
+                    .then(|| format!("starred:{}", offset + RESOURCES_PAGE_SIZE));
+                (response.data.documents, next)
+            }
+            _ => anyhow::bail!("Invalid cursor"),
+        };
+
+        let mut listed: Vec<Resource> = documents.iter()
+            .filter(|d| self.policy.allows_collection(d.collection_id.as_deref()))
+            .map(resources::document_resource)
+            .collect();
+        if cursor.is_none() {
+            listed.insert(0, resources::audit_summary_resource());
+        }
+
+        Ok(ListResourcesResult {
+            resources: listed,
+            next_cursor,
         })
     }
 
@@ -239,6 +978,11 @@ impl OutlineServer {
     ) -> Result<CallToolResult, ErrorData> {
         let params = params.0;
 
+        if params.collection_id.is_some() {
+            self.policy.check_collection(params.collection_id.as_deref())
+                .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        }
+
         let request = ListDocumentsRequest {
             backlink_document_id: None,
             collection_id: params.collection_id,
@@ -251,8 +995,9 @@ impl OutlineServer {
             user_id: None,
         };
 
-        let response = self.client.list_documents(request).await
+        let mut response = self.client().list_documents(request).await
             .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        response.data.retain(|d| self.policy.allows_collection(d.collection_id.as_deref()));
 
         let json = serde_json::to_string_pretty(&response)
             .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
@@ -294,7 +1039,7 @@ impl OutlineServer {
     ) -> Result<CallToolResult, ErrorData> {
         let params = params.0;
 
-        let document = self.client.get_document(params.id).await
+        let document = self.allowed_document(&params.id).await
             .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
 
         let json = serde_json::to_string_pretty(&document)
@@ -303,81 +1048,270 @@ impl OutlineServer {
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    /// Create a new document in Outline.
+    /// Get the outline of a document: its heading tree without the text.
     ///
-    /// Creates a new document with the specified title and content. Documents can be created
-    /// as drafts or published immediately. Can be placed in a collection or nested under
-    /// a parent document.
+    /// Lists every heading with a section ID (e.g. "2.1"), its byte range and size. Use this
+    /// instead of outline_documents_get for long documents, then read only the sections you
+    /// need with outline_documents_get_section.
     ///
     /// Parameters:
-    /// - title (required): Document title (appears in navigation and search)
-    /// - text (required): Document content in Markdown format. Supports headings, lists, code blocks, links, images, and tables.
-    /// - collectionId (optional): Place document in a specific collection. Get IDs from outline_collections_list.
-    /// - parentDocumentId (optional): Nest under another document. Creates hierarchical structure.
-    /// - publish (optional): Set to true to publish immediately. Default false creates a draft.
-    ///
-    /// IMPORTANT - Document Links: When linking to other documents, always use the full UUID format:
-    /// [Link Text](/doc/xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx)
-    /// Do NOT use the short urlId format. The full UUID creates proper document page connections
-    /// that display as embedded document cards in the Outline UI.
+    /// - id (required): The document's unique identifier (UUID format)
     ///
-    /// Returns: JSON object containing the created document with:
-    /// - id: New document's unique identifier (use this for document links)
-    /// - title, text, emoji, collectionId, parentDocumentId
-    /// - createdAt/updatedAt: ISO 8601 timestamps
-    /// - revision: Initial version number (1)
+    /// Returns: Indented list of "[sectionId] Title bytes start-end, size". Section "0" is the
+    /// text before the first heading, if any. Section IDs change when headings are added or
+    /// removed, so re-read the outline after editing.
     ///
-    /// Example - Create draft: {"title": "Meeting Notes", "text": "# Meeting Notes\n\n## Attendees\n..."}
-    /// Example - Create in collection: {"title": "API Docs", "text": "...", "collectionId": "abc123", "publish": true}
-    /// Example - Link to another doc: {"title": "Overview", "text": "See [Details](/doc/abc12345-def6-7890-abcd-ef1234567890)"}
+    /// Example: {"id": "abc123-def456-..."}
     #[tool(annotations(
-        title = "Create Document",
-        read_only_hint = false,
+        title = "Get Document Outline",
+        read_only_hint = true,
         destructive_hint = false,
-        idempotent_hint = false,
+        idempotent_hint = true,
         open_world_hint = false
     ))]
-    async fn outline_documents_create(
+    async fn outline_documents_outline(
         &self,
-        params: Parameters<CreateDocumentParams>,
+        params: Parameters<GetDocumentParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let params = params.0;
-
-        let request = CreateDocumentRequest {
-            title: params.title,
-            text: params.text,
-            collection_id: params.collection_id,
-            parent_document_id: params.parent_document_id,
-            template_id: None,
-            template: None,
-            emoji: None,
-            publish: params.publish,
-        };
-
-        let document = self.client.create_document(request).await
-            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
-
-        let json = serde_json::to_string_pretty(&document)
+        let document = self.allowed_document(&params.0.id).await
             .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
 
-        Ok(CallToolResult::success(vec![Content::text(json)]))
+        let outline = sections::render_outline(&document.title, &document.id, &document.text);
+        Ok(CallToolResult::success(vec![Content::text(outline)]))
     }
 
-    /// Update an existing document.
+    /// Read one section of a document, paginated for huge sections.
     ///
-    /// Modifies an existing document's title, content, or publish status. All update fields
-    /// are optional - only specified fields will be changed. Use outline_documents_get first
-    /// to retrieve current content if making partial text edits.
+    /// Select the section by sectionId (from outline_documents_outline), by headingPath, or by
+    /// a byte range (start/end). Sections include their heading line and subsections. Long
+    /// sections are returned in pages; pass the returned nextOffset as offset to continue.
     ///
     /// Parameters:
-    /// - id (required): Document identifier to update
-    /// - title (optional): New document title
-    /// - text (optional): New document content in Markdown format. Replaces entire content.
-    /// - publish (optional): Set to true to publish a draft document
+    /// - id (required): The document's unique identifier
+    /// - sectionId (optional): Section ID such as "2.1"; "0" is the text before the first heading
+    /// - headingPath (optional): Heading titles, outer to inner. ["Install"] finds the first
+    ///   Install heading anywhere; ["Setup", "Install"] only one directly under Setup.
+    /// - start/end (optional): Byte range to read instead of a section
+    /// - offset (optional): Bytes into the selection to start at. Default 0.
+    /// - maxBytes (optional): Page size. Default 16000, max 100000.
     ///
-    /// IMPORTANT - Document Links: When linking to other documents, always use the full UUID format:
-    /// [Link Text](/doc/xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx)
-    /// Do NOT use the short urlId format. The full UUID creates proper document page connections
+    /// Returns: A header line with the byte range shown, the text, and a nextOffset line when
+    /// more remains.
+    ///
+    /// Example - By ID: {"id": "abc123", "sectionId": "2.1"}
+    /// Example - By heading: {"id": "abc123", "headingPath": ["Runbook", "Failover"]}
+    /// Example - Next page: {"id": "abc123", "sectionId": "2.1", "offset": 16000}
+    #[tool(annotations(
+        title = "Get Document Section",
+        read_only_hint = true,
+        destructive_hint = false,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_documents_get_section(
+        &self,
+        params: Parameters<GetSectionParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        let document = self.allowed_document(&params.id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        let text = &document.text;
+
+        let selected = match (&params.section_id, &params.heading_path, params.start) {
+            (Some(id), None, None) => sections::range_by_id(text, id),
+            (None, Some(path), None) => sections::range_by_path(text, path),
+            (None, None, Some(start)) => sections::checked_range(text, start, params.end),
+            _ => Err(anyhow::anyhow!("Give exactly one of sectionId, headingPath or start")),
+        }
+        .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let max_bytes = params.max_bytes.unwrap_or(sections::DEFAULT_PAGE_BYTES).min(sections::MAX_PAGE_BYTES);
+        let page = sections::page(text, selected.clone(), params.offset.unwrap_or(0), max_bytes);
+
+        let mut out = format!(
+            "Bytes {}-{} of selection {}-{} ({} bytes total in document)\n\n",
+            page.start, page.end, selected.start, selected.end, text.len()
+        );
+        out.push_str(&text[page.clone()]);
+        if page.end < selected.end {
+            if !out.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str(&format!(
+                "\n[{} more bytes; nextOffset: {}]\n",
+                selected.end - page.end,
+                page.end - selected.start
+            ));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(out)]))
+    }
+
+    /// Edit part of a document without rewriting the whole text.
+    ///
+    /// Applies edits server-side, in order, to the current text and saves the result. Each edit
+    /// is either a search/replace or a replace-section. If any anchor no longer matches (search
+    /// text missing or ambiguous, heading missing or ambiguous, expected text gone), or if the
+    /// document gets a new revision while the edits are applied, the tool fails and nothing is
+    /// saved, so content is never dropped by a stale rewrite.
+    ///
+    /// Parameters:
+    /// - id (required): The document's unique identifier
+    /// - edits (required): List of edits, each one of:
+    ///   - {"search": "...", "replace": "..."}: search must match exactly once (or set
+    ///     "replaceAll": true)
+    ///   - {"headingPath": [...], "content": "...", "expect": "..."}: replaces the body under the
+    ///     heading, keeping the heading line; the path must match one heading (add parent
+    ///     headings to tell duplicates apart); "expect" (optional) must still appear in the body
+    ///
+    /// Returns: One line per applied edit and the new document revision.
+    ///
+    /// Example - Fix a typo: {"id": "abc123", "edits": [{"search": "recieve", "replace": "receive"}]}
+    /// Example - Rewrite a section: {"id": "abc123", "edits": [{"headingPath": ["Failover"], "content": "1. Promote the replica\n"}]}
+    #[tool(annotations(
+        title = "Patch Document",
+        read_only_hint = false,
+        destructive_hint = false,
+        idempotent_hint = false,
+        open_world_hint = false
+    ))]
+    async fn outline_documents_patch(
+        &self,
+        params: Parameters<PatchDocumentParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        if params.edits.is_empty() {
+            return Err(ErrorData::new(ErrorCode(-32000), "No edits given".to_string(), None));
+        }
+        let edits: Vec<Edit> = params.edits.into_iter()
+            .enumerate()
+            .map(|(i, edit)| edit.into_edit(i + 1))
+            .collect::<Result<_>>()
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let document = self.allowed_document(&params.id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let (text, applied) = sections::apply_edits(&document.text, &edits)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        if text == document.text {
+            let message = format!("{}\nNo change; document not saved", applied.join("\n"));
+            return Ok(CallToolResult::success(vec![Content::text(message)]));
+        }
+
+        let request = UpdateDocumentRequest {
+            id: document.id.clone(),
+            title: None,
+            text: Some(text),
+            emoji: None,
+            append: None,
+            publish: None,
+            done: None,
+        };
+        // Edits were anchored against this revision; don't overwrite anything newer
+        let updated = self.client().update_document_if_revision(request, document.revision).await
+            .map_err(|e| match e.downcast_ref::<RevisionConflict>() {
+                Some(conflict) => ErrorData::new(
+                    ErrorCode(-32000),
+                    format!("{}; nothing was saved, re-read the document and apply the edits again", conflict),
+                    None,
+                ),
+                None => ErrorData::new(ErrorCode(-32000), e.to_string(), None),
+            })?;
+
+        let message = format!(
+            "{}\nSaved \"{}\" (revision {}, {} bytes)",
+            applied.join("\n"),
+            updated.title,
+            updated.revision,
+            updated.text.len()
+        );
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    /// Create a new document in Outline.
+    ///
+    /// Creates a new document with the specified title and content. Documents can be created
+    /// as drafts or published immediately. Can be placed in a collection or nested under
+    /// a parent document.
+    ///
+    /// Parameters:
+    /// - title (required): Document title (appears in navigation and search)
+    /// - text (required): Document content in Markdown format. Supports headings, lists, code blocks, links, images, and tables.
+    /// - collectionId (optional): Place document in a specific collection. Get IDs from outline_collections_list.
+    /// - parentDocumentId (optional): Nest under another document. Creates hierarchical structure.
+    /// - publish (optional): Set to true to publish immediately. Default false creates a draft.
+    ///
+    /// IMPORTANT - Document Links: When linking to other documents, always use the full UUID format:
+    /// [Link Text](/doc/xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx)
+    /// Do NOT use the short urlId format. The full UUID creates proper document page connections
+    /// that display as embedded document cards in the Outline UI.
+    ///
+    /// Returns: JSON object containing the created document with:
+    /// - id: New document's unique identifier (use this for document links)
+    /// - title, text, emoji, collectionId, parentDocumentId
+    /// - createdAt/updatedAt: ISO 8601 timestamps
+    /// - revision: Initial version number (1)
+    ///
+    /// Example - Create draft: {"title": "Meeting Notes", "text": "# Meeting Notes\n\n## Attendees\n..."}
+    /// Example - Create in collection: {"title": "API Docs", "text": "...", "collectionId": "abc123", "publish": true}
+    /// Example - Link to another doc: {"title": "Overview", "text": "See [Details](/doc/abc12345-def6-7890-abcd-ef1234567890)"}
+    #[tool(annotations(
+        title = "Create Document",
+        read_only_hint = false,
+        destructive_hint = false,
+        idempotent_hint = false,
+        open_world_hint = false
+    ))]
+    async fn outline_documents_create(
+        &self,
+        params: Parameters<CreateDocumentParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        self.policy.check_publish(params.publish)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        self.check_destination(params.collection_id.as_deref(), params.parent_document_id.as_deref(), None).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let request = CreateDocumentRequest {
+            title: params.title,
+            text: params.text,
+            collection_id: params.collection_id,
+            parent_document_id: params.parent_document_id,
+            template_id: None,
+            template: None,
+            emoji: None,
+            publish: if self.policy.drafts_only { Some(false) } else { params.publish },
+        };
+
+        let document = self.client().create_document(request).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let json = serde_json::to_string_pretty(&document)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    /// Update an existing document.
+    ///
+    /// Modifies an existing document's title, content, or publish status. All update fields
+    /// are optional - only specified fields will be changed. Use outline_documents_get first
+    /// to retrieve current content if making partial text edits.
+    ///
+    /// Parameters:
+    /// - id (required): Document identifier to update
+    /// - title (optional): New document title
+    /// - text (optional): New document content in Markdown format. Replaces entire content.
+    /// - publish (optional): Set to true to publish a draft document
+    ///
+    /// IMPORTANT - Document Links: When linking to other documents, always use the full UUID format:
+    /// [Link Text](/doc/xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx)
+    /// Do NOT use the short urlId format. The full UUID creates proper document page connections
     /// that display as embedded document cards in the Outline UI.
     ///
     /// Returns: JSON object containing the updated document with all fields
@@ -402,13 +1336,8 @@ impl OutlineServer {
         // Log the text being updated to help debug escaping issues
         if let Some(ref text) = params.text {
             tracing::debug!("Updating document {} with text length: {}", params.id, text.len());
-            // Log first 200 chars to see if brackets are escaped
-            let preview = if text.len() > 200 {
-                format!("{}...", &text[..200])
-            } else {
-                text.clone()
-            };
-            tracing::info!("Text preview:\n{}", preview);
+            // Log the start of the text to see if brackets are escaped
+            tracing::info!("Text preview:\n{}", search::preview(text, 200));
 
             // Check if brackets are escaped
             if text.contains("!\\[") {
@@ -416,6 +1345,11 @@ impl OutlineServer {
             }
         }
 
+        self.policy.check_publish(params.publish)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        self.allowed_document(&params.id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
         let request = UpdateDocumentRequest {
             id: params.id,
             title: params.title.clone(),
@@ -426,7 +1360,7 @@ impl OutlineServer {
             done: None,
         };
 
-        let document = self.client.update_document(request).await
+        let document = self.client().update_document(request).await
             .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
 
         // Log what was returned after update
@@ -473,7 +1407,19 @@ impl OutlineServer {
         let params = params.0;
         let permanent = params.permanent.unwrap_or(false);
 
-        self.client.delete_document(params.id, permanent).await
+        let document = self.allowed_document(&params.id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        if self.policy.dry_runs("outline_documents_delete") {
+            let action = if permanent { "permanently delete" } else { "move to trash" };
+            let message = format!(
+                "Dry run: would {} document \"{}\" ({}) and any nested documents. Nothing was changed.",
+                action, document.title, document.id
+            );
+            return Ok(CallToolResult::success(vec![Content::text(message)]));
+        }
+
+        self.client().delete_document(params.id, permanent).await
             .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
 
         let message = if permanent {
@@ -485,223 +1431,1342 @@ impl OutlineServer {
         Ok(CallToolResult::success(vec![Content::text(message.to_string())]))
     }
 
-    /// Search for documents by text query.
-    ///
-    /// Full-text search across all documents in your Outline workspace. Searches document
-    /// titles and content. Returns ranked results with context snippets.
+    /// Move a document to another collection or under another parent.
     ///
-    /// **Context Optimization**: By default, only document snippets (300 chars) are returned
-    /// to reduce context size. Use outline_documents_get to retrieve full content for
-    /// specific documents of interest.
+    /// Nested documents move along with it. Give collectionId to move between collections,
+    /// parentDocumentId to nest under a document, or neither to move to the top level of its
+    /// current collection.
     ///
     /// Parameters:
-    /// - query (required): Search terms. Supports natural language queries.
-    /// - collectionId (optional): Limit search to a specific collection
-    /// - limit (optional): Maximum results to return. Default 25, max 100.
-    /// - offset (optional): Number of results to skip for pagination.
-    /// - includeContent (optional): Set true to include full document text. Default false.
-    ///
-    /// Returns: JSON object with:
-    /// - results: Array of search results, each containing:
-    ///   - id: Document ID (use with outline_documents_get for full content)
-    ///   - title: Document title
-    ///   - ranking: Relevance score (higher = more relevant)
-    ///   - context: Text snippet showing where query matched
-    ///   - snippet: First 300 chars of document (always included)
-    ///   - text: Full document content (only if includeContent=true)
-    ///   - collection_id: Parent collection ID
-    ///   - updated_at: Last modified timestamp
-    /// - has_more: Whether more results are available
-    /// - next_offset: Offset value for fetching next page
+    /// - id (required): Document identifier to move
+    /// - collectionId (optional): Destination collection
+    /// - parentDocumentId (optional): Destination parent document
+    /// - index (optional): Position among the new siblings (0 = first)
     ///
-    /// Tips for effective searching:
-    /// - Use specific terms for precise results
-    /// - Use pagination (offset) for large result sets
-    /// - Call outline_documents_get for full content of relevant documents
+    /// Returns: JSON object containing the moved document
     ///
-    /// Example - Basic search: {"query": "API authentication"}
-    /// Example - With pagination: {"query": "deployment", "limit": 10, "offset": 10}
-    /// Example - Full content: {"query": "config", "includeContent": true, "limit": 5}
+    /// Example - Move to another collection: {"id": "abc123", "collectionId": "col456"}
+    /// Example - Nest under a document: {"id": "abc123", "parentDocumentId": "def789"}
     #[tool(annotations(
-        title = "Search Documents",
-        read_only_hint = true,
+        title = "Move Document",
+        read_only_hint = false,
         destructive_hint = false,
         idempotent_hint = true,
         open_world_hint = false
     ))]
-    async fn outline_documents_search(
+    async fn outline_documents_move(
         &self,
-        params: Parameters<SearchDocumentsParams>,
+        params: Parameters<MoveDocumentParams>,
     ) -> Result<CallToolResult, ErrorData> {
         let params = params.0;
 
-        // Enforce limits: default 25, max 100
-        let limit = params.limit.unwrap_or(25).min(100);
-
-        let request = SearchDocumentsRequest {
-            query: params.query,
-            collection_id: params.collection_id,
-            user_id: None,
-            date_filter: None,
-            include_archived: None,
-            include_drafts: None,
-            offset: params.offset,
-            limit: Some(limit),
+        let document = self.allowed_document(&params.id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        self.check_destination(
+            params.collection_id.as_deref(),
+            params.parent_document_id.as_deref(),
+            document.collection_id.as_deref(),
+        )
+        .await
+        .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let request = MoveDocumentRequest {
+            id: params.id,
+            // The API needs a collection even when only the parent changes
+            collection_id: params.collection_id.or(document.collection_id),
+            parent_document_id: params.parent_document_id,
+            index: params.index,
         };
 
-        let response = self.client.search_documents(request).await
+        let document = self.client().move_document(request).await
             .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
 
-        // Transform results to optimized format
-        let result_count = response.data.len() as u32;
-        let results: Vec<SearchResultSummary> = response.data.into_iter().map(|r| {
-            // Create snippet: first SNIPPET_MAX_LENGTH chars, ending at word boundary
-            let text = &r.document.text;
-            let snippet = if text.len() <= SNIPPET_MAX_LENGTH {
-                text.clone()
-            } else {
-                // Find last space before limit to avoid cutting words
-                let truncate_at = text[..SNIPPET_MAX_LENGTH]
-                    .rfind(|c: char| c.is_whitespace())
-                    .unwrap_or(SNIPPET_MAX_LENGTH);
-                format!("{}...", &text[..truncate_at])
-            };
-
-            SearchResultSummary {
-                id: r.document.id,
-                title: r.document.title,
-                ranking: r.ranking,
-                context: r.context,
-                snippet,
-                text: if params.include_content { Some(r.document.text) } else { None },
-                collection_id: r.document.collection_id,
-                updated_at: r.document.updated_at,
-            }
-        }).collect();
-
-        // Calculate pagination info
-        let current_offset = params.offset.unwrap_or(0);
-        let has_more = result_count >= limit;
-        let next_offset = if has_more { Some(current_offset + result_count) } else { None };
-
-        let search_response = SearchResponse {
-            results,
-            total: None, // Outline API doesn't provide total count
-            has_more,
-            next_offset,
-        };
-
-        let json = serde_json::to_string_pretty(&search_response)
+        let json = serde_json::to_string_pretty(&document)
             .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
 
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    // ========================================================================
-    // Collection Tools
-    // ========================================================================
-
-    /// List all collections in your Outline workspace.
+    /// Archive a document.
     ///
-    /// Collections are top-level organizational containers for documents. Use this tool
-    /// to discover available collections and get their IDs for filtering document operations.
+    /// Archived documents disappear from the sidebar and search but keep their content and
+    /// history. Use outline_documents_unarchive to bring one back.
     ///
     /// Parameters:
-    /// - limit (optional): Maximum number of collections to return. Default 25.
-    /// - offset (optional): Number to skip for pagination.
+    /// - id (required): Document identifier to archive
     ///
-    /// Returns: JSON object with collections array, each containing:
-    /// - id: Collection identifier (use with other tools' collectionId parameter)
-    /// - name: Collection display name
-    /// - description: Collection description (if set)
-    /// - color: Hex color code for UI display
-    /// - icon: Collection icon identifier
-    /// - permission: User's permission level (read, read_write, admin)
-    /// - documentCount: Number of documents in collection
+    /// Returns: JSON object containing the archived document
     ///
-    /// Example - List all: Call with no parameters
-    /// Example - Paginate: {"limit": 10, "offset": 10}
+    /// Example: {"id": "abc123"}
     #[tool(annotations(
-        title = "List Collections",
-        read_only_hint = true,
+        title = "Archive Document",
+        read_only_hint = false,
         destructive_hint = false,
         idempotent_hint = true,
         open_world_hint = false
     ))]
-    async fn outline_collections_list(
+    async fn outline_documents_archive(
         &self,
-        params: Parameters<ListCollectionsParams>,
+        params: Parameters<GetDocumentParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let params = params.0;
-
-        let request = ListCollectionsRequest {
-            limit: params.limit,
-            offset: params.offset,
-        };
+        let id = params.0.id;
+        self.allowed_document(&id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
 
-        let response = self.client.list_collections(request).await
+        let document = self.client().archive_document(id).await
             .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
 
-        let json = serde_json::to_string_pretty(&response)
+        let json = serde_json::to_string_pretty(&document)
             .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
 
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    /// Get a specific collection by ID.
+    /// Unarchive a document.
     ///
-    /// Retrieves detailed information about a single collection including its metadata
-    /// and document structure.
+    /// Returns an archived document to its collection.
     ///
     /// Parameters:
-    /// - id (required): Collection identifier (UUID format)
+    /// - id (required): Document identifier to unarchive
     ///
-    /// Returns: JSON object containing:
-    /// - id: Collection identifier
-    /// - name: Collection display name
-    /// - description: Collection description
-    /// - color: Hex color code
-    /// - icon: Icon identifier
-    /// - permission: User's permission level
-    /// - documentCount: Total documents in collection
-    /// - createdAt/updatedAt: ISO 8601 timestamps
+    /// Returns: JSON object containing the unarchived document
     ///
-    /// Example: {"id": "abc123-def456-..."}
+    /// Example: {"id": "abc123"}
     #[tool(annotations(
-        title = "Get Collection",
-        read_only_hint = true,
+        title = "Unarchive Document",
+        read_only_hint = false,
         destructive_hint = false,
         idempotent_hint = true,
         open_world_hint = false
     ))]
-    async fn outline_collections_get(
+    async fn outline_documents_unarchive(
         &self,
-        params: Parameters<GetCollectionParams>,
+        params: Parameters<GetDocumentParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let params = params.0;
+        let id = params.0.id;
+        self.allowed_document(&id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
 
-        let collection = self.client.get_collection(params.id).await
+        let document = self.client().unarchive_document(id).await
             .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
 
-        let json = serde_json::to_string_pretty(&collection)
+        let json = serde_json::to_string_pretty(&document)
             .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
 
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
-}
 
-// Implement ServerHandler trait
-#[tool_handler]
-impl ServerHandler for OutlineServer {
+    /// Restore a document from trash, or roll it back to an earlier revision.
+    ///
+    /// Without revisionId, a document in the trash is restored. With revisionId, the document's
+    /// current content is replaced by that revision's content (the current content stays in
+    /// the revision history).
+    ///
+    /// Parameters:
+    /// - id (required): Document identifier to restore
+    /// - revisionId (optional): Revision to roll back to
+    /// - collectionId (optional): Collection to restore into, if the original was deleted
+    ///
+    /// Returns: JSON object containing the restored document
+    ///
+    /// Example - Restore from trash: {"id": "abc123"}
+    /// Example - Roll back: {"id": "abc123", "revisionId": "rev456"}
+    #[tool(annotations(
+        title = "Restore Document",
+        read_only_hint = false,
+        destructive_hint = true,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_documents_restore(
+        &self,
+        params: Parameters<RestoreDocumentParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        let document = self.allowed_document(&params.id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        if params.collection_id.is_some() {
+            self.policy.check_collection(params.collection_id.as_deref())
+                .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        }
+
+        if self.policy.dry_runs("outline_documents_restore") {
+            let action = match &params.revision_id {
+                Some(revision) => format!("replace the content of document \"{}\" ({}) with revision {}", document.title, document.id, revision),
+                None => format!("restore document \"{}\" ({}) from the trash", document.title, document.id),
+            };
+            let message = format!("Dry run: would {}. Nothing was changed.", action);
+            return Ok(CallToolResult::success(vec![Content::text(message)]));
+        }
+
+        let request = RestoreDocumentRequest {
+            id: params.id,
+            revision_id: params.revision_id,
+            collection_id: params.collection_id,
+        };
+
+        let document = self.client().restore_document(request).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let json = serde_json::to_string_pretty(&document)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    /// Duplicate a document.
+    ///
+    /// Creates a copy of a document, optionally with its nested documents, in the same place
+    /// or in another collection or parent.
+    ///
+    /// Parameters:
+    /// - id (required): Document identifier to copy
+    /// - title (optional): Title for the copy
+    /// - recursive (optional): Also copy nested documents. Default false.
+    /// - publish (optional): Publish the copy. Default false.
+    /// - collectionId (optional): Collection for the copy
+    /// - parentDocumentId (optional): Document to nest the copy under
+    ///
+    /// Returns: JSON array of the new documents, the copy first
+    ///
+    /// Example: {"id": "abc123", "title": "Runbook (2025)", "recursive": true}
+    #[tool(annotations(
+        title = "Duplicate Document",
+        read_only_hint = false,
+        destructive_hint = false,
+        idempotent_hint = false,
+        open_world_hint = false
+    ))]
+    async fn outline_documents_duplicate(
+        &self,
+        params: Parameters<DuplicateDocumentParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        self.policy.check_publish(params.publish)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        let document = self.allowed_document(&params.id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        self.check_destination(
+            params.collection_id.as_deref(),
+            params.parent_document_id.as_deref(),
+            document.collection_id.as_deref(),
+        )
+        .await
+        .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let request = DuplicateDocumentRequest {
+            id: params.id,
+            title: params.title,
+            recursive: Some(params.recursive),
+            publish: if self.policy.drafts_only { Some(false) } else { params.publish },
+            collection_id: params.collection_id,
+            parent_document_id: params.parent_document_id,
+        };
+
+        let documents = self.client().duplicate_document(request).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let json = serde_json::to_string_pretty(&documents)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    /// Create a template from a document.
+    ///
+    /// Makes a new template with the document's content; the document itself is unchanged.
+    /// Templates can then be used with the draft_from_template prompt.
+    ///
+    /// Parameters:
+    /// - id (required): Document identifier to create the template from
+    ///
+    /// Returns: JSON object containing the new template
+    ///
+    /// Example: {"id": "abc123"}
+    #[tool(annotations(
+        title = "Templatize Document",
+        read_only_hint = false,
+        destructive_hint = false,
+        idempotent_hint = false,
+        open_world_hint = false
+    ))]
+    async fn outline_documents_templatize(
+        &self,
+        params: Parameters<GetDocumentParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let id = params.0.id;
+        self.allowed_document(&id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let template = self.client().templatize_document(id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let json = serde_json::to_string_pretty(&template)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    /// Search for documents by text query.
+    ///
+    /// Full-text search across all documents in your Outline workspace. Searches document
+    /// titles and content. Returns ranked results with context snippets.
+    ///
+    /// **Context Optimization**: By default, only document snippets (300 chars) are returned
+    /// to reduce context size. Use outline_documents_get to retrieve full content for
+    /// specific documents of interest.
+    ///
+    /// Parameters:
+    /// - query (required): Search terms. Supports natural language queries.
+    /// - collectionId (optional): Limit search to a specific collection
+    /// - limit (optional): Maximum results to return. Default 25, max 100.
+    /// - offset (optional): Number of results to skip for pagination.
+    /// - includeContent (optional): Set true to include full document text. Default false.
+    /// - author (optional): Only documents by this user (ID, name or email)
+    /// - dateFilter (optional): "day", "week", "month" or "year" - updated within that period
+    /// - updatedAfter / updatedBefore (optional): Date range (YYYY-MM-DD or RFC 3339)
+    /// - status (optional): "published", "draft" or "archived"
+    /// - includeDrafts / includeArchived (optional): Also return drafts / archived documents
+    /// - includeHeadingPath (optional): Add the headings enclosing each match. Default false.
+    ///
+    /// Returns: JSON object with:
+    /// - results: Array of search results, each containing:
+    ///   - id: Document ID (use with outline_documents_get for full content)
+    ///   - title: Document title
+    ///   - ranking: Relevance score (higher = more relevant)
+    ///   - context: Text snippet showing where query matched
+    ///   - snippet: Up to 300 chars of the document around the match (always included)
+    ///   - heading_path: Headings enclosing the match (only if includeHeadingPath=true)
+    ///   - text: Full document content (only if includeContent=true)
+    ///   - collection_id: Parent collection ID
+    ///   - status: "published", "draft" or "archived"
+    ///   - updated_at: Last modified timestamp
+    /// - has_more: Whether more results are available
+    /// - next_offset: Offset value for fetching next page. Results filtered out by status, date
+    ///   range or policy are skipped, so this can be further along than offset + limit.
+    ///
+    /// Tips for effective searching:
+    /// - Use specific terms for precise results
+    /// - Use pagination (offset) for large result sets
+    /// - Call outline_documents_get for full content of relevant documents
+    ///
+    /// Example - Basic search: {"query": "API authentication"}
+    /// Example - With pagination: {"query": "deployment", "limit": 10, "offset": 10}
+    /// Example - Full content: {"query": "config", "includeContent": true, "limit": 5}
+    /// Example - Filtered: {"query": "incident", "author": "jane@example.com", "updatedAfter": "2024-01-01", "status": "published"}
+    #[tool(annotations(
+        title = "Search Documents",
+        read_only_hint = true,
+        destructive_hint = false,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_documents_search(
+        &self,
+        params: Parameters<SearchDocumentsParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        // Enforce limits: default 25, max 100
+        let limit = params.limit.unwrap_or(25).min(100);
+
+        if params.collection_id.is_some() {
+            self.policy.check_collection(params.collection_id.as_deref())
+                .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        }
+
+        let user_id = match &params.author {
+            Some(author) => Some(self.resolve_user(author).await
+                .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?),
+            None => None,
+        };
+        let updated_after = params.updated_after.as_deref().map(audit::parse_since).transpose()
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        let updated_before = params.updated_before.as_deref().map(audit::parse_since).transpose()
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        // Outline can only include drafts or archived documents; the status itself is filtered here
+        let mut request = SearchDocumentsRequest {
+            query: params.query,
+            collection_id: params.collection_id,
+            user_id,
+            date_filter: params.date_filter.map(DateFilter::from),
+            include_archived: match params.status {
+                Some(status) => Some(status == SearchStatus::Archived),
+                None => params.include_archived,
+            },
+            include_drafts: match params.status {
+                Some(status) => Some(status == SearchStatus::Draft),
+                None => params.include_drafts,
+            },
+            offset: None,
+            limit: Some(limit),
+        };
+
+        let updated_in_range = |updated_at: &str| {
+            let Ok(updated) = chrono::DateTime::parse_from_rfc3339(updated_at) else {
+                return true;
+            };
+            updated_after.is_none_or(|after| updated >= after) && updated_before.is_none_or(|before| updated < before)
+        };
+        let wanted = |r: &outline_api::SearchResult| {
+            self.policy.allows_collection(r.document.collection_id.as_deref())
+                && params.status.is_none_or(|status| SearchStatus::of(&r.document) == status)
+                && updated_in_range(&r.document.updated_at)
+        };
+
+        // Some filters run here rather than in Outline, so keep fetching until the page is full.
+        // The offset counts Outline's results, including the ones filtered out.
+        let mut offset = params.offset.unwrap_or(0);
+        let mut matches = Vec::new();
+        let mut has_more = false;
+        for _ in 0..MAX_SEARCH_PAGES {
+            request.offset = Some(offset);
+            let response = self.client().search_documents(request.clone()).await
+                .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+            let fetched = response.data.len() as u32;
+            let mut consumed = 0;
+            for result in response.data {
+                consumed += 1;
+                if wanted(&result) {
+                    matches.push(result);
+                    if matches.len() as u32 == limit {
+                        break;
+                    }
+                }
+            }
+            offset += consumed;
+
+            has_more = consumed < fetched || fetched >= limit;
+            if !has_more || matches.len() as u32 == limit {
+                break;
+            }
+        }
+
+        // Transform results to optimized format
+        let results: Vec<SearchResultSummary> = matches.into_iter()
+            .map(|r| {
+                let text = &r.document.text;
+                let offset = search::match_offset(text, &r.context).unwrap_or(0);
+
+                SearchResultSummary {
+                    snippet: search::snippet(text, offset, SNIPPET_MAX_LENGTH),
+                    heading_path: params.include_heading_path.then(|| search::heading_path(text, offset)),
+                    status: SearchStatus::of(&r.document).as_str(),
+                    id: r.document.id,
+                    title: r.document.title,
+                    ranking: r.ranking,
+                    context: r.context,
+                    text: if params.include_content { Some(r.document.text) } else { None },
+                    collection_id: r.document.collection_id,
+                    updated_at: r.document.updated_at,
+                }
+            }).collect();
+
+        let next_offset = has_more.then_some(offset);
+
+        let search_response = SearchResponse {
+            results,
+            total: None, // Outline API doesn't provide total count
+            has_more,
+            next_offset,
+        };
+
+        let json = serde_json::to_string_pretty(&search_response)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    // ========================================================================
+    // Collection Tools
+    // ========================================================================
+
+    /// List all collections in your Outline workspace.
+    ///
+    /// Collections are top-level organizational containers for documents. Use this tool
+    /// to discover available collections and get their IDs for filtering document operations.
+    ///
+    /// Parameters:
+    /// - limit (optional): Maximum number of collections to return. Default 25.
+    /// - offset (optional): Number to skip for pagination.
+    ///
+    /// Returns: JSON object with collections array, each containing:
+    /// - id: Collection identifier (use with other tools' collectionId parameter)
+    /// - name: Collection display name
+    /// - description: Collection description (if set)
+    /// - color: Hex color code for UI display
+    /// - icon: Collection icon identifier
+    /// - permission: User's permission level (read, read_write, admin)
+    /// - documentCount: Number of documents in collection
+    ///
+    /// Example - List all: Call with no parameters
+    /// Example - Paginate: {"limit": 10, "offset": 10}
+    #[tool(annotations(
+        title = "List Collections",
+        read_only_hint = true,
+        destructive_hint = false,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_collections_list(
+        &self,
+        params: Parameters<ListCollectionsParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        let request = ListCollectionsRequest {
+            limit: params.limit,
+            offset: params.offset,
+        };
+
+        let mut response = self.client().list_collections(request).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        response.data.retain(|c| self.policy.allows_collection(Some(&c.id)));
+
+        let json = serde_json::to_string_pretty(&response)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    /// Get a specific collection by ID.
+    ///
+    /// Retrieves detailed information about a single collection including its metadata
+    /// and document structure.
+    ///
+    /// Parameters:
+    /// - id (required): Collection identifier (UUID format)
+    ///
+    /// Returns: JSON object containing:
+    /// - id: Collection identifier
+    /// - name: Collection display name
+    /// - description: Collection description
+    /// - color: Hex color code
+    /// - icon: Icon identifier
+    /// - permission: User's permission level
+    /// - documentCount: Total documents in collection
+    /// - createdAt/updatedAt: ISO 8601 timestamps
+    ///
+    /// Example: {"id": "abc123-def456-..."}
+    #[tool(annotations(
+        title = "Get Collection",
+        read_only_hint = true,
+        destructive_hint = false,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_collections_get(
+        &self,
+        params: Parameters<GetCollectionParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        self.policy.check_collection(Some(&params.id))
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let collection = self.client().get_collection(params.id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let json = serde_json::to_string_pretty(&collection)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    /// Get the nested document structure of one or all collections in one call.
+    ///
+    /// Returns each collection's published documents as an indented tree with document IDs,
+    /// which is much cheaper than listing documents page by page. Drafts are not included.
+    ///
+    /// Parameters:
+    /// - id (optional): Collection identifier. Omit to get every accessible collection.
+    ///
+    /// Returns: Markdown with a heading per collection and a nested list of
+    /// "Title (documentId)" entries
+    ///
+    /// Example - One collection: {"id": "abc123"}
+    /// Example - Everything: {}
+    #[tool(annotations(
+        title = "Get Collection Tree",
+        read_only_hint = true,
+        destructive_hint = false,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_collections_tree(
+        &self,
+        params: Parameters<CollectionTreeParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let collections = match params.0.id {
+            Some(id) => {
+                self.policy.check_collection(Some(&id))
+                    .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+                vec![self.client().get_collection(id).await
+                    .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?]
+            }
+            None => {
+                let mut collections = self.list_all_collections().await
+                    .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+                collections.retain(|c| self.policy.allows_collection(Some(&c.id)));
+                collections
+            }
+        };
+
+        let mut out = String::new();
+        for collection in &collections {
+            let tree = self.client()
+                .list_collection_documents(CollectionDocumentsRequest::new(collection.id.clone()))
+                .await
+                .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&resources::render_navigation(collection, &tree.data));
+        }
+        if collections.is_empty() {
+            out.push_str("No collections\n");
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(out)]))
+    }
+
+    /// Create a new collection.
+    ///
+    /// Parameters:
+    /// - name (required): Collection name
+    /// - description (optional): Description in Markdown
+    /// - color (optional): Hex color such as "#4E5C6E"
+    /// - private (optional): Set true to restrict the collection to its members
+    ///
+    /// Returns: JSON object containing the new collection
+    ///
+    /// Example: {"name": "Runbooks", "description": "Operational procedures"}
+    #[tool(annotations(
+        title = "Create Collection",
+        read_only_hint = false,
+        destructive_hint = false,
+        idempotent_hint = false,
+        open_world_hint = false
+    ))]
+    async fn outline_collections_create(
+        &self,
+        params: Parameters<CreateCollectionParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        self.policy.check_new_collection()
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let request = CreateCollectionRequest {
+            name: params.name,
+            description: params.description,
+            color: params.color,
+            private: params.private,
+            permission: None,
+        };
+
+        let collection = self.client().create_collection(request).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let json = serde_json::to_string_pretty(&collection)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    /// Update a collection's name, description or color.
+    ///
+    /// Only the fields given are changed.
+    ///
+    /// Parameters:
+    /// - id (required): Collection identifier to update
+    /// - name (optional): New name
+    /// - description (optional): New description in Markdown
+    /// - color (optional): New hex color
+    ///
+    /// Returns: JSON object containing the updated collection
+    ///
+    /// Example: {"id": "abc123", "name": "Runbooks (archived)"}
+    #[tool(annotations(
+        title = "Update Collection",
+        read_only_hint = false,
+        destructive_hint = false,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_collections_update(
+        &self,
+        params: Parameters<UpdateCollectionParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        self.policy.check_collection(Some(&params.id))
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let request = UpdateCollectionRequest {
+            id: params.id,
+            name: params.name,
+            description: params.description,
+            color: params.color,
+            permission: None,
+        };
+
+        let collection = self.client().update_collection(request).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let json = serde_json::to_string_pretty(&collection)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    /// Move a collection to a new position in the sidebar.
+    ///
+    /// Parameters:
+    /// - id (required): Collection identifier to move
+    /// - index (required): New position (0 = top)
+    ///
+    /// Returns: JSON object containing the moved collection
+    ///
+    /// Example: {"id": "abc123", "index": 0}
+    #[tool(annotations(
+        title = "Move Collection",
+        read_only_hint = false,
+        destructive_hint = false,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_collections_move(
+        &self,
+        params: Parameters<MoveCollectionParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        self.policy.check_collection(Some(&params.id))
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let collection = self.client().move_collection(MoveCollectionRequest::new(params.id, params.index)).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let json = serde_json::to_string_pretty(&collection)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    /// Archive a collection and every document in it.
+    ///
+    /// The collection and its documents disappear from the sidebar and search for everyone.
+    /// This server cannot unarchive collections; that has to be done in Outline.
+    ///
+    /// Parameters:
+    /// - id (required): Collection identifier to archive
+    ///
+    /// Returns: JSON object containing the archived collection
+    ///
+    /// Example: {"id": "abc123"}
+    #[tool(annotations(
+        title = "Archive Collection",
+        read_only_hint = false,
+        destructive_hint = true,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_collections_archive(
+        &self,
+        params: Parameters<GetCollectionParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let id = params.0.id;
+
+        self.policy.check_collection(Some(&id))
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        if self.policy.dry_runs("outline_collections_archive") {
+            let collection = self.client().get_collection(id.clone()).await
+                .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+            let tree = self.client().list_collection_documents(CollectionDocumentsRequest::new(id)).await
+                .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+            let count: usize = tree.data.iter().map(|node| node.count()).sum();
+            let message = format!(
+                "Dry run: would archive collection \"{}\" ({}) and its {} published document(s). Nothing was changed.",
+                collection.name, collection.id, count
+            );
+            return Ok(CallToolResult::success(vec![Content::text(message)]));
+        }
+
+        let collection = self.client().archive_collection(id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let json = serde_json::to_string_pretty(&collection)
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    // ========================================================================
+    // Comment Tools
+    // ========================================================================
+
+    /// List the comment threads on a document.
+    ///
+    /// Returns review feedback and discussions as a readable thread tree: each top-level
+    /// comment starts a thread and replies are nested beneath it. Use this before replying
+    /// so the reply lands in the right thread.
+    ///
+    /// Parameters:
+    /// - documentId (required): Document whose comments to list
+    /// - includeResolved (optional): Include resolved threads. Default true.
+    ///
+    /// Returns: Plain text thread tree. The first line counts open and resolved threads.
+    /// Each comment shows:
+    /// - Author name and creation timestamp
+    /// - Comment ID (use as parentCommentId to reply, or with update/resolve tools)
+    /// - [open] or [resolved by <name> at <time>] on top-level comments
+    /// - The comment text, indented below
+    ///
+    /// Example - All threads: {"documentId": "abc123"}
+    /// Example - Open threads only: {"documentId": "abc123", "includeResolved": false}
+    #[tool(annotations(
+        title = "List Comments",
+        read_only_hint = true,
+        destructive_hint = false,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_comments_list(
+        &self,
+        params: Parameters<ListCommentsParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        self.allowed_document(&params.document_id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        // Threads need every comment to be grouped correctly, so fetch all pages
+        let mut comments = self.list_all_comments(&params.document_id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        if !params.include_resolved.unwrap_or(true) {
+            let resolved: Vec<String> = comments.iter()
+                .filter(|c| c.parent_comment_id.is_none() && c.resolved_at.is_some())
+                .map(|c| c.id.clone())
+                .collect();
+            comments.retain(|c| {
+                let thread = c.parent_comment_id.as_ref().unwrap_or(&c.id);
+                !resolved.contains(thread)
+            });
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(render_threads(&comments))]))
+    }
+
+    /// Comment on a document, or reply to an existing thread.
+    ///
+    /// Starts a new top-level comment thread, or with parentCommentId adds a reply to an
+    /// existing thread. Get comment IDs from outline_comments_list.
+    ///
+    /// Parameters:
+    /// - documentId (required): Document to comment on
+    /// - text (required): Comment text
+    /// - parentCommentId (optional): Reply to this comment instead of starting a new thread
+    ///
+    /// Returns: The created comment, rendered as in outline_comments_list
+    ///
+    /// Example - New thread: {"documentId": "abc123", "text": "Should this section mention rate limits?"}
+    /// Example - Reply: {"documentId": "abc123", "text": "Added in the latest revision.", "parentCommentId": "def456"}
+    #[tool(annotations(
+        title = "Create Comment",
+        read_only_hint = false,
+        destructive_hint = false,
+        idempotent_hint = false,
+        open_world_hint = false
+    ))]
+    async fn outline_comments_create(
+        &self,
+        params: Parameters<CreateCommentParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        self.allowed_document(&params.document_id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let mut request = CreateCommentRequest::new(params.document_id, CommentData::new(params.text));
+        if let Some(parent) = params.parent_comment_id {
+            request = request.parent_comment_id(parent);
+        }
+
+        let comment = self.client().create_comment(request).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(render_comment_summary(&comment))]))
+    }
+
+    /// Edit the text of an existing comment.
+    ///
+    /// Replaces the comment's text. Only the comment's author can edit it.
+    ///
+    /// Parameters:
+    /// - id (required): Comment identifier (from outline_comments_list)
+    /// - text (required): New comment text
+    ///
+    /// Returns: The updated comment, rendered as in outline_comments_list
+    ///
+    /// Example: {"id": "def456", "text": "Should this section mention rate limits and quotas?"}
+    #[tool(annotations(
+        title = "Update Comment",
+        read_only_hint = false,
+        destructive_hint = false,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_comments_update(
+        &self,
+        params: Parameters<UpdateCommentParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        self.check_comment(&params.id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let request = UpdateCommentRequest::new(params.id, CommentData::new(params.text));
+        let comment = self.client().update_comment(request).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(render_comment_summary(&comment))]))
+    }
+
+    /// Mark a comment thread as resolved.
+    ///
+    /// Resolves the thread started by the given top-level comment, e.g. once the feedback
+    /// has been addressed. Resolved threads stay visible and can be reopened with
+    /// outline_comments_unresolve.
+    ///
+    /// Parameters:
+    /// - id (required): Identifier of the thread's top-level comment
+    ///
+    /// Returns: The resolved comment, rendered as in outline_comments_list
+    ///
+    /// Example: {"id": "def456"}
+    #[tool(annotations(
+        title = "Resolve Comment Thread",
+        read_only_hint = false,
+        destructive_hint = false,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_comments_resolve(
+        &self,
+        params: Parameters<CommentThreadParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let id = params.0.id;
+        self.check_comment(&id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let comment = self.client().resolve_comment(id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(render_comment_summary(&comment))]))
+    }
+
+    /// Reopen a resolved comment thread.
+    ///
+    /// Marks the thread started by the given top-level comment as open again.
+    ///
+    /// Parameters:
+    /// - id (required): Identifier of the thread's top-level comment
+    ///
+    /// Returns: The reopened comment, rendered as in outline_comments_list
+    ///
+    /// Example: {"id": "def456"}
+    #[tool(annotations(
+        title = "Unresolve Comment Thread",
+        read_only_hint = false,
+        destructive_hint = false,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_comments_unresolve(
+        &self,
+        params: Parameters<CommentThreadParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let id = params.0.id;
+        self.check_comment(&id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let comment = self.client().unresolve_comment(id).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(render_comment_summary(&comment))]))
+    }
+
+    /// Upload a file as an attachment and get the markdown to embed it.
+    ///
+    /// Use this to put diagrams, screenshots or other files into a document: upload the
+    /// file, then insert the returned markdown with outline_documents_update or
+    /// outline_documents_patch. Images embed as images, other files as links.
+    ///
+    /// Provide the file either as base64 (data) or, when the server runs over stdio on
+    /// your machine, as a local path. Files are limited to 25 MB.
+    ///
+    /// Parameters:
+    /// - data (data or path required): File contents as base64, or a data: URL
+    /// - path (data or path required): Local file path (stdio transport only)
+    /// - name (optional): File name; required with data, defaults to the file name with path
+    /// - contentType (optional): MIME type. Default: guessed from the name or data: URL
+    /// - documentId (optional): Document the attachment belongs to (recommended)
+    /// - alt (optional): Alt text for the embed snippet. Default: the file name
+    ///
+    /// Returns: The markdown embed snippet, followed by the attachment's ID, type and size
+    ///
+    /// Example - Base64: {"name": "flow.png", "data": "iVBORw0KGgo...", "documentId": "abc123"}
+    /// Example - Local file: {"path": "/home/me/screenshots/login.png", "documentId": "abc123", "alt": "Login page"}
+    #[tool(annotations(
+        title = "Upload Attachment",
+        read_only_hint = false,
+        destructive_hint = false,
+        idempotent_hint = false,
+        open_world_hint = false
+    ))]
+    async fn outline_attachments_upload(
+        &self,
+        params: Parameters<UploadAttachmentParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        match &params.document_id {
+            Some(id) => self.allowed_document(id).await.map(|_| ()),
+            None => self.policy.check_collection(None),
+        }
+        .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let (data, name, data_type) = match (params.data, params.path) {
+            (Some(data), None) => {
+                let name = params.name.ok_or_else(|| ErrorData::new(
+                    ErrorCode(-32602),
+                    "name is required when uploading data".to_string(),
+                    None,
+                ))?;
+                let (bytes, data_type) = attachments::decode_base64(&data)
+                    .map_err(|e| ErrorData::new(ErrorCode(-32602), e.to_string(), None))?;
+                (bytes, name, data_type)
+            }
+            (None, Some(path)) => {
+                if self.session_auth {
+                    // Over HTTP the path would name a file on the server, not the caller's machine
+                    return Err(ErrorData::new(
+                        ErrorCode(-32602),
+                        "Local paths can only be uploaded over the stdio transport; send the file as base64 data".to_string(),
+                        None,
+                    ));
+                }
+                let path = PathBuf::from(path);
+                let bytes = attachments::read_local(&path)
+                    .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+                let name = params.name
+                    .or_else(|| path.file_name().map(|n| n.to_string_lossy().into_owned()))
+                    .unwrap_or_else(|| "file".to_string());
+                (bytes, name, None)
+            }
+            _ => {
+                return Err(ErrorData::new(
+                    ErrorCode(-32602),
+                    "Provide exactly one of data or path".to_string(),
+                    None,
+                ));
+            }
+        };
+
+        let content_type = params.content_type
+            .or(data_type)
+            .unwrap_or_else(|| attachments::guess_content_type(&name));
+        let size = data.len() as u64;
+
+        let mut request = CreateAttachmentRequest::new(name, content_type, data);
+        if let Some(document_id) = params.document_id {
+            request = request.document_id(document_id);
+        }
+
+        let attachment = self.client().create_attachment(request).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        let text = format!(
+            "{}\n\nAttachment {} ({}, {})",
+            attachments::embed_markdown(&attachment, params.alt.as_deref()),
+            attachment.id,
+            attachment.content_type,
+            attachments::size(size)
+        );
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    /// Fetch an attachment, returning images as image content.
+    ///
+    /// Images embedded in documents appear as /api/attachments.redirect?id=... links.
+    /// Pass such a link (or the attachment ID) to see the image itself. PNG, JPEG, GIF
+    /// and WebP images are returned as image content; text files as text; anything else
+    /// is described by type and size. Downloads are limited to 10 MB.
+    ///
+    /// Parameters:
+    /// - id (required): Attachment ID, or a link from a document's markdown
+    /// - documentId (optional): Document that embeds the attachment. Required when the
+    ///   server restricts which collections may be accessed.
+    ///
+    /// Returns: Image content plus a one-line description, or the file's text
+    ///
+    /// Example - From a document link: {"id": "/api/attachments.redirect?id=9bcd64b4-6a4e-4bcc-b3d3-3c6d4c3c6f2e", "documentId": "abc123"}
+    /// Example - By ID: {"id": "9bcd64b4-6a4e-4bcc-b3d3-3c6d4c3c6f2e"}
+    #[tool(annotations(
+        title = "Get Attachment",
+        read_only_hint = true,
+        destructive_hint = false,
+        idempotent_hint = true,
+        open_world_hint = false
+    ))]
+    async fn outline_attachments_get(
+        &self,
+        params: Parameters<GetAttachmentParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        let id = outline_api::attachment_id(&params.id)
+            .ok_or_else(|| ErrorData::new(
+                ErrorCode(-32602),
+                format!("'{}' is not an attachment ID or attachments.redirect link", params.id),
+                None,
+            ))?
+            .to_string();
+
+        // Attachments don't say which document they belong to, so check the one that embeds it
+        match &params.document_id {
+            Some(document_id) => {
+                let document = self.allowed_document(document_id).await
+                    .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+                if !document.text.contains(&id) {
+                    return Err(ErrorData::new(
+                        ErrorCode(-32000),
+                        format!("Document {} does not embed attachment {}", document_id, id),
+                        None,
+                    ));
+                }
+            }
+            None if self.policy.filters_collections() => {
+                return Err(ErrorData::new(
+                    ErrorCode(-32000),
+                    "Blocked by policy: pass the documentId of the document that embeds this attachment".to_string(),
+                    None,
+                ));
+            }
+            None => {}
+        }
+
+        let content = self.client().download_attachment(id.clone(), attachments::MAX_DOWNLOAD_BYTES).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+        let description = format!(
+            "Attachment {} ({}, {})",
+            id,
+            content.content_type,
+            attachments::size(content.data.len() as u64)
+        );
+
+        if attachments::is_inline_image(&content.content_type) {
+            use base64::Engine;
+            let data = base64::engine::general_purpose::STANDARD.encode(&content.data);
+            return Ok(CallToolResult::success(vec![
+                Content::image(data, content.content_type),
+                Content::text(description),
+            ]));
+        }
+
+        let is_text = content.content_type.starts_with("text/")
+            || matches!(content.content_type.as_str(), "application/json" | "application/xml");
+        match String::from_utf8(content.data) {
+            Ok(text) if is_text => Ok(CallToolResult::success(vec![
+                Content::text(format!("{}\n\n{}", description, text)),
+            ])),
+            _ => Ok(CallToolResult::success(vec![Content::text(format!(
+                "{}\n\nThis file type can't be shown; link to it in a document instead.",
+                description
+            ))])),
+        }
+    }
+}
+
+// Implement ServerHandler trait
+impl ServerHandler for OutlineServer {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let started = Instant::now();
+        let tool = request.name.to_string();
+        let arguments = request.arguments.clone();
+        let client = context.peer.peer_info().map(|info| info.client_info.name.clone());
+        let caller = Caller::new(client, context.extensions.get::<axum::http::request::Parts>());
+
+        let result = self.tool_router.call(ToolCallContext::new(self, request, context)).await;
+
+        self.audit.record(&tool, arguments.as_ref(), caller, &result, started.elapsed(), self.policy.dry_runs(&tool));
+        result
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(self.tool_router.list_all()))
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
             capabilities: ServerCapabilities::builder()
                 .enable_tools()
+                .enable_prompts()
+                .enable_resources()
+                .enable_resources_subscribe()
                 .build(),
             server_info: Implementation::from_build_env(),
-            instructions: Some("MCP server for Outline documentation workspace. Provides access to documents, collections, and search capabilities.".to_string()),
+            instructions: Some("MCP server for Outline documentation workspace. Provides access to documents, collections, comments, and search capabilities. Documents, collections and comment threads are also available as outline:// resources, and prompts cover common documentation workflows.".to_string()),
+        }
+    }
+
+    async fn initialize(
+        &self,
+        request: InitializeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<InitializeResult, ErrorData> {
+        if self.session_auth {
+            let token = context.extensions
+                .get::<axum::http::request::Parts>()
+                .and_then(|parts| crate::http::bearer_token(&parts.headers))
+                .ok_or_else(|| ErrorData::invalid_request("Missing bearer token", None))?;
+
+            let client = OutlineClient::new(self.api_base_url.clone())
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?
+                .with_token(token.to_string());
+            *self.client.write().unwrap() = Arc::new(client);
+            *self.token_fingerprint.write().unwrap() = Some(audit::token_fingerprint(token));
+        }
+
+        if context.peer.peer_info().is_none() {
+            tracing::info!(client = %request.client_info.name, "Client connected");
+            context.peer.set_peer_info(request);
+        }
+        Ok(self.get_info())
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, ErrorData> {
+        let mut prompts = prompts::builtin_prompts();
+        prompts.extend(
+            prompts::load_user_prompts(&self.prompts_dir)
+                .iter()
+                .map(prompts::PromptDefinition::to_prompt),
+        );
+        Ok(ListPromptsResult::with_all_items(prompts))
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        let args = prompts::argument_values(request.arguments);
+
+        let (description, text) = if prompts::BUILTIN_PROMPTS.contains(&request.name.as_str()) {
+            let description = prompts::builtin_prompts()
+                .into_iter()
+                .find(|p| p.name == request.name)
+                .and_then(|p| p.description);
+            let text = self.builtin_prompt(&request.name, &args).await
+                .map_err(|e| ErrorData::new(ErrorCode(-32000), format!("{:#}", e), None))?;
+            (description, text)
+        } else {
+            let definition = prompts::load_user_prompts(&self.prompts_dir)
+                .into_iter()
+                .find(|p| p.name == request.name)
+                .ok_or_else(|| ErrorData::invalid_params(format!("Unknown prompt: {}", request.name), None))?;
+            let text = self.user_prompt(&definition, &args).await
+                .map_err(|e| ErrorData::new(ErrorCode(-32000), format!("{:#}", e), None))?;
+            (definition.description, text)
+        };
+
+        Ok(GetPromptResult {
+            description,
+            messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+        })
+    }
+
+    async fn list_resources(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        let cursor = request.and_then(|r| r.cursor);
+        self.list_resources_page(cursor.as_deref()).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, ErrorData> {
+        Ok(ListResourceTemplatesResult::with_all_items(resources::templates()))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        let uri = ResourceUri::parse(&request.uri).ok_or_else(|| {
+            ErrorData::resource_not_found(format!("Unknown resource: {}", request.uri), None)
+        })?;
+
+        let text = self.read_resource_uri(&uri).await
+            .map_err(|e| ErrorData::new(ErrorCode(-32000), e.to_string(), None))?;
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: request.uri,
+                mime_type: Some(uri.mime_type().to_string()),
+                text,
+                meta: None,
+            }],
+        })
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        if ResourceUri::parse(&request.uri).is_none() {
+            return Err(ErrorData::resource_not_found(format!("Unknown resource: {}", request.uri), None));
         }
+        tracing::info!("Subscribed to {}", request.uri);
+        self.subscriptions.subscribe(request.uri, self.client(), context.peer);
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.subscriptions.unsubscribe(&request.uri);
+        Ok(())
     }
 }
 
@@ -710,21 +2775,15 @@ impl ServerHandler for OutlineServer {
 // ============================================================================
 
 /// Run the MCP server with stdio transport
-pub async fn run() -> Result<()> {
+pub async fn run(config: Config) -> Result<()> {
+    let audit = Arc::new(AuditLog::new(config.audit_log()?));
+
     // Create the service
-    let service = OutlineServer::new().await
+    let service = OutlineServer::new(&config, audit).await
         .context("Failed to create Outline server")?;
 
     tracing::info!("Server created successfully");
-    tracing::info!("Serving tools:");
-    tracing::info!("  - outline_documents_list");
-    tracing::info!("  - outline_documents_get");
-    tracing::info!("  - outline_documents_create");
-    tracing::info!("  - outline_documents_update");
-    tracing::info!("  - outline_documents_delete");
-    tracing::info!("  - outline_documents_search");
-    tracing::info!("  - outline_collections_list");
-    tracing::info!("  - outline_collections_get");
+    log_capabilities(&config);
 
     // Create stdio transport
     use tokio::io::{stdin, stdout};
@@ -744,3 +2803,19 @@ pub async fn run() -> Result<()> {
 
     Ok(())
 }
+
+/// Log the tools, prompts and resources the server offers
+pub fn log_capabilities(config: &Config) {
+    tracing::info!("Policy: {}", config.policy.describe());
+    if let Ok(path) = config.audit_log() {
+        tracing::info!("Audit log: {}", path.display());
+    }
+    let mut tools = OutlineServer::policy_tool_router(&config.policy).list_all();
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    tracing::info!("Serving tools:");
+    for tool in tools {
+        tracing::info!("  - {}", tool.name);
+    }
+    tracing::info!("Serving prompts: summarize_collection, draft_from_template, weekly_changes (plus user prompts)");
+    tracing::info!("Serving resources: outline://collection/{{id}}, outline://document/{{id}}, outline://document/{{id}}/comments");
+}
@@ -1,10 +1,79 @@
+use serde::Deserialize;
+
 /// All available actions in the TUI
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Variants are named by their identifier in the `[keymap]` config section.
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub enum Action {
     // Navigation
     Quit,
     ToggleFocus,
     Refresh,
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    PageUp,
+    PageDown,
+    GoToTop,
+    GoToBottom,
+    Open,
+    ToggleExpand,
+    Expand,
+    Collapse,
+    GoToDocument,
+    HistoryBack,
+    HistoryForward,
+    NextTab,
+    PreviousTab,
+    SwitchSplit,
+    ToggleComments,
+    ToggleLinks,
+    ToggleToc,
+    NextComment,
+    PreviousComment,
+    NextLink,
+    PreviousLink,
+    NextTask,
+    PreviousTask,
+    NextHeading,
+    PreviousHeading,
+
+    // Editing (vim normal mode)
+    EditDocument,
+    InsertMode,
+    InsertAtLineStart,
+    Append,
+    AppendAtLineEnd,
+    OpenLineBelow,
+    OpenLineAbove,
+    VisualMode,
+    WordForward,
+    WordBack,
+    LineStart,
+    LineEnd,
+    DeleteChar,
+    ToggleTask,
+    Undo,
+    Redo,
+    SaveAndExit,
+
+    // Panes and views (comments, search results, merge)
+    Cancel,
+    ReplyToComment,
+    ToggleResolved,
+    FocusQuery,
+    FilterCollection,
+    FilterAuthor,
+    FilterDate,
+    ToggleDrafts,
+    ToggleArchived,
+    ClearFilters,
+    NextConflict,
+    KeepMine,
+    TakeTheirs,
+    SaveMerge,
 
     // Document operations - Phase 1
     CreateDocument,
@@ -98,6 +167,70 @@ impl Action {
             Action::Quit => "Quit application",
             Action::ToggleFocus => "Switch between sidebar and editor",
             Action::Refresh => "Refresh data from server",
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::PageUp => "Move up a page",
+            Action::PageDown => "Move down a page",
+            Action::GoToTop => "Go to the top",
+            Action::GoToBottom => "Go to the bottom",
+            Action::Open => "Open the selection / follow the picked link",
+            Action::ToggleExpand => "Expand or collapse",
+            Action::Expand => "Expand, or go to the first child",
+            Action::Collapse => "Collapse, or go to the parent",
+            Action::GoToDocument => "Go to document (fuzzy finder)",
+            Action::HistoryBack => "Back to the previous document",
+            Action::HistoryForward => "Forward to the next document",
+            Action::NextTab => "Next buffer",
+            Action::PreviousTab => "Previous buffer",
+            Action::SwitchSplit => "Switch to the buffer in the split",
+            Action::ToggleComments => "Show/hide the comments pane",
+            Action::ToggleLinks => "Show/hide the links pane",
+            Action::ToggleToc => "Show/hide the table of contents",
+            Action::NextComment => "Next commented line",
+            Action::PreviousComment => "Previous commented line",
+            Action::NextLink => "Pick the next link in the text",
+            Action::PreviousLink => "Pick the previous link in the text",
+            Action::NextTask => "Pick the next checklist item",
+            Action::PreviousTask => "Pick the previous checklist item",
+            Action::NextHeading => "Next heading",
+            Action::PreviousHeading => "Previous heading",
+
+            // Editing (vim normal mode)
+            Action::EditDocument => "Edit document (vim mode)",
+            Action::InsertMode => "Insert before the cursor",
+            Action::InsertAtLineStart => "Insert at the start of the line",
+            Action::Append => "Insert after the cursor",
+            Action::AppendAtLineEnd => "Insert at the end of the line",
+            Action::OpenLineBelow => "Open a line below",
+            Action::OpenLineAbove => "Open a line above",
+            Action::VisualMode => "Visual mode (select)",
+            Action::WordForward => "Next word",
+            Action::WordBack => "Previous word",
+            Action::LineStart => "Start of the line",
+            Action::LineEnd => "End of the line",
+            Action::DeleteChar => "Delete the character under the cursor",
+            Action::ToggleTask => "Tick or untick the picked checklist item",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::SaveAndExit => "Save and return to view mode",
+
+            // Panes and views
+            Action::Cancel => "Back to the document / close",
+            Action::ReplyToComment => "Reply to the selected thread",
+            Action::ToggleResolved => "Show/hide resolved threads",
+            Action::FocusQuery => "Edit the search query",
+            Action::FilterCollection => "Cycle the collection filter",
+            Action::FilterAuthor => "Cycle the author filter",
+            Action::FilterDate => "Cycle the date filter",
+            Action::ToggleDrafts => "Include/exclude drafts",
+            Action::ToggleArchived => "Include/exclude archived documents",
+            Action::ClearFilters => "Clear the filters",
+            Action::NextConflict => "Scroll to the next conflict",
+            Action::KeepMine => "Keep my version, overwriting the server's",
+            Action::TakeTheirs => "Take the server's version, discarding mine",
+            Action::SaveMerge => "Save the merged text",
 
             // Document operations - Phase 1
             Action::CreateDocument => "Create new document",
@@ -187,7 +320,21 @@ impl Action {
 
     pub fn category(&self) -> &str {
         match self {
-            Action::Quit | Action::ToggleFocus | Action::Refresh | Action::ShowHelp | Action::ShowMenu | Action::EnterCommandMode => "Navigation",
+            Action::Quit | Action::ToggleFocus | Action::Refresh | Action::ShowHelp | Action::ShowMenu | Action::EnterCommandMode |
+            Action::MoveUp | Action::MoveDown | Action::MoveLeft | Action::MoveRight | Action::PageUp | Action::PageDown |
+            Action::GoToTop | Action::GoToBottom | Action::Open | Action::ToggleExpand | Action::Expand | Action::Collapse |
+            Action::GoToDocument | Action::HistoryBack | Action::HistoryForward | Action::NextTab | Action::PreviousTab |
+            Action::SwitchSplit | Action::ToggleComments | Action::ToggleLinks | Action::ToggleToc | Action::NextComment |
+            Action::PreviousComment | Action::NextLink | Action::PreviousLink | Action::NextTask |
+            Action::PreviousTask | Action::NextHeading | Action::PreviousHeading => "Navigation",
+            Action::EditDocument | Action::InsertMode | Action::InsertAtLineStart | Action::Append | Action::AppendAtLineEnd |
+            Action::OpenLineBelow | Action::OpenLineAbove | Action::VisualMode | Action::WordForward | Action::WordBack |
+            Action::LineStart | Action::LineEnd | Action::DeleteChar | Action::ToggleTask | Action::Undo |
+            Action::Redo | Action::SaveAndExit => "Editing",
+            Action::Cancel | Action::ReplyToComment | Action::ToggleResolved | Action::FocusQuery |
+            Action::FilterCollection | Action::FilterAuthor | Action::FilterDate | Action::ToggleDrafts |
+            Action::ToggleArchived | Action::ClearFilters | Action::NextConflict | Action::KeepMine |
+            Action::TakeTheirs | Action::SaveMerge => "Panes",
             Action::CreateDocument | Action::UpdateDocument | Action::DeleteDocument | Action::SearchDocuments |
             Action::ArchiveDocument | Action::UnarchiveDocument | Action::StarDocument | Action::UnstarDocument |
             Action::UnpublishDocument | Action::TemplatizeDocument | Action::MoveDocument | Action::RestoreDocument |
@@ -1,16 +1,43 @@
 use outline_api::{Collection, Document};
-use outline_api::collaboration::{CollaborationClient, CollaborationEvent, ConnectionStatus, DocumentSync};
+use outline_api::merge::{merge3, ChunkKind, Merge, MergeChunk};
+use outline_api::collaboration::{CollaborationClient, CollaborationEvent, ConnectionStatus, DocumentStore, DocumentSync};
 use ratatui::layout::Rect;
 use ratatui::widgets::ListState;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tui_textarea::TextArea;
+use crate::actions::Action;
+use crate::attachments::AttachmentCache;
+use crate::buffers::{has_unsaved_changes, Buffer, BufferList, Split, SplitDirection};
+use crate::comments::CommentsPanel;
+use crate::config::Config;
+use crate::finder::Finder;
+use crate::graphics::ImageLayer;
+use crate::keymap::{KeyChord, KeyContext, KeyLookup, Keymap};
+use crate::links::{History, LinksPanel};
 use crate::modals::Modal;
+use crate::render::{self, Rendered};
+use crate::search::SearchView;
+use crate::state::UiState;
+use crate::theme::Theme;
+use crate::toc::TableOfContents;
+use crate::tree::{SidebarTree, TreeUpdate};
+use crate::users::UserNames;
+
+/// Lines shown above a line the viewer jumps to
+const ANCHOR_CONTEXT_LINES: usize = 2;
 
 /// Which pane is currently focused
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FocusedPane {
     Sidebar,
     Editor,
+    Comments,
+    Links,
+    Toc,
 }
 
 /// Current mode of the editor
@@ -38,6 +65,7 @@ pub enum AppView {
 }
 
 /// Auth setup selection
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AuthChoice {
     OAuth2,
@@ -45,14 +73,32 @@ pub enum AuthChoice {
     Exit,
 }
 
+/// A document in the sidebar tree
+#[derive(Debug, Clone)]
+pub struct DocumentEntry {
+    pub id: String,
+    pub title: String,
+    pub icon: String,
+    pub collection_id: String,
+    pub has_children: bool,
+}
+
 /// Item in the sidebar tree
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum SidebarItem {
     Collection(Collection),
-    Document(Document, usize), // Document and its indent level
+    Document(DocumentEntry, usize), // Document and its indent level
 }
 
 impl SidebarItem {
+    pub fn id(&self) -> &str {
+        match self {
+            SidebarItem::Collection(c) => &c.id,
+            SidebarItem::Document(d, _) => &d.id,
+        }
+    }
+
     pub fn title(&self) -> &str {
         match self {
             SidebarItem::Collection(c) => &c.name,
@@ -63,7 +109,15 @@ impl SidebarItem {
     pub fn icon(&self) -> &str {
         match self {
             SidebarItem::Collection(c) => c.icon(),
-            SidebarItem::Document(d, _) => d.icon(),
+            SidebarItem::Document(d, _) => &d.icon,
+        }
+    }
+
+    /// Whether the item can be expanded to show documents under it
+    pub fn is_expandable(&self) -> bool {
+        match self {
+            SidebarItem::Collection(_) => true,
+            SidebarItem::Document(d, _) => d.has_children,
         }
     }
 
@@ -75,6 +129,37 @@ impl SidebarItem {
     }
 }
 
+/// Three-way merge shown when a save finds the document changed on the server
+pub struct MergeView {
+    /// Latest version of the document on the server ("theirs")
+    pub latest: Document,
+    /// Local edited text ("mine")
+    pub mine: String,
+    /// Line-based merge of the loaded text (base), mine and theirs
+    pub merge: Merge,
+    /// Scroll offset in rows
+    pub scroll: u16,
+}
+
+impl MergeView {
+    /// Number of rows a chunk takes when the three versions are shown side by side
+    pub fn chunk_height(chunk: &MergeChunk) -> usize {
+        chunk.base.len().max(chunk.mine.len()).max(chunk.theirs.len())
+    }
+
+    /// Row of the first conflict below the current scroll position
+    pub fn next_conflict_row(&self) -> Option<u16> {
+        let mut row = 0;
+        for chunk in &self.merge.chunks {
+            if chunk.kind == ChunkKind::Conflict && row > self.scroll as usize {
+                return Some(row.min(u16::MAX as usize) as u16);
+            }
+            row += Self::chunk_height(chunk);
+        }
+        None
+    }
+}
+
 /// Application state
 pub struct App {
     /// Whether the app should quit
@@ -101,15 +186,91 @@ pub struct App {
     /// Vim mode state
     pub vim_mode: VimMode,
 
-    /// Sidebar items (collections and documents in tree order)
+    /// Sidebar items (the visible rows of `sidebar_tree`, in tree order)
     pub sidebar_items: Vec<SidebarItem>,
 
+    /// Collections and documents loaded so far, with expand/collapse state
+    pub sidebar_tree: SidebarTree,
+
+    /// Sender handed to background loaders
+    pub tree_tx: mpsc::UnboundedSender<TreeUpdate>,
+
+    /// Results from background loaders
+    pub tree_rx: mpsc::UnboundedReceiver<TreeUpdate>,
+
+    /// State saved between sessions
+    pub ui_state: UiState,
+
+    /// The "go to document" finder, while it's open
+    pub finder: Option<Finder>,
+
+    /// The search view, kept while hidden so its results can be revisited
+    pub search_view: Option<SearchView>,
+
+    /// Comments on the current document
+    pub comments: Option<CommentsPanel>,
+
+    /// Whether the comments pane is showing
+    pub show_comments: bool,
+
+    /// Backlinks and outgoing links of the current document
+    pub links: Option<LinksPanel>,
+
+    /// Whether the links pane is showing
+    pub show_links: bool,
+
+    /// Documents visited before (and after) the current one
+    pub history: History,
+
+    /// Heading outline of the current document
+    pub toc: TableOfContents,
+
+    /// Whether the table of contents pane is showing
+    pub show_toc: bool,
+
+    /// Keys typed so far of a multi-key binding (e.g. `]` of `]c`)
+    pub pending_keys: Vec<KeyChord>,
+
+    /// Effective key bindings
+    pub keymap: Keymap,
+
+    /// Colour palette
+    pub theme: Theme,
+
+    /// The current document as the viewer shows it
+    pub rendered: Rendered,
+
+    /// Hash of what `rendered` was made from: the text, width, known user names and
+    /// image sizes
+    rendered_from: u64,
+
+    /// Width of the viewer's text, updated when it's drawn
+    pub render_width: u16,
+
+    /// Names of users mentioned in documents
+    pub users: UserNames,
+
+    /// Checklist item picked with `]t`/`[t`, by index into `rendered.tasks`
+    pub task_cursor: Option<usize>,
+
+    /// Attachments of documents, for images and opening or saving files
+    pub attachments: AttachmentCache,
+
+    /// Images drawn over the viewer with the terminal's graphics protocol
+    pub image_layer: ImageLayer,
+
     /// Sidebar list state for navigation
     pub sidebar_state: ListState,
 
-    /// Currently loaded document
+    /// Currently loaded document (the active buffer)
     pub current_document: Option<Document>,
 
+    /// Open documents; the active one's state is in the fields of `App`
+    pub buffers: BufferList,
+
+    /// Another buffer shown next to the active one
+    pub split: Option<Split>,
+
     /// Document text content
     pub document_text: String,
 
@@ -130,20 +291,41 @@ pub struct App {
     #[allow(dead_code)]
     pub collaboration_rx: Option<mpsc::Receiver<CollaborationEvent>>,
 
-    /// Document sync handler
-    #[allow(dead_code)]
-    pub document_sync: Option<DocumentSync>,
+    /// Document sync handler (shared with the collaboration client, persisted locally)
+    pub document_sync: Option<Arc<DocumentSync>>,
+
+    /// Whether the collaborative document's text mirrors the loaded document
+    ///
+    /// Outline's own editor keeps content in a ProseMirror fragment rather than
+    /// the Y.Text this client edits, so a synced document whose text differs
+    /// from the one loaded over REST is left alone and saves use the API.
+    collaborative_text: bool,
 
     /// Collaboration connection status
     #[allow(dead_code)]
     pub collaboration_status: ConnectionStatus,
 
+    /// Merge view, when a save found the document changed on the server
+    pub merge_view: Option<MergeView>,
+
     /// Modal dialog state
     pub modal: Modal,
 
     /// Sidebar rendered area (for mouse click detection)
     pub sidebar_area: Option<Rect>,
 
+    /// Comments pane area for mouse click detection (None while hidden)
+    pub comments_area: Option<Rect>,
+
+    /// Links pane area for mouse click detection (None while hidden)
+    pub links_area: Option<Rect>,
+
+    /// Table of contents pane area for mouse click detection (None while hidden)
+    pub toc_area: Option<Rect>,
+
+    /// Viewer text area for clicking checkboxes (None while the document isn't showing)
+    pub content_area: Option<Rect>,
+
     /// Pending document creation context (parent_id, collection_id)
     pub pending_doc_create: Option<(Option<String>, Option<String>)>,
 }
@@ -153,6 +335,23 @@ impl App {
         let mut sidebar_state = ListState::default();
         sidebar_state.select(Some(0));
 
+        let ui_state = UiState::load();
+        let show_comments = ui_state.show_comments;
+        let show_links = ui_state.show_links;
+        let show_toc = ui_state.show_toc;
+        let (tree_tx, tree_rx) = mpsc::unbounded_channel();
+
+        // Keymap and theme come from the config file; mistakes in it are reported, not fatal
+        let (config, mut problems) = match Config::load() {
+            Ok(config) => (config, Vec::new()),
+            Err(e) => (Config::default(), vec![format!("{:#}", e)]),
+        };
+        let (keymap, keymap_problems) = Keymap::from_config(&config.keymap);
+        let (theme, theme_problems) = Theme::from_config(&config.theme);
+        problems.extend(keymap_problems);
+        problems.extend(theme_problems);
+        let status_message = (!problems.is_empty()).then(|| format!("Config: {}", problems.join("; ")));
+
         Self {
             should_quit: false,
             view: AppView::Main,
@@ -163,18 +362,49 @@ impl App {
             textarea: TextArea::default(),
             vim_mode: VimMode::Normal,
             sidebar_items: Vec::new(),
+            sidebar_tree: SidebarTree::new(ui_state.expanded.clone()),
+            tree_tx,
+            tree_rx,
+            ui_state,
+            finder: None,
+            search_view: None,
+            comments: None,
+            show_comments,
+            links: None,
+            show_links,
+            history: History::default(),
+            toc: TableOfContents::default(),
+            show_toc,
+            pending_keys: Vec::new(),
+            keymap,
+            theme,
+            rendered: Rendered::default(),
+            rendered_from: 0,
+            render_width: 80,
+            users: UserNames::new(),
+            task_cursor: None,
+            attachments: AttachmentCache::new(),
+            image_layer: ImageLayer::new(&config.images),
             sidebar_state,
             current_document: None,
+            buffers: BufferList::default(),
+            split: None,
             document_text: String::new(),
             scroll_offset: 0,
-            status_message: None,
+            status_message,
             is_loading: false,
             collaboration_client: None,
             collaboration_rx: None,
             document_sync: None,
+            collaborative_text: false,
             collaboration_status: ConnectionStatus::Disconnected,
+            merge_view: None,
             modal: Modal::new(),
             sidebar_area: None,
+            comments_area: None,
+            links_area: None,
+            toc_area: None,
+            content_area: None,
             pending_doc_create: None,
         }
     }
@@ -263,12 +493,543 @@ impl App {
             .and_then(|i| self.sidebar_items.get(i))
     }
 
+    /// Select the item the current one is nested under
+    pub fn sidebar_parent(&mut self) {
+        let Some(i) = self.sidebar_state.selected() else {
+            return;
+        };
+        let Some(level) = self.sidebar_items.get(i).map(SidebarItem::indent_level) else {
+            return;
+        };
+        if let Some(parent) = self.sidebar_items[..i].iter().rposition(|item| item.indent_level() < level) {
+            self.sidebar_state.select(Some(parent));
+        }
+    }
+
+    /// Select the first item nested under the current one, if it is showing
+    pub fn sidebar_first_child(&mut self) {
+        let Some(i) = self.sidebar_state.selected() else {
+            return;
+        };
+        let (Some(item), Some(next)) = (self.sidebar_items.get(i), self.sidebar_items.get(i + 1)) else {
+            return;
+        };
+        if next.indent_level() > item.indent_level() {
+            self.sidebar_state.select(Some(i + 1));
+        }
+    }
+
+    /// Apply results from background loaders (call this regularly in event loop)
+    pub fn process_tree_updates(&mut self) {
+        let mut changed = false;
+
+        while let Ok(update) = self.tree_rx.try_recv() {
+            changed = true;
+            match update {
+                TreeUpdate::Collections(Ok(collections)) => {
+                    let count = collections.len();
+                    self.sidebar_tree.set_collections(collections);
+                    self.set_status(format!("Loaded {} collections", count));
+                }
+                TreeUpdate::Collections(Err(e)) => {
+                    self.sidebar_tree.loading_collections = false;
+                    self.set_status(format!("Error loading collections: {}", e));
+                }
+                TreeUpdate::Documents { collection_id, result } => {
+                    if let Err(e) = &result {
+                        let name = self.sidebar_tree.collection(&collection_id)
+                            .map(|node| node.collection.name.clone())
+                            .unwrap_or_default();
+                        self.set_status(format!("Error loading {}: {}", name, e));
+                    }
+                    self.sidebar_tree.set_documents(&collection_id, result);
+                }
+            }
+        }
+
+        if changed {
+            self.rebuild_sidebar();
+            if let Some(finder) = &mut self.finder {
+                finder.set_candidates(&self.sidebar_tree);
+            }
+        }
+    }
+
+    /// Apply finder and search results and start pending requests (call this regularly in event loop)
+    pub fn process_finder_updates(&mut self) {
+        if let Some(finder) = &mut self.finder {
+            finder.tick();
+        }
+        if let Some(search) = &mut self.search_view {
+            search.tick();
+        }
+    }
+
+    /// Whether the search view is showing
+    pub fn search_view_open(&self) -> bool {
+        self.search_view.as_ref().is_some_and(|view| view.open)
+    }
+
+    /// Scroll the viewer to the first line containing `term`, ignoring case; returns whether it was found
+    pub fn scroll_to_text(&mut self, term: &str) -> bool {
+        let needle = term.to_lowercase();
+        let line = self.rendered.text.lines.iter().position(|line| {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<String>()
+                .to_lowercase()
+                .contains(&needle)
+        });
+
+        match line {
+            Some(line) => {
+                self.scroll_to_line(line);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Select a document in the sidebar if it is showing
+    pub fn select_in_sidebar(&mut self, id: &str) {
+        if let Some(index) = self.sidebar_items.iter().position(|item| item.id() == id) {
+            self.sidebar_state.select(Some(index));
+        }
+    }
+
+    /// Rebuild the visible sidebar rows from the tree, keeping the selected item selected
+    pub fn rebuild_sidebar(&mut self) {
+        let selected_id = self.selected_sidebar_item().map(|item| item.id().to_string());
+
+        self.sidebar_items = self.sidebar_tree.flatten();
+        self.is_loading = self.sidebar_tree.is_loading();
+
+        let index = selected_id
+            .and_then(|id| self.sidebar_items.iter().position(|item| item.id() == id))
+            .or(self.sidebar_state.selected())
+            .map(|i| i.min(self.sidebar_items.len().saturating_sub(1)));
+        self.sidebar_state.select(index);
+    }
+
+    /// Save expand/collapse state and other session state
+    pub fn save_ui_state(&mut self) {
+        self.ui_state.expanded = self.sidebar_tree.expanded.clone();
+        self.ui_state.show_comments = self.show_comments;
+        self.ui_state.show_links = self.show_links;
+        self.ui_state.show_toc = self.show_toc;
+        if let Err(e) = self.ui_state.save() {
+            tracing::debug!("Failed to save UI state: {}", e);
+        }
+    }
+
     /// Switch focus between panes
     pub fn toggle_focus(&mut self) {
-        self.focused_pane = match self.focused_pane {
-            FocusedPane::Sidebar => FocusedPane::Editor,
-            FocusedPane::Editor => FocusedPane::Sidebar,
+        let mut panes = vec![FocusedPane::Sidebar, FocusedPane::Editor];
+        if self.show_comments {
+            panes.push(FocusedPane::Comments);
+        }
+        if self.show_links {
+            panes.push(FocusedPane::Links);
+        }
+        if self.show_toc {
+            panes.push(FocusedPane::Toc);
+        }
+        let current = panes.iter().position(|pane| *pane == self.focused_pane).unwrap_or(0);
+        self.focused_pane = panes[(current + 1) % panes.len()];
+    }
+
+    /// Show or hide the comments pane, focusing it when shown
+    pub fn toggle_comments(&mut self) {
+        self.show_comments = !self.show_comments;
+        self.focused_pane = if self.show_comments {
+            FocusedPane::Comments
+        } else if self.focused_pane == FocusedPane::Comments {
+            FocusedPane::Editor
+        } else {
+            self.focused_pane
+        };
+        self.save_ui_state();
+    }
+
+    /// Show or hide the links pane, focusing it when shown
+    pub fn toggle_links(&mut self) {
+        self.show_links = !self.show_links;
+        self.focused_pane = if self.show_links {
+            FocusedPane::Links
+        } else if self.focused_pane == FocusedPane::Links {
+            FocusedPane::Editor
+        } else {
+            self.focused_pane
+        };
+        self.save_ui_state();
+    }
+
+    /// Show or hide the table of contents pane, focusing it when shown
+    pub fn toggle_toc(&mut self) {
+        self.show_toc = !self.show_toc;
+        self.focused_pane = if self.show_toc {
+            FocusedPane::Toc
+        } else if self.focused_pane == FocusedPane::Toc {
+            FocusedPane::Editor
+        } else {
+            self.focused_pane
+        };
+        self.save_ui_state();
+    }
+
+    /// Render the current document again if its text, the viewer width, the known user
+    /// names or the downloaded images changed (call this before drawing)
+    pub fn sync_rendered(&mut self) {
+        self.users.tick(&self.document_text);
+
+        let mut hasher = DefaultHasher::new();
+        self.document_text.hash(&mut hasher);
+        self.render_width.hash(&mut hasher);
+        self.users.names().len().hash(&mut hasher);
+        self.attachments.sizes().len().hash(&mut hasher);
+        let hash = hasher.finish();
+        if hash == self.rendered_from {
+            return;
+        }
+        self.rendered_from = hash;
+        // Images that can't be drawn here show their alt text
+        let images: HashMap<String, (u32, u32)> = self
+            .attachments
+            .sizes()
+            .iter()
+            .filter(|(id, _)| self.attachments.get(id).is_some_and(|a| self.image_layer.can_draw(a)))
+            .map(|(id, size)| (id.clone(), *size))
+            .collect();
+        self.rendered =
+            render::render(&self.document_text, self.render_width as usize, &self.theme, self.users.names(), &images);
+        if self.task_cursor.is_some_and(|i| i >= self.rendered.tasks.len()) {
+            self.task_cursor = None;
+        }
+    }
+
+    /// Keep the heading outline in step with the document text and scroll position
+    /// (call this regularly in event loop)
+    pub fn sync_toc(&mut self) {
+        self.toc.set_entries(&self.rendered.headings);
+        // While the pane has focus its selection drives the scroll position instead
+        if self.focused_pane != FocusedPane::Toc {
+            self.toc.follow(self.scroll_offset as usize + ANCHOR_CONTEXT_LINES);
+        }
+    }
+
+    /// The heading whose section is at the top of the view
+    pub fn current_section(&self) -> Option<usize> {
+        self.toc.section_at(self.scroll_offset as usize + ANCHOR_CONTEXT_LINES)
+    }
+
+    /// Scroll to the next (or previous) heading; returns whether there was one
+    pub fn jump_to_heading(&mut self, forward: bool) -> bool {
+        let current = self.scroll_offset as usize + ANCHOR_CONTEXT_LINES;
+        match self.toc.next_heading(current, forward).map(|entry| entry.line) {
+            Some(line) => {
+                self.scroll_to_line(line);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Scroll to the heading selected in the table of contents
+    pub fn scroll_to_selected_heading(&mut self) {
+        if let Some(line) = self.toc.selected_entry().map(|entry| entry.line) {
+            self.scroll_to_line(line);
+        }
+    }
+
+    /// Download the images of the current document and finish opening or saving
+    /// attachments (call this regularly in event loop)
+    pub fn process_attachment_updates(&mut self) {
+        if let Some(status) = self.attachments.tick() {
+            self.set_status(status);
+        }
+        if self.image_layer.enabled() {
+            for image in &self.rendered.images {
+                self.attachments.fetch(&image.id);
+            }
+        }
+    }
+
+    /// Apply loaded backlinks and pick up edits to the document's links
+    /// (call this regularly in event loop)
+    pub fn process_links_updates(&mut self) {
+        if let Some(panel) = &mut self.links {
+            panel.tick();
+            panel.set_text(&self.document_text);
+        }
+    }
+
+    /// Rendered lines of the current document's outgoing links, with their indices
+    pub fn link_positions(&self) -> Vec<(usize, usize)> {
+        let Some(panel) = &self.links else {
+            return Vec::new();
+        };
+        panel.positions(&self.rendered.links)
+    }
+
+    /// Move the link cursor to the next (or previous) link in the text and scroll to it;
+    /// returns whether there was one
+    pub fn jump_to_link(&mut self, forward: bool) -> bool {
+        let positions = self.link_positions();
+        let cursor = self.links.as_ref().and_then(|panel| panel.cursor);
+
+        // Step from the picked link while it's on screen, otherwise from the top of the view
+        let top = self.scroll_offset as usize;
+        let current = cursor
+            .and_then(|index| positions.iter().find(|(_, i)| *i == index))
+            .filter(|(line, _)| *line >= top)
+            .copied();
+        let target = match (current, forward) {
+            (Some((_, index)), true) => positions.iter().find(|(_, i)| *i > index),
+            (Some((_, index)), false) => positions.iter().rev().find(|(_, i)| *i < index),
+            (None, true) => positions.iter().find(|(line, _)| *line >= top),
+            (None, false) => positions.iter().rev().find(|(line, _)| *line < top + ANCHOR_CONTEXT_LINES),
+        };
+
+        match target.copied() {
+            Some((line, index)) => {
+                if let Some(panel) = &mut self.links {
+                    panel.cursor = Some(index);
+                }
+                self.scroll_to_line(line);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pick the next (or previous) checklist item and scroll to it; returns whether there was one
+    pub fn jump_to_task(&mut self, forward: bool) -> bool {
+        let tasks = &self.rendered.tasks;
+
+        // Step from the picked item while it's on screen, otherwise from the top of the view
+        let top = self.scroll_offset as usize;
+        let current = self.task_cursor.filter(|&i| tasks.get(i).is_some_and(|task| task.line >= top));
+        let target = match (current, forward) {
+            (Some(i), true) => Some(i + 1).filter(|&next| next < tasks.len()),
+            (Some(i), false) => i.checked_sub(1),
+            (None, true) => tasks.iter().position(|task| task.line >= top),
+            (None, false) => tasks.iter().rposition(|task| task.line < top + ANCHOR_CONTEXT_LINES),
         };
+
+        match target {
+            Some(index) => {
+                let line = tasks[index].line;
+                self.task_cursor = Some(index);
+                self.scroll_to_line(line);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The checklist item whose checkbox is at a screen position
+    pub fn task_at(&self, column: u16, row: u16) -> Option<usize> {
+        let area = self.content_area?;
+        if column < area.x || row < area.y || row >= area.y + area.height {
+            return None;
+        }
+        let line = (row - area.y) as usize + self.scroll_offset as usize;
+        let column = (column - area.x) as usize;
+        self.rendered
+            .tasks
+            .iter()
+            .position(|task| task.line == line && (task.column..task.column + 2).contains(&column))
+    }
+
+    /// The document text with a checklist item ticked or unticked
+    pub fn toggled_task(&self, index: usize) -> Option<String> {
+        let task = self.rendered.tasks.get(index)?;
+        Some(task.toggle(&self.document_text))
+    }
+
+    /// Apply finished comment requests (call this regularly in event loop)
+    pub fn process_comment_updates(&mut self) {
+        if let Some(message) = self.comments.as_mut().and_then(CommentsPanel::tick) {
+            self.set_status(message);
+        }
+    }
+
+    /// Rendered lines of the current document that comment threads are anchored to, with the thread IDs
+    pub fn comment_anchors(&self) -> Vec<(usize, String)> {
+        let Some(panel) = &self.comments else {
+            return Vec::new();
+        };
+        panel
+            .anchors(&self.rendered.text)
+            .into_iter()
+            .map(|(line, comment)| (line, comment.id.clone()))
+            .collect()
+    }
+
+    /// Scroll to the next (or previous) commented line and select its thread; returns whether there was one
+    pub fn jump_to_comment(&mut self, forward: bool) -> bool {
+        let anchors = self.comment_anchors();
+        let current = self.scroll_offset as usize + ANCHOR_CONTEXT_LINES;
+        let target = if forward {
+            anchors.iter().find(|(line, _)| *line > current)
+        } else {
+            anchors.iter().rev().find(|(line, _)| *line < current)
+        };
+
+        match target.cloned() {
+            Some((line, id)) => {
+                self.scroll_to_line(line);
+                if let Some(panel) = &mut self.comments {
+                    panel.select_comment(&id);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Scroll to where the selected thread is anchored; returns whether it is anchored
+    pub fn jump_to_selected_comment(&mut self) -> bool {
+        let Some(id) = self
+            .comments
+            .as_ref()
+            .and_then(CommentsPanel::selected_thread)
+            .map(|c| c.id.clone())
+        else {
+            return false;
+        };
+
+        match self.comment_anchors().into_iter().find(|(_, anchor)| *anchor == id) {
+            Some((line, _)) => {
+                self.scroll_to_line(line);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Scroll so a rendered line shows near the top, with a little context above it
+    fn scroll_to_line(&mut self, line: usize) {
+        self.scroll_offset = line.saturating_sub(ANCHOR_CONTEXT_LINES).min(u16::MAX as usize) as u16;
+    }
+
+    /// Move the active buffer's state out of the app, leaving it empty
+    fn take_buffer(&mut self) -> Option<Buffer> {
+        let document = self.current_document.take()?;
+        self.task_cursor = None;
+        Some(Buffer {
+            document,
+            text: std::mem::take(&mut self.document_text),
+            scroll_offset: std::mem::take(&mut self.scroll_offset),
+            textarea: std::mem::take(&mut self.textarea),
+            editor_mode: std::mem::replace(&mut self.editor_mode, EditorMode::View),
+            vim_mode: std::mem::replace(&mut self.vim_mode, VimMode::Normal),
+            comments: self.comments.take(),
+            links: self.links.take(),
+            toc: std::mem::take(&mut self.toc),
+        })
+    }
+
+    /// Make a buffer's state the app's
+    fn restore_buffer(&mut self, buffer: Buffer) {
+        self.current_document = Some(buffer.document);
+        self.document_text = buffer.text;
+        self.scroll_offset = buffer.scroll_offset;
+        self.textarea = buffer.textarea;
+        self.editor_mode = buffer.editor_mode;
+        self.vim_mode = buffer.vim_mode;
+        self.comments = buffer.comments;
+        self.links = buffer.links;
+        self.toc = buffer.toc;
+    }
+
+    /// Keep the open document in its buffer and start an empty one for a document being opened
+    pub fn open_buffer(&mut self) {
+        let current = self.take_buffer();
+        let previous = self.buffers.active();
+        let active = self.buffers.open(current);
+
+        // The new buffer is inserted after the previous one, shifting later buffers along
+        if let Some(split) = &mut self.split {
+            if active != previous && split.buffer >= active {
+                split.buffer += 1;
+            }
+        }
+    }
+
+    /// Make another open buffer active; returns whether it switched
+    pub fn switch_buffer(&mut self, index: usize) -> bool {
+        if self.buffers.get(index).is_none() {
+            return false;
+        }
+        let Some(current) = self.take_buffer() else {
+            return false;
+        };
+        let previous = self.buffers.active();
+        if let Some(buffer) = self.buffers.switch(index, current) {
+            self.restore_buffer(buffer);
+        }
+
+        // Switching to the buffer in the split swaps the two
+        if let Some(split) = &mut self.split {
+            if split.buffer == index {
+                split.buffer = previous;
+            }
+        }
+        true
+    }
+
+    /// Close the active buffer and switch to its neighbour, if any is left
+    pub fn close_buffer(&mut self) {
+        let closed = self.buffers.active();
+        self.take_buffer();
+        match self.buffers.close_active() {
+            Some(buffer) => self.restore_buffer(buffer),
+            None => self.scroll_offset = 0,
+        }
+
+        let active = self.buffers.active();
+        self.split = self.split.and_then(|mut split| {
+            if split.buffer > closed {
+                split.buffer -= 1;
+            }
+            // The buffer in the split may have become the active one
+            (split.buffer != active).then_some(split)
+        });
+    }
+
+    /// Show another buffer next to the active one
+    pub fn open_split(&mut self, buffer: usize, direction: SplitDirection) -> bool {
+        if self.buffers.get(buffer).is_none() {
+            return false;
+        }
+        self.split = Some(Split { buffer, direction });
+        true
+    }
+
+    /// Resolve a key press against the keymap, collecting the keys of multi-key bindings
+    pub fn resolve_key(&mut self, context: KeyContext, chord: KeyChord) -> Option<Action> {
+        let mut keys = std::mem::take(&mut self.pending_keys);
+        keys.push(chord);
+        match self.keymap.lookup(context, &keys) {
+            KeyLookup::Action(action) => Some(action),
+            KeyLookup::Pending => {
+                self.pending_keys = keys;
+                None
+            }
+            KeyLookup::Unbound => None,
+        }
+    }
+
+    /// Whether the active buffer has unsaved changes
+    pub fn is_dirty(&self) -> bool {
+        has_unsaved_changes(self.editor_mode, &self.textarea, &self.document_text)
+    }
+
+    /// Whether any open buffer has unsaved changes
+    pub fn any_dirty(&self) -> bool {
+        self.is_dirty() || self.buffers.any_dirty()
     }
 
     /// Toggle editor mode
@@ -344,36 +1105,156 @@ impl App {
         // Stop any existing collaboration
         self.stop_collaboration().await;
 
-        // Create document sync
-        let doc_sync = DocumentSync::new();
+        // Start collaboration client (loads any locally persisted state)
+        let (mut client, rx) = start_collaboration(api_base_url, api_token, document_id).await?;
+        let doc_sync = client.doc_sync();
 
-        // Initialize sync with current document text
-        doc_sync.set_text(&self.document_text)?;
+        // Edits that haven't reached the server yet are newer than the REST copy
+        if !doc_sync.pending_updates()?.is_empty() {
+            self.document_text = doc_sync.get_text()?;
+            self.load_text_into_editor();
+        }
 
-        // Start collaboration client
-        let (mut client, rx) = start_collaboration(api_base_url, api_token, document_id).await?;
+        // Keep the local document even if we can't connect, so edits are
+        // recorded and merged on the next successful sync
+        self.collaboration_rx = Some(rx);
+        self.document_sync = Some(doc_sync);
+        self.refresh_collaborative_text();
 
         // Connect to WebSocket
         client.connect().await?;
 
         // Store collaboration state
         self.collaboration_client = Some(client);
-        self.collaboration_rx = Some(rx);
-        self.document_sync = Some(doc_sync);
         self.collaboration_status = ConnectionStatus::Connecting;
 
         Ok(())
     }
 
+    /// Open the locally persisted copy of a document (used when the server is unreachable)
+    ///
+    /// Returns `false` if no usable local copy exists.
+    pub fn open_local_copy(&mut self, api_base_url: &str, document_id: &str) -> anyhow::Result<bool> {
+        let store = DocumentStore::open_default(api_base_url)?;
+        let Some(document) = store.load_document(document_id)? else {
+            return Ok(false);
+        };
+
+        let doc_sync = DocumentSync::open(store, document_id)?;
+        if !doc_sync.has_state() {
+            return Ok(false);
+        }
+
+        self.document_text = doc_sync.get_text()?;
+        self.current_document = Some(document);
+        self.document_sync = Some(Arc::new(doc_sync));
+        self.collaborative_text = true;
+        self.scroll_offset = 0;
+
+        Ok(true)
+    }
+
+    /// Record an edit in the local collaborative document
+    ///
+    /// The edit is sent immediately when connected; otherwise it stays pending
+    /// and is delivered on the next sync. Returns `true` if it was sent.
+    pub async fn commit_collaborative_edit(&mut self, text: &str) -> anyhow::Result<bool> {
+        let doc_sync = self.document_sync.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No collaboration session"))?;
+
+        let Some(update) = doc_sync.apply_local_text(text)? else {
+            return Ok(self.collaboration_client.is_some());
+        };
+
+        match &self.collaboration_client {
+            Some(client) => {
+                // Stays pending until the server acknowledges it
+                client.send_update(update).await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Show a merge of local edits against the latest server version
+    ///
+    /// The currently loaded document is the common base of both versions.
+    pub fn open_merge_view(&mut self, latest: Document, mine: String) {
+        let base = self.current_document.as_ref()
+            .map(|doc| doc.text.as_str())
+            .unwrap_or_default();
+        let merge = merge3(base, &mine, &latest.text);
+
+        self.merge_view = Some(MergeView {
+            latest,
+            mine,
+            merge,
+            scroll: 0,
+        });
+    }
+
+    /// Check whether the open document is edited through a collaborative document
+    ///
+    /// True only once the collaborative text has matched the loaded document;
+    /// until then edits are saved through the API.
+    pub fn has_collaborative_document(&self) -> bool {
+        self.collaborative_text
+            && self.document_sync.as_ref().is_some_and(|sync| sync.has_state())
+    }
+
+    /// Recheck whether the collaborative document's text mirrors the loaded document
+    fn refresh_collaborative_text(&mut self) {
+        self.collaborative_text = self.document_sync.as_ref().is_some_and(|sync| {
+            sync.has_state()
+                && sync.get_text().is_ok_and(|text| text == self.document_text)
+        });
+    }
+
+    /// Undo (or redo) the local user's last collaborative edit
+    ///
+    /// Unsaved editor changes are undone in the editor alone, so nothing is
+    /// published until they are saved; once the editor matches the document the
+    /// last saved edit is undone. Changes made by other collaborators are left
+    /// alone. Returns `false` if there was nothing to undo or redo.
+    pub async fn collaborative_undo(&mut self, redo: bool) -> anyhow::Result<bool> {
+        if redo && self.textarea.redo() {
+            return Ok(true);
+        }
+        if self.is_dirty() {
+            return Ok(!redo && self.textarea.undo());
+        }
+
+        let doc_sync = self.document_sync.clone()
+            .ok_or_else(|| anyhow::anyhow!("No collaboration session"))?;
+        let update = if redo { doc_sync.redo()? } else { doc_sync.undo()? };
+        let Some(update) = update else {
+            return Ok(false);
+        };
+
+        if let Some(client) = &self.collaboration_client {
+            client.send_update(update).await?;
+        }
+
+        // Reload the editor, keeping the cursor where it was
+        let (row, col) = self.textarea.cursor();
+        self.document_text = doc_sync.get_text()?;
+        self.load_text_into_editor();
+        self.textarea.move_cursor(tui_textarea::CursorMove::Jump(
+            row.min(u16::MAX as usize) as u16,
+            col.min(u16::MAX as usize) as u16,
+        ));
+
+        Ok(true)
+    }
+
     /// Stop collaboration
-    #[allow(dead_code)]
     pub async fn stop_collaboration(&mut self) {
-        if let Some(client) = &self.collaboration_client {
+        if let Some(mut client) = self.collaboration_client.take() {
             let _ = client.disconnect().await;
         }
-        self.collaboration_client = None;
         self.collaboration_rx = None;
         self.document_sync = None;
+        self.collaborative_text = false;
         self.collaboration_status = ConnectionStatus::Disconnected;
     }
 
@@ -395,6 +1276,13 @@ impl App {
                     self.set_status(format!("Collaboration: {:?}", status));
                 }
                 CollaborationEvent::DocumentUpdated(content) => {
+                    // Text that doesn't mirror the loaded document (e.g. an empty
+                    // Y.Text next to Outline's ProseMirror content) is not shown
+                    if !self.collaborative_text {
+                        self.refresh_collaborative_text();
+                        continue;
+                    }
+
                     self.document_text = content.clone();
 
                     // If in edit mode, update the textarea with remote changes
@@ -432,3 +1320,206 @@ impl Default for App {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_undo_with_unsaved_edits_sends_nothing() {
+        let dir = std::env::temp_dir()
+            .join(format!("outline-tui-undo-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // A saved (and delivered) edit that collaborative undo could revert
+        let doc_sync = Arc::new(DocumentSync::open(DocumentStore::new(&dir), "doc").unwrap());
+        doc_sync.apply_local_text("one").unwrap();
+        doc_sync.mark_delivered(&doc_sync.pending_updates().unwrap()).unwrap();
+
+        // Never connected, so anything sent would fail
+        let (event_tx, _event_rx) = mpsc::channel(8);
+        let client = CollaborationClient::new(
+            "http://localhost".to_string(),
+            "token".to_string(),
+            "doc".to_string(),
+            event_tx,
+            Arc::clone(&doc_sync),
+        )
+        .unwrap();
+
+        let mut app = App::new();
+        app.document_sync = Some(Arc::clone(&doc_sync));
+        app.collaboration_client = Some(client);
+        app.document_text = doc_sync.get_text().unwrap();
+        app.load_text_into_editor();
+        app.editor_mode = EditorMode::Edit;
+        app.textarea.move_cursor(tui_textarea::CursorMove::End);
+        app.textarea.insert_str(" two");
+        assert!(app.is_dirty());
+
+        assert!(app.collaborative_undo(false).await.unwrap());
+        assert_eq!(app.get_text_from_editor(), "one");
+        assert_eq!(doc_sync.get_text().unwrap(), "one");
+        assert!(doc_sync.pending_updates().unwrap().is_empty());
+        assert!(doc_sync.can_undo());
+
+        // Redo brings the unsaved edit back, still without publishing it
+        assert!(app.collaborative_undo(true).await.unwrap());
+        assert_eq!(app.get_text_from_editor(), "one two");
+        assert!(doc_sync.pending_updates().unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unmirrored_collaborative_text_is_not_used() {
+        // Synced state, but the Y.Text is empty (Outline keeps content elsewhere)
+        let doc_sync = Arc::new(DocumentSync::new());
+        doc_sync.apply_local_text("draft").unwrap();
+        doc_sync.apply_local_text("").unwrap();
+        assert!(doc_sync.has_state());
+
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let mut app = App::new();
+        app.document_text = "# Loaded over REST".to_string();
+        app.document_sync = Some(Arc::clone(&doc_sync));
+        app.collaboration_rx = Some(event_rx);
+        app.refresh_collaborative_text();
+        assert!(!app.has_collaborative_document());
+
+        // Sync updates don't replace the loaded text with the empty Y.Text
+        event_tx.try_send(CollaborationEvent::DocumentUpdated(String::new())).unwrap();
+        app.process_collaboration_events();
+        assert_eq!(app.document_text, "# Loaded over REST");
+        assert!(!app.has_collaborative_document());
+
+        // Once the Y.Text mirrors the document, edits go through the CRDT
+        doc_sync.apply_local_text("# Loaded over REST").unwrap();
+        event_tx.try_send(CollaborationEvent::DocumentUpdated("# Loaded over REST".to_string())).unwrap();
+        app.process_collaboration_events();
+        assert!(app.has_collaborative_document());
+    }
+
+    fn document(id: &str) -> Document {
+        serde_json::from_value(serde_json::json!({ "id": id, "title": format!("Doc {}", id) })).unwrap()
+    }
+
+    /// Open documents in new buffers, in order, leaving the last one active
+    fn app_with_buffers(ids: &[&str]) -> App {
+        let mut app = App::new();
+        for id in ids {
+            app.open_buffer();
+            app.current_document = Some(document(id));
+            app.document_text = format!("Text of {}", id);
+            app.load_text_into_editor();
+        }
+        app
+    }
+
+    fn active_id(app: &App) -> &str {
+        app.current_document.as_ref().map_or("", |doc| doc.id.as_str())
+    }
+
+    /// The document shown in the split, if any
+    fn split_id(app: &App) -> Option<&str> {
+        let split = app.split?;
+        Some(app.buffers.get(split.buffer).unwrap().document.id.as_str())
+    }
+
+    #[test]
+    fn test_opening_a_buffer_shifts_the_split_along() {
+        let mut app = app_with_buffers(&["a", "b"]);
+        assert!(app.switch_buffer(0));
+        assert!(app.open_split(1, SplitDirection::Vertical));
+        assert_eq!(split_id(&app), Some("b"));
+
+        // The new buffer goes right after the active one, before the split's
+        app.open_buffer();
+        app.current_document = Some(document("c"));
+        assert_eq!(app.buffers.active(), 1);
+        assert_eq!(app.split.unwrap().buffer, 2);
+        assert_eq!(split_id(&app), Some("b"));
+    }
+
+    #[test]
+    fn test_switching_to_the_split_swaps_the_two() {
+        let mut app = app_with_buffers(&["a", "b", "c"]);
+        assert!(app.open_split(0, SplitDirection::Horizontal));
+        assert!(!app.open_split(2, SplitDirection::Horizontal));
+
+        assert!(app.switch_buffer(0));
+        assert_eq!(active_id(&app), "a");
+        assert_eq!(app.document_text, "Text of a");
+        assert_eq!(app.get_text_from_editor(), "Text of a");
+        assert_eq!(split_id(&app), Some("c"));
+    }
+
+    #[test]
+    fn test_closing_the_buffer_from_the_split() {
+        let mut app = app_with_buffers(&["a", "b", "c"]);
+        assert!(app.open_split(1, SplitDirection::Vertical));
+
+        // Bring the split's buffer over and close it; the split keeps showing the other one
+        assert!(app.switch_buffer(1));
+        assert_eq!(split_id(&app), Some("c"));
+        app.close_buffer();
+        assert_eq!(active_id(&app), "a");
+        assert_eq!(app.split.unwrap().buffer, 1);
+        assert_eq!(split_id(&app), Some("c"));
+        assert_eq!(app.buffers.len(), 2);
+    }
+
+    #[test]
+    fn test_closing_a_buffer_before_the_split() {
+        let mut app = app_with_buffers(&["a", "b", "c", "d"]);
+        assert!(app.switch_buffer(1));
+        assert!(app.open_split(3, SplitDirection::Vertical));
+
+        app.close_buffer();
+        assert_eq!(active_id(&app), "a");
+        assert_eq!(app.document_text, "Text of a");
+        assert_eq!(app.split.unwrap().buffer, 2);
+        assert_eq!(split_id(&app), Some("d"));
+    }
+
+    #[test]
+    fn test_closing_a_buffer_after_the_split() {
+        let mut app = app_with_buffers(&["a", "b", "c"]);
+        assert!(app.open_split(0, SplitDirection::Vertical));
+
+        app.close_buffer();
+        assert_eq!(active_id(&app), "b");
+        assert_eq!(app.split.unwrap().buffer, 0);
+        assert_eq!(split_id(&app), Some("a"));
+    }
+
+    #[test]
+    fn test_split_closes_when_its_buffer_becomes_active() {
+        let mut app = app_with_buffers(&["a", "b"]);
+        assert!(app.open_split(0, SplitDirection::Vertical));
+
+        app.close_buffer();
+        assert_eq!(active_id(&app), "a");
+        assert!(app.split.is_none());
+
+        app.close_buffer();
+        assert!(app.current_document.is_none());
+        assert_eq!(app.buffers.len(), 0);
+    }
+
+    #[test]
+    fn test_failed_open_leaves_the_split_as_it_was() {
+        let mut app = app_with_buffers(&["a", "b", "c"]);
+        assert!(app.switch_buffer(0));
+        assert!(app.open_split(1, SplitDirection::Vertical));
+
+        // A document that can't be loaded gets a buffer that's closed again
+        app.open_buffer();
+        assert_eq!(split_id(&app), Some("b"));
+        app.close_buffer();
+
+        assert_eq!(active_id(&app), "a");
+        assert_eq!(app.buffers.len(), 3);
+        assert_eq!(app.split.unwrap().buffer, 1);
+        assert_eq!(split_id(&app), Some("b"));
+    }
+}
@@ -0,0 +1,318 @@
+//! Attachments of documents, downloaded in the background and cached on disk
+//!
+//! Files go to `<cache dir>/outline-tui/attachments/<id>.<ext>`, so images show up
+//! straight away the next time a document is opened.
+
+use crate::images::{self, Format, Image};
+use anyhow::{Context, Result};
+use outline_api::OutlineClient;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Largest attachment downloaded
+const MAX_DOWNLOAD_BYTES: u64 = 25 * 1024 * 1024;
+
+/// File extensions of common attachment types
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+    ("image/svg+xml", "svg"),
+    ("application/pdf", "pdf"),
+    ("application/zip", "zip"),
+    ("application/json", "json"),
+    ("text/plain", "txt"),
+    ("text/csv", "csv"),
+    ("text/markdown", "md"),
+    ("video/mp4", "mp4"),
+    ("audio/mpeg", "mp3"),
+    ("application/vnd.openxmlformats-officedocument.wordprocessingml.document", "docx"),
+    ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet", "xlsx"),
+    ("application/vnd.openxmlformats-officedocument.presentationml.presentation", "pptx"),
+];
+
+/// A downloaded attachment
+pub struct Attachment {
+    /// The cached copy, if it could be written
+    pub path: Option<PathBuf>,
+    pub content_type: String,
+    pub data: Vec<u8>,
+    /// Width and height in pixels, for images in a format whose header is understood
+    pub size: Option<(u32, u32)>,
+    /// The decoded image, for PNGs
+    pub image: Option<Image>,
+}
+
+impl Attachment {
+    fn new(path: Option<PathBuf>, content_type: String, data: Vec<u8>) -> Self {
+        let size = images::dimensions(&data);
+        let image = match Format::sniff(&data) {
+            Some(Format::Png) => Image::decode_png(&data)
+                .map_err(|e| tracing::debug!("Couldn't decode image {:?}: {:#}", path, e))
+                .ok(),
+            _ => None,
+        };
+        Self { path, content_type, data, size, image }
+    }
+}
+
+enum Entry {
+    Loading,
+    Ready(Arc<Attachment>),
+    Failed,
+}
+
+/// What to do with an attachment once it's downloaded
+enum Intent {
+    Open,
+    /// Save it to the downloads folder under this file name
+    Save(String),
+}
+
+/// Attachments downloaded so far, by ID
+pub struct AttachmentCache {
+    client: Option<Arc<OutlineClient>>,
+    dir: Option<PathBuf>,
+    entries: HashMap<String, Entry>,
+    /// Pixel sizes of the images among them
+    sizes: HashMap<String, (u32, u32)>,
+    pending: Vec<(String, Intent)>,
+    tx: mpsc::UnboundedSender<(String, Result<Attachment, String>)>,
+    rx: mpsc::UnboundedReceiver<(String, Result<Attachment, String>)>,
+}
+
+impl AttachmentCache {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            client: None,
+            dir: dirs::cache_dir().map(|dir| dir.join("outline-tui").join("attachments")),
+            entries: HashMap::new(),
+            sizes: HashMap::new(),
+            pending: Vec::new(),
+            tx,
+            rx,
+        }
+    }
+
+    /// Use this client for downloads from now on
+    pub fn set_client(&mut self, client: Arc<OutlineClient>) {
+        self.client = Some(client);
+    }
+
+    /// A downloaded attachment
+    pub fn get(&self, id: &str) -> Option<&Arc<Attachment>> {
+        match self.entries.get(id) {
+            Some(Entry::Ready(attachment)) => Some(attachment),
+            _ => None,
+        }
+    }
+
+    /// Pixel sizes of the images downloaded, by attachment ID
+    pub fn sizes(&self) -> &HashMap<String, (u32, u32)> {
+        &self.sizes
+    }
+
+    /// Start loading an attachment, from the disk cache or the server, unless it's
+    /// loaded or loading already
+    pub fn fetch(&mut self, id: &str) {
+        if self.entries.contains_key(id) {
+            return;
+        }
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        self.entries.insert(id.to_string(), Entry::Loading);
+
+        // IDs come from the document text, so only plain ones are used as file names
+        let dir = self.dir.clone().filter(|_| id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+        let id = id.to_string();
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            let result = load(client, dir, id.clone()).await.map_err(|e| format!("{:#}", e));
+            let _ = tx.send((id, result));
+        });
+    }
+
+    /// Open an attachment with the system's default application, downloading it first
+    pub fn open(&mut self, id: &str) {
+        self.pending.push((id.to_string(), Intent::Open));
+        self.retry(id);
+    }
+
+    /// Save an attachment to the downloads folder, downloading it first
+    pub fn save(&mut self, id: &str, name: &str) {
+        self.pending.push((id.to_string(), Intent::Save(name.to_string())));
+        self.retry(id);
+    }
+
+    /// Fetch an attachment, trying again if it failed before
+    fn retry(&mut self, id: &str) {
+        if matches!(self.entries.get(id), Some(Entry::Failed)) {
+            self.entries.remove(id);
+        }
+        self.fetch(id);
+    }
+
+    /// Apply finished downloads and carry out the opens and saves waiting for them
+    /// (call this regularly in event loop)
+    ///
+    /// Returns a message for the status bar when an open or save finished or failed.
+    pub fn tick(&mut self) -> Option<String> {
+        let mut status = None;
+        while let Ok((id, result)) = self.rx.try_recv() {
+            match result {
+                Ok(attachment) => {
+                    if let Some(size) = attachment.size {
+                        self.sizes.insert(id.clone(), size);
+                    }
+                    self.entries.insert(id, Entry::Ready(Arc::new(attachment)));
+                }
+                Err(e) => {
+                    tracing::debug!("Couldn't load attachment {}: {}", id, e);
+                    self.entries.insert(id.clone(), Entry::Failed);
+                    if self.pending.iter().any(|(pending, _)| *pending == id) {
+                        self.pending.retain(|(pending, _)| *pending != id);
+                        status = Some(format!("Couldn't download the attachment: {}", e));
+                    }
+                }
+            }
+        }
+
+        let pending = std::mem::take(&mut self.pending);
+        for (id, intent) in pending {
+            let Some(attachment) = self.get(&id).cloned() else {
+                self.pending.push((id, intent));
+                continue;
+            };
+            let result = match &intent {
+                Intent::Open => open_file(&attachment).map(|path| format!("Opened {}", path.display())),
+                Intent::Save(name) => save_file(&attachment, name).map(|path| format!("Saved {}", path.display())),
+            };
+            status = Some(result.unwrap_or_else(|e| format!("Error: {:#}", e)));
+        }
+        status
+    }
+}
+
+/// Read an attachment from the disk cache, or download it and cache it
+async fn load(client: Arc<OutlineClient>, dir: Option<PathBuf>, id: String) -> Result<Attachment> {
+    if let Some(path) = dir.as_deref().and_then(|dir| cached(dir, &id)) {
+        let content_type = path
+            .extension()
+            .and_then(|ext| EXTENSIONS.iter().find(|(_, e)| ext == *e))
+            .map_or("application/octet-stream", |(content_type, _)| content_type)
+            .to_string();
+        return tokio::task::spawn_blocking(move || {
+            let data = fs::read(&path)?;
+            Ok(Attachment::new(Some(path), content_type, data))
+        })
+        .await?;
+    }
+
+    let content = client.download_attachment(id.clone(), MAX_DOWNLOAD_BYTES).await?;
+    let path = dir.and_then(|dir| {
+        let path = dir.join(format!("{}.{}", id, extension(&content.content_type)));
+        let written = fs::create_dir_all(&dir).and_then(|_| fs::write(&path, &content.data));
+        written.map_err(|e| tracing::debug!("Couldn't cache attachment {}: {}", id, e)).ok().map(|_| path)
+    });
+    Ok(tokio::task::spawn_blocking(move || Attachment::new(path, content.content_type, content.data)).await?)
+}
+
+/// The cached file of an attachment, whatever its extension
+fn cached(dir: &Path, id: &str) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.file_stem().is_some_and(|stem| stem == id))
+}
+
+fn extension(content_type: &str) -> &'static str {
+    let content_type = content_type.split(';').next().unwrap_or("").trim();
+    EXTENSIONS
+        .iter()
+        .find(|(known, _)| *known == content_type)
+        .map_or("bin", |(_, extension)| extension)
+}
+
+/// The file name of an attachment link's text, which Outline ends with the file's size
+/// (e.g. `report.pdf 24 KB`)
+pub fn file_name(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let sized = words.len() > 2
+        && words[words.len() - 2].parse::<f64>().is_ok()
+        && matches!(words[words.len() - 1], "B" | "bytes" | "KB" | "MB" | "GB");
+    let name = if sized { words[..words.len() - 2].join(" ") } else { words.join(" ") };
+
+    // Keep the name to a single path component
+    let name: String = name
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':') || c.is_control() { '_' } else { c })
+        .collect();
+    let name = name.trim_start_matches('.');
+    if name.is_empty() {
+        "attachment".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Open the cached copy with the system's default application
+fn open_file(attachment: &Attachment) -> Result<PathBuf> {
+    let path = attachment.path.clone().context("The attachment couldn't be cached to open it")?;
+
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = Command::new("xdg-open");
+
+    command
+        .arg(&path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to start the default application")?;
+    Ok(path)
+}
+
+/// Write the attachment to the downloads folder, next to any file of the same name
+fn save_file(attachment: &Attachment, name: &str) -> Result<PathBuf> {
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .context("Failed to determine the downloads folder")?;
+
+    let mut name = name.to_string();
+    if Path::new(&name).extension().is_none() {
+        let extension = extension(&attachment.content_type);
+        if extension != "bin" {
+            name = format!("{}.{}", name, extension);
+        }
+    }
+
+    // Add " (1)", " (2)", … before the extension rather than overwrite a file
+    let original = Path::new(&name);
+    let stem = original.file_stem().and_then(|s| s.to_str()).unwrap_or("attachment").to_string();
+    let suffix = original.extension().and_then(|s| s.to_str()).map(|e| format!(".{}", e)).unwrap_or_default();
+    let mut path = dir.join(&name);
+    let mut copy = 1;
+    while path.exists() {
+        path = dir.join(format!("{} ({}){}", stem, copy, suffix));
+        copy += 1;
+    }
+
+    fs::write(&path, &attachment.data).context(format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
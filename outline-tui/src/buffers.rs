@@ -0,0 +1,145 @@
+use crate::app::{EditorMode, VimMode};
+use crate::comments::CommentsPanel;
+use crate::links::LinksPanel;
+use crate::toc::TableOfContents;
+use outline_api::Document;
+use tui_textarea::TextArea;
+
+/// An open document and where the user was in it
+///
+/// The active buffer lives in `App` (`current_document`, `document_text`, ...); the others are
+/// kept here until they're switched to.
+pub struct Buffer {
+    pub document: Document,
+    pub text: String,
+    pub scroll_offset: u16,
+    pub textarea: TextArea<'static>,
+    pub editor_mode: EditorMode,
+    pub vim_mode: VimMode,
+    pub comments: Option<CommentsPanel>,
+    pub links: Option<LinksPanel>,
+    pub toc: TableOfContents,
+}
+
+impl Buffer {
+    /// Whether the editor holds changes that haven't been saved
+    pub fn is_dirty(&self) -> bool {
+        has_unsaved_changes(self.editor_mode, &self.textarea, &self.text)
+    }
+}
+
+/// Whether an editor's text differs from the saved document text
+pub fn has_unsaved_changes(mode: EditorMode, textarea: &TextArea, saved: &str) -> bool {
+    mode == EditorMode::Edit && !textarea.lines().iter().map(String::as_str).eq(saved.lines())
+}
+
+/// How a split arranges the two documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitDirection {
+    /// Side by side
+    Vertical,
+    /// One above the other
+    Horizontal,
+}
+
+/// A second buffer shown next to the active one
+#[derive(Debug, Clone, Copy)]
+pub struct Split {
+    /// Index into the buffer list
+    pub buffer: usize,
+    pub direction: SplitDirection,
+}
+
+/// Open documents, in tab order
+#[derive(Default)]
+pub struct BufferList {
+    /// `None` marks the active buffer, whose state is in `App`
+    slots: Vec<Option<Buffer>>,
+    active: usize,
+}
+
+impl BufferList {
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// An inactive buffer
+    pub fn get(&self, index: usize) -> Option<&Buffer> {
+        self.slots.get(index)?.as_ref()
+    }
+
+    /// Position of the buffer holding a document (by ID or URL ID), the active one included
+    pub fn position(&self, document_id: &str, active: Option<&Document>) -> Option<usize> {
+        let matches = |doc: &Document| doc.id == document_id || doc.url_id == document_id;
+        self.slots.iter().enumerate().find_map(|(i, slot)| {
+            let doc = match slot {
+                Some(buffer) => Some(&buffer.document),
+                None => active,
+            };
+            doc.filter(|doc| matches(doc)).map(|_| i)
+        })
+    }
+
+    /// Add a slot for a new active buffer after the current one, storing the current one
+    /// (if any) in its slot; returns the new buffer's index
+    pub fn open(&mut self, current: Option<Buffer>) -> usize {
+        if self.slots.is_empty() {
+            self.slots.push(None);
+            self.active = 0;
+            return 0;
+        }
+        // With nothing open in the active slot, it's reused
+        if let Some(buffer) = current {
+            self.slots[self.active] = Some(buffer);
+            self.active += 1;
+            self.slots.insert(self.active, None);
+        }
+        self.active
+    }
+
+    /// Make another buffer active, storing the current one; returns the buffer to restore
+    pub fn switch(&mut self, index: usize, current: Buffer) -> Option<Buffer> {
+        if index == self.active || index >= self.slots.len() {
+            return None;
+        }
+        let target = self.slots[index].take()?;
+        self.slots[self.active] = Some(current);
+        self.active = index;
+        Some(target)
+    }
+
+    /// Drop the active buffer; returns the one before it (or after, for the first) to make
+    /// active, if any is left
+    pub fn close_active(&mut self) -> Option<Buffer> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        self.slots.remove(self.active);
+        if self.slots.is_empty() {
+            self.active = 0;
+            return None;
+        }
+        self.active = self.active.saturating_sub(1);
+        self.slots[self.active].take()
+    }
+
+    /// Titles and unsaved state of every buffer, the active one described by the arguments
+    pub fn tabs(&self, active_title: &str, active_dirty: bool) -> Vec<(String, bool)> {
+        self.slots
+            .iter()
+            .map(|slot| match slot {
+                Some(buffer) => (buffer.document.title.clone(), buffer.is_dirty()),
+                None => (active_title.to_string(), active_dirty),
+            })
+            .collect()
+    }
+
+    /// Whether any inactive buffer has unsaved changes
+    pub fn any_dirty(&self) -> bool {
+        self.slots.iter().flatten().any(Buffer::is_dirty)
+    }
+}
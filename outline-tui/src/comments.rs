@@ -0,0 +1,572 @@
+use chrono::{DateTime, Utc};
+use outline_api::{
+    Comment, CommentData, CreateCommentRequest, ListCommentsRequest, OutlineClient, UpdateCommentRequest,
+};
+use ratatui::text::Text;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Comments fetched per `comments.list` request
+const PAGE_SIZE: u32 = 100;
+
+/// Characters of a comment's anchor text used to find it in the document
+const ANCHOR_MATCH_CHARS: usize = 40;
+
+/// Result of a background comment request
+enum CommentUpdate {
+    Loaded(Result<Vec<Comment>, String>),
+    /// A comment was created or changed
+    Saved(Result<Box<Comment>, String>),
+    Deleted {
+        id: String,
+        result: Result<(), String>,
+    },
+}
+
+/// Loading state of the comment list
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommentsStatus {
+    Loading,
+    Loaded,
+    Failed(String),
+}
+
+/// What the input box at the bottom of the pane is for
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComposeMode {
+    /// Start a new thread
+    New,
+    Reply { parent_id: String },
+    Edit { id: String },
+}
+
+/// Text being written in the comments pane
+#[derive(Debug, Clone)]
+pub struct Compose {
+    pub mode: ComposeMode,
+    pub text: String,
+}
+
+/// A visible row: a comment and how deeply it is nested in its thread
+#[derive(Debug, Clone, Copy)]
+pub struct CommentRow {
+    pub index: usize,
+    pub depth: usize,
+}
+
+/// Comments on the current document, shown as threads in the comments pane
+pub struct CommentsPanel {
+    pub document_id: String,
+    pub comments: Vec<Comment>,
+    pub status: CommentsStatus,
+    /// Index of the selected row
+    pub selected: usize,
+    pub compose: Option<Compose>,
+    /// Comment waiting for the user to confirm its deletion
+    pub confirm_delete: Option<String>,
+    pub show_resolved: bool,
+    /// Set while a change is being saved
+    pub saving: bool,
+    client: Arc<OutlineClient>,
+    tx: mpsc::UnboundedSender<CommentUpdate>,
+    rx: mpsc::UnboundedReceiver<CommentUpdate>,
+}
+
+impl CommentsPanel {
+    /// Start loading a document's comments in the background
+    pub fn load(client: Arc<OutlineClient>, document_id: String) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let panel = Self {
+            document_id,
+            comments: Vec::new(),
+            status: CommentsStatus::Loading,
+            selected: 0,
+            compose: None,
+            confirm_delete: None,
+            show_resolved: false,
+            saving: false,
+            client,
+            tx,
+            rx,
+        };
+        panel.fetch();
+        panel
+    }
+
+    /// Fetch the comments again
+    pub fn reload(&mut self) {
+        self.status = CommentsStatus::Loading;
+        self.fetch();
+    }
+
+    fn fetch(&self) {
+        let client = self.client.clone();
+        let tx = self.tx.clone();
+        let document_id = self.document_id.clone();
+
+        tokio::spawn(async move {
+            let result = list_all(&client, &document_id).await.map_err(|e| e.to_string());
+            let _ = tx.send(CommentUpdate::Loaded(result));
+        });
+    }
+
+    /// Apply finished requests; returns a message for the status bar, if any
+    /// (call this regularly in event loop)
+    pub fn tick(&mut self) -> Option<String> {
+        let mut message = None;
+
+        while let Ok(update) = self.rx.try_recv() {
+            match update {
+                CommentUpdate::Loaded(Ok(comments)) => {
+                    self.comments = comments;
+                    self.status = CommentsStatus::Loaded;
+                }
+                CommentUpdate::Loaded(Err(e)) => self.status = CommentsStatus::Failed(e),
+                CommentUpdate::Saved(result) => {
+                    self.saving = false;
+                    match result {
+                        Ok(comment) => {
+                            let id = comment.id.clone();
+                            let mut comment = *comment;
+                            match self.comments.iter_mut().find(|c| c.id == comment.id) {
+                                Some(existing) => {
+                                    // Only listing returns anchor text
+                                    comment.anchor_text = comment.anchor_text.or(existing.anchor_text.take());
+                                    *existing = comment;
+                                }
+                                None => self.comments.push(comment),
+                            }
+                            self.select_comment(&id);
+                            message = Some("Comment saved".to_string());
+                        }
+                        Err(e) => message = Some(format!("Failed to save comment: {}", e)),
+                    }
+                }
+                CommentUpdate::Deleted { id, result } => {
+                    self.saving = false;
+                    match result {
+                        Ok(()) => {
+                            // Replies go with their parent
+                            self.comments
+                                .retain(|c| c.id != id && c.parent_comment_id.as_deref() != Some(id.as_str()));
+                            message = Some("Comment deleted".to_string());
+                        }
+                        Err(e) => message = Some(format!("Failed to delete comment: {}", e)),
+                    }
+                }
+            }
+            self.clamp_selection();
+        }
+
+        message
+    }
+
+    /// Thread roots in display order, with replies nested under their parent
+    pub fn rows(&self) -> Vec<CommentRow> {
+        let ids: Vec<&str> = self.comments.iter().map(|c| c.id.as_str()).collect();
+        let mut replies: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut roots = Vec::new();
+
+        for (i, comment) in self.comments.iter().enumerate() {
+            match comment.parent_comment_id.as_deref() {
+                Some(parent) if ids.contains(&parent) => replies.entry(parent).or_default().push(i),
+                _ => roots.push(i),
+            }
+        }
+
+        let by_date = |a: &usize, b: &usize| self.comments[*a].created_at.cmp(&self.comments[*b].created_at);
+        roots.sort_by(by_date);
+        for children in replies.values_mut() {
+            children.sort_by(by_date);
+        }
+
+        let mut visited = HashSet::new();
+        let mut rows = Vec::new();
+        for root in roots {
+            self.push_thread(&mut rows, &replies, &mut visited, root);
+        }
+
+        // Comments in a reply cycle are never reached from a root; start each cycle at its earliest comment
+        let mut unreached: Vec<usize> = (0..self.comments.len()).filter(|i| !visited.contains(i)).collect();
+        unreached.sort_by(by_date);
+        for start in unreached {
+            self.push_thread(&mut rows, &replies, &mut visited, start);
+        }
+        rows
+    }
+
+    /// Push a thread's rows, unless its first comment is resolved and resolved threads are hidden
+    fn push_thread(
+        &self,
+        rows: &mut Vec<CommentRow>,
+        replies: &HashMap<&str, Vec<usize>>,
+        visited: &mut HashSet<usize>,
+        root: usize,
+    ) {
+        let mut thread = Vec::new();
+        push_rows(&mut thread, &self.comments, replies, visited, root, 0);
+        if self.comments[root].resolved_at.is_none() || self.show_resolved {
+            rows.extend(thread);
+        }
+    }
+
+    /// Number of open and resolved threads
+    pub fn thread_counts(&self) -> (usize, usize) {
+        let roots = self.comments.iter().filter(|c| c.parent_comment_id.is_none());
+        let (resolved, open): (Vec<_>, Vec<_>) = roots.partition(|c| c.resolved_at.is_some());
+        (open.len(), resolved.len())
+    }
+
+    pub fn selected_comment(&self) -> Option<&Comment> {
+        self.rows().get(self.selected).map(|row| &self.comments[row.index])
+    }
+
+    /// The root comment of the selected row's thread
+    pub fn selected_thread(&self) -> Option<&Comment> {
+        let rows = self.rows();
+        let thread = rows.get(..=self.selected)?.iter().rev().find(|row| row.depth == 0)?;
+        Some(&self.comments[thread.index])
+    }
+
+    /// Select a comment (e.g. a thread's root)
+    pub fn select_comment(&mut self, id: &str) {
+        if let Some(i) = self.rows().iter().position(|row| self.comments[row.index].id == id) {
+            self.selected = i;
+        }
+    }
+
+    fn clamp_selection(&mut self) {
+        self.selected = self.selected.min(self.rows().len().saturating_sub(1));
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.rows().len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn toggle_resolved(&mut self) {
+        self.show_resolved = !self.show_resolved;
+        self.clamp_selection();
+    }
+
+    /// Open the input box to start a thread, reply to the selected thread or edit the selected comment
+    pub fn start_compose(&mut self, mode: ComposeMode) {
+        let text = match &mode {
+            ComposeMode::Edit { id } => self
+                .comments
+                .iter()
+                .find(|c| c.id == *id)
+                .map(|c| c.data.text.clone())
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+        self.confirm_delete = None;
+        self.compose = Some(Compose { mode, text });
+    }
+
+    /// Send the text in the input box
+    pub fn submit_compose(&mut self) {
+        let Some(compose) = self.compose.take() else {
+            return;
+        };
+        let text = compose.text.trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+
+        let client = self.client.clone();
+        let tx = self.tx.clone();
+        let document_id = self.document_id.clone();
+        self.saving = true;
+
+        tokio::spawn(async move {
+            let data = CommentData::new(text);
+            let result = match compose.mode {
+                ComposeMode::New => {
+                    client.create_comment(CreateCommentRequest::new(document_id, data)).await
+                }
+                ComposeMode::Reply { parent_id } => {
+                    let request = CreateCommentRequest::new(document_id, data).parent_comment_id(parent_id);
+                    client.create_comment(request).await
+                }
+                ComposeMode::Edit { id } => client.update_comment(UpdateCommentRequest::new(id, data)).await,
+            };
+            let _ = tx.send(CommentUpdate::Saved(result.map(Box::new).map_err(|e| e.to_string())));
+        });
+    }
+
+    /// Resolve the selected thread, or reopen it if it is resolved
+    pub fn toggle_thread_resolved(&mut self) {
+        let Some(thread) = self.selected_thread() else {
+            return;
+        };
+        let id = thread.id.clone();
+        let resolved = thread.resolved_at.is_some();
+        let client = self.client.clone();
+        let tx = self.tx.clone();
+        self.saving = true;
+
+        tokio::spawn(async move {
+            let result = if resolved {
+                client.unresolve_comment(id).await
+            } else {
+                client.resolve_comment(id).await
+            };
+            let _ = tx.send(CommentUpdate::Saved(result.map(Box::new).map_err(|e| e.to_string())));
+        });
+    }
+
+    /// Ask to delete the selected comment; a second call with the same comment deletes it
+    pub fn delete_selected(&mut self) {
+        let Some(id) = self.selected_comment().map(|c| c.id.clone()) else {
+            return;
+        };
+        if self.confirm_delete.as_deref() != Some(id.as_str()) {
+            self.confirm_delete = Some(id);
+            return;
+        }
+
+        self.confirm_delete = None;
+        let client = self.client.clone();
+        let tx = self.tx.clone();
+        self.saving = true;
+
+        tokio::spawn(async move {
+            let result = client.delete_comment(id.clone()).await.map_err(|e| e.to_string());
+            let _ = tx.send(CommentUpdate::Deleted { id, result });
+        });
+    }
+
+    /// Lines of the rendered document that threads are anchored to, in document order
+    ///
+    /// Threads are found by their anchor text, or by their stored position when the server has no anchor text.
+    pub fn anchors(&self, document: &Text) -> Vec<(usize, &Comment)> {
+        let lines: Vec<String> = document
+            .lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect::<String>()
+                    .to_lowercase()
+            })
+            .collect();
+
+        let mut anchors: Vec<(usize, &Comment)> = self
+            .comments
+            .iter()
+            .filter(|c| c.parent_comment_id.is_none())
+            .filter(|c| c.resolved_at.is_none() || self.show_resolved)
+            .filter_map(|comment| {
+                let by_text = comment.anchor_text.as_deref().and_then(|anchor| {
+                    let needle: String = anchor
+                        .lines()
+                        .find(|l| !l.trim().is_empty())?
+                        .trim()
+                        .chars()
+                        .take(ANCHOR_MATCH_CHARS)
+                        .collect::<String>()
+                        .to_lowercase();
+                    lines.iter().position(|line| line.contains(&needle))
+                });
+                let by_position = || {
+                    comment
+                        .data
+                        .position
+                        .as_ref()
+                        .map(|p| (p.line as usize).min(lines.len().saturating_sub(1)))
+                };
+                by_text.or_else(by_position).map(|line| (line, comment))
+            })
+            .collect();
+
+        anchors.sort_by_key(|(line, _)| *line);
+        anchors
+    }
+}
+
+/// Push a comment and its replies, skipping comments already shown so a reply cycle ends
+fn push_rows(
+    rows: &mut Vec<CommentRow>,
+    comments: &[Comment],
+    replies: &HashMap<&str, Vec<usize>>,
+    visited: &mut HashSet<usize>,
+    index: usize,
+    depth: usize,
+) {
+    if !visited.insert(index) {
+        return;
+    }
+    rows.push(CommentRow { index, depth });
+    if let Some(children) = replies.get(comments[index].id.as_str()) {
+        for &child in children {
+            push_rows(rows, comments, replies, visited, child, depth + 1);
+        }
+    }
+}
+
+/// Fetch every comment on a document, following pagination
+async fn list_all(client: &OutlineClient, document_id: &str) -> anyhow::Result<Vec<Comment>> {
+    let mut comments = Vec::new();
+    loop {
+        let request = ListCommentsRequest::new(document_id.to_string())
+            .offset(comments.len() as u32)
+            .limit(PAGE_SIZE)
+            .include_anchor_text(true);
+        let response = client.list_comments(request).await?;
+        let count = response.data.len() as u32;
+        comments.extend(response.data);
+        if count < PAGE_SIZE {
+            return Ok(comments);
+        }
+    }
+}
+
+/// How long ago a timestamp was, e.g. `3h ago`
+pub fn age(timestamp: &str) -> String {
+    let Ok(at) = DateTime::parse_from_rfc3339(timestamp) else {
+        return timestamp.to_string();
+    };
+    let seconds = (Utc::now() - at.with_timezone(&Utc)).num_seconds().max(0);
+    match seconds {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86399 => format!("{}h ago", seconds / 3600),
+        86400..=2_591_999 => format!("{}d ago", seconds / 86400),
+        _ => at.format("%Y-%m-%d").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The panel starts a fetch when created; tests never await, so it never runs.
+    fn panel(comments: Vec<Comment>) -> CommentsPanel {
+        let client = Arc::new(OutlineClient::new("http://localhost".to_string()).unwrap());
+        let mut panel = CommentsPanel::load(client, "doc".to_string());
+        panel.comments = comments;
+        panel.status = CommentsStatus::Loaded;
+        panel
+    }
+
+    /// A comment created at minute `minute`, replying to `parent` if given
+    fn comment(id: &str, parent: Option<&str>, minute: u32) -> Comment {
+        Comment {
+            id: id.to_string(),
+            document_id: "doc".to_string(),
+            parent_comment_id: parent.map(str::to_string),
+            created_by: serde_json::from_value(serde_json::json!({ "id": "user", "name": "User" })).unwrap(),
+            created_at: format!("2024-01-01T10:{:02}:00Z", minute),
+            updated_at: String::new(),
+            data: CommentData::new(format!("Comment {}", id)),
+            resolved_at: None,
+            resolved_by: None,
+            anchor_text: None,
+        }
+    }
+
+    fn resolved(mut comment: Comment) -> Comment {
+        comment.resolved_at = Some("2024-01-02T00:00:00Z".to_string());
+        comment
+    }
+
+    /// Visible rows as `(id, depth)`
+    fn rows(panel: &CommentsPanel) -> Vec<(&str, usize)> {
+        panel
+            .rows()
+            .iter()
+            .map(|row| (panel.comments[row.index].id.as_str(), row.depth))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_replies_nest_under_their_parent_in_date_order() {
+        let panel = panel(vec![
+            comment("reply-late", Some("first"), 5),
+            comment("second", None, 2),
+            comment("nested", Some("reply-early"), 4),
+            comment("reply-early", Some("first"), 3),
+            comment("first", None, 1),
+        ]);
+
+        assert_eq!(
+            rows(&panel),
+            vec![("first", 0), ("reply-early", 1), ("nested", 2), ("reply-late", 1), ("second", 0)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_orphaned_replies_start_their_own_thread() {
+        let panel = panel(vec![
+            comment("thread", None, 1),
+            comment("orphan", Some("deleted"), 2),
+            comment("orphan-reply", Some("orphan"), 3),
+        ]);
+
+        assert_eq!(rows(&panel), vec![("thread", 0), ("orphan", 0), ("orphan-reply", 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_reply_cycles_are_shown_once_at_the_top_level() {
+        let mut panel = panel(vec![
+            comment("thread", None, 1),
+            comment("b", Some("a"), 3),
+            comment("a", Some("b"), 2),
+            comment("tail", Some("b"), 4),
+            comment("self", Some("self"), 5),
+        ]);
+
+        assert_eq!(
+            rows(&panel),
+            vec![("thread", 0), ("a", 0), ("b", 1), ("tail", 2), ("self", 0)]
+        );
+
+        // Walking up to the thread stops at the row the cycle was started at
+        panel.select_comment("tail");
+        assert_eq!(panel.selected_comment().unwrap().id, "tail");
+        assert_eq!(panel.selected_thread().unwrap().id, "a");
+        panel.select_comment("self");
+        assert_eq!(panel.selected_thread().unwrap().id, "self");
+    }
+
+    #[tokio::test]
+    async fn test_resolved_threads_are_hidden_with_their_replies() {
+        let mut panel = panel(vec![
+            resolved(comment("done", None, 1)),
+            comment("done-reply", Some("done"), 2),
+            comment("open", None, 3),
+        ]);
+        assert_eq!(panel.thread_counts(), (1, 1));
+        assert_eq!(rows(&panel), vec![("open", 0)]);
+
+        panel.toggle_resolved();
+        assert_eq!(rows(&panel), vec![("done", 0), ("done-reply", 1), ("open", 0)]);
+
+        panel.select_comment("done-reply");
+        assert_eq!(panel.selected_thread().unwrap().id, "done");
+
+        // Hiding them again keeps the selection on a visible row
+        panel.selected = 2;
+        panel.toggle_resolved();
+        assert_eq!(panel.selected, 0);
+        assert_eq!(panel.selected_comment().unwrap().id, "open");
+    }
+
+    #[tokio::test]
+    async fn test_no_comments() {
+        let mut panel = panel(Vec::new());
+        assert!(panel.rows().is_empty());
+        assert!(panel.selected_comment().is_none());
+        assert!(panel.selected_thread().is_none());
+        panel.select_next();
+        assert_eq!(panel.selected, 0);
+    }
+}
@@ -1,16 +1,31 @@
+use crate::graphics::ImagesConfig;
+use crate::keymap::KeymapConfig;
+use crate::theme::ThemeConfig;
 use anyhow::{Context, Result};
 use outline_api::auth::OAuth2Config;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Config {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instance_url: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output_format: Option<String>,
+
+    /// Key bindings changed from the built-in ones
+    #[serde(default)]
+    pub keymap: KeymapConfig,
+
+    /// Colour palette
+    #[serde(default)]
+    pub theme: ThemeConfig,
+
+    /// How images in documents are drawn
+    #[serde(default)]
+    pub images: ImagesConfig,
 }
 
 impl Config {
@@ -86,12 +101,3 @@ impl Config {
         })
     }
 }
-
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            instance_url: None,
-            output_format: None,
-        }
-    }
-}
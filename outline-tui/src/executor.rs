@@ -77,8 +77,8 @@ pub async fn execute_action(
                 let doc_id = doc.id.clone();
                 let doc_title = doc.title.clone();
                 client.delete_document(doc_id, permanent).await?;
-                app.current_document = None;
-                app.document_text.clear();
+                app.stop_collaboration().await;
+                app.close_buffer();
                 Ok(format!("Deleted document: {}", doc_title))
             } else {
                 Err(anyhow::anyhow!("No document selected"))
@@ -152,10 +152,10 @@ pub async fn execute_action(
         Action::ExportDocument => {
             if let Some(doc) = &app.current_document {
                 let format = input_values.first()
-                    .and_then(|s| match s.to_lowercase().as_str() {
-                        "html" => Some(ExportFormat::Html),
-                        "pdf" => Some(ExportFormat::Pdf),
-                        _ => Some(ExportFormat::Markdown),
+                    .map(|s| match s.to_lowercase().as_str() {
+                        "html" => ExportFormat::Html,
+                        "pdf" => ExportFormat::Pdf,
+                        _ => ExportFormat::Markdown,
                     })
                     .unwrap_or(ExportFormat::Markdown);
 
@@ -0,0 +1,590 @@
+use nucleo_matcher::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo_matcher::{Config as MatcherConfig, Matcher, Utf32Str};
+use outline_api::{NavigationNode, OutlineClient, SearchDocumentsRequest};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use crate::tree::{Documents, SidebarTree};
+
+/// Separator between the parts of a breadcrumb
+pub const BREADCRUMB_SEPARATOR: &str = " › ";
+
+/// Most local matches shown
+const MAX_MATCHES: usize = 100;
+
+/// Full-text results requested per search
+const SEARCH_LIMIT: u32 = 20;
+
+/// Shortest query sent to `documents.search`
+const MIN_SEARCH_CHARS: usize = 2;
+
+/// Pause after the last keystroke before searching the server
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Extra score for matching within the title rather than the breadcrumb
+const TITLE_BONUS: u32 = 16;
+
+/// A loaded document the finder can match
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub id: String,
+    pub icon: String,
+    /// `Collection › Parent › Title`, the text matched against
+    pub path: String,
+    /// Character index where the title starts in `path`
+    pub title_start: usize,
+    title: String,
+}
+
+/// A candidate matching the query
+#[derive(Debug, Clone)]
+pub struct FinderMatch {
+    pub candidate: Candidate,
+    pub score: u32,
+    /// Character indices in `candidate.path` that matched
+    pub indices: Vec<u32>,
+}
+
+/// A full-text search result
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub id: String,
+    pub title: String,
+    pub icon: String,
+    /// Text around the match, with `<b>` marking the matched words
+    pub context: String,
+}
+
+/// State of the full-text search for the current query
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchStatus {
+    /// Query too short to search
+    Idle,
+    /// Waiting for typing to pause
+    Waiting(Instant),
+    Searching,
+    Done,
+    Failed(String),
+}
+
+/// A document preview, fetched when the document is first highlighted
+#[derive(Debug, Clone)]
+pub enum Preview {
+    Loading,
+    Loaded(String),
+    Failed(String),
+}
+
+/// Result of a background finder request
+enum FinderUpdate {
+    Preview {
+        id: String,
+        result: Result<String, String>,
+    },
+    Search {
+        query: String,
+        result: Result<Vec<SearchHit>, String>,
+    },
+}
+
+/// The "go to document" fuzzy finder
+pub struct Finder {
+    pub query: String,
+    /// Local matches, best first
+    pub matches: Vec<FinderMatch>,
+    /// Full-text results for the last completed search
+    pub hits: Vec<SearchHit>,
+    pub search: SearchStatus,
+    /// Index of the highlighted row, counting matches then hits
+    pub selected: usize,
+    candidates: Vec<Candidate>,
+    previews: HashMap<String, Preview>,
+    matcher: Matcher,
+    client: Arc<OutlineClient>,
+    tx: mpsc::UnboundedSender<FinderUpdate>,
+    rx: mpsc::UnboundedReceiver<FinderUpdate>,
+}
+
+impl Finder {
+    pub fn new(client: Arc<OutlineClient>, tree: &SidebarTree) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut finder = Self {
+            query: String::new(),
+            matches: Vec::new(),
+            hits: Vec::new(),
+            search: SearchStatus::Idle,
+            selected: 0,
+            candidates: Vec::new(),
+            previews: HashMap::new(),
+            matcher: Matcher::new(MatcherConfig::DEFAULT),
+            client,
+            tx,
+            rx,
+        };
+        finder.set_candidates(tree);
+        finder
+    }
+
+    /// Rebuild the candidates from the loaded tree, e.g. after more collections load
+    pub fn set_candidates(&mut self, tree: &SidebarTree) {
+        self.candidates.clear();
+        for node in &tree.collections {
+            if let Documents::Loaded(documents) = &node.documents {
+                for document in documents {
+                    push_candidates(&mut self.candidates, &node.collection.name, document);
+                }
+            }
+        }
+        self.update_matches();
+    }
+
+    /// Number of loaded documents being matched
+    pub fn candidate_count(&self) -> usize {
+        self.candidates.len()
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        self.query_changed();
+    }
+
+    pub fn backspace(&mut self) {
+        self.query.pop();
+        self.query_changed();
+    }
+
+    pub fn clear_query(&mut self) {
+        self.query.clear();
+        self.query_changed();
+    }
+
+    fn query_changed(&mut self) {
+        self.selected = 0;
+        self.update_matches();
+
+        if self.query.trim().chars().count() >= MIN_SEARCH_CHARS {
+            self.search = SearchStatus::Waiting(Instant::now() + SEARCH_DEBOUNCE);
+        } else {
+            self.search = SearchStatus::Idle;
+            self.hits.clear();
+        }
+    }
+
+    /// Score every candidate against the query
+    fn update_matches(&mut self) {
+        let query = self.query.trim();
+        if query.is_empty() {
+            self.matches = self
+                .candidates
+                .iter()
+                .take(MAX_MATCHES)
+                .map(|candidate| FinderMatch {
+                    candidate: candidate.clone(),
+                    score: 0,
+                    indices: Vec::new(),
+                })
+                .collect();
+            self.clamp_selection();
+            return;
+        }
+
+        let pattern = Pattern::parse(query, CaseMatching::Smart, Normalization::Smart);
+        let mut buf = Vec::new();
+        let mut matches = Vec::new();
+
+        for candidate in &self.candidates {
+            let mut indices = Vec::new();
+            let Some(score) = pattern.indices(Utf32Str::new(&candidate.path, &mut buf), &mut self.matcher, &mut indices) else {
+                continue;
+            };
+            // Prefer documents whose own title matches over ones matched via their breadcrumb
+            let title_score = pattern
+                .score(Utf32Str::new(&candidate.title, &mut buf), &mut self.matcher)
+                .map_or(0, |s| s + TITLE_BONUS);
+
+            indices.sort_unstable();
+            indices.dedup();
+            matches.push(FinderMatch {
+                candidate: candidate.clone(),
+                score: score.max(title_score),
+                indices,
+            });
+        }
+
+        matches.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then(a.candidate.path.len().cmp(&b.candidate.path.len()))
+        });
+        matches.truncate(MAX_MATCHES);
+        self.matches = matches;
+        self.clamp_selection();
+    }
+
+    /// Full-text results not already listed as local matches
+    pub fn visible_hits(&self) -> impl Iterator<Item = &SearchHit> {
+        self.hits
+            .iter()
+            .filter(|hit| !self.matches.iter().any(|m| m.candidate.id == hit.id))
+    }
+
+    fn row_count(&self) -> usize {
+        self.matches.len() + self.visible_hits().count()
+    }
+
+    fn clamp_selection(&mut self) {
+        self.selected = self.selected.min(self.row_count().saturating_sub(1));
+    }
+
+    pub fn select_next(&mut self) {
+        let count = self.row_count();
+        if count > 0 {
+            self.selected = (self.selected + 1) % count;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        let count = self.row_count();
+        if count > 0 {
+            self.selected = (self.selected + count - 1) % count;
+        }
+    }
+
+    /// ID of the highlighted document
+    pub fn selected_id(&self) -> Option<&str> {
+        match self.matches.get(self.selected) {
+            Some(m) => Some(&m.candidate.id),
+            None => self
+                .visible_hits()
+                .nth(self.selected - self.matches.len())
+                .map(|hit| hit.id.as_str()),
+        }
+    }
+
+    /// Title of the highlighted document
+    pub fn selected_title(&self) -> Option<&str> {
+        match self.matches.get(self.selected) {
+            Some(m) => Some(&m.candidate.title),
+            None => self
+                .visible_hits()
+                .nth(self.selected - self.matches.len())
+                .map(|hit| hit.title.as_str()),
+        }
+    }
+
+    /// Preview of the highlighted document, if it has been requested
+    pub fn selected_preview(&self) -> Option<&Preview> {
+        self.selected_id().and_then(|id| self.previews.get(id))
+    }
+
+    /// Apply background results, start a search once typing pauses,
+    /// and fetch the highlighted document's preview (call this regularly in event loop)
+    pub fn tick(&mut self) {
+        while let Ok(update) = self.rx.try_recv() {
+            match update {
+                FinderUpdate::Preview { id, result } => {
+                    let preview = match result {
+                        Ok(text) => Preview::Loaded(text),
+                        Err(e) => Preview::Failed(e),
+                    };
+                    self.previews.insert(id, preview);
+                }
+                FinderUpdate::Search { query, result } => {
+                    // Ignore results for a query that has since changed
+                    if query != self.query.trim() || self.search != SearchStatus::Searching {
+                        continue;
+                    }
+                    match result {
+                        Ok(hits) => {
+                            self.hits = hits;
+                            self.search = SearchStatus::Done;
+                        }
+                        Err(e) => {
+                            self.hits.clear();
+                            self.search = SearchStatus::Failed(e);
+                        }
+                    }
+                    self.clamp_selection();
+                }
+            }
+        }
+
+        if matches!(self.search, SearchStatus::Waiting(at) if Instant::now() >= at) {
+            self.start_search();
+        }
+
+        if let Some(id) = self.selected_id().map(str::to_string) {
+            if !self.previews.contains_key(&id) {
+                self.fetch_preview(id);
+            }
+        }
+    }
+
+    fn start_search(&mut self) {
+        self.search = SearchStatus::Searching;
+        let query = self.query.trim().to_string();
+        let client = self.client.clone();
+        let tx = self.tx.clone();
+
+        tokio::spawn(async move {
+            let request = SearchDocumentsRequest::builder(query.clone())
+                .limit(SEARCH_LIMIT)
+                .build();
+            let result = client
+                .search_documents(request)
+                .await
+                .map(|response| {
+                    response
+                        .data
+                        .into_iter()
+                        .map(|result| SearchHit {
+                            icon: result.document.icon().to_string(),
+                            id: result.document.id,
+                            title: result.document.title,
+                            context: result.context,
+                        })
+                        .collect()
+                })
+                .map_err(|e| e.to_string());
+            let _ = tx.send(FinderUpdate::Search { query, result });
+        });
+    }
+
+    fn fetch_preview(&mut self, id: String) {
+        self.previews.insert(id.clone(), Preview::Loading);
+        let client = self.client.clone();
+        let tx = self.tx.clone();
+
+        tokio::spawn(async move {
+            let result = client
+                .get_document(id.clone())
+                .await
+                .map(|document| document.text)
+                .map_err(|e| e.to_string());
+            let _ = tx.send(FinderUpdate::Preview { id, result });
+        });
+    }
+}
+
+/// Add a document and its children, with their breadcrumbs
+fn push_candidates(candidates: &mut Vec<Candidate>, breadcrumb: &str, node: &NavigationNode) {
+    let title = if node.title.is_empty() { "Untitled" } else { &node.title };
+    let prefix = format!("{}{}", breadcrumb, BREADCRUMB_SEPARATOR);
+    candidates.push(Candidate {
+        id: node.id.clone(),
+        icon: node.icon().to_string(),
+        path: format!("{}{}", prefix, title),
+        title_start: prefix.chars().count(),
+        title: title.to_string(),
+    });
+
+    let child_breadcrumb = format!("{}{}", prefix, title);
+    for child in &node.children {
+        push_candidates(candidates, &child_breadcrumb, child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::CollectionNode;
+    use outline_api::Collection;
+    use std::collections::BTreeSet;
+
+    fn node(id: &str, title: &str, children: Vec<NavigationNode>) -> NavigationNode {
+        NavigationNode {
+            id: id.to_string(),
+            title: title.to_string(),
+            url: format!("/doc/{}", id),
+            icon: None,
+            children,
+        }
+    }
+
+    fn collection(name: &str, documents: Documents) -> CollectionNode {
+        CollectionNode {
+            collection: Collection {
+                id: name.to_lowercase(),
+                name: name.to_string(),
+                description: None,
+                color: None,
+                icon: None,
+                created_at: String::new(),
+                updated_at: String::new(),
+                deleted_at: None,
+                archived_at: None,
+                permission: None,
+            },
+            documents,
+        }
+    }
+
+    fn tree(collections: Vec<CollectionNode>) -> SidebarTree {
+        let mut tree = SidebarTree::new(BTreeSet::new());
+        tree.collections = collections;
+        tree
+    }
+
+    fn engineering() -> CollectionNode {
+        collection(
+            "Engineering",
+            Documents::Loaded(vec![
+                node("deploy", "Deploy", vec![node("checklist", "Checklist", vec![])]),
+                node("runbook", "Deployment runbook", vec![]),
+                node("blank", "", vec![]),
+            ]),
+        )
+    }
+
+    fn finder(tree: &SidebarTree) -> Finder {
+        let client = OutlineClient::new("http://localhost".to_string()).unwrap();
+        Finder::new(Arc::new(client), tree)
+    }
+
+    fn typed(finder: &mut Finder, query: &str) {
+        finder.clear_query();
+        query.chars().for_each(|c| finder.push_char(c));
+    }
+
+    fn ids(finder: &Finder) -> Vec<&str> {
+        finder.matches.iter().map(|m| m.candidate.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_breadcrumb_paths() {
+        let finder = finder(&tree(vec![engineering()]));
+        let paths: Vec<(&str, usize)> = finder
+            .candidates
+            .iter()
+            .map(|c| (c.path.as_str(), c.title_start))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("Engineering › Deploy", 14),
+                ("Engineering › Deploy › Checklist", 23),
+                ("Engineering › Deployment runbook", 14),
+                ("Engineering › Untitled", 14),
+            ]
+        );
+
+        // title_start counts characters, so the title slices out of the path
+        for candidate in &finder.candidates {
+            let title: String = candidate.path.chars().skip(candidate.title_start).collect();
+            assert_eq!(title, candidate.title);
+        }
+    }
+
+    #[test]
+    fn test_empty_query_lists_in_tree_order() {
+        let finder = finder(&tree(vec![engineering()]));
+        assert_eq!(ids(&finder), vec!["deploy", "checklist", "runbook", "blank"]);
+        assert!(finder.matches.iter().all(|m| m.score == 0 && m.indices.is_empty()));
+        assert_eq!(finder.search, SearchStatus::Idle);
+    }
+
+    #[test]
+    fn test_title_matches_rank_above_breadcrumb_matches() {
+        let mut finder = finder(&tree(vec![engineering()]));
+        typed(&mut finder, "deploy");
+
+        // Equal title scores fall back to the shorter path; the child only
+        // matches through its parent's name in the breadcrumb
+        assert_eq!(ids(&finder), vec!["deploy", "runbook", "checklist"]);
+        assert_eq!(finder.matches[0].score, finder.matches[1].score);
+        assert!(finder.matches[1].score > finder.matches[2].score);
+    }
+
+    #[test]
+    fn test_shorter_path_wins_ties() {
+        let mut finder = finder(&tree(vec![collection(
+            "Eng",
+            Documents::Loaded(vec![
+                node("team", "Team", vec![node("team-notes", "Notes", vec![])]),
+                node("notes", "Notes", vec![]),
+            ]),
+        )]));
+        typed(&mut finder, "notes");
+        assert_eq!(ids(&finder), vec!["notes", "team-notes"]);
+        assert_eq!(finder.matches[0].score, finder.matches[1].score);
+    }
+
+    #[test]
+    fn test_match_indices_point_into_path() {
+        let mut finder = finder(&tree(vec![engineering()]));
+        typed(&mut finder, "checklist");
+
+        assert_eq!(ids(&finder), vec!["checklist"]);
+        let m = &finder.matches[0];
+        let start = m.candidate.title_start as u32;
+        assert_eq!(m.indices, (start..start + 9).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_smart_case() {
+        let mut finder = finder(&tree(vec![engineering()]));
+        typed(&mut finder, "runbook");
+        assert_eq!(ids(&finder), vec!["runbook"]);
+
+        // An uppercase letter makes the query case-sensitive
+        typed(&mut finder, "Runbook");
+        assert!(finder.matches.is_empty());
+    }
+
+    #[test]
+    fn test_set_candidates_refreshes_matches() {
+        let mut pending = tree(vec![engineering(), collection("Product", Documents::NotLoaded)]);
+        let mut finder = finder(&pending);
+        typed(&mut finder, "roadmap");
+        assert_eq!(finder.candidate_count(), 4);
+        assert!(finder.matches.is_empty());
+
+        pending.collections[1].documents = Documents::Loaded(vec![node("roadmap", "Roadmap", vec![])]);
+        finder.set_candidates(&pending);
+        assert_eq!(finder.candidate_count(), 5);
+        assert_eq!(finder.query, "roadmap");
+        assert_eq!(ids(&finder), vec!["roadmap"]);
+        assert_eq!(finder.matches[0].candidate.path, "Product › Roadmap");
+
+        // A selection past the end is pulled back when candidates go away
+        typed(&mut finder, "");
+        finder.selected = 4;
+        finder.set_candidates(&tree(vec![engineering()]));
+        assert_eq!(finder.selected, 3);
+    }
+
+    #[test]
+    fn test_selection_wraps_across_matches_and_hits() {
+        let mut finder = finder(&tree(vec![engineering()]));
+        typed(&mut finder, "deploy");
+        finder.hits = vec![
+            SearchHit {
+                id: "deploy".to_string(),
+                title: "Deploy".to_string(),
+                icon: String::new(),
+                context: String::new(),
+            },
+            SearchHit {
+                id: "elsewhere".to_string(),
+                title: "Mentions deploys".to_string(),
+                icon: String::new(),
+                context: String::new(),
+            },
+        ];
+
+        // Hits already listed as local matches aren't shown twice
+        let visible: Vec<&str> = finder.visible_hits().map(|hit| hit.id.as_str()).collect();
+        assert_eq!(visible, vec!["elsewhere"]);
+
+        finder.select_previous();
+        assert_eq!(finder.selected, 3);
+        assert_eq!(finder.selected_id(), Some("elsewhere"));
+        assert_eq!(finder.selected_title(), Some("Mentions deploys"));
+
+        finder.select_next();
+        assert_eq!(finder.selected_id(), Some("deploy"));
+        assert_eq!(finder.selected_title(), Some("Deploy"));
+    }
+}
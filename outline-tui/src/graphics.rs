@@ -0,0 +1,375 @@
+//! Drawing images in the terminal with the kitty, iTerm2 and sixel graphics protocols
+//!
+//! ratatui only knows about text, so images are written straight to the terminal after
+//! each frame, into cells the viewer leaves blank for them. Terminals without a graphics
+//! protocol get the image as coloured half blocks instead, drawn by the viewer as text.
+
+use crate::attachments::{Attachment, AttachmentCache};
+use crate::images::{Format, Image};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use crossterm::{cursor::MoveTo, queue};
+use ratatui::layout::Rect;
+use ratatui::text::Line;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+/// Cell size assumed when the terminal doesn't report its size in pixels
+const DEFAULT_CELL_SIZE: (u16, u16) = (8, 16);
+
+/// Largest piece of base64 in one kitty escape sequence
+const KITTY_CHUNK: usize = 4096;
+
+/// How images are drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GraphicsProtocol {
+    /// Pick from the terminal's environment variables
+    #[default]
+    Auto,
+    Kitty,
+    Iterm,
+    Sixel,
+    /// Coloured `▀` characters, for any terminal with true colour
+    Halfblocks,
+    /// Don't show images, only their alt text
+    None,
+}
+
+impl GraphicsProtocol {
+    /// The protocol to use, guessing it for `Auto`
+    ///
+    /// Inside tmux escape sequences would need wrapping, so half blocks are used there.
+    fn resolve(self) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        let (term, program) = (var("TERM"), var("TERM_PROGRAM"));
+
+        if !var("TMUX").is_empty() || term.starts_with("screen") {
+            Self::Halfblocks
+        } else if !var("KITTY_WINDOW_ID").is_empty()
+            || matches!(term.as_str(), "xterm-kitty" | "xterm-ghostty")
+            || matches!(program.as_str(), "WezTerm" | "ghostty")
+        {
+            Self::Kitty
+        } else if program == "iTerm.app" || var("LC_TERMINAL") == "iTerm2" {
+            Self::Iterm
+        } else if term.contains("sixel") || term.starts_with("foot") || term.starts_with("mlterm") {
+            Self::Sixel
+        } else {
+            Self::Halfblocks
+        }
+    }
+}
+
+/// The `[images]` config section
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImagesConfig {
+    #[serde(default)]
+    pub protocol: GraphicsProtocol,
+}
+
+/// Size of a terminal cell in pixels
+pub fn cell_size() -> (u16, u16) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => {
+            ((size.width / size.columns).max(1), (size.height / size.rows).max(1))
+        }
+        _ => DEFAULT_CELL_SIZE,
+    }
+}
+
+/// An image to draw over cells of the screen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    pub attachment: String,
+    pub area: Rect,
+}
+
+/// Images on the screen, kept in step with the frames ratatui draws
+///
+/// The viewer asks for images with `place` while drawing; `flush` then writes the
+/// escape sequences for them once the frame is on the screen.
+pub struct ImageLayer {
+    protocol: GraphicsProtocol,
+    /// Images placed in the frame being drawn
+    wanted: Vec<Placement>,
+    /// Images on the screen now
+    shown: Vec<Placement>,
+    /// IDs of images sent to kitty already, by attachment
+    kitty_ids: HashMap<String, u32>,
+    /// Encoded sixels and half-block lines, by attachment and size in cells
+    sixels: HashMap<(String, u16, u16), String>,
+    half_blocks: HashMap<(String, u16, u16), Vec<Line<'static>>>,
+}
+
+impl ImageLayer {
+    pub fn new(config: &ImagesConfig) -> Self {
+        Self {
+            protocol: config.protocol.resolve(),
+            wanted: Vec::new(),
+            shown: Vec::new(),
+            kitty_ids: HashMap::new(),
+            sixels: HashMap::new(),
+            half_blocks: HashMap::new(),
+        }
+    }
+
+    /// Whether images are shown at all
+    pub fn enabled(&self) -> bool {
+        self.protocol != GraphicsProtocol::None
+    }
+
+    /// Forget the images of the last frame (call this before drawing one)
+    pub fn clear(&mut self) {
+        self.wanted.clear();
+    }
+
+    /// Whether the attachment can be drawn with the graphics protocol
+    ///
+    /// kitty and sixel output are made from decoded PNGs; iTerm2 decodes any format itself.
+    pub fn can_place(&self, attachment: &Attachment) -> bool {
+        match self.protocol {
+            GraphicsProtocol::Kitty => Format::sniff(&attachment.data) == Some(Format::Png),
+            GraphicsProtocol::Sixel => attachment.image.is_some(),
+            GraphicsProtocol::Iterm => attachment.size.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Whether the attachment can be drawn at all, with the protocol or as half blocks
+    pub fn can_draw(&self, attachment: &Attachment) -> bool {
+        self.enabled() && (self.can_place(attachment) || attachment.image.is_some())
+    }
+
+    /// Draw an attachment over `area` once the frame is drawn
+    pub fn place(&mut self, attachment: &str, area: Rect) {
+        self.wanted.push(Placement { attachment: attachment.to_string(), area });
+    }
+
+    /// An image drawn as text, `cols` by `rows` cells
+    pub fn half_blocks(&mut self, id: &str, image: &Image, cols: u16, rows: u16) -> &[Line<'static>] {
+        self.half_blocks
+            .entry((id.to_string(), cols, rows))
+            .or_insert_with(|| image.half_blocks(cols, rows))
+    }
+
+    /// Whether the images on the screen differ from the ones placed in the last frame
+    pub fn changed(&self) -> bool {
+        self.wanted != self.shown
+    }
+
+    /// Whether the screen must be cleared before the images change
+    ///
+    /// iTerm2 and sixel images are painted into the cells, which ratatui thinks are blank
+    /// and so never redraws. kitty keeps images apart from the text and can remove them.
+    pub fn needs_clear(&self) -> bool {
+        self.protocol != GraphicsProtocol::Kitty && !self.shown.is_empty()
+    }
+
+    /// Write the images placed in the last frame to the terminal
+    pub fn flush(&mut self, out: &mut impl Write, attachments: &AttachmentCache) -> io::Result<()> {
+        if self.protocol == GraphicsProtocol::Kitty {
+            // Remove all placements, keeping the image data for the next ones
+            write!(out, "\x1b_Ga=d,d=a,q=2\x1b\\")?;
+        }
+
+        let wanted = self.wanted.clone();
+        for placement in &wanted {
+            let Some(attachment) = attachments.get(&placement.attachment) else {
+                continue;
+            };
+            let Rect { x, y, width, height } = placement.area;
+            queue!(out, MoveTo(x, y))?;
+            match self.protocol {
+                GraphicsProtocol::Kitty => {
+                    let next = self.kitty_ids.len() as u32 + 1;
+                    let id = match self.kitty_ids.get(&placement.attachment) {
+                        Some(&id) => id,
+                        None => {
+                            write_kitty_image(out, next, &attachment.data)?;
+                            self.kitty_ids.insert(placement.attachment.clone(), next);
+                            next
+                        }
+                    };
+                    // C=1 leaves the cursor where it is
+                    write!(out, "\x1b_Ga=p,i={},c={},r={},C=1,q=2\x1b\\", id, width, height)?;
+                }
+                GraphicsProtocol::Iterm => {
+                    write!(
+                        out,
+                        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+                        attachment.data.len(),
+                        width,
+                        height,
+                        STANDARD.encode(&attachment.data)
+                    )?;
+                }
+                GraphicsProtocol::Sixel => {
+                    let Some(image) = &attachment.image else {
+                        continue;
+                    };
+                    let key = (placement.attachment.clone(), width, height);
+                    let sixel = self.sixels.entry(key).or_insert_with(|| {
+                        let (cell_width, cell_height) = cell_size();
+                        let scaled = image.resize(width as u32 * cell_width as u32, height as u32 * cell_height as u32);
+                        sixel(&scaled)
+                    });
+                    out.write_all(sixel.as_bytes())?;
+                }
+                _ => {}
+            }
+        }
+        out.flush()?;
+
+        self.shown = wanted;
+        Ok(())
+    }
+}
+
+/// Send PNG data to kitty under an image ID, in chunks
+fn write_kitty_image(out: &mut impl Write, id: u32, png: &[u8]) -> io::Result<()> {
+    let encoded = STANDARD.encode(png);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(KITTY_CHUNK).collect();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        if i == 0 {
+            write!(out, "\x1b_Ga=t,f=100,i={},q=2,m={};", id, more)?;
+        } else {
+            write!(out, "\x1b_Gm={};", more)?;
+        }
+        out.write_all(chunk)?;
+        write!(out, "\x1b\\")?;
+    }
+    Ok(())
+}
+
+/// Encode an image as sixels, in a palette of 6 levels of each of red, green and blue
+///
+/// Transparent pixels are left unpainted.
+fn sixel(image: &Image) -> String {
+    let level = |value: u8| (value as usize * 5 + 127) / 255;
+    let color = |x: u32, y: u32| {
+        image.is_opaque(x, y).then(|| {
+            let [r, g, b] = image.rgb(x, y);
+            level(r) * 36 + level(g) * 6 + level(b)
+        })
+    };
+
+    let mut out = format!("\x1bP0;1;0q\"1;1;{};{}", image.width, image.height);
+    for index in 0..216 {
+        let percent = |step: usize| step * 100 / 5;
+        let _ = write!(out, "#{};2;{};{};{}", index, percent(index / 36), percent(index / 6 % 6), percent(index % 6));
+    }
+
+    for band in (0..image.height).step_by(6) {
+        let rows = (image.height - band).min(6);
+        // Pixels of each colour in this band, as sixel bits by column
+        let mut colors: Vec<(usize, Vec<u8>)> = Vec::new();
+        for x in 0..image.width {
+            for dy in 0..rows {
+                let Some(index) = color(x, band + dy) else {
+                    continue;
+                };
+                let position = match colors.iter().position(|(c, _)| *c == index) {
+                    Some(position) => position,
+                    None => {
+                        colors.push((index, vec![0; image.width as usize]));
+                        colors.len() - 1
+                    }
+                };
+                colors[position].1[x as usize] |= 1 << dy;
+            }
+        }
+
+        for (i, (index, bits)) in colors.iter().enumerate() {
+            if i > 0 {
+                out.push('$');
+            }
+            let _ = write!(out, "#{}", index);
+            let mut run = 0;
+            let mut previous = None;
+            for &value in bits.iter().chain([0xff].iter()) {
+                if Some(value) == previous {
+                    run += 1;
+                    continue;
+                }
+                if let Some(bits) = previous {
+                    push_run(&mut out, bits, run);
+                }
+                previous = Some(value);
+                run = 1;
+            }
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Append a run of one sixel, compressed when it's long
+fn push_run(out: &mut String, bits: u8, run: usize) {
+    let sixel = (63 + bits) as char;
+    if run > 3 {
+        let _ = write!(out, "!{}{}", run, sixel);
+    } else {
+        out.extend(std::iter::repeat_n(sixel, run));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32, pixels: &[[u8; 4]]) -> Image {
+        Image { width, height, pixels: pixels.concat() }
+    }
+
+    /// The sixel data after the header and colour registers
+    fn bands(out: &str) -> &str {
+        let registers = out.rfind("#215;2;100;100;100").expect("colour registers");
+        &out[registers + "#215;2;100;100;100".len()..]
+    }
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const CLEAR: [u8; 4] = [255, 255, 255, 0];
+
+    #[test]
+    fn test_sixel_header_and_registers() {
+        let out = sixel(&image(1, 1, &[RED]));
+        assert!(out.starts_with("\x1bP0;1;0q\"1;1;1;1#0;2;0;0;0#1;2;0;0;20"), "{:?}", out);
+        assert!(out.contains("#180;2;100;0;0"));
+        assert_eq!(out.matches(";2;").count(), 216);
+        assert!(out.ends_with("-\x1b\\"));
+        assert_eq!(bands(&out), "#180@-\x1b\\");
+    }
+
+    #[test]
+    fn test_sixel_bands_and_runs() {
+        // Six rows share a band; the seventh starts another
+        assert_eq!(bands(&sixel(&image(1, 7, &[RED; 7]))), "#180~-#180@-\x1b\\");
+
+        // Long runs are compressed, short ones written out
+        assert_eq!(bands(&sixel(&image(5, 1, &[RED; 5]))), "#180!5@-\x1b\\");
+        assert_eq!(bands(&sixel(&image(3, 1, &[RED; 3]))), "#180@@@-\x1b\\");
+
+        // Each colour in a band gets its own pass, separated by `$`
+        assert_eq!(bands(&sixel(&image(2, 1, &[RED, BLUE]))), "#180@?$#5?@-\x1b\\");
+    }
+
+    #[test]
+    fn test_sixel_colours() {
+        // Transparent pixels are left out
+        assert_eq!(bands(&sixel(&image(2, 1, &[CLEAR, CLEAR]))), "-\x1b\\");
+        assert_eq!(bands(&sixel(&image(2, 1, &[CLEAR, RED]))), "#180?@-\x1b\\");
+
+        // Colours snap to the nearest of the 6×6×6 registers
+        assert_eq!(bands(&sixel(&image(1, 1, &[[128, 128, 128, 255]]))), "#129@-\x1b\\");
+        assert_eq!(bands(&sixel(&image(1, 1, &[[20, 240, 100, 200]]))), "#32@-\x1b\\");
+    }
+}
@@ -0,0 +1,548 @@
+//! Attached images: recognising their format, reading their size and decoding PNGs
+//!
+//! Only PNG is decoded to pixels (for sixel and half-block output, and kitty, which takes
+//! PNG data as is). Other formats can still be shown by terminals that decode images
+//! themselves (iTerm2's protocol), so for those only the size is read from the header.
+
+use anyhow::{bail, ensure, Context, Result};
+use flate2::read::ZlibDecoder;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use std::io::Read;
+
+/// Largest image decoded, in pixels
+const MAX_PIXELS: u64 = 40_000_000;
+
+/// Alpha below which a pixel counts as transparent
+const OPAQUE: u8 = 128;
+
+/// Image formats, recognised by their first bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Png,
+    Jpeg,
+    Gif,
+}
+
+impl Format {
+    pub fn sniff(data: &[u8]) -> Option<Self> {
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+            Some(Self::Jpeg)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some(Self::Gif)
+        } else {
+            None
+        }
+    }
+}
+
+/// Width and height of an image in pixels, read from its header
+pub fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |at: usize| Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?) as u32);
+    let be32 = |at: usize| Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?));
+    let le16 = |at: usize| Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?) as u32);
+
+    let size = match Format::sniff(data)? {
+        // IHDR is always the first chunk
+        Format::Png => (be32(16)?, be32(20)?),
+        Format::Gif => (le16(6)?, le16(8)?),
+        Format::Jpeg => {
+            // Walk the segments up to the start-of-frame marker
+            let mut at = 2;
+            loop {
+                while data.get(at) == Some(&0xff) && data.get(at + 1) == Some(&0xff) {
+                    at += 1;
+                }
+                let marker = *data.get(at + 1)?;
+                let length = be16(at + 2)? as usize;
+                let is_frame = matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+                if is_frame {
+                    break (be16(at + 7)?, be16(at + 5)?);
+                }
+                at += 2 + length;
+            }
+        }
+    };
+    Some(size).filter(|&(width, height)| width > 0 && height > 0)
+}
+
+/// Decoded RGBA pixels
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    /// Four bytes per pixel, row by row
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Decode a PNG (not interlaced)
+    pub fn decode_png(data: &[u8]) -> Result<Self> {
+        ensure!(Format::sniff(data) == Some(Format::Png), "Not a PNG image");
+
+        let mut header = None;
+        let mut palette: &[u8] = &[];
+        let mut transparency: &[u8] = &[];
+        let mut compressed = Vec::new();
+        let mut at = 8;
+        while at + 8 <= data.len() {
+            let length = u32::from_be_bytes(data[at..at + 4].try_into()?) as usize;
+            let kind = &data[at + 4..at + 8];
+            let body = data.get(at + 8..at + 8 + length).context("Truncated PNG")?;
+            at += 12 + length;
+            match kind {
+                b"IHDR" => header = Some(body),
+                b"PLTE" => palette = body,
+                b"tRNS" => transparency = body,
+                b"IDAT" => compressed.extend_from_slice(body),
+                b"IEND" => break,
+                _ => {}
+            }
+        }
+
+        let header = header.filter(|h| h.len() >= 13).context("PNG without a header")?;
+        let width = u32::from_be_bytes(header[0..4].try_into()?);
+        let height = u32::from_be_bytes(header[4..8].try_into()?);
+        let (depth, color_type, interlace) = (header[8] as usize, header[9], header[12]);
+        ensure!(width > 0 && height > 0, "Empty PNG");
+        ensure!((width as u64) * (height as u64) <= MAX_PIXELS, "PNG is too large to show");
+        ensure!(interlace == 0, "Interlaced PNGs can't be shown");
+        let channels = match color_type {
+            0 | 3 => 1,
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => bail!("Unknown PNG colour type {}", color_type),
+        };
+        ensure!(matches!(depth, 1 | 2 | 4 | 8 | 16), "Unknown PNG bit depth {}", depth);
+
+        let (width, height) = (width as usize, height as usize);
+        let stride = (width * channels * depth).div_ceil(8);
+        // Filters work on whole pixels, or bytes when pixels are smaller
+        let step = (channels * depth / 8).max(1);
+
+        let raw = inflate(&compressed, (stride + 1) * height)?;
+        ensure!(raw.len() >= (stride + 1) * height, "Truncated PNG data");
+
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        let mut previous = vec![0u8; stride];
+        for row in raw.chunks_exact(stride + 1).take(height) {
+            let mut line = row[1..].to_vec();
+            unfilter(row[0], &mut line, &previous, step)?;
+
+            // One sample, scaled to 8 bits unless it's a palette index
+            let sample = |index: usize| -> u8 {
+                match depth {
+                    8 => line[index],
+                    16 => line[index * 2],
+                    _ => {
+                        let bit = index * depth;
+                        let value = (line[bit / 8] >> (8 - depth - bit % 8)) & ((1 << depth) - 1) as u8;
+                        if color_type == 3 {
+                            value
+                        } else {
+                            value * (255 / ((1 << depth) - 1)) as u8
+                        }
+                    }
+                }
+            };
+            for x in 0..width {
+                let at = x * channels;
+                let rgba = match color_type {
+                    0 => {
+                        let gray = sample(at);
+                        [gray, gray, gray, 255]
+                    }
+                    2 => [sample(at), sample(at + 1), sample(at + 2), 255],
+                    3 => {
+                        let index = sample(at) as usize;
+                        let color = palette.get(index * 3..index * 3 + 3).unwrap_or(&[0, 0, 0]);
+                        let alpha = transparency.get(index).copied().unwrap_or(255);
+                        [color[0], color[1], color[2], alpha]
+                    }
+                    4 => {
+                        let gray = sample(at);
+                        [gray, gray, gray, sample(at + 1)]
+                    }
+                    _ => [sample(at), sample(at + 1), sample(at + 2), sample(at + 3)],
+                };
+                pixels.extend_from_slice(&rgba);
+            }
+            previous = line;
+        }
+
+        Ok(Self { width: width as u32, height: height as u32, pixels })
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let at = ((y * self.width + x) * 4) as usize;
+        self.pixels[at..at + 4].try_into().unwrap_or([0; 4])
+    }
+
+    /// Scale to a new size, averaging the pixels each new one covers
+    pub fn resize(&self, width: u32, height: u32) -> Self {
+        let (width, height) = (width.max(1), height.max(1));
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        // The source pixel a target edge falls on, in u64 since the products can be large
+        let edge = |at: u32, source: u32, target: u32| (at as u64 * source as u64 / target as u64) as u32;
+        for y in 0..height {
+            let top = edge(y, self.height, height);
+            let bottom = edge(y + 1, self.height, height).max(top + 1).min(self.height);
+            for x in 0..width {
+                let left = edge(x, self.width, width);
+                let right = edge(x + 1, self.width, width).max(left + 1).min(self.width);
+
+                let mut sum = [0u32; 4];
+                for sy in top..bottom {
+                    for sx in left..right {
+                        let pixel = self.pixel(sx, sy);
+                        // Weight colours by alpha so transparent edges don't darken
+                        let alpha = pixel[3] as u32;
+                        for c in 0..3 {
+                            sum[c] += pixel[c] as u32 * alpha;
+                        }
+                        sum[3] += alpha;
+                    }
+                }
+                let count = (bottom - top) * (right - left);
+                let rgba = match sum[3] {
+                    0 => [0, 0, 0, 0],
+                    alpha => [
+                        (sum[0] / alpha) as u8,
+                        (sum[1] / alpha) as u8,
+                        (sum[2] / alpha) as u8,
+                        (alpha / count) as u8,
+                    ],
+                };
+                pixels.extend_from_slice(&rgba);
+            }
+        }
+        Self { width, height, pixels }
+    }
+
+    /// Draw with `▀`/`▄` characters, two pixels to a cell, filling `cols` by `rows` cells
+    pub fn half_blocks(&self, cols: u16, rows: u16) -> Vec<Line<'static>> {
+        let scaled = self.resize(cols as u32, rows as u32 * 2);
+        let color = |pixel: [u8; 4]| (pixel[3] >= OPAQUE).then(|| Color::Rgb(pixel[0], pixel[1], pixel[2]));
+
+        (0..rows as u32)
+            .map(|row| {
+                let spans: Vec<Span<'static>> = (0..cols as u32)
+                    .map(|col| {
+                        let top = color(scaled.pixel(col, row * 2));
+                        let bottom = color(scaled.pixel(col, row * 2 + 1));
+                        match (top, bottom) {
+                            (Some(top), Some(bottom)) => Span::styled("▀", Style::default().fg(top).bg(bottom)),
+                            (Some(top), None) => Span::styled("▀", Style::default().fg(top)),
+                            (None, Some(bottom)) => Span::styled("▄", Style::default().fg(bottom)),
+                            (None, None) => Span::raw(" "),
+                        }
+                    })
+                    .collect();
+                Line::from(spans)
+            })
+            .collect()
+    }
+
+    /// Whether a pixel shows (isn't transparent)
+    pub fn is_opaque(&self, x: u32, y: u32) -> bool {
+        self.pixel(x, y)[3] >= OPAQUE
+    }
+
+    /// Red, green and blue of a pixel
+    pub fn rgb(&self, x: u32, y: u32) -> [u8; 3] {
+        let [r, g, b, _] = self.pixel(x, y);
+        [r, g, b]
+    }
+}
+
+/// Decompress image data, stopping at the size the header promises
+///
+/// Anything past `limit` is never inflated, so a small file can't expand into
+/// gigabytes of memory.
+fn inflate(compressed: &[u8], limit: usize) -> Result<Vec<u8>> {
+    let mut raw = Vec::new();
+    ZlibDecoder::new(compressed)
+        .take(limit as u64)
+        .read_to_end(&mut raw)
+        .context("Corrupt PNG data")?;
+    Ok(raw)
+}
+
+/// Undo a PNG row filter in place, given the unfiltered row above
+fn unfilter(filter: u8, line: &mut [u8], previous: &[u8], step: usize) -> Result<()> {
+    for x in 0..line.len() {
+        let left = if x >= step { line[x - step] } else { 0 };
+        let up = previous[x];
+        let up_left = if x >= step { previous[x - step] } else { 0 };
+        let predicted = match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            4 => paeth(left, up, up_left),
+            _ => bail!("Unknown PNG filter {}", filter),
+        };
+        line[x] = line[x].wrapping_add(predicted);
+    }
+    Ok(())
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let distance = |value: u8| (estimate - value as i16).abs();
+    if distance(left) <= distance(up) && distance(left) <= distance(up_left) {
+        left
+    } else if distance(up) <= distance(up_left) {
+        up
+    } else {
+        up_left
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], body: &[u8]) {
+        out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        out.extend_from_slice(kind);
+        out.extend_from_slice(body);
+        // The decoder doesn't check CRCs
+        out.extend_from_slice(&[0; 4]);
+    }
+
+    /// A PNG from its header fields, extra chunks before IDAT and the raw
+    /// (already filtered) scanlines
+    fn png(width: u32, height: u32, depth: u8, color_type: u8, extra: &[(&[u8; 4], &[u8])], scanlines: &[u8]) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        header.extend_from_slice(&[depth, color_type, 0, 0, 0]);
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut out, b"IHDR", &header);
+        for (kind, body) in extra {
+            chunk(&mut out, kind, body);
+        }
+        chunk(&mut out, b"IDAT", &zlib(scanlines));
+        chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    /// Filter rows of raw bytes the way an encoder would, with filter `filters[y]` on row `y`
+    fn filtered(rows: &[Vec<u8>], filters: &[u8], step: usize) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut previous = vec![0u8; rows[0].len()];
+        for (row, &filter) in rows.iter().zip(filters) {
+            out.push(filter);
+            for x in 0..row.len() {
+                let left = if x >= step { row[x - step] } else { 0 };
+                let up = previous[x];
+                let up_left = if x >= step { previous[x - step] } else { 0 };
+                let predicted = match filter {
+                    0 => 0,
+                    1 => left,
+                    2 => up,
+                    3 => ((left as u16 + up as u16) / 2) as u8,
+                    _ => paeth(left, up, up_left),
+                };
+                out.push(row[x].wrapping_sub(predicted));
+            }
+            previous = row.clone();
+        }
+        out
+    }
+
+    fn decode(data: &[u8]) -> Image {
+        Image::decode_png(data).unwrap()
+    }
+
+    fn error(data: &[u8]) -> String {
+        match Image::decode_png(data) {
+            Ok(_) => panic!("decoded"),
+            Err(e) => format!("{:#}", e),
+        }
+    }
+
+    fn solid(width: u32, height: u32, rgba: [u8; 4]) -> Image {
+        Image { width, height, pixels: rgba.repeat((width * height) as usize) }
+    }
+
+    #[test]
+    fn test_paeth() {
+        assert_eq!(paeth(10, 20, 15), 15);
+        assert_eq!(paeth(10, 20, 10), 20);
+        assert_eq!(paeth(20, 10, 10), 20);
+        assert_eq!(paeth(10, 10, 10), 10);
+        assert_eq!(paeth(0, 255, 128), 128);
+    }
+
+    #[test]
+    fn test_filters() {
+        // Sub and Up by hand: each byte adds the one to its left / above
+        let scanlines = [1, 10, 5, 5, 2, 1, 1, 1];
+        let image = decode(&png(3, 2, 8, 0, &[], &scanlines));
+        let grays: Vec<u8> = image.pixels.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(grays, vec![10, 15, 20, 11, 16, 21]);
+
+        // Every filter, on every row position, decodes to the same RGBA pixels
+        let rows: Vec<Vec<u8>> = (0..4u8)
+            .map(|y| (0..5 * 4).map(|i| (i as u8).wrapping_mul(37).wrapping_add(y.wrapping_mul(91))).collect())
+            .collect();
+        let expected: Vec<u8> = rows.concat();
+        for first in 0..5 {
+            let filters: Vec<u8> = (0..4).map(|y| (first + y) % 5).collect();
+            let image = decode(&png(5, 4, 8, 6, &[], &filtered(&rows, &filters, 4)));
+            assert_eq!((image.width, image.height), (5, 4));
+            assert_eq!(image.pixels, expected, "filters {:?}", filters);
+        }
+    }
+
+    #[test]
+    fn test_palette_with_transparency() {
+        let palette = [255, 0, 0, 0, 255, 0, 0, 0, 255];
+        // Only the first entry has an alpha; the rest are opaque
+        let transparency = [0];
+        // 2-bit indices 0, 1, 2, 1 in one byte
+        let image = decode(&png(4, 1, 2, 3, &[(b"PLTE", &palette), (b"tRNS", &transparency)], &[0, 0b00_01_10_01]));
+        assert_eq!(
+            image.pixels,
+            [[255, 0, 0, 0], [0, 255, 0, 255], [0, 0, 255, 255], [0, 255, 0, 255]].concat()
+        );
+        assert!(!image.is_opaque(0, 0));
+        assert!(image.is_opaque(1, 0));
+        assert_eq!(image.rgb(2, 0), [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_bit_depths() {
+        let grays = |image: Image| image.pixels.chunks(4).map(|p| p[0]).collect::<Vec<u8>>();
+
+        // 1 bit: ten pixels spill into a second byte
+        let image = decode(&png(10, 1, 1, 0, &[], &[0, 0b1010_0000, 0b1100_0000]));
+        assert_eq!(grays(image), vec![255, 0, 255, 0, 0, 0, 0, 0, 255, 255]);
+
+        let image = decode(&png(4, 1, 2, 0, &[], &[0, 0b00_01_10_11]));
+        assert_eq!(grays(image), vec![0, 85, 170, 255]);
+
+        let image = decode(&png(3, 1, 4, 0, &[], &[0, 0x0f, 0x80]));
+        assert_eq!(grays(image), vec![0, 255, 136]);
+
+        // 16 bits keep the high byte
+        let image = decode(&png(1, 1, 16, 2, &[], &[0, 0x12, 0x34, 0xab, 0xcd, 0xff, 0x00]));
+        assert_eq!(image.pixels, vec![0x12, 0xab, 0xff, 255]);
+
+        let image = decode(&png(1, 1, 16, 4, &[], &[0, 0x80, 0x01, 0x40, 0x02]));
+        assert_eq!(image.pixels, vec![0x80, 0x80, 0x80, 0x40]);
+
+        let image = decode(&png(2, 1, 8, 4, &[], &[0, 7, 200, 9, 100]));
+        assert_eq!(image.pixels, vec![7, 7, 7, 200, 9, 9, 9, 100]);
+    }
+
+    #[test]
+    fn test_rejects_bad_images() {
+        let good = png(2, 1, 8, 0, &[], &[0, 1, 2]);
+        assert!(Image::decode_png(&good).is_ok());
+
+        assert!(error(b"GIF89a....").contains("Not a PNG"));
+        assert!(error(&good[..good.len() - 20]).contains("Truncated PNG"));
+        assert!(error(&good[..8]).contains("without a header"));
+
+        // Fewer rows than the header promises
+        assert!(error(&png(2, 2, 8, 0, &[], &[0, 1, 2])).contains("Truncated PNG data"));
+        assert!(error(&png(2, 1, 8, 0, &[], &[5, 1, 2])).contains("Unknown PNG filter 5"));
+        assert!(error(&png(2, 1, 3, 0, &[], &[0, 1])).contains("bit depth 3"));
+        assert!(error(&png(2, 1, 8, 5, &[], &[0, 1, 2])).contains("colour type 5"));
+        assert!(error(&png(0, 1, 8, 0, &[], &[0])).contains("Empty PNG"));
+        assert!(error(&png(50_000, 50_000, 8, 0, &[], &[0])).contains("too large"));
+
+        let mut interlaced = good.clone();
+        interlaced[8 + 8 + 12] = 1;
+        assert!(error(&interlaced).contains("Interlaced"));
+
+        // Garbage where the compressed data should be
+        let mut corrupt = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut corrupt, b"IHDR", &good[16..29]);
+        chunk(&mut corrupt, b"IDAT", b"not zlib at all");
+        assert!(error(&corrupt).contains("Corrupt PNG data"));
+    }
+
+    #[test]
+    fn test_inflate_stops_at_the_limit() {
+        let bomb = zlib(&vec![0; 4 << 20]);
+        assert!(bomb.len() < 10_000);
+        assert_eq!(inflate(&bomb, 100).unwrap().len(), 100);
+        assert_eq!(inflate(&zlib(b"short"), 100).unwrap(), b"short");
+
+        // Extra data after the image is ignored rather than inflated
+        let mut scanlines = vec![0, 42];
+        scanlines.resize(4 << 20, 0);
+        let image = decode(&png(1, 1, 8, 0, &[], &scanlines));
+        assert_eq!(image.pixels, vec![42, 42, 42, 255]);
+    }
+
+    #[test]
+    fn test_resize() {
+        // Downscaling averages, weighting colours by alpha
+        let image = Image {
+            width: 2,
+            height: 1,
+            pixels: vec![200, 0, 0, 255, 0, 0, 0, 0],
+        };
+        let scaled = image.resize(1, 1);
+        assert_eq!(scaled.pixels, vec![200, 0, 0, 127]);
+
+        let image = Image {
+            width: 3,
+            height: 1,
+            pixels: vec![30, 30, 30, 255, 60, 60, 60, 255, 120, 120, 120, 255],
+        };
+        assert_eq!(image.resize(2, 1).pixels, vec![30, 30, 30, 255, 90, 90, 90, 255]);
+
+        // Upscaling repeats pixels; a zero size still gives one pixel
+        let scaled = solid(1, 1, [1, 2, 3, 255]).resize(3, 2);
+        assert_eq!((scaled.width, scaled.height), (3, 2));
+        assert_eq!(scaled.pixels, [1, 2, 3, 255].repeat(6));
+        let scaled = solid(4, 4, [9, 9, 9, 255]).resize(0, 0);
+        assert_eq!((scaled.width, scaled.height, scaled.pixels.len()), (1, 1, 4));
+
+        assert_eq!(solid(2, 2, [0, 0, 0, 0]).resize(1, 1).pixels, vec![0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_half_blocks() {
+        let image = Image {
+            width: 1,
+            height: 2,
+            pixels: vec![255, 0, 0, 255, 0, 0, 0, 0],
+        };
+        let lines = image.half_blocks(1, 1);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].spans[0].content, "▀");
+        assert_eq!(lines[0].spans[0].style.fg, Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(lines[0].spans[0].style.bg, None);
+
+        assert_eq!(solid(3, 3, [0, 0, 0, 0]).half_blocks(2, 1)[0].spans.len(), 2);
+    }
+
+    #[test]
+    fn test_dimensions() {
+        assert_eq!(dimensions(&png(640, 480, 8, 2, &[], &[0])), Some((640, 480)));
+        assert_eq!(dimensions(b"GIF89a\x20\x03\x58\x02"), Some((800, 600)));
+        let jpeg = [0xff, 0xd8, 0xff, 0xe0, 0x00, 0x02, 0xff, 0xc0, 0x00, 0x11, 0x08, 0x01, 0xe0, 0x02, 0x80];
+        assert_eq!(dimensions(&jpeg), Some((640, 480)));
+        assert_eq!(dimensions(b"GIF89a\x00\x00\x10\x00"), None);
+        assert_eq!(dimensions(b"plain text"), None);
+    }
+}
@@ -0,0 +1,710 @@
+use crate::actions::Action;
+use crossterm::event::{KeyCode, KeyModifiers};
+use serde::de::IntoDeserializer;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Where a binding applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyContext {
+    /// Both the sidebar and the document view
+    Global,
+    Sidebar,
+    /// The document in view mode
+    View,
+    /// The editor in vim normal mode (visual mode uses its movement keys)
+    Normal,
+    /// The comments pane (global bindings apply too)
+    Comments,
+    /// The links pane (global bindings apply too)
+    Links,
+    /// The table of contents (global bindings apply too)
+    Toc,
+    /// The search view's result list
+    Search,
+    /// The three-way merge view
+    Merge,
+}
+
+impl KeyContext {
+    /// Every context, in the order the help lists them
+    pub const ALL: [KeyContext; 9] = [
+        KeyContext::Global,
+        KeyContext::Sidebar,
+        KeyContext::View,
+        KeyContext::Normal,
+        KeyContext::Toc,
+        KeyContext::Comments,
+        KeyContext::Links,
+        KeyContext::Search,
+        KeyContext::Merge,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            KeyContext::Global => "Sidebar, Document and Panes",
+            KeyContext::Sidebar => "Sidebar",
+            KeyContext::View => "Document",
+            KeyContext::Normal => "Edit Mode (normal)",
+            KeyContext::Comments => "Comments Pane",
+            KeyContext::Links => "Links Pane",
+            KeyContext::Toc => "Contents Pane",
+            KeyContext::Search => "Search Results",
+            KeyContext::Merge => "Merge",
+        }
+    }
+}
+
+/// Built-in bindings, in the order the help lists them
+const DEFAULT_BINDINGS: &[(KeyContext, &str, Action)] = &[
+    (KeyContext::Global, "tab", Action::ToggleFocus),
+    (KeyContext::Global, "ctrl-p", Action::GoToDocument),
+    (KeyContext::Global, "/", Action::SearchDocuments),
+    (KeyContext::Global, "m", Action::ShowMenu),
+    (KeyContext::Global, ":", Action::EnterCommandMode),
+    (KeyContext::Global, "h", Action::ShowHelp),
+    (KeyContext::Global, "?", Action::ShowHelp),
+    (KeyContext::Global, "C", Action::ToggleComments),
+    (KeyContext::Global, "B", Action::ToggleLinks),
+    (KeyContext::Global, "T", Action::ToggleToc),
+    (KeyContext::Global, "H", Action::HistoryBack),
+    (KeyContext::Global, "L", Action::HistoryForward),
+    (KeyContext::Global, "q", Action::Quit),
+    (KeyContext::Sidebar, "k", Action::MoveUp),
+    (KeyContext::Sidebar, "up", Action::MoveUp),
+    (KeyContext::Sidebar, "j", Action::MoveDown),
+    (KeyContext::Sidebar, "down", Action::MoveDown),
+    (KeyContext::Sidebar, "pageup", Action::PageUp),
+    (KeyContext::Sidebar, "pagedown", Action::PageDown),
+    (KeyContext::Sidebar, "home", Action::GoToTop),
+    (KeyContext::Sidebar, "end", Action::GoToBottom),
+    (KeyContext::Sidebar, "enter", Action::Open),
+    (KeyContext::Sidebar, "space", Action::ToggleExpand),
+    (KeyContext::Sidebar, "right", Action::Expand),
+    (KeyContext::Sidebar, "left", Action::Collapse),
+    (KeyContext::Sidebar, "c", Action::CreateDocument),
+    (KeyContext::Sidebar, "r", Action::Refresh),
+    (KeyContext::View, "k", Action::MoveUp),
+    (KeyContext::View, "up", Action::MoveUp),
+    (KeyContext::View, "j", Action::MoveDown),
+    (KeyContext::View, "down", Action::MoveDown),
+    (KeyContext::View, "pageup", Action::PageUp),
+    (KeyContext::View, "pagedown", Action::PageDown),
+    (KeyContext::View, "home", Action::GoToTop),
+    (KeyContext::View, "g g", Action::GoToTop),
+    (KeyContext::View, "end", Action::GoToBottom),
+    (KeyContext::View, "] ]", Action::NextHeading),
+    (KeyContext::View, "[ [", Action::PreviousHeading),
+    (KeyContext::View, "] c", Action::NextComment),
+    (KeyContext::View, "[ c", Action::PreviousComment),
+    (KeyContext::View, "] l", Action::NextLink),
+    (KeyContext::View, "[ l", Action::PreviousLink),
+    (KeyContext::View, "] t", Action::NextTask),
+    (KeyContext::View, "[ t", Action::PreviousTask),
+    (KeyContext::View, "space", Action::ToggleTask),
+    (KeyContext::View, "enter", Action::Open),
+    (KeyContext::View, "backspace", Action::HistoryBack),
+    (KeyContext::View, "alt-left", Action::HistoryBack),
+    (KeyContext::View, "alt-right", Action::HistoryForward),
+    (KeyContext::View, "g t", Action::NextTab),
+    (KeyContext::View, "g T", Action::PreviousTab),
+    (KeyContext::View, "ctrl-w", Action::SwitchSplit),
+    (KeyContext::View, "e", Action::EditDocument),
+    (KeyContext::View, "u", Action::UpdateDocument),
+    (KeyContext::View, "d", Action::DeleteDocument),
+    (KeyContext::View, "a", Action::ArchiveDocument),
+    (KeyContext::View, "s", Action::StarDocument),
+    (KeyContext::View, "x", Action::ExportDocument),
+    (KeyContext::Normal, "i", Action::InsertMode),
+    (KeyContext::Normal, "I", Action::InsertAtLineStart),
+    (KeyContext::Normal, "a", Action::Append),
+    (KeyContext::Normal, "A", Action::AppendAtLineEnd),
+    (KeyContext::Normal, "o", Action::OpenLineBelow),
+    (KeyContext::Normal, "O", Action::OpenLineAbove),
+    (KeyContext::Normal, "v", Action::VisualMode),
+    (KeyContext::Normal, "h", Action::MoveLeft),
+    (KeyContext::Normal, "left", Action::MoveLeft),
+    (KeyContext::Normal, "j", Action::MoveDown),
+    (KeyContext::Normal, "down", Action::MoveDown),
+    (KeyContext::Normal, "k", Action::MoveUp),
+    (KeyContext::Normal, "up", Action::MoveUp),
+    (KeyContext::Normal, "l", Action::MoveRight),
+    (KeyContext::Normal, "right", Action::MoveRight),
+    (KeyContext::Normal, "w", Action::WordForward),
+    (KeyContext::Normal, "b", Action::WordBack),
+    (KeyContext::Normal, "0", Action::LineStart),
+    (KeyContext::Normal, "home", Action::LineStart),
+    (KeyContext::Normal, "$", Action::LineEnd),
+    (KeyContext::Normal, "end", Action::LineEnd),
+    (KeyContext::Normal, "g", Action::GoToTop),
+    (KeyContext::Normal, "G", Action::GoToBottom),
+    (KeyContext::Normal, "x", Action::DeleteChar),
+    (KeyContext::Normal, "u", Action::Undo),
+    (KeyContext::Normal, "ctrl-r", Action::Redo),
+    (KeyContext::Normal, "enter", Action::Open),
+    (KeyContext::Normal, ":", Action::EnterCommandMode),
+    (KeyContext::Normal, "esc", Action::SaveAndExit),
+    (KeyContext::Toc, "k", Action::MoveUp),
+    (KeyContext::Toc, "up", Action::MoveUp),
+    (KeyContext::Toc, "j", Action::MoveDown),
+    (KeyContext::Toc, "down", Action::MoveDown),
+    (KeyContext::Toc, "enter", Action::Open),
+    (KeyContext::Toc, "space", Action::ToggleExpand),
+    (KeyContext::Toc, "right", Action::Expand),
+    (KeyContext::Toc, "left", Action::Collapse),
+    (KeyContext::Toc, "esc", Action::Cancel),
+    (KeyContext::Comments, "k", Action::MoveUp),
+    (KeyContext::Comments, "up", Action::MoveUp),
+    (KeyContext::Comments, "j", Action::MoveDown),
+    (KeyContext::Comments, "down", Action::MoveDown),
+    (KeyContext::Comments, "enter", Action::Open),
+    (KeyContext::Comments, "]", Action::NextComment),
+    (KeyContext::Comments, "[", Action::PreviousComment),
+    (KeyContext::Comments, "n", Action::CreateComment),
+    (KeyContext::Comments, "r", Action::ReplyToComment),
+    (KeyContext::Comments, "e", Action::UpdateComment),
+    (KeyContext::Comments, "d", Action::DeleteComment),
+    (KeyContext::Comments, "R", Action::ResolveComment),
+    (KeyContext::Comments, "s", Action::ToggleResolved),
+    (KeyContext::Comments, "L", Action::Refresh),
+    (KeyContext::Comments, "esc", Action::Cancel),
+    (KeyContext::Links, "k", Action::MoveUp),
+    (KeyContext::Links, "up", Action::MoveUp),
+    (KeyContext::Links, "j", Action::MoveDown),
+    (KeyContext::Links, "down", Action::MoveDown),
+    (KeyContext::Links, "enter", Action::Open),
+    (KeyContext::Links, "alt-left", Action::HistoryBack),
+    (KeyContext::Links, "alt-right", Action::HistoryForward),
+    (KeyContext::Links, "esc", Action::Cancel),
+    (KeyContext::Search, "k", Action::MoveUp),
+    (KeyContext::Search, "up", Action::MoveUp),
+    (KeyContext::Search, "j", Action::MoveDown),
+    (KeyContext::Search, "down", Action::MoveDown),
+    (KeyContext::Search, "pageup", Action::PageUp),
+    (KeyContext::Search, "pagedown", Action::PageDown),
+    (KeyContext::Search, "enter", Action::Open),
+    (KeyContext::Search, "/", Action::FocusQuery),
+    (KeyContext::Search, "i", Action::FocusQuery),
+    (KeyContext::Search, "tab", Action::FocusQuery),
+    (KeyContext::Search, "c", Action::FilterCollection),
+    (KeyContext::Search, "a", Action::FilterAuthor),
+    (KeyContext::Search, "d", Action::FilterDate),
+    (KeyContext::Search, "D", Action::ToggleDrafts),
+    (KeyContext::Search, "A", Action::ToggleArchived),
+    (KeyContext::Search, "x", Action::ClearFilters),
+    (KeyContext::Search, "q", Action::Cancel),
+    (KeyContext::Search, "esc", Action::Cancel),
+    (KeyContext::Merge, "k", Action::MoveUp),
+    (KeyContext::Merge, "up", Action::MoveUp),
+    (KeyContext::Merge, "j", Action::MoveDown),
+    (KeyContext::Merge, "down", Action::MoveDown),
+    (KeyContext::Merge, "pageup", Action::PageUp),
+    (KeyContext::Merge, "pagedown", Action::PageDown),
+    (KeyContext::Merge, "n", Action::NextConflict),
+    (KeyContext::Merge, "enter", Action::SaveMerge),
+    (KeyContext::Merge, "e", Action::EditDocument),
+    (KeyContext::Merge, "m", Action::KeepMine),
+    (KeyContext::Merge, "t", Action::TakeTheirs),
+    (KeyContext::Merge, "esc", Action::Cancel),
+];
+
+/// The `[keymap]` config section: key sequences mapped to action names
+///
+/// Entries directly under `[keymap]` apply to the sidebar, the document view and the
+/// side panes; `[keymap.sidebar]`, `[keymap.view]`, `[keymap.normal]` (vim normal mode),
+/// `[keymap.toc]`, `[keymap.comments]`, `[keymap.links]`, `[keymap.search]` (search
+/// results) and `[keymap.merge]` apply to one context. An action of `"none"` removes a
+/// built-in binding.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeymapConfig {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sidebar: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub view: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub normal: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub toc: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub comments: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub links: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub search: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub merge: BTreeMap<String, String>,
+    #[serde(flatten)]
+    pub global: BTreeMap<String, String>,
+}
+
+/// A key with its modifiers, e.g. `ctrl-p`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    /// A chord as the terminal reports it
+    ///
+    /// Shift is part of the key for characters (`G`, `?`) and back-tab, so it's dropped there.
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers = modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    fn is_plain_char(&self) -> bool {
+        matches!(self.code, KeyCode::Char(c) if c != ' ') && self.modifiers.is_empty()
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    /// Parse `j`, `G`, `ctrl-p`, `alt-left`, `shift-tab`, `pagedown`, `f1`, ...
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        let prefixes = [
+            ("ctrl-", KeyModifiers::CONTROL),
+            ("alt-", KeyModifiers::ALT),
+            ("shift-", KeyModifiers::SHIFT),
+        ];
+        while let Some((prefix, modifier)) = prefixes.iter().find(|(prefix, _)| {
+            rest.len() > prefix.len() && rest.get(..prefix.len()).is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+        }) {
+            modifiers |= *modifier;
+            rest = &rest[prefix.len()..];
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::Char(c.to_ascii_uppercase()),
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key '{}'", s)),
+                },
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// One or more chords pressed in turn, written space-separated (`g t`, `] c`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySequence(Vec<KeyChord>);
+
+impl FromStr for KeySequence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chords = s
+            .split_whitespace()
+            .map(KeyChord::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        if chords.is_empty() {
+            return Err("empty key sequence".to_string());
+        }
+        Ok(Self(chords))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    /// Sequences of plain characters are written together, like `gt`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.0.iter().all(KeyChord::is_plain_char) { "" } else { " " };
+        for (i, chord) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{}", separator)?;
+            }
+            write!(f, "{}", chord)?;
+        }
+        Ok(())
+    }
+}
+
+/// What the keys pressed so far lead to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyLookup {
+    Action(Action),
+    /// The start of a longer sequence; wait for the next key
+    Pending,
+    Unbound,
+}
+
+/// Effective key bindings: the built-in ones with the config's changes applied
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(KeyContext, KeySequence, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS
+            .iter()
+            .map(|(context, keys, action)| {
+                let keys = keys.parse().expect("built-in key bindings are valid");
+                (*context, keys, action.clone())
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Apply the `[keymap]` config section to the built-in bindings
+    ///
+    /// Entries that can't be parsed are skipped and described in the returned messages.
+    pub fn from_config(config: &KeymapConfig) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut problems = Vec::new();
+        let sections = [
+            (KeyContext::Global, &config.global),
+            (KeyContext::Sidebar, &config.sidebar),
+            (KeyContext::View, &config.view),
+            (KeyContext::Normal, &config.normal),
+            (KeyContext::Toc, &config.toc),
+            (KeyContext::Comments, &config.comments),
+            (KeyContext::Links, &config.links),
+            (KeyContext::Search, &config.search),
+            (KeyContext::Merge, &config.merge),
+        ];
+
+        for (context, entries) in sections {
+            for (keys, action) in entries {
+                if let Err(e) = keymap.bind(context, keys, action) {
+                    problems.push(format!("keymap \"{}\": {}", keys, e));
+                }
+            }
+        }
+        (keymap, problems)
+    }
+
+    /// Bind a key sequence to an action by name, replacing its binding in the context
+    fn bind(&mut self, context: KeyContext, keys: &str, action: &str) -> Result<(), String> {
+        let keys: KeySequence = keys.parse()?;
+        let existing = self.bindings.iter().position(|(c, k, _)| *c == context && *k == keys);
+
+        if action.eq_ignore_ascii_case("none") {
+            if let Some(index) = existing {
+                self.bindings.remove(index);
+            }
+            return Ok(());
+        }
+        let action = Action::deserialize(action.into_deserializer())
+            .map_err(|_: serde::de::value::Error| format!("unknown action '{}'", action))?;
+        match existing {
+            Some(index) => self.bindings[index].2 = action,
+            None => self.bindings.push((context, keys, action)),
+        }
+        Ok(())
+    }
+
+    /// Look up the keys pressed so far
+    ///
+    /// Bindings of the context win over global ones, which apply in the sidebar, the
+    /// document view and the side panes. A complete match wins over longer sequences
+    /// starting with it.
+    pub fn lookup(&self, context: KeyContext, keys: &[KeyChord]) -> KeyLookup {
+        let mut pending = false;
+        for context in Self::search_order(context) {
+            for (c, sequence, action) in &self.bindings {
+                if *c != context {
+                    continue;
+                }
+                if sequence.0 == keys {
+                    return KeyLookup::Action(action.clone());
+                }
+                pending |= sequence.0.starts_with(keys);
+            }
+        }
+        if pending {
+            KeyLookup::Pending
+        } else {
+            KeyLookup::Unbound
+        }
+    }
+
+    /// Bindings of one context for the help, with the keys of each action joined
+    pub fn help(&self, context: KeyContext) -> Vec<(String, &Action)> {
+        let mut entries: Vec<(String, &Action)> = Vec::new();
+        for (c, keys, action) in &self.bindings {
+            if *c != context {
+                continue;
+            }
+            match entries.iter_mut().find(|(_, a)| *a == action) {
+                Some((text, _)) => text.push_str(&format!(", {}", keys)),
+                None => entries.push((keys.to_string(), action)),
+            }
+        }
+        entries
+    }
+
+    /// The first key sequence bound to an action, for hints
+    pub fn keys_for(&self, context: KeyContext, action: &Action) -> Option<String> {
+        Self::search_order(context).into_iter().find_map(|context| {
+            self.bindings
+                .iter()
+                .find(|(c, _, a)| *c == context && a == action)
+                .map(|(_, keys, _)| keys.to_string())
+        })
+    }
+
+    /// Contexts whose bindings apply in a context, most specific first
+    fn search_order(context: KeyContext) -> Vec<KeyContext> {
+        match context {
+            KeyContext::Sidebar | KeyContext::View | KeyContext::Toc | KeyContext::Comments | KeyContext::Links => {
+                vec![context, KeyContext::Global]
+            }
+            _ => vec![context],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(s: &str) -> KeyChord {
+        s.parse().unwrap()
+    }
+
+    fn keys(s: &str) -> Vec<KeyChord> {
+        s.parse::<KeySequence>().unwrap().0
+    }
+
+    fn config(toml: &str) -> KeymapConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_parse_chords() {
+        let plain = |code| KeyChord::new(code, KeyModifiers::NONE);
+        assert_eq!(chord("j"), plain(KeyCode::Char('j')));
+        assert_eq!(chord("G"), plain(KeyCode::Char('G')));
+        assert_eq!(chord("shift-g"), plain(KeyCode::Char('G')));
+        assert_eq!(chord("space"), plain(KeyCode::Char(' ')));
+        assert_eq!(chord("PageDown"), plain(KeyCode::PageDown));
+        assert_eq!(chord("f12"), plain(KeyCode::F(12)));
+        assert_eq!(chord("shift-tab"), plain(KeyCode::BackTab));
+        assert_eq!(chord("ctrl-p"), KeyChord::new(KeyCode::Char('p'), KeyModifiers::CONTROL));
+        assert_eq!(chord("Ctrl-Alt-x"), KeyChord::new(KeyCode::Char('x'), KeyModifiers::CONTROL | KeyModifiers::ALT));
+        assert_eq!(chord("alt-left"), KeyChord::new(KeyCode::Left, KeyModifiers::ALT));
+        // `-` on its own is a key, not a dangling modifier
+        assert_eq!(chord("ctrl--"), KeyChord::new(KeyCode::Char('-'), KeyModifiers::CONTROL));
+
+        for bad in ["f13", "ctrl-", "hyper-x", "enterr"] {
+            assert!(bad.parse::<KeyChord>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_terminal_shift_is_part_of_characters() {
+        // Terminals report `G` with Shift held; it must match a binding written `G`
+        assert_eq!(KeyChord::new(KeyCode::Char('G'), KeyModifiers::SHIFT), chord("G"));
+        assert_eq!(KeyChord::new(KeyCode::BackTab, KeyModifiers::SHIFT), chord("backtab"));
+        assert_ne!(KeyChord::new(KeyCode::Up, KeyModifiers::SHIFT), chord("up"));
+    }
+
+    #[test]
+    fn test_parse_and_display_sequences() {
+        assert_eq!(keys("g  t"), vec![chord("g"), chord("t")]);
+        assert!("".parse::<KeySequence>().is_err());
+        assert!("g nope".parse::<KeySequence>().is_err());
+
+        let shown = |s: &str| s.parse::<KeySequence>().unwrap().to_string();
+        assert_eq!(shown("g t"), "gt");
+        assert_eq!(shown("] c"), "]c");
+        assert_eq!(shown("g space"), "g Space");
+        assert_eq!(shown("ctrl-w"), "Ctrl+w");
+        assert_eq!(shown("alt-left"), "Alt+←");
+        assert_eq!(shown("esc"), "Esc");
+    }
+
+    #[test]
+    fn test_default_bindings_have_no_conflicts() {
+        let keymap = Keymap::default();
+
+        for context in KeyContext::ALL {
+            let reachable: Vec<&KeySequence> = Keymap::search_order(context)
+                .into_iter()
+                .flat_map(|c| keymap.bindings.iter().filter(move |(bc, _, _)| *bc == c))
+                .map(|(_, keys, _)| keys)
+                .collect();
+
+            // A sequence that's the start of another makes the longer one unreachable
+            for a in &reachable {
+                for b in &reachable {
+                    assert!(
+                        a.0 == b.0 || !b.0.starts_with(&a.0),
+                        "{:?}: \"{}\" hides \"{}\"",
+                        context,
+                        a,
+                        b
+                    );
+                }
+            }
+        }
+
+        for (context, keys, action) in DEFAULT_BINDINGS {
+            let duplicates = DEFAULT_BINDINGS.iter().filter(|(c, k, _)| c == context && k == keys).count();
+            assert_eq!(duplicates, 1, "{:?} \"{}\" is bound twice", context, keys);
+            assert_eq!(
+                keymap.lookup(*context, &self::keys(keys)),
+                KeyLookup::Action(action.clone()),
+                "{:?} \"{}\"",
+                context,
+                keys
+            );
+        }
+    }
+
+    #[test]
+    fn test_context_bindings_win_over_global_ones() {
+        let keymap = Keymap::default();
+        let lookup = |context, s: &str| keymap.lookup(context, &keys(s));
+
+        assert_eq!(lookup(KeyContext::Comments, "L"), KeyLookup::Action(Action::Refresh));
+        assert_eq!(lookup(KeyContext::Links, "L"), KeyLookup::Action(Action::HistoryForward));
+        assert_eq!(lookup(KeyContext::Toc, "C"), KeyLookup::Action(Action::ToggleComments));
+        assert_eq!(lookup(KeyContext::Search, "q"), KeyLookup::Action(Action::Cancel));
+        assert_eq!(lookup(KeyContext::Sidebar, "q"), KeyLookup::Action(Action::Quit));
+
+        // The search results, merge view and editor don't take global keys
+        assert_eq!(lookup(KeyContext::Merge, "q"), KeyLookup::Unbound);
+        assert_eq!(lookup(KeyContext::Search, "C"), KeyLookup::Unbound);
+        assert_eq!(lookup(KeyContext::Normal, "q"), KeyLookup::Unbound);
+
+        assert_eq!(lookup(KeyContext::View, "g"), KeyLookup::Pending);
+        assert_eq!(lookup(KeyContext::View, "g t"), KeyLookup::Action(Action::NextTab));
+        assert_eq!(lookup(KeyContext::View, "g x"), KeyLookup::Unbound);
+        assert_eq!(lookup(KeyContext::Normal, "g"), KeyLookup::Action(Action::GoToTop));
+    }
+
+    #[test]
+    fn test_config_overrides() {
+        let (keymap, problems) = Keymap::from_config(&config(
+            r#"
+            "ctrl-f" = "GoToDocument"
+            "q" = "None"
+
+            [view]
+            "e" = "MoveUp"
+            "g e" = "EditDocument"
+
+            [comments]
+            "a" = "CreateComment"
+            "n" = "none"
+
+            [merge]
+            "y" = "KeepMine"
+            "#,
+        ));
+        assert!(problems.is_empty(), "{:?}", problems);
+        let lookup = |context, s: &str| keymap.lookup(context, &keys(s));
+
+        assert_eq!(lookup(KeyContext::Sidebar, "ctrl-f"), KeyLookup::Action(Action::GoToDocument));
+        assert_eq!(lookup(KeyContext::Comments, "ctrl-f"), KeyLookup::Action(Action::GoToDocument));
+        assert_eq!(lookup(KeyContext::Sidebar, "q"), KeyLookup::Unbound);
+        // Removing the global binding leaves the pane's own
+        assert_eq!(lookup(KeyContext::Search, "q"), KeyLookup::Action(Action::Cancel));
+
+        assert_eq!(lookup(KeyContext::View, "e"), KeyLookup::Action(Action::MoveUp));
+        assert_eq!(lookup(KeyContext::View, "g e"), KeyLookup::Action(Action::EditDocument));
+        assert_eq!(keymap.keys_for(KeyContext::View, &Action::EditDocument).as_deref(), Some("ge"));
+
+        assert_eq!(lookup(KeyContext::Comments, "a"), KeyLookup::Action(Action::CreateComment));
+        assert_eq!(lookup(KeyContext::Comments, "n"), KeyLookup::Unbound);
+        assert_eq!(keymap.keys_for(KeyContext::Comments, &Action::CreateComment).as_deref(), Some("a"));
+
+        // A new key adds to the built-in one rather than replacing it
+        assert_eq!(lookup(KeyContext::Merge, "y"), KeyLookup::Action(Action::KeepMine));
+        assert_eq!(lookup(KeyContext::Merge, "m"), KeyLookup::Action(Action::KeepMine));
+        let merge_help = keymap.help(KeyContext::Merge);
+        let keep_mine = merge_help.iter().find(|(_, a)| **a == Action::KeepMine).unwrap();
+        assert_eq!(keep_mine.0, "m, y");
+    }
+
+    #[test]
+    fn test_config_problems_are_reported() {
+        let (keymap, problems) = Keymap::from_config(&config(
+            r#"
+            "hyper-x" = "Quit"
+            "z" = "Fly"
+
+            [toc]
+            "o" = "Open"
+            "#,
+        ));
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert!(problems.iter().any(|p| p.contains("unknown key 'hyper-x'")), "{:?}", problems);
+        assert!(problems.iter().any(|p| p.contains("unknown action 'Fly'")), "{:?}", problems);
+
+        // The entries that could be read still apply
+        assert_eq!(keymap.lookup(KeyContext::Toc, &keys("o")), KeyLookup::Action(Action::Open));
+        assert_eq!(keymap.lookup(KeyContext::Sidebar, &keys("z")), KeyLookup::Unbound);
+    }
+
+    #[test]
+    fn test_help_lists_every_context() {
+        let keymap = Keymap::default();
+        for context in KeyContext::ALL {
+            assert!(!keymap.help(context).is_empty(), "{:?}", context);
+        }
+
+        let toc = keymap.help(KeyContext::Toc);
+        assert_eq!(toc[0], ("k, ↑".to_string(), &Action::MoveUp));
+        assert!(toc.iter().any(|(keys, action)| keys == "Esc" && **action == Action::Cancel));
+    }
+}
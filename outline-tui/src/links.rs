@@ -0,0 +1,484 @@
+use crate::render::RenderedLink;
+use outline_api::markdown::{self, Link, LinkTarget};
+use outline_api::{Document, ListDocumentsRequest, OutlineClient};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Backlinks fetched per `documents.list` request
+const PAGE_SIZE: u32 = 100;
+
+/// Documents remembered by the back history
+const MAX_HISTORY: usize = 100;
+
+/// A document that links to the current one
+#[derive(Debug, Clone)]
+pub struct Backlink {
+    pub id: String,
+    pub title: String,
+    pub icon: String,
+    pub collection_id: Option<String>,
+}
+
+impl From<Document> for Backlink {
+    fn from(document: Document) -> Self {
+        Self {
+            icon: document.icon().to_string(),
+            id: document.id,
+            title: document.title,
+            collection_id: document.collection_id,
+        }
+    }
+}
+
+/// Loading state of the backlinks
+#[derive(Debug, Clone, PartialEq)]
+pub enum BacklinksStatus {
+    Loading,
+    Loaded,
+    Failed(String),
+}
+
+/// A row of the links pane
+pub enum LinkRow<'a> {
+    Backlink(&'a Backlink),
+    Outgoing(&'a Link),
+}
+
+/// Links of the current document: documents linking to it, and links and mentions in its text
+pub struct LinksPanel {
+    pub backlinks: Vec<Backlink>,
+    pub status: BacklinksStatus,
+    /// Links in the document text, in order
+    pub outgoing: Vec<Link>,
+    /// Index of the selected row; backlinks come first, then outgoing links
+    pub selected: usize,
+    /// Outgoing link picked in the document with `]l`/`[l`
+    pub cursor: Option<usize>,
+    /// Hash of the text `outgoing` was parsed from
+    parsed: u64,
+    base_url: String,
+    rx: mpsc::UnboundedReceiver<Result<Vec<Backlink>, String>>,
+}
+
+impl LinksPanel {
+    /// Parse a document's links and start loading its backlinks in the background
+    pub fn load(client: Arc<OutlineClient>, document_id: String, text: &str) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut panel = Self {
+            backlinks: Vec::new(),
+            status: BacklinksStatus::Loading,
+            outgoing: Vec::new(),
+            selected: 0,
+            cursor: None,
+            parsed: 0,
+            base_url: client.base_url().to_string(),
+            rx,
+        };
+        panel.set_text(text);
+
+        tokio::spawn(async move {
+            let result = list_backlinks(&client, &document_id).await.map_err(|e| e.to_string());
+            let _ = tx.send(result);
+        });
+        panel
+    }
+
+    /// Apply loaded backlinks (call this regularly in event loop)
+    pub fn tick(&mut self) {
+        while let Ok(result) = self.rx.try_recv() {
+            match result {
+                Ok(backlinks) => {
+                    self.backlinks = backlinks;
+                    self.status = BacklinksStatus::Loaded;
+                }
+                Err(e) => self.status = BacklinksStatus::Failed(e),
+            }
+        }
+        self.clamp_selection();
+    }
+
+    /// Parse the outgoing links again if the document text changed
+    pub fn set_text(&mut self, text: &str) {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        let hash = hasher.finish();
+        if hash == self.parsed {
+            return;
+        }
+        self.parsed = hash;
+        self.outgoing = markdown::links(text);
+        if self.cursor.is_some_and(|i| i >= self.outgoing.len()) {
+            self.cursor = None;
+        }
+        self.clamp_selection();
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.backlinks.len() + self.outgoing.len()
+    }
+
+    pub fn row(&self, index: usize) -> Option<LinkRow<'_>> {
+        match self.backlinks.get(index) {
+            Some(backlink) => Some(LinkRow::Backlink(backlink)),
+            None => self.outgoing.get(index - self.backlinks.len()).map(LinkRow::Outgoing),
+        }
+    }
+
+    fn clamp_selection(&mut self) {
+        self.selected = self.selected.min(self.row_count().saturating_sub(1));
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.row_count() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// What a link points at, for this Outline instance
+    pub fn target(&self, link: &Link) -> LinkTarget {
+        link.target(&self.base_url)
+    }
+
+    /// Where the selected row leads
+    pub fn selected_target(&self) -> Option<LinkTarget> {
+        match self.row(self.selected)? {
+            LinkRow::Backlink(backlink) => Some(LinkTarget::Document(backlink.id.clone())),
+            LinkRow::Outgoing(link) => Some(self.target(link)),
+        }
+    }
+
+    /// The outgoing link picked in the document
+    pub fn cursor_link(&self) -> Option<&Link> {
+        self.outgoing.get(self.cursor?)
+    }
+
+    /// Rendered lines of the outgoing links, by index into `outgoing`
+    pub fn positions(&self, rendered: &[RenderedLink]) -> Vec<(usize, usize)> {
+        self.outgoing
+            .iter()
+            .enumerate()
+            .filter_map(|(index, link)| {
+                let found = rendered.iter().find(|r| r.offset == link.range.start)?;
+                Some((found.line, index))
+            })
+            .collect()
+    }
+}
+
+async fn list_backlinks(client: &OutlineClient, document_id: &str) -> anyhow::Result<Vec<Backlink>> {
+    let mut backlinks = Vec::new();
+    loop {
+        let request = ListDocumentsRequest::builder()
+            .backlink_document_id(document_id.to_string())
+            .offset(backlinks.len() as u32)
+            .limit(PAGE_SIZE)
+            .build();
+        let response = client.list_documents(request).await?;
+        let count = response.data.len() as u32;
+        backlinks.extend(response.data.into_iter().map(Backlink::from));
+        if count < PAGE_SIZE {
+            return Ok(backlinks);
+        }
+    }
+}
+
+/// Browser-like back/forward history of visited documents
+#[derive(Debug, Default)]
+pub struct History {
+    back: Vec<String>,
+    forward: Vec<String>,
+}
+
+impl History {
+    /// Record leaving a document for a new one, which drops the forward history
+    pub fn visit(&mut self, from: String) {
+        if self.back.last() != Some(&from) {
+            self.back.push(from);
+        }
+        if self.back.len() > MAX_HISTORY {
+            self.back.remove(0);
+        }
+        self.forward.clear();
+    }
+
+    /// The document a step back (or forward) leads to
+    pub fn peek(&self, back: bool) -> Option<&str> {
+        let from = if back { &self.back } else { &self.forward };
+        from.last().map(String::as_str)
+    }
+
+    /// Move one step back (or forward) from `current`, returning the document stepped to
+    ///
+    /// Call this once the document from `peek` has opened, so a failed load leaves the history as it was.
+    pub fn step(&mut self, back: bool, current: Option<String>) -> Option<String> {
+        let (from, to) = if back {
+            (&mut self.back, &mut self.forward)
+        } else {
+            (&mut self.forward, &mut self.back)
+        };
+        let target = from.pop()?;
+        to.extend(current);
+        Some(target)
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn client(base_url: &str) -> Arc<OutlineClient> {
+        Arc::new(OutlineClient::new(base_url.to_string()).unwrap().with_token("token".to_string()))
+    }
+
+    // The backlinks request is spawned when the panel is created; tests that don't await never run it.
+    fn panel(text: &str) -> LinksPanel {
+        LinksPanel::load(client("https://docs.example.com/api"), "current".to_string(), text)
+    }
+
+    fn backlink(id: &str) -> Backlink {
+        Backlink {
+            id: id.to_string(),
+            title: id.to_string(),
+            icon: String::new(),
+            collection_id: None,
+        }
+    }
+
+    fn visit(history: &mut History, ids: &[&str]) {
+        for id in ids {
+            history.visit(id.to_string());
+        }
+    }
+
+    /// Step the way the app does: open the peeked document, then record the step
+    fn go(history: &mut History, back: bool, current: &str) -> Option<String> {
+        let target = history.peek(back)?.to_string();
+        assert_eq!(history.step(back, Some(current.to_string())), Some(target.clone()));
+        Some(target)
+    }
+
+    #[test]
+    fn test_back_and_forward_return_to_the_same_documents() {
+        let mut history = History::default();
+        assert!(!history.can_go_back());
+        assert_eq!(history.peek(true), None);
+
+        // Opened a, then b, then c, and c is open
+        visit(&mut history, &["a", "b"]);
+        assert_eq!(go(&mut history, true, "c").as_deref(), Some("b"));
+        assert_eq!(go(&mut history, true, "b").as_deref(), Some("a"));
+        assert!(!history.can_go_back());
+        assert_eq!(history.step(true, Some("a".to_string())), None);
+
+        assert_eq!(go(&mut history, false, "a").as_deref(), Some("b"));
+        assert_eq!(go(&mut history, false, "b").as_deref(), Some("c"));
+        assert!(!history.can_go_forward());
+        assert_eq!(history.peek(true), Some("b"));
+    }
+
+    #[test]
+    fn test_visit_truncates_forward_entries() {
+        let mut history = History::default();
+        visit(&mut history, &["a", "b"]);
+        go(&mut history, true, "c");
+        assert!(history.can_go_forward());
+
+        // Opening d from b drops c from the forward history
+        history.visit("b".to_string());
+        assert!(!history.can_go_forward());
+        assert_eq!(history.peek(false), None);
+        assert_eq!(go(&mut history, true, "d").as_deref(), Some("b"));
+        assert_eq!(go(&mut history, true, "b").as_deref(), Some("a"));
+        assert_eq!(history.peek(false), Some("b"));
+    }
+
+    #[test]
+    fn test_peeking_leaves_the_history_unchanged() {
+        let mut history = History::default();
+        visit(&mut history, &["a", "b"]);
+
+        // A document that fails to open is peeked at but never stepped to
+        assert_eq!(history.peek(true), Some("b"));
+        assert_eq!(history.peek(true), Some("b"));
+        assert!(!history.can_go_forward());
+        assert_eq!(go(&mut history, true, "c").as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_stepping_without_an_open_document() {
+        let mut history = History::default();
+        visit(&mut history, &["a", "b"]);
+
+        assert_eq!(history.step(true, None).as_deref(), Some("b"));
+        // Nothing was open, so there is nothing to go forward to
+        assert!(!history.can_go_forward());
+        assert_eq!(history.peek(true), Some("a"));
+    }
+
+    #[test]
+    fn test_repeated_and_old_visits() {
+        let mut history = History::default();
+        visit(&mut history, &["a", "a", "b"]);
+        assert_eq!(go(&mut history, true, "c").as_deref(), Some("b"));
+        assert_eq!(go(&mut history, true, "b").as_deref(), Some("a"));
+        assert!(!history.can_go_back());
+
+        let mut history = History::default();
+        for i in 0..MAX_HISTORY + 10 {
+            history.visit(i.to_string());
+        }
+        let mut steps = 0;
+        while history.step(true, None).is_some() {
+            steps += 1;
+        }
+        assert_eq!(steps, MAX_HISTORY);
+    }
+
+    #[tokio::test]
+    async fn test_rows_list_backlinks_then_outgoing_links() {
+        let mut panel = panel("See [Guide](/doc/guide-abc123) and [site](https://example.org).\n");
+        assert_eq!(panel.row_count(), 2);
+        assert!(matches!(panel.row(0), Some(LinkRow::Outgoing(link)) if link.text == "Guide"));
+        assert_eq!(panel.selected_target(), Some(LinkTarget::Document("abc123".to_string())));
+
+        panel.select_next();
+        panel.select_next();
+        assert_eq!(panel.selected, 1);
+        assert_eq!(panel.selected_target(), Some(LinkTarget::External("https://example.org".to_string())));
+
+        // Backlinks arriving go before the outgoing links
+        panel.backlinks = vec![backlink("first"), backlink("second")];
+        assert_eq!(panel.row_count(), 4);
+        assert!(matches!(panel.row(1), Some(LinkRow::Backlink(b)) if b.id == "second"));
+        assert!(matches!(panel.row(3), Some(LinkRow::Outgoing(link)) if link.text == "site"));
+        assert!(panel.row(4).is_none());
+        assert_eq!(panel.selected_target(), Some(LinkTarget::Document("second".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_selection_and_cursor_are_clamped_when_links_go() {
+        let text = "[one](/doc/one-1) [two](/doc/two-2) [three](/doc/three-3)";
+        let mut panel = panel(text);
+        panel.selected = 2;
+        panel.cursor = Some(2);
+        assert_eq!(panel.cursor_link().unwrap().text, "three");
+
+        // Unchanged text is not parsed again
+        panel.set_text(text);
+        assert_eq!(panel.cursor, Some(2));
+
+        panel.set_text("[one](/doc/one-1)");
+        assert_eq!(panel.row_count(), 1);
+        assert_eq!(panel.selected, 0);
+        assert_eq!(panel.cursor, None);
+        assert!(panel.cursor_link().is_none());
+
+        panel.set_text("");
+        assert_eq!(panel.selected, 0);
+        assert!(panel.selected_target().is_none());
+    }
+
+    /// Serve `documents.list` with `total` backlinks, recording the offset of each request
+    async fn serve_backlinks(total: usize) -> (String, Arc<Mutex<Vec<u64>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let offsets = Arc::new(Mutex::new(Vec::new()));
+        let seen = offsets.clone();
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buffer = [0; 4096];
+                let body = loop {
+                    let n = socket.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                            .and_then(|v| v.parse::<usize>().ok())
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            break body.to_string();
+                        }
+                    }
+                };
+
+                let request: serde_json::Value = serde_json::from_str(&body).unwrap();
+                let offset = request["offset"].as_u64().unwrap();
+                let limit = request["limit"].as_u64().unwrap();
+                assert_eq!(request["backlinkDocumentId"], "current");
+                seen.lock().unwrap().push(offset);
+
+                let data: Vec<serde_json::Value> = (offset..(offset + limit).min(total as u64))
+                    .map(|i| serde_json::json!({ "id": format!("doc-{}", i), "title": format!("Doc {}", i) }))
+                    .collect();
+                let body = serde_json::json!({ "data": data }).to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (url, offsets)
+    }
+
+    #[tokio::test]
+    async fn test_backlinks_are_fetched_page_by_page() {
+        let (url, offsets) = serve_backlinks(PAGE_SIZE as usize + 50).await;
+        let backlinks = list_backlinks(&client(&url), "current").await.unwrap();
+
+        assert_eq!(backlinks.len(), 150);
+        assert_eq!(backlinks[0].id, "doc-0");
+        assert_eq!(backlinks[149].title, "Doc 149");
+        assert_eq!(*offsets.lock().unwrap(), vec![0, 100]);
+    }
+
+    #[tokio::test]
+    async fn test_full_last_page_of_backlinks_is_followed_by_an_empty_one() {
+        let (url, offsets) = serve_backlinks(PAGE_SIZE as usize).await;
+        let backlinks = list_backlinks(&client(&url), "current").await.unwrap();
+
+        assert_eq!(backlinks.len(), 100);
+        assert_eq!(*offsets.lock().unwrap(), vec![0, 100]);
+    }
+
+    #[tokio::test]
+    async fn test_panel_loads_backlinks_in_the_background() {
+        let (url, _) = serve_backlinks(3).await;
+        let mut panel = LinksPanel::load(client(&url), "current".to_string(), "[a](/doc/a-1)");
+        assert_eq!(panel.status, BacklinksStatus::Loading);
+
+        for _ in 0..100 {
+            panel.tick();
+            if panel.status != BacklinksStatus::Loading {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(panel.status, BacklinksStatus::Loaded);
+        assert_eq!(panel.row_count(), 4);
+        assert!(matches!(panel.row(3), Some(LinkRow::Outgoing(_))));
+    }
+}
@@ -1,21 +1,39 @@
 mod actions;
 mod app;
+mod attachments;
+mod buffers;
+mod comments;
 mod config;
 mod executor;
+mod finder;
+mod graphics;
+mod images;
+mod keymap;
+mod links;
 mod modals;
+mod render;
+mod search;
+mod state;
+mod theme;
+mod toc;
+mod tree;
 mod ui;
+mod users;
 
 use anyhow::Result;
 use app::{App, EditorMode, FocusedPane, SidebarItem};
 use config::Config;
+use keymap::{KeyChord, KeyContext, KeyLookup};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use outline_api::markdown::LinkTarget;
 use outline_api::{auth, OutlineClient};
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
+use std::sync::Arc;
 use tracing::{info, debug};
 
 #[tokio::main]
@@ -73,30 +91,50 @@ async fn run_app(
         // Show authentication setup page
         app.view = AppView::AuthSetup;
     } else {
-        // Load initial data
+        // Start loading the sidebar; it fills in while the UI runs
         app.view = AppView::Main;
-        if let Err(e) = load_collections_and_documents(app, Some(terminal)).await {
+        if let Err(e) = refresh_sidebar(app).await {
             app.set_status(format!("Error loading data: {}", e));
         }
     }
 
     loop {
+        app.sync_rendered();
         terminal.draw(|f| ui::render(f, app))?;
 
-        // Process collaboration events
+        // Images are written over the frame once it's on the screen
+        if app.image_layer.changed() {
+            if app.image_layer.needs_clear() {
+                terminal.clear()?;
+                terminal.draw(|f| ui::render(f, app))?;
+            }
+            app.image_layer.flush(terminal.backend_mut(), &app.attachments)?;
+        }
+
+        // Process collaboration events and sidebar loads
         app.process_collaboration_events();
+        app.process_tree_updates();
+        app.process_finder_updates();
+        app.process_comment_updates();
+        app.process_links_updates();
+        app.process_attachment_updates();
+        app.sync_toc();
 
         // Handle events (only process key press, not release)
         if event::poll(std::time::Duration::from_millis(100))? {
             match event::read()? {
-                Event::Key(key) => {
+                Event::Key(key)
                     // Only handle key press events, ignore release events
-                    if key.kind == KeyEventKind::Press {
-                        handle_key_event(app, key.code, key.modifiers).await?;
+                    if key.kind == KeyEventKind::Press => {
+                        // A failed action is reported, never a reason to leave the TUI
+                        if let Err(e) = handle_key_event(app, key.code, key.modifiers).await {
+                            app.set_status(format!("Error: {}", e));
+                        }
                     }
-                }
                 Event::Mouse(mouse) => {
-                    handle_mouse_event(app, mouse)?;
+                    if let Err(e) = handle_mouse_event(app, mouse).await {
+                        app.set_status(format!("Error: {}", e));
+                    }
                 }
                 _ => {}
             }
@@ -126,11 +164,28 @@ async fn handle_key_event(
             // If a modal is open, handle modal keys first
             if app.modal.is_open() {
                 handle_modal_keys(app, key, modifiers).await?;
+            } else if app.search_view_open() {
+                handle_search_keys(app, key, modifiers).await?;
+            } else if app.finder.is_some() {
+                handle_finder_keys(app, key, modifiers).await?;
+            } else if app.editor_mode == EditorMode::View
+                && app.pending_keys.is_empty()
+                && app.keymap.lookup(KeyContext::Global, &[KeyChord::new(key, modifiers)])
+                    == KeyLookup::Action(actions::Action::GoToDocument)
+            {
+                if let Err(e) = open_finder(app).await {
+                    app.set_status(format!("Error opening finder: {}", e));
+                }
+            } else if app.merge_view.is_some() {
+                handle_merge_keys(app, key, modifiers).await?;
             } else {
                 // Normal key handling
                 match app.focused_pane {
                     FocusedPane::Sidebar => handle_sidebar_keys(app, key, modifiers).await?,
                     FocusedPane::Editor => handle_editor_keys(app, key, modifiers).await?,
+                    FocusedPane::Comments => handle_comments_keys(app, key, modifiers).await?,
+                    FocusedPane::Links => handle_links_keys(app, key, modifiers).await?,
+                    FocusedPane::Toc => handle_toc_keys(app, key, modifiers).await?,
                 }
             }
         }
@@ -158,8 +213,7 @@ async fn handle_auth_keys(app: &mut App, key: KeyCode) -> Result<()> {
                     Ok(_) => {
                         app.view = AppView::Main;
                         app.set_status("API token saved! Loading data...".to_string());
-                        // Load data (no terminal for live updates here, will block)
-                        if let Err(e) = load_collections_and_documents(app, None).await {
+                        if let Err(e) = refresh_sidebar(app).await {
                             app.set_status(format!("Error loading data: {}", e));
                         }
                     }
@@ -215,8 +269,7 @@ async fn handle_auth_keys(app: &mut App, key: KeyCode) -> Result<()> {
                             Ok(_tokens) => {
                                 app.view = AppView::Main;
                                 app.set_status("OAuth2 authenticated! Loading data...".to_string());
-                                // Load data (no terminal for live updates here, will block)
-                                if let Err(e) = load_collections_and_documents(app, None).await {
+                                if let Err(e) = refresh_sidebar(app).await {
                                     app.set_status(format!("Error loading data: {}", e));
                                 }
                             }
@@ -274,9 +327,15 @@ async fn handle_modal_keys(
                 _ => {}
             }
         }
-        ModalType::Help => {
-            if matches!(key, KeyCode::Esc | KeyCode::Char('q')) {
-                app.modal.close();
+        ModalType::Help { .. } => {
+            match key {
+                KeyCode::Esc | KeyCode::Char('q') => app.modal.close(),
+                KeyCode::Down | KeyCode::Char('j') => app.modal.scroll_help(1),
+                KeyCode::Up | KeyCode::Char('k') => app.modal.scroll_help(-1),
+                KeyCode::PageDown | KeyCode::Char(' ') => app.modal.scroll_help(10),
+                KeyCode::PageUp => app.modal.scroll_help(-10),
+                KeyCode::Home => app.modal.scroll_help(i32::MIN / 2),
+                _ => {}
             }
         }
         ModalType::TextInput { title, .. } => {
@@ -363,12 +422,59 @@ async fn handle_modal_keys(
                 KeyCode::Enter | KeyCode::Char('y') => {
                     if let Some(action) = app.modal.get_pending_action() {
                         app.modal.close();
-                        execute_action_direct(app, action, Vec::new()).await?;
+                        if action == actions::Action::Quit {
+                            app.should_quit = true;
+                        } else {
+                            execute_action_direct(app, action, Vec::new()).await?;
+                        }
                     }
                 }
                 _ => {}
             }
         }
+        ModalType::CommandInput { .. } => {
+            match key {
+                KeyCode::Esc => {
+                    app.modal.close();
+                }
+                KeyCode::Enter => {
+                    let input = app.modal.get_command_input().unwrap_or_default();
+                    app.modal.close();
+                    if let Err(e) = run_command(app, &input).await {
+                        app.set_status(format!("Error: {}", e));
+                    }
+                }
+                KeyCode::Backspace => {
+                    // Backspace on an empty command line leaves it, as in vim
+                    if app.modal.get_command_input().is_some_and(|input| input.is_empty()) {
+                        app.modal.close();
+                    } else {
+                        app.modal.handle_backspace();
+                    }
+                }
+                KeyCode::Char(c) => {
+                    app.modal.handle_char(c);
+                }
+                _ => {}
+            }
+        }
+        ModalType::Attachment { id, name } => {
+            let (id, name) = (id.clone(), name.clone());
+            match key {
+                KeyCode::Char('o') | KeyCode::Enter => {
+                    app.modal.close();
+                    app.attachments.open(&id);
+                    app.set_status(format!("Opening {}...", name));
+                }
+                KeyCode::Char('s') => {
+                    app.modal.close();
+                    app.attachments.save(&id, &name);
+                    app.set_status(format!("Saving {}...", name));
+                }
+                KeyCode::Esc | KeyCode::Char('q') => app.modal.close(),
+                _ => {}
+            }
+        }
         ModalType::List { .. } | ModalType::Message { .. } => {
             match key {
                 KeyCode::Esc | KeyCode::Enter => {
@@ -393,7 +499,120 @@ async fn handle_modal_keys(
     Ok(())
 }
 
-fn handle_mouse_event(app: &mut App, mouse: MouseEvent) -> Result<()> {
+async fn handle_finder_keys(
+    app: &mut App,
+    key: KeyCode,
+    modifiers: KeyModifiers,
+) -> Result<()> {
+    let Some(finder) = app.finder.as_mut() else {
+        return Ok(());
+    };
+    let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+
+    match key {
+        KeyCode::Esc => {
+            app.finder = None;
+        }
+        KeyCode::Char('c') if ctrl => {
+            app.finder = None;
+        }
+        KeyCode::Enter => {
+            if let Some(id) = finder.selected_id().map(str::to_string) {
+                app.finder = None;
+                app.select_in_sidebar(&id);
+                match open_document(app, id).await {
+                    Ok(()) => app.focused_pane = FocusedPane::Editor,
+                    Err(e) => app.set_status(format!("Error loading document: {}", e)),
+                }
+            }
+        }
+        KeyCode::Up => finder.select_previous(),
+        KeyCode::Down | KeyCode::Tab => finder.select_next(),
+        KeyCode::BackTab => finder.select_previous(),
+        KeyCode::Char('p') | KeyCode::Char('k') if ctrl => finder.select_previous(),
+        KeyCode::Char('n') | KeyCode::Char('j') if ctrl => finder.select_next(),
+        KeyCode::Char('u') if ctrl => finder.clear_query(),
+        KeyCode::Backspace => finder.backspace(),
+        KeyCode::Char(c) if !ctrl => finder.push_char(c),
+        _ => {}
+    }
+
+    Ok(())
+}
+
+async fn handle_search_keys(
+    app: &mut App,
+    key: KeyCode,
+    modifiers: KeyModifiers,
+) -> Result<()> {
+    use actions::Action;
+    use search::SearchFocus;
+
+    let Some(view) = app.search_view.as_mut() else {
+        return Ok(());
+    };
+    let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+
+    match view.focus {
+        SearchFocus::Query => match key {
+            KeyCode::Esc => view.open = false,
+            KeyCode::Enter => {
+                if let Some(query) = view.submit() {
+                    search::remember_query(&mut app.ui_state.search_history, query);
+                    app.save_ui_state();
+                }
+            }
+            KeyCode::Up => view.history_previous(&app.ui_state.search_history),
+            KeyCode::Down => view.history_next(&app.ui_state.search_history),
+            KeyCode::Tab if !view.results.is_empty() => view.focus = SearchFocus::Results,
+            KeyCode::Char('u') if ctrl => view.input.clear(),
+            KeyCode::Backspace => view.backspace(),
+            KeyCode::Char(c) if !ctrl => view.push_char(c),
+            _ => {}
+        },
+        SearchFocus::Results => {
+            let Some(action) = app.resolve_key(KeyContext::Search, KeyChord::new(key, modifiers)) else {
+                return Ok(());
+            };
+            let Some(view) = app.search_view.as_mut() else {
+                return Ok(());
+            };
+            match action {
+                Action::Cancel => view.open = false,
+                Action::FocusQuery => view.focus = SearchFocus::Query,
+                Action::MoveUp => view.select_previous(),
+                Action::MoveDown => view.select_next(),
+                Action::PageUp => view.page_up(),
+                Action::PageDown => view.page_down(),
+                Action::FilterCollection => {
+                    let collections: Vec<(String, String)> = app
+                        .sidebar_tree
+                        .collections
+                        .iter()
+                        .map(|node| (node.collection.id.clone(), node.collection.name.clone()))
+                        .collect();
+                    view.cycle_collection(&collections);
+                }
+                Action::FilterAuthor => view.cycle_author(),
+                Action::FilterDate => view.cycle_date(),
+                Action::ToggleDrafts => view.toggle_drafts(),
+                Action::ToggleArchived => view.toggle_archived(),
+                Action::ClearFilters => view.clear_filters(),
+                Action::Open => {
+                    if let Some(entry) = view.selected_entry().cloned() {
+                        view.open = false;
+                        open_search_result(app, entry).await;
+                    }
+                }
+                action => run_key_action(app, action).await?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_mouse_event(app: &mut App, mouse: MouseEvent) -> Result<()> {
     // Don't handle mouse events if a modal is open
     if app.modal.is_open() {
         return Ok(());
@@ -405,15 +624,50 @@ fn handle_mouse_event(app: &mut App, mouse: MouseEvent) -> Result<()> {
             match app.focused_pane {
                 FocusedPane::Sidebar => app.sidebar_previous(),
                 FocusedPane::Editor => app.scroll_up(),
+                FocusedPane::Comments => {
+                    if let Some(panel) = &mut app.comments {
+                        panel.select_previous();
+                    }
+                }
+                FocusedPane::Links => {
+                    if let Some(panel) = &mut app.links {
+                        panel.select_previous();
+                    }
+                }
+                FocusedPane::Toc => {
+                    app.toc.select_previous();
+                    app.scroll_to_selected_heading();
+                }
             }
         }
         MouseEventKind::ScrollDown => {
             match app.focused_pane {
                 FocusedPane::Sidebar => app.sidebar_next(),
                 FocusedPane::Editor => app.scroll_down(),
+                FocusedPane::Comments => {
+                    if let Some(panel) = &mut app.comments {
+                        panel.select_next();
+                    }
+                }
+                FocusedPane::Links => {
+                    if let Some(panel) = &mut app.links {
+                        panel.select_next();
+                    }
+                }
+                FocusedPane::Toc => {
+                    app.toc.select_next();
+                    app.scroll_to_selected_heading();
+                }
             }
         }
         MouseEventKind::Down(_) => {
+            // Clicking a checkbox ticks or unticks it
+            if let Some(index) = app.task_at(mouse.column, mouse.row) {
+                app.focused_pane = FocusedPane::Editor;
+                app.task_cursor = Some(index);
+                return toggle_task(app, index).await;
+            }
+
             // Check if click is in the sidebar area (for item selection)
             if let Some(sidebar_area) = app.sidebar_area {
                 // Check if the click is within the sidebar area
@@ -464,8 +718,17 @@ fn handle_mouse_event(app: &mut App, mouse: MouseEvent) -> Result<()> {
             let sidebar_width = (width * 25) / 100;
 
             // Determine which pane was clicked based on X coordinate
+            let within = |area: ratatui::layout::Rect| {
+                mouse.column >= area.x && mouse.row >= area.y && mouse.row < area.y + area.height
+            };
             let new_pane = if mouse.column < sidebar_width {
                 FocusedPane::Sidebar
+            } else if app.toc_area.is_some_and(within) {
+                FocusedPane::Toc
+            } else if app.links_area.is_some_and(within) {
+                FocusedPane::Links
+            } else if app.comments_area.is_some_and(within) {
+                FocusedPane::Comments
             } else {
                 FocusedPane::Editor
             };
@@ -483,74 +746,405 @@ fn handle_mouse_event(app: &mut App, mouse: MouseEvent) -> Result<()> {
 async fn handle_sidebar_keys(
     app: &mut App,
     key: KeyCode,
-    _modifiers: KeyModifiers,
+    modifiers: KeyModifiers,
 ) -> Result<()> {
     use actions::Action;
 
-    match key {
-        KeyCode::Char('q') => {
-            app.should_quit = true;
-        }
-        KeyCode::Up | KeyCode::Char('k') => {
+    let Some(action) = app.resolve_key(KeyContext::Sidebar, KeyChord::new(key, modifiers)) else {
+        return Ok(());
+    };
+    match action {
+        Action::MoveUp => {
             app.sidebar_previous();
         }
-        KeyCode::Down | KeyCode::Char('j') => {
+        Action::MoveDown => {
             app.sidebar_next();
         }
-        KeyCode::PageUp => {
+        Action::PageUp => {
             app.sidebar_page_up();
         }
-        KeyCode::PageDown => {
+        Action::PageDown => {
             app.sidebar_page_down();
         }
-        KeyCode::Home => {
+        Action::GoToTop => {
             app.sidebar_home();
         }
-        KeyCode::End => {
+        Action::GoToBottom => {
             app.sidebar_end();
         }
-        KeyCode::Tab => {
-            app.toggle_focus();
-        }
-        KeyCode::Enter => {
-            // Load the selected document
-            if let Some(item) = app.selected_sidebar_item() {
-                if let SidebarItem::Document(doc, _) = item {
+        Action::Open => {
+            // Open the selected document, or expand/collapse the selected collection
+            match app.selected_sidebar_item() {
+                Some(SidebarItem::Document(doc, _)) => {
                     let doc_id = doc.id.clone();
-                    if let Err(e) = load_document(app, doc_id).await {
+                    if let Err(e) = open_document(app, doc_id).await {
                         app.set_status(format!("Error loading document: {}", e));
                     }
                 }
+                Some(SidebarItem::Collection(_)) => {
+                    set_sidebar_expanded(app, None).await?;
+                }
+                None => {}
             }
         }
-        KeyCode::Char('r') => {
-            // Refresh data
+        Action::ToggleExpand => {
+            set_sidebar_expanded(app, None).await?;
+        }
+        Action::Expand => {
+            // Expand, or go down to the first child when already expanded
+            let expanded = app.selected_sidebar_item()
+                .is_some_and(|item| item.is_expandable() && app.sidebar_tree.is_expanded(item.id()));
+            if expanded {
+                app.sidebar_first_child();
+            } else {
+                set_sidebar_expanded(app, Some(true)).await?;
+            }
+        }
+        Action::Collapse => {
+            // Collapse, or go up to the parent when already collapsed
+            let expanded = app.selected_sidebar_item()
+                .is_some_and(|item| item.is_expandable() && app.sidebar_tree.is_expanded(item.id()));
+            if expanded {
+                set_sidebar_expanded(app, Some(false)).await?;
+            } else {
+                app.sidebar_parent();
+            }
+        }
+        action => run_key_action(app, action).await?,
+    }
+
+    Ok(())
+}
+
+/// Run a bound action that means the same wherever it's pressed
+async fn run_key_action(app: &mut App, action: actions::Action) -> Result<()> {
+    use actions::Action;
+
+    match action {
+        Action::Quit => request_quit(app),
+        Action::ToggleFocus => app.toggle_focus(),
+        Action::ShowMenu => app.modal.show_action_menu(),
+        Action::ShowHelp => app.modal.show_help(),
+        Action::EnterCommandMode => app.modal.show_command_input(),
+        Action::GoToDocument => {
+            if let Err(e) = open_finder(app).await {
+                app.set_status(format!("Error opening finder: {}", e));
+            }
+        }
+        Action::Refresh => {
             app.set_status("Refreshing...".to_string());
-            if let Err(e) = load_collections_and_documents(app, None).await {
+            if let Err(e) = refresh_sidebar(app).await {
                 app.set_status(format!("Error refreshing: {}", e));
+            }
+        }
+        Action::ToggleComments => app.toggle_comments(),
+        Action::ToggleLinks => app.toggle_links(),
+        Action::ToggleToc => app.toggle_toc(),
+        Action::HistoryBack => go_through_history(app, true).await,
+        Action::HistoryForward => go_through_history(app, false).await,
+        Action::NextTab => cycle_buffer(app, true).await,
+        Action::PreviousTab => cycle_buffer(app, false).await,
+        // Movement, editing and pane keys bound where they don't apply
+        action if matches!(action.category(), "Navigation" | "Editing" | "Panes") => {
+            app.set_status(format!("Not available here: {}", action.description()));
+        }
+        action => execute_action_with_prompt(app, action).await?,
+    }
+    Ok(())
+}
+
+async fn handle_comments_keys(
+    app: &mut App,
+    key: KeyCode,
+    modifiers: KeyModifiers,
+) -> Result<()> {
+    use actions::Action;
+    use comments::ComposeMode;
+
+    // Writing a comment takes every key as text
+    if let Some(panel) = app.comments.as_mut() {
+        if let Some(compose) = panel.compose.as_mut() {
+            match key {
+                KeyCode::Esc => panel.compose = None,
+                KeyCode::Enter if modifiers.contains(KeyModifiers::ALT) => compose.text.push('\n'),
+                KeyCode::Enter => panel.submit_compose(),
+                KeyCode::Backspace => {
+                    compose.text.pop();
+                }
+                KeyCode::Char(c) => compose.text.push(c),
+                _ => {}
+            }
+            return Ok(());
+        }
+    }
+
+    let action = app.resolve_key(KeyContext::Comments, KeyChord::new(key, modifiers));
+
+    // Anything other than a second delete cancels a pending delete
+    if action != Some(Action::DeleteComment) {
+        if let Some(panel) = app.comments.as_mut() {
+            panel.confirm_delete = None;
+        }
+    }
+    let Some(action) = action else {
+        return Ok(());
+    };
+
+    // Without a document only leaving the pane and the global actions make sense
+    let Some(panel) = app.comments.as_mut() else {
+        match action {
+            Action::Cancel => app.focused_pane = FocusedPane::Editor,
+            Action::MoveUp | Action::MoveDown | Action::Open | Action::NextComment | Action::PreviousComment
+            | Action::Refresh | Action::CreateComment | Action::ReplyToComment | Action::UpdateComment
+            | Action::DeleteComment | Action::ResolveComment | Action::UnresolveComment
+            | Action::ToggleResolved => {}
+            action => run_key_action(app, action).await?,
+        }
+        return Ok(());
+    };
+
+    match action {
+        Action::Cancel => app.focused_pane = FocusedPane::Editor,
+        Action::MoveUp => panel.select_previous(),
+        Action::MoveDown => panel.select_next(),
+        Action::CreateComment => panel.start_compose(ComposeMode::New),
+        Action::ReplyToComment => {
+            if let Some(thread) = panel.selected_thread() {
+                let parent_id = thread.id.clone();
+                panel.start_compose(ComposeMode::Reply { parent_id });
+            }
+        }
+        Action::UpdateComment => {
+            if let Some(comment) = panel.selected_comment() {
+                let id = comment.id.clone();
+                panel.start_compose(ComposeMode::Edit { id });
+            }
+        }
+        Action::DeleteComment => panel.delete_selected(),
+        Action::ResolveComment | Action::UnresolveComment => panel.toggle_thread_resolved(),
+        Action::ToggleResolved => panel.toggle_resolved(),
+        Action::Refresh => panel.reload(),
+        Action::Open => {
+            if app.jump_to_selected_comment() {
+                app.focused_pane = FocusedPane::Editor;
             } else {
-                app.set_status("Refreshed!".to_string());
+                app.set_status("This comment isn't attached to text in the document".to_string());
+            }
+        }
+        Action::NextComment => jump_to_comment(app, true),
+        Action::PreviousComment => jump_to_comment(app, false),
+        action => run_key_action(app, action).await?,
+    }
+
+    Ok(())
+}
+
+/// Jump to the next or previous commented line, saying so when there is none
+fn jump_to_comment(app: &mut App, forward: bool) {
+    if !app.jump_to_comment(forward) {
+        let direction = if forward { "below" } else { "above" };
+        app.set_status(format!("No more comments {}", direction));
+    }
+}
+
+async fn handle_links_keys(
+    app: &mut App,
+    key: KeyCode,
+    modifiers: KeyModifiers,
+) -> Result<()> {
+    use actions::Action;
+
+    let Some(action) = app.resolve_key(KeyContext::Links, KeyChord::new(key, modifiers)) else {
+        return Ok(());
+    };
+    match action {
+        Action::Cancel => app.focused_pane = FocusedPane::Editor,
+        Action::MoveUp => {
+            if let Some(panel) = &mut app.links {
+                panel.select_previous();
             }
         }
-        // Keyboard shortcuts
-        KeyCode::Char('m') | KeyCode::Char(':') => {
-            app.modal.show_action_menu();
+        Action::MoveDown => {
+            if let Some(panel) = &mut app.links {
+                panel.select_next();
+            }
+        }
+        Action::Open => {
+            let selected = app.links.as_ref().and_then(|panel| {
+                let text = match panel.row(panel.selected)? {
+                    links::LinkRow::Backlink(backlink) => backlink.title.clone(),
+                    links::LinkRow::Outgoing(link) => link.text.clone(),
+                };
+                Some((panel.selected_target()?, text))
+            });
+            if let Some((target, text)) = selected {
+                follow_link(app, target, &text).await;
+            }
+        }
+        action => run_key_action(app, action).await?,
+    }
+
+    Ok(())
+}
+
+async fn handle_toc_keys(
+    app: &mut App,
+    key: KeyCode,
+    modifiers: KeyModifiers,
+) -> Result<()> {
+    use actions::Action;
+
+    let Some(action) = app.resolve_key(KeyContext::Toc, KeyChord::new(key, modifiers)) else {
+        return Ok(());
+    };
+    match action {
+        Action::Cancel => app.focused_pane = FocusedPane::Editor,
+        // Moving through the outline scrolls the document along with it
+        Action::MoveUp => {
+            app.toc.select_previous();
+            app.scroll_to_selected_heading();
         }
-        KeyCode::Char('h') | KeyCode::Char('?') => {
-            app.modal.show_help();
+        Action::MoveDown => {
+            app.toc.select_next();
+            app.scroll_to_selected_heading();
         }
-        KeyCode::Char('c') => {
-            create_new_document(app).await?;
+        Action::Open => {
+            app.scroll_to_selected_heading();
+            app.focused_pane = FocusedPane::Editor;
         }
-        KeyCode::Char('/') => {
-            execute_action_with_prompt(app, Action::SearchDocuments).await?;
+        Action::ToggleExpand => app.toc.set_collapsed(None),
+        Action::Expand => app.toc.set_collapsed(Some(false)),
+        Action::Collapse => {
+            // Fold, or go up to the parent heading when already folded
+            let selected = app.toc.selected;
+            if app.toc.has_children(selected) && !app.toc.collapsed.contains(&selected) {
+                app.toc.set_collapsed(Some(true));
+            } else {
+                app.toc.select_parent();
+                app.scroll_to_selected_heading();
+            }
         }
-        _ => {}
+        action => run_key_action(app, action).await?,
     }
 
     Ok(())
 }
 
+/// Jump to the next or previous heading, saying so when there is none
+fn jump_to_heading(app: &mut App, forward: bool) {
+    if !app.jump_to_heading(forward) {
+        let direction = if forward { "below" } else { "above" };
+        app.set_status(format!("No more headings {}", direction));
+    }
+}
+
+/// Move the link cursor to the next or previous link, saying so when there is none
+fn jump_to_link(app: &mut App, forward: bool) {
+    if !app.jump_to_link(forward) {
+        let direction = if forward { "below" } else { "above" };
+        app.set_status(format!("No more links {}", direction));
+    }
+}
+
+/// Pick the next or previous checklist item, saying so if there isn't one
+fn jump_to_task(app: &mut App, forward: bool) {
+    if !app.jump_to_task(forward) {
+        let direction = if forward { "below" } else { "above" };
+        app.set_status(format!("No more checklist items {}", direction));
+    }
+}
+
+/// Tick or untick a checklist item and save the document
+async fn toggle_task(app: &mut App, index: usize) -> Result<()> {
+    match app.toggled_task(index) {
+        Some(text) => commit_document_text(app, text).await,
+        None => Ok(()),
+    }
+}
+
+/// Follow the link picked with `]l`/`[l`
+async fn follow_link_at_cursor(app: &mut App) {
+    let picked = app.links.as_ref().and_then(|panel| {
+        let link = panel.cursor_link()?;
+        Some((panel.target(link), link.text.clone()))
+    });
+    match picked {
+        Some((target, text)) => follow_link(app, target, &text).await,
+        None => app.set_status("No link picked; use ]l or [l to pick one".to_string()),
+    }
+}
+
+/// Follow the link under the cursor in the text editor
+async fn follow_link_in_editor(app: &mut App) {
+    let (row, col) = app.textarea.cursor();
+    let Some(line) = app.textarea.lines().get(row) else {
+        return;
+    };
+    let at = line.char_indices().nth(col).map_or(line.len(), |(i, _)| i);
+    let Some(link) = outline_api::markdown::links(line)
+        .into_iter()
+        .find(|link| link.range.contains(&at))
+    else {
+        app.set_status("No link under the cursor".to_string());
+        return;
+    };
+
+    let saved = app.document_text.lines().collect::<Vec<_>>().join("\n");
+    if app.get_text_from_editor() != saved {
+        app.set_status("Save your changes (Esc) before following a link".to_string());
+        return;
+    }
+    let Some(target) = app.links.as_ref().map(|panel| panel.target(&link)) else {
+        return;
+    };
+    app.toggle_editor_mode();
+    follow_link(app, target, &link.text).await;
+}
+
+/// Open what a link points at: documents in the viewer, web pages in the browser and
+/// attachments with the choice of opening or saving them
+async fn follow_link(app: &mut App, target: LinkTarget, text: &str) {
+    match target {
+        LinkTarget::Document(id) => match open_document(app, id).await {
+            Ok(()) => {
+                if let Some(id) = app.current_document.as_ref().map(|doc| doc.id.clone()) {
+                    app.select_in_sidebar(&id);
+                }
+                app.focused_pane = FocusedPane::Editor;
+            }
+            Err(e) => app.set_status(format!("Error loading document: {}", e)),
+        },
+        LinkTarget::External(url) => match webbrowser::open(&url) {
+            Ok(()) => app.set_status(format!("Opened {} in the browser", url)),
+            Err(e) => app.set_status(format!("Couldn't open {}: {}", url, e)),
+        },
+        LinkTarget::User(_) => app.set_status(format!("{} is a user mention", text)),
+        LinkTarget::Other(url) => match outline_api::attachment_id(&url).filter(|_| url.contains("attachments.redirect")) {
+            Some(id) => app.modal.show_attachment(id.to_string(), attachments::file_name(text)),
+            None => app.set_status(format!("Can't follow {}", url)),
+        },
+    }
+}
+
+/// Go back (or forward) through the documents visited
+async fn go_through_history(app: &mut App, back: bool) {
+    let current = app.current_document.as_ref().map(|doc| doc.id.clone());
+    let Some(target) = app.history.peek(back).map(str::to_string) else {
+        let direction = if back { "back" } else { "forward" };
+        app.set_status(format!("Nothing to go {} to", direction));
+        return;
+    };
+
+    match load_document(app, target.clone()).await {
+        Ok(()) => {
+            app.history.step(back, current);
+            app.select_in_sidebar(&target);
+            app.focused_pane = FocusedPane::Editor;
+        }
+        Err(e) => app.set_status(format!("Error loading document: {}", e)),
+    }
+}
+
 async fn handle_editor_keys(
     app: &mut App,
     key: KeyCode,
@@ -564,61 +1158,62 @@ async fn handle_editor_keys(
     }
 
     // View mode keybindings
-    match key {
-        KeyCode::Char('q') => {
-            app.should_quit = true;
-        }
-        KeyCode::Tab => {
-            app.toggle_focus();
-        }
-        KeyCode::Up | KeyCode::Char('k') => {
+    let Some(action) = app.resolve_key(KeyContext::View, KeyChord::new(key, modifiers)) else {
+        return Ok(());
+    };
+    match action {
+        Action::MoveUp => {
             app.scroll_up();
         }
-        KeyCode::Down | KeyCode::Char('j') => {
+        Action::MoveDown => {
             app.scroll_down();
         }
-        KeyCode::PageUp => {
+        Action::PageUp => {
             app.scroll_page_up();
         }
-        KeyCode::PageDown => {
+        Action::PageDown => {
             app.scroll_page_down();
         }
-        KeyCode::Home => {
+        Action::GoToTop => {
             app.scroll_to_top();
         }
-        KeyCode::End => {
+        Action::GoToBottom => {
             app.scroll_to_bottom();
         }
-        KeyCode::Char('e') => {
+        Action::NextComment => jump_to_comment(app, true),
+        Action::PreviousComment => jump_to_comment(app, false),
+        Action::NextLink => jump_to_link(app, true),
+        Action::PreviousLink => jump_to_link(app, false),
+        Action::NextTask => jump_to_task(app, true),
+        Action::PreviousTask => jump_to_task(app, false),
+        Action::ToggleTask => match app.task_cursor {
+            Some(index) => toggle_task(app, index).await?,
+            None => app.set_status("No checklist item picked; use ]t or [t to pick one".to_string()),
+        },
+        Action::NextHeading => jump_to_heading(app, true),
+        Action::PreviousHeading => jump_to_heading(app, false),
+        Action::Open => {
+            follow_link_at_cursor(app).await;
+        }
+        // Move to the other side of a split
+        Action::SwitchSplit => {
+            match app.split {
+                Some(split) => {
+                    if let Err(e) = switch_to_buffer(app, split.buffer).await {
+                        app.set_status(format!("Error switching buffer: {}", e));
+                    }
+                }
+                None => app.set_status("No split open (:sp or :vs)".to_string()),
+            }
+        }
+        Action::EditDocument => {
             if app.current_document.is_some() {
                 // Entering edit mode - load text into editor
                 app.load_text_into_editor();
                 app.toggle_editor_mode();
             }
         }
-        // Keyboard shortcuts (only in view mode)
-        KeyCode::Char('m') | KeyCode::Char(':') => {
-            app.modal.show_action_menu();
-        }
-        KeyCode::Char('h') | KeyCode::Char('?') => {
-            app.modal.show_help();
-        }
-        KeyCode::Char('u') => {
-            execute_action_with_prompt(app, Action::UpdateDocument).await?;
-        }
-        KeyCode::Char('d') => {
-            execute_action_with_prompt(app, Action::DeleteDocument).await?;
-        }
-        KeyCode::Char('a') => {
-            execute_action_direct(app, Action::ArchiveDocument, Vec::new()).await?;
-        }
-        KeyCode::Char('s') => {
-            execute_action_direct(app, Action::StarDocument, Vec::new()).await?;
-        }
-        KeyCode::Char('x') => {
-            execute_action_with_prompt(app, Action::ExportDocument).await?;
-        }
-        _ => {}
+        action => run_key_action(app, action).await?,
     }
 
     Ok(())
@@ -629,93 +1224,89 @@ async fn handle_vim_keys(
     key: KeyCode,
     modifiers: KeyModifiers,
 ) -> Result<()> {
+    use actions::Action;
     use app::VimMode;
     use crossterm::event::KeyEvent;
+    use tui_textarea::CursorMove;
 
     match app.vim_mode {
         VimMode::Normal => {
-            match key {
+            let Some(action) = app.resolve_key(KeyContext::Normal, KeyChord::new(key, modifiers)) else {
+                return Ok(());
+            };
+            if let Some(movement) = cursor_move(&action) {
+                app.textarea.move_cursor(movement);
+                return Ok(());
+            }
+            match action {
                 // Mode switches
-                KeyCode::Char('i') => {
+                Action::InsertMode => {
                     app.vim_mode = VimMode::Insert;
                 }
-                KeyCode::Char('I') => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Head);
+                Action::InsertAtLineStart => {
+                    app.textarea.move_cursor(CursorMove::Head);
                     app.vim_mode = VimMode::Insert;
                 }
-                KeyCode::Char('a') => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Forward);
+                Action::Append => {
+                    app.textarea.move_cursor(CursorMove::Forward);
                     app.vim_mode = VimMode::Insert;
                 }
-                KeyCode::Char('A') => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::End);
+                Action::AppendAtLineEnd => {
+                    app.textarea.move_cursor(CursorMove::End);
                     app.vim_mode = VimMode::Insert;
                 }
-                KeyCode::Char('v') => {
+                Action::VisualMode => {
                     app.vim_mode = VimMode::Visual;
                     app.textarea.start_selection();
                 }
-                KeyCode::Char('o') => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::End);
+                Action::OpenLineBelow => {
+                    app.textarea.move_cursor(CursorMove::End);
                     app.textarea.insert_newline();
                     app.vim_mode = VimMode::Insert;
                 }
-                KeyCode::Char('O') => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Head);
+                Action::OpenLineAbove => {
+                    app.textarea.move_cursor(CursorMove::Head);
                     app.textarea.insert_newline();
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Up);
+                    app.textarea.move_cursor(CursorMove::Up);
                     app.vim_mode = VimMode::Insert;
                 }
-                // Movement
-                KeyCode::Char('h') | KeyCode::Left => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Back);
-                }
-                KeyCode::Char('j') | KeyCode::Down => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Down);
-                }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Up);
-                }
-                KeyCode::Char('l') | KeyCode::Right => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Forward);
-                }
-                KeyCode::Char('w') => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::WordForward);
-                }
-                KeyCode::Char('b') => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::WordBack);
-                }
-                KeyCode::Char('0') | KeyCode::Home => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Head);
-                }
-                KeyCode::Char('$') | KeyCode::End => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::End);
-                }
-                KeyCode::Char('g') => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Top);
-                }
-                KeyCode::Char('G') => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Bottom);
+                Action::Open => {
+                    follow_link_in_editor(app).await;
                 }
                 // Editing
-                KeyCode::Char('x') => {
+                Action::DeleteChar => {
                     app.textarea.delete_next_char();
                 }
-                KeyCode::Char('u') => {
+                // With a collaborative document, undo only the local user's edits
+                Action::Undo if app.has_collaborative_document() => {
+                    match app.collaborative_undo(false).await {
+                        Ok(true) => {}
+                        Ok(false) => app.set_status("Already at oldest change".to_string()),
+                        Err(e) => app.set_status(format!("Undo failed: {}", e)),
+                    }
+                }
+                Action::Redo if app.has_collaborative_document() => {
+                    match app.collaborative_undo(true).await {
+                        Ok(true) => {}
+                        Ok(false) => app.set_status("Already at newest change".to_string()),
+                        Err(e) => app.set_status(format!("Redo failed: {}", e)),
+                    }
+                }
+                Action::Undo => {
                     app.textarea.undo();
                 }
-                KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => {
+                Action::Redo => {
                     app.textarea.redo();
                 }
                 // Exit to view mode (save changes)
-                KeyCode::Esc => {
+                Action::SaveAndExit => {
                     if let Err(e) = save_document_changes(app).await {
                         app.set_status(format!("Error saving document: {}", e));
-                    } else {
+                    } else if app.merge_view.is_none() {
                         app.toggle_editor_mode();
                     }
                 }
-                _ => {}
+                action => run_key_action(app, action).await?,
             }
         }
         VimMode::Insert => {
@@ -745,20 +1336,16 @@ async fn handle_vim_keys(
                     app.textarea.cut();
                     app.vim_mode = VimMode::Normal;
                 }
-                // Movement in visual mode
-                KeyCode::Char('h') | KeyCode::Left => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Back);
-                }
-                KeyCode::Char('j') | KeyCode::Down => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Down);
-                }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Up);
-                }
-                KeyCode::Char('l') | KeyCode::Right => {
-                    app.textarea.move_cursor(tui_textarea::CursorMove::Forward);
+                // Movement uses the normal mode bindings
+                _ => {
+                    let movement = app
+                        .resolve_key(KeyContext::Normal, KeyChord::new(key, modifiers))
+                        .as_ref()
+                        .and_then(cursor_move);
+                    if let Some(movement) = movement {
+                        app.textarea.move_cursor(movement);
+                    }
                 }
-                _ => {}
             }
         }
     }
@@ -766,6 +1353,26 @@ async fn handle_vim_keys(
     Ok(())
 }
 
+/// The editor cursor movement of a movement action
+fn cursor_move(action: &actions::Action) -> Option<tui_textarea::CursorMove> {
+    use actions::Action;
+    use tui_textarea::CursorMove;
+
+    Some(match action {
+        Action::MoveLeft => CursorMove::Back,
+        Action::MoveDown => CursorMove::Down,
+        Action::MoveUp => CursorMove::Up,
+        Action::MoveRight => CursorMove::Forward,
+        Action::WordForward => CursorMove::WordForward,
+        Action::WordBack => CursorMove::WordBack,
+        Action::LineStart => CursorMove::Head,
+        Action::LineEnd => CursorMove::End,
+        Action::GoToTop => CursorMove::Top,
+        Action::GoToBottom => CursorMove::Bottom,
+        _ => return None,
+    })
+}
+
 /// Create a new document with intuitive flow
 async fn create_new_document(app: &mut App) -> Result<()> {
     use modals::InputField;
@@ -775,7 +1382,7 @@ async fn create_new_document(app: &mut App) -> Result<()> {
         match item {
             SidebarItem::Document(doc, _) => {
                 // Selected a document - use it as parent, same collection
-                (Some(doc.id.clone()), doc.collection_id.clone())
+                (Some(doc.id.clone()), Some(doc.collection_id.clone()))
             }
             SidebarItem::Collection(col) => {
                 // Selected a collection - no parent, use collection
@@ -802,36 +1409,176 @@ async fn create_new_document(app: &mut App) -> Result<()> {
 
 /// Save document changes to server
 async fn save_document_changes(app: &mut App) -> Result<()> {
-    use outline_api::UpdateDocumentRequest;
+    // Get updated text from editor
+    let new_text = app.get_text_from_editor();
+    commit_document_text(app, new_text).await
+}
 
+/// Save new text for the current document
+async fn commit_document_text(app: &mut App, new_text: String) -> Result<()> {
     let doc = app.current_document.as_ref()
         .ok_or_else(|| anyhow::anyhow!("No document loaded"))?;
 
-    // Get updated text from editor
-    let new_text = app.get_text_from_editor();
+    // With a synced local copy that mirrors the document, record the edit as a
+    // CRDT update so it merges with concurrent edits (immediately, or on
+    // reconnect when offline)
+    if app.has_collaborative_document() {
+        let title = doc.title.clone();
+        app.document_text = new_text.clone();
+        if app.commit_collaborative_edit(&new_text).await? {
+            app.set_status(format!("Saved: {}", title));
+        } else {
+            app.set_status(format!("Saved offline: {} (will sync when reconnected)", title));
+        }
+        return Ok(());
+    }
+
     app.document_text = new_text.clone();
+    save_document_text(app, new_text).await
+}
+
+/// Save text to the server unless the document changed since it was loaded
+///
+/// If someone else saved in the meantime, a merge view is opened instead of
+/// overwriting their changes.
+async fn save_document_text(app: &mut App, text: String) -> Result<()> {
+    use outline_api::{RevisionConflict, UpdateDocumentRequest};
+
+    let doc = app.current_document.as_ref()
+        .ok_or_else(|| anyhow::anyhow!("No document loaded"))?;
+    let revision = doc.revision;
 
-    // Update document on server
     let client = create_api_client().await?;
     let request = UpdateDocumentRequest {
         id: doc.id.clone(),
         title: None,
-        text: Some(new_text),
+        text: Some(text.clone()),
         emoji: None,
         append: None,
         publish: None,
         done: None,
     };
 
-    let updated = client.update_document(request).await?;
-    app.set_status(format!("Saved: {}", updated.title));
+    match client.update_document_if_revision(request, revision).await {
+        Ok(updated) => {
+            app.set_status(format!("Saved: {}", updated.title));
+            app.document_text = text;
+
+            // Update current document with server response
+            app.current_document = Some(updated);
+            Ok(())
+        }
+        Err(e) => match e.downcast::<RevisionConflict>() {
+            Ok(conflict) => {
+                info!("Save conflict: {}", conflict);
+                app.open_merge_view(*conflict.current, text);
+                let conflicts = app.merge_view.as_ref()
+                    .map(|view| view.merge.conflict_count())
+                    .unwrap_or(0);
+                app.set_status(format!(
+                    "Document changed on the server ({} conflicting section(s)) - review the merge",
+                    conflicts
+                ));
+                Ok(())
+            }
+            Err(e) => Err(e),
+        },
+    }
+}
+
+/// Handle keys in the three-way merge view
+async fn handle_merge_keys(app: &mut App, key: KeyCode, modifiers: KeyModifiers) -> Result<()> {
+    use actions::Action;
 
-    // Update current document with server response
-    app.current_document = Some(updated);
+    let Some(action) = app.resolve_key(KeyContext::Merge, KeyChord::new(key, modifiers)) else {
+        return Ok(());
+    };
+    let Some(view) = app.merge_view.as_mut() else {
+        return Ok(());
+    };
+
+    match action {
+        Action::MoveUp => {
+            view.scroll = view.scroll.saturating_sub(1);
+        }
+        Action::MoveDown => {
+            view.scroll = view.scroll.saturating_add(1);
+        }
+        Action::PageUp => {
+            view.scroll = view.scroll.saturating_sub(10);
+        }
+        Action::PageDown => {
+            view.scroll = view.scroll.saturating_add(10);
+        }
+        Action::NextConflict => {
+            if let Some(row) = view.next_conflict_row() {
+                view.scroll = row;
+            }
+        }
+        // Keep my version, overwriting the server's changes
+        Action::KeepMine => {
+            let Some(view) = app.merge_view.take() else { return Ok(()) };
+            let mine = view.mine;
+            app.current_document = Some(view.latest);
+            save_resolved_text(app, mine).await;
+        }
+        // Take their version, discarding my changes
+        Action::TakeTheirs => {
+            let Some(view) = app.merge_view.take() else { return Ok(()) };
+            app.document_text = view.latest.text.clone();
+            app.set_status(format!("Discarded local changes to: {}", view.latest.title));
+            app.current_document = Some(view.latest);
+            app.editor_mode = EditorMode::View;
+        }
+        // Save the merged text when nothing conflicts
+        Action::SaveMerge => {
+            let Some(merged) = view.merge.resolved_text() else {
+                let keys = |action| app.keymap.keys_for(KeyContext::Merge, &action).unwrap_or_default();
+                let status = format!(
+                    "Resolve conflicts first: {} to edit, {} to keep mine, {} to take theirs",
+                    keys(Action::EditDocument),
+                    keys(Action::KeepMine),
+                    keys(Action::TakeTheirs)
+                );
+                app.set_status(status);
+                return Ok(());
+            };
+            let Some(view) = app.merge_view.take() else { return Ok(()) };
+            app.current_document = Some(view.latest);
+            save_resolved_text(app, merged).await;
+        }
+        // Edit the merged text, with conflict markers around conflicting sections
+        Action::EditDocument => {
+            let Some(view) = app.merge_view.take() else { return Ok(()) };
+            app.document_text = view.merge.text_with_markers();
+            app.current_document = Some(view.latest);
+            app.load_text_into_editor();
+            app.editor_mode = EditorMode::Edit;
+            let save = app.keymap.keys_for(KeyContext::Normal, &Action::SaveAndExit).unwrap_or_default();
+            app.set_status(format!("Resolve the conflict markers, then {} to save", save));
+        }
+        // Back to my edits without saving
+        Action::Cancel => {
+            app.merge_view = None;
+            app.set_status("Merge cancelled - changes not saved".to_string());
+        }
+        _ => {}
+    }
 
     Ok(())
 }
 
+/// Save the outcome of a merge against the latest server version
+async fn save_resolved_text(app: &mut App, text: String) {
+    match save_document_text(app, text).await {
+        Ok(()) if app.merge_view.is_none() => {
+            app.editor_mode = EditorMode::View;
+        }
+        Ok(()) => {}
+        Err(e) => app.set_status(format!("Error saving document: {}", e)),
+    }
+}
+
 /// Complete document creation and open in editor
 async fn complete_document_creation(app: &mut App, title: String) -> Result<()> {
     use outline_api::CreateDocumentRequest;
@@ -856,7 +1603,9 @@ async fn complete_document_creation(app: &mut App, title: String) -> Result<()>
     let doc = client.create_document(request).await?;
     app.set_status(format!("Created: {}", doc.title));
 
-    // Load the new document
+    // Open the new document in a buffer of its own
+    app.stop_collaboration().await;
+    app.open_buffer();
     app.current_document = Some(doc.clone());
     app.document_text = doc.text.clone();
 
@@ -877,6 +1626,11 @@ async fn execute_action_with_prompt(app: &mut App, action: actions::Action) -> R
         return create_new_document(app).await;
     }
 
+    // Search has its own view
+    if action == actions::Action::SearchDocuments {
+        return open_search(app).await;
+    }
+
     if action_requires_input(&action) {
         let fields = get_input_fields_for_action(&action, app);
         if fields.is_empty() {
@@ -920,9 +1674,15 @@ async fn execute_action_direct(app: &mut App, action: actions::Action, input_val
     match execute_action(action.clone(), app, &client, input_values).await {
         Ok(message) => {
             app.modal.show_message("Success".to_string(), message);
+            // Deleting the active document moves to another buffer, which needs its own session
+            if action == actions::Action::DeleteDocument {
+                if let Some(doc_id) = app.current_document.as_ref().map(|doc| doc.id.clone()) {
+                    start_document_collaboration(app, doc_id).await;
+                }
+            }
             // Refresh data after certain actions
             if should_refresh_after_action(&action) {
-                let _ = load_collections_and_documents(app, None).await;
+                let _ = refresh_sidebar(app).await;
             }
         }
         Err(e) => {
@@ -951,177 +1711,363 @@ async fn create_api_client() -> Result<OutlineClient> {
     Ok(OutlineClient::new(api_base_url)?.with_token(api_token))
 }
 
-async fn load_collections_and_documents(
-    app: &mut App,
-    mut terminal: Option<&mut Terminal<CrosstermBackend<io::Stdout>>>,
-) -> Result<()> {
-    use outline_api::{ListCollectionsRequest, ListDocumentsRequest, Document};
-    use std::collections::HashMap;
+/// Reload collections in the background, along with the documents of loaded or expanded collections
+///
+/// Returns once the load has started; results arrive through `App::process_tree_updates`.
+async fn refresh_sidebar(app: &mut App) -> Result<()> {
+    let client = Arc::new(create_api_client().await?);
+
+    let mut preload = app.sidebar_tree.loaded_collection_ids();
+    preload.extend(app.sidebar_tree.expanded.iter().cloned());
 
+    app.sidebar_tree.loading_collections = true;
     app.is_loading = true;
-    app.set_status("Loading collections...".to_string());
-    if let Some(term) = terminal.as_deref_mut() {
-        term.draw(|f| ui::render(f, app))?;
-    }
+    tree::spawn_load(client, app.tree_tx.clone(), preload);
 
-    let client = create_api_client().await?;
+    Ok(())
+}
 
-    // Load collections
-    let request = ListCollectionsRequest::new();
-    let collections_response = client.list_collections(request).await?;
-
-    // Build sidebar items
-    let mut sidebar_items = Vec::new();
-
-    let total_collections = collections_response.data.len();
-    for (idx, collection) in collections_response.data.iter().enumerate() {
-        app.set_status(format!("Loading collection {} of {}: {}", idx + 1, total_collections, collection.name));
-        if let Some(term) = terminal.as_deref_mut() {
-            term.draw(|f| ui::render(f, app))?;
-        }
-        sidebar_items.push(SidebarItem::Collection(collection.clone()));
-
-        // Load documents for this collection
-        let docs_request = ListDocumentsRequest {
-            backlink_document_id: None,
-            collection_id: Some(collection.id.clone()),
-            direction: None,
-            limit: None,
-            offset: None,
-            parent_document_id: None,
-            sort: None,
-            template: None,
-            user_id: None,
-        };
-
-        if let Ok(docs_response) = client.list_documents(docs_request).await {
-            debug!("Loaded {} documents from list API for collection {}", docs_response.data.len(), collection.id);
-
-            // Fetch full document info to get emojis (simple approach)
-            let mut docs_with_emoji = Vec::new();
-            let total_docs = docs_response.data.len();
-            for (doc_idx, doc) in docs_response.data.into_iter().enumerate() {
-                if total_docs > 5 {
-                    // Only show detailed status for collections with many documents
-                    app.set_status(format!("Loading {} ({}/{})", collection.name, doc_idx + 1, total_docs));
-                    if let Some(term) = terminal.as_deref_mut() {
-                        term.draw(|f| ui::render(f, app))?;
-                    }
-                }
-                debug!("Fetching full document info for: {} ({})", doc.title, doc.id);
+/// Open the "go to document" finder over the loaded documents
+///
+/// Collections not loaded yet are fetched in the background so their documents become findable.
+async fn open_finder(app: &mut App) -> Result<()> {
+    let client = Arc::new(create_api_client().await?);
 
-                // Fetch full document to get emoji
-                match client.get_document(doc.id.clone()).await {
-                    Ok(full_doc) => {
-                        debug!("  Got full doc - emoji: {:?}, title: {}", full_doc.emoji, full_doc.title);
-                        docs_with_emoji.push(full_doc);
-                    }
-                    Err(e) => {
-                        debug!("  Failed to get full doc: {}, using list version", e);
-                        docs_with_emoji.push(doc); // Fallback to doc without emoji
-                    }
-                }
-            }
+    let unloaded = app.sidebar_tree.unloaded_collection_ids();
+    if !unloaded.is_empty() {
+        for id in &unloaded {
+            app.sidebar_tree.mark_loading(id);
+        }
+        app.is_loading = true;
+        tree::spawn_load_collections(client.clone(), app.tree_tx.clone(), unloaded);
+    }
 
-            debug!("Finished loading full document info, building tree...");
+    app.finder = Some(finder::Finder::new(client, &app.sidebar_tree));
+    Ok(())
+}
 
-            // Build tree structure
-            let mut doc_map: HashMap<String, Document> = HashMap::new();
-            let mut children_map: HashMap<Option<String>, Vec<String>> = HashMap::new();
+/// Show the search view, keeping the previous search's results if there was one
+async fn open_search(app: &mut App) -> Result<()> {
+    match &mut app.search_view {
+        Some(view) => view.reopen(),
+        None => {
+            let client = Arc::new(create_api_client().await?);
+            app.search_view = Some(search::SearchView::new(client));
+        }
+    }
+    Ok(())
+}
 
-            for doc in docs_with_emoji {
-                let doc_id = doc.id.clone();
-                let parent_id = doc.parent_document_id.clone();
+/// Open a search result, scrolled to the first match
+async fn open_search_result(app: &mut App, entry: search::SearchEntry) {
+    app.select_in_sidebar(&entry.id);
+    if let Err(e) = open_document(app, entry.id.clone()).await {
+        app.set_status(format!("Error loading document: {}", e));
+        return;
+    }
+    app.focused_pane = FocusedPane::Editor;
 
-                doc_map.insert(doc_id.clone(), doc);
-                children_map.entry(parent_id).or_insert_with(Vec::new).push(doc_id);
-            }
+    if let Some(term) = entry.first_match() {
+        if app.scroll_to_text(&term) {
+            app.set_status(format!("Showing first match for \"{}\"", term));
+        } else {
+            app.set_status(format!("\"{}\" not found in the current text", term));
+        }
+    }
+}
 
-            // Find root documents (no parent or parent not in this collection)
-            let roots: Vec<String> = doc_map
-                .keys()
-                .filter(|id| {
-                    let doc = &doc_map[*id];
-                    doc.parent_document_id.is_none() ||
-                    !doc_map.contains_key(doc.parent_document_id.as_ref().unwrap())
-                })
-                .cloned()
-                .collect();
+/// Expand or collapse the selected sidebar item (`None` toggles)
+///
+/// A collection's documents are fetched the first time it is expanded.
+async fn set_sidebar_expanded(app: &mut App, expand: Option<bool>) -> Result<()> {
+    let Some(item) = app.selected_sidebar_item() else {
+        return Ok(());
+    };
+    if !item.is_expandable() {
+        return Ok(());
+    }
+    let id = item.id().to_string();
+    let is_collection = matches!(item, SidebarItem::Collection(_));
 
-            // Recursively add documents in tree order
-            fn add_document_tree(
-                doc_id: &str,
-                doc_map: &HashMap<String, Document>,
-                children_map: &HashMap<Option<String>, Vec<String>>,
-                sidebar_items: &mut Vec<SidebarItem>,
-                indent_level: usize,
-            ) {
-                if let Some(doc) = doc_map.get(doc_id) {
-                    sidebar_items.push(SidebarItem::Document(doc.clone(), indent_level));
-
-                    // Add children
-                    if let Some(child_ids) = children_map.get(&Some(doc_id.to_string())) {
-                        for child_id in child_ids {
-                            add_document_tree(
-                                child_id,
-                                doc_map,
-                                children_map,
-                                sidebar_items,
-                                indent_level + 1,
-                            );
-                        }
-                    }
-                }
-            }
+    let expand = expand.unwrap_or(!app.sidebar_tree.is_expanded(&id));
+    app.sidebar_tree.set_expanded(&id, expand);
 
-            // Add all root documents and their children
-            for root_id in roots {
-                add_document_tree(&root_id, &doc_map, &children_map, &mut sidebar_items, 1);
-            }
-        }
+    if expand && is_collection && app.sidebar_tree.needs_documents(&id) {
+        let client = Arc::new(create_api_client().await?);
+        app.sidebar_tree.mark_loading(&id);
+        tree::spawn_load_documents(client, app.tree_tx.clone(), id);
     }
 
-    app.sidebar_items = sidebar_items;
-    app.is_loading = false;
-
-    // Log summary of what was loaded
-    let doc_count = app.sidebar_items.iter().filter(|item| matches!(item, SidebarItem::Document(_, _))).count();
-    let emoji_count = app.sidebar_items.iter().filter(|item| {
-        if let SidebarItem::Document(doc, _) = item {
-            doc.emoji.is_some()
-        } else {
-            false
-        }
-    }).count();
+    app.rebuild_sidebar();
+    app.save_ui_state();
+    Ok(())
+}
 
-    info!("Loaded {} documents, {} have emojis", doc_count, emoji_count);
-    app.set_status(format!("Loaded {} collections and {} documents", total_collections, doc_count));
+/// Open a document, recording the one being left in the navigation history
+async fn open_document(app: &mut App, doc_id: String) -> Result<()> {
+    let previous = app.current_document.as_ref().map(|doc| doc.id.clone());
+    load_document(app, doc_id).await?;
 
+    let current = app.current_document.as_ref().map(|doc| doc.id.clone());
+    if let Some(previous) = previous.filter(|previous| current.as_ref() != Some(previous)) {
+        app.history.visit(previous);
+    }
     Ok(())
 }
 
 async fn load_document(app: &mut App, doc_id: String) -> Result<()> {
+    use outline_api::collaboration::DocumentStore;
+
+    // An open document is switched to rather than fetched again
+    if let Some(index) = app.buffers.position(&doc_id, app.current_document.as_ref()) {
+        return switch_to_buffer(app, index).await;
+    }
+
     info!("Loading document: {}", doc_id);
-    let client = create_api_client().await?;
+    app.stop_collaboration().await;
+    app.merge_view = None;
 
     debug!("Fetching document from API...");
-    let document = client.get_document(doc_id.clone()).await?;
+    let client = create_api_client().await.map(Arc::new);
+    let fetched = match &client {
+        Ok(client) => client.get_document(doc_id.clone()).await,
+        Err(e) => Err(anyhow::anyhow!("{}", e)),
+    };
+    let document = match fetched {
+        Ok(document) => document,
+        Err(e) => {
+            // Fall back to the locally persisted copy when the server is unreachable
+            app.open_buffer();
+            let opened = match &client {
+                Ok(client) => app.open_local_copy(client.base_url(), &doc_id).unwrap_or(false),
+                Err(_) => false,
+            };
+            if opened {
+                info!("Opened local copy of {} ({})", doc_id, e);
+                app.set_status("Offline: editing local copy, changes will sync when reconnected".to_string());
+                return Ok(());
+            }
+            app.close_buffer();
+            return Err(e);
+        }
+    };
+
+    // A link by URL ID can lead to a document that's already open
+    if let Some(index) = app.buffers.position(&document.id, app.current_document.as_ref()) {
+        return switch_to_buffer(app, index).await;
+    }
+
+    // Cache metadata so the document can be reopened offline
+    if let Ok(client) = &client {
+        let cached = DocumentStore::open_default(client.base_url())
+            .and_then(|store| store.save_document(&document));
+        if let Err(e) = cached {
+            debug!("Failed to cache document metadata: {}", e);
+        }
+    }
+
+    // Links can refer to a document by its URL ID; use the full ID from here on
+    let doc_id = document.id.clone();
+    app.open_buffer();
     app.document_text = document.text.clone();
     app.current_document = Some(document);
     app.scroll_offset = 0;
+
+    // Comments and backlinks load in the background; the panes and gutter fill in when they arrive
+    if let Ok(client) = client {
+        app.users.set_client(client.clone());
+        app.attachments.set_client(client.clone());
+        app.comments = Some(comments::CommentsPanel::load(client.clone(), doc_id.clone()));
+        app.links = Some(links::LinksPanel::load(client, doc_id.clone(), &app.document_text));
+    }
     info!("Document loaded successfully");
 
-    // Start collaboration for this document
+    start_document_collaboration(app, doc_id).await;
+    Ok(())
+}
+
+/// Start collaboration for the active document
+///
+/// Never fails: without a config, token or connection the buffer stays open
+/// offline and the reason is shown in the status bar.
+async fn start_document_collaboration(app: &mut App, doc_id: String) {
+    let result = match collaboration_credentials().await {
+        Ok((api_base_url, api_token)) => app.start_collaboration(api_base_url, api_token, doc_id).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        debug!("Failed to start collaboration: {}", e);
+        app.set_status(format!("Offline: collaboration not available - {}", e));
+    }
+}
+
+/// API base URL and access token for the collaboration socket
+async fn collaboration_credentials() -> Result<(String, String)> {
     let config = Config::load()?;
     let api_base_url = config.get_api_base_url()?;
     let api_token = auth::get_access_token().await?;
+    Ok((api_base_url, api_token))
+}
 
-    if let Err(e) = app.start_collaboration(api_base_url, api_token, doc_id).await {
-        debug!("Failed to start collaboration: {}", e);
-        app.set_status(format!("Note: Collaboration not available - {}", e));
-        // Don't fail the whole operation, just log it
+/// Make another open buffer active, moving collaboration over to its document
+async fn switch_to_buffer(app: &mut App, index: usize) -> Result<()> {
+    if index == app.buffers.active() {
+        return Ok(());
+    }
+    if app.merge_view.is_some() {
+        app.set_status("Resolve the merge before switching documents".to_string());
+        return Ok(());
+    }
+
+    app.stop_collaboration().await;
+    if !app.switch_buffer(index) {
+        return Err(anyhow::anyhow!("No buffer {}", index + 1));
+    }
+    if let Some(doc_id) = app.current_document.as_ref().map(|doc| doc.id.clone()) {
+        start_document_collaboration(app, doc_id).await;
+    }
+    Ok(())
+}
+
+/// Switch to the next or previous buffer, wrapping around
+async fn cycle_buffer(app: &mut App, forward: bool) {
+    let count = app.buffers.len();
+    if count < 2 {
+        app.set_status("No other buffers open".to_string());
+        return;
+    }
+    let active = app.buffers.active();
+    let index = if forward { (active + 1) % count } else { (active + count - 1) % count };
+    if let Err(e) = switch_to_buffer(app, index).await {
+        app.set_status(format!("Error switching buffer: {}", e));
+    }
+}
+
+/// Close the active buffer, unless it has unsaved changes and `force` isn't set
+async fn close_active_buffer(app: &mut App, force: bool) {
+    if app.current_document.is_none() {
+        app.set_status("No buffer to close".to_string());
+        return;
+    }
+    if app.is_dirty() && !force {
+        app.set_status("Unsaved changes (use :bd! to discard them)".to_string());
+        return;
+    }
+
+    app.stop_collaboration().await;
+    app.merge_view = None;
+    app.close_buffer();
+    if let Some(doc_id) = app.current_document.as_ref().map(|doc| doc.id.clone()) {
+        start_document_collaboration(app, doc_id).await;
+    }
+}
+
+/// Quit, asking first if any buffer has unsaved changes
+fn request_quit(app: &mut App) {
+    if !app.any_dirty() {
+        app.should_quit = true;
+        return;
+    }
+    let dirty: Vec<String> = app
+        .buffers
+        .tabs(app.current_document.as_ref().map_or("", |doc| doc.title.as_str()), app.is_dirty())
+        .into_iter()
+        .filter(|(_, dirty)| *dirty)
+        .map(|(title, _)| title)
+        .collect();
+    app.modal.show_confirmation(
+        "Unsaved Changes".to_string(),
+        format!("Unsaved changes in: {}\n\nQuit anyway? (y/n)", dirty.join(", ")),
+        actions::Action::Quit,
+    );
+}
+
+/// Run a command typed on the `:` command line
+async fn run_command(app: &mut App, input: &str) -> Result<()> {
+    use buffers::SplitDirection;
+
+    let input = input.trim();
+    let (command, argument) = match input.split_once(char::is_whitespace) {
+        Some((command, argument)) => (command, argument.trim()),
+        None => (input, ""),
+    };
+
+    match command {
+        "" => {}
+        "ls" | "buffers" => {
+            let active_title = app.current_document.as_ref().map_or("", |doc| doc.title.as_str());
+            let active = app.buffers.active();
+            let items: Vec<String> = app
+                .buffers
+                .tabs(active_title, app.is_dirty())
+                .into_iter()
+                .enumerate()
+                .map(|(i, (title, dirty))| {
+                    let marker = if i == active {
+                        "%"
+                    } else if app.split.is_some_and(|split| split.buffer == i) {
+                        "="
+                    } else {
+                        " "
+                    };
+                    format!("{:>3} {} {}{}", i + 1, marker, title, if dirty { " [+]" } else { "" })
+                })
+                .collect();
+            app.modal.show_list("Buffers (:b N to switch)".to_string(), items);
+        }
+        "b" | "buffer" => match find_buffer(app, argument) {
+            Some(index) => switch_to_buffer(app, index).await?,
+            None => app.set_status(format!("No matching buffer: {}", argument)),
+        },
+        "bn" | "bnext" => cycle_buffer(app, true).await,
+        "bp" | "bprevious" => cycle_buffer(app, false).await,
+        "bd" | "bdelete" => close_active_buffer(app, false).await,
+        "bd!" | "bdelete!" => close_active_buffer(app, true).await,
+        "sp" | "split" | "vs" | "vsplit" => {
+            let direction = if command.starts_with('v') {
+                SplitDirection::Vertical
+            } else {
+                SplitDirection::Horizontal
+            };
+            // Without an argument, split with the neighbouring buffer
+            let index = if argument.is_empty() {
+                let active = app.buffers.active();
+                (0..app.buffers.len()).filter(|&i| i != active).min_by_key(|&i| i.abs_diff(active))
+            } else {
+                find_buffer(app, argument)
+            };
+            match index {
+                Some(index) if app.open_split(index, direction) => {}
+                _ => app.set_status("Open another document to split with".to_string()),
+            }
+        }
+        "only" | "close" => app.split = None,
+        "w" | "write" => {
+            if app.editor_mode == EditorMode::Edit {
+                save_document_changes(app).await?;
+            }
+        }
+        "q" | "quit" | "qa" | "qall" => request_quit(app),
+        "q!" | "quit!" | "qa!" | "qall!" => app.should_quit = true,
+        _ => app.set_status(format!("Not a command: {}", command)),
     }
 
     Ok(())
 }
 
+/// A buffer by number (1-based) or by part of its title
+fn find_buffer(app: &App, argument: &str) -> Option<usize> {
+    if let Ok(number) = argument.parse::<usize>() {
+        return (1..=app.buffers.len()).contains(&number).then(|| number - 1);
+    }
+    if argument.is_empty() {
+        return None;
+    }
+    let needle = argument.to_lowercase();
+    let active_title = app.current_document.as_ref().map_or("", |doc| doc.title.as_str());
+    app.buffers
+        .tabs(active_title, false)
+        .iter()
+        .position(|(title, _)| title.to_lowercase().contains(&needle))
+}
+
@@ -2,12 +2,19 @@ use crate::actions::Action;
 use ratatui::widgets::ListState;
 
 /// Different types of modal dialogs
+#[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum ModalType {
     None,
     ActionMenu,
-    Help,
-    CommandInput,
+    /// Key bindings, scrolled down by `scroll` lines
+    Help {
+        scroll: u16,
+    },
+    /// The `:` command line
+    CommandInput {
+        value: String,
+    },
     TextInput {
         title: String,
         prompt: String,
@@ -34,6 +41,11 @@ pub enum ModalType {
         title: String,
         message: String,
     },
+    /// Choice of opening or saving a linked attachment
+    Attachment {
+        id: String,
+        name: String,
+    },
     AuthSetup {
         selected: usize,
     },
@@ -67,6 +79,7 @@ pub struct Modal {
     pub menu_state: ListState,
 }
 
+#[allow(dead_code)]
 impl Modal {
     pub fn new() -> Self {
         Self {
@@ -92,11 +105,27 @@ impl Modal {
     }
 
     pub fn show_help(&mut self) {
-        self.modal_type = ModalType::Help;
+        self.modal_type = ModalType::Help { scroll: 0 };
+    }
+
+    /// Scroll the help up (negative) or down
+    pub fn scroll_help(&mut self, lines: i32) {
+        if let ModalType::Help { scroll } = &mut self.modal_type {
+            *scroll = (*scroll as i32 + lines).max(0) as u16;
+        }
     }
 
     pub fn show_command_input(&mut self) {
-        self.modal_type = ModalType::CommandInput;
+        self.modal_type = ModalType::CommandInput { value: String::new() };
+    }
+
+    /// Get the text typed on the command line
+    pub fn get_command_input(&self) -> Option<String> {
+        if let ModalType::CommandInput { value } = &self.modal_type {
+            Some(value.clone())
+        } else {
+            None
+        }
     }
 
     pub fn show_text_input(&mut self, title: String, prompt: String, action: Action) {
@@ -141,6 +170,10 @@ impl Modal {
         self.modal_type = ModalType::Message { title, message };
     }
 
+    pub fn show_attachment(&mut self, id: String, name: String) {
+        self.modal_type = ModalType::Attachment { id, name };
+    }
+
     pub fn show_auth_setup(&mut self) {
         self.modal_type = ModalType::AuthSetup { selected: 0 };
     }
@@ -168,7 +201,7 @@ impl Modal {
     /// Handle character input for text fields
     pub fn handle_char(&mut self, c: char) {
         match &mut self.modal_type {
-            ModalType::TextInput { value, .. } => {
+            ModalType::TextInput { value, .. } | ModalType::CommandInput { value } => {
                 value.push(c);
             }
             ModalType::MultiInput { fields, current_field, .. } => {
@@ -183,7 +216,7 @@ impl Modal {
     /// Handle backspace in text fields
     pub fn handle_backspace(&mut self) {
         match &mut self.modal_type {
-            ModalType::TextInput { value, .. } => {
+            ModalType::TextInput { value, .. } | ModalType::CommandInput { value } => {
                 value.pop();
             }
             ModalType::MultiInput { fields, current_field, .. } => {
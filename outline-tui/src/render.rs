@@ -0,0 +1,1011 @@
+//! Rendering of Outline's Markdown for the document viewer
+//!
+//! Understands the parts of Outline's dialect that plain Markdown renderers show as
+//! syntax: `:::info` notices, checklists and `@[Name](mention://…)` mentions, along with
+//! tables, highlighted code and attached images. Every block takes the same number of lines at any width
+//! (tables shrink their columns instead of wrapping), so line numbers found here stay
+//! valid when the pane is resized.
+
+use crate::graphics;
+use crate::theme::Theme;
+use crate::toc::TocEntry;
+use outline_api::attachment_id;
+use outline_api::markdown::{Link, LinkTarget};
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::LazyLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+use unicode_width::UnicodeWidthChar;
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static CODE_THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+/// Narrowest a table column is shrunk to
+const MIN_COLUMN_WIDTH: usize = 3;
+
+/// Tallest an image is drawn, in lines
+const MAX_IMAGE_ROWS: usize = 24;
+
+/// A rendered document, and where things ended up in it
+#[derive(Debug, Default)]
+pub struct Rendered {
+    pub text: Text<'static>,
+    pub headings: Vec<TocEntry>,
+    /// Links and mentions, in document order
+    pub links: Vec<RenderedLink>,
+    /// Checklist items, in document order
+    pub tasks: Vec<Task>,
+    /// Attached images, in document order
+    pub images: Vec<RenderedImage>,
+}
+
+/// A link or mention in the rendered text
+#[derive(Debug, Clone)]
+pub struct RenderedLink {
+    /// Byte offset of the link in the Markdown
+    pub offset: usize,
+    pub line: usize,
+    /// Spans of the line showing the link (empty for links inside tables)
+    pub spans: Range<usize>,
+}
+
+/// A checklist item
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub line: usize,
+    /// Display column of the checkbox
+    pub column: usize,
+    /// Byte range of the `[ ]` or `[x]` marker in the Markdown
+    pub marker: Range<usize>,
+    pub checked: bool,
+}
+
+/// An attached image, drawn over blank lines left for it
+#[derive(Debug, Clone)]
+pub struct RenderedImage {
+    /// Attachment ID
+    pub id: String,
+    /// First of the lines left for the image
+    pub line: usize,
+    /// Display column of the image's left edge
+    pub column: usize,
+    /// Size in cells; no lines are left (`rows` is 0) while the image's size isn't known
+    pub cols: usize,
+    pub rows: usize,
+}
+
+impl Task {
+    /// The Markdown with this item ticked or unticked
+    pub fn toggle(&self, markdown: &str) -> String {
+        let marker = if self.checked { "[ ]" } else { "[x]" };
+        format!("{}{}{}", &markdown[..self.marker.start], marker, &markdown[self.marker.end..])
+    }
+}
+
+/// Render Markdown for a pane `width` columns wide
+///
+/// Mentions of users in `users` (by ID) show their current name rather than the one
+/// written in the text. Attached images whose pixel size is in `images` get room to be
+/// drawn in; others show their alt text.
+pub fn render(
+    markdown: &str,
+    width: usize,
+    theme: &Theme,
+    users: &HashMap<String, String>,
+    images: &HashMap<String, (u32, u32)>,
+) -> Rendered {
+    let mut renderer = Renderer::new(width, *theme, users, images);
+    for segment in segments(markdown) {
+        match segment.notice {
+            Some(kind) => renderer.notice(kind, markdown, segment.range),
+            None => renderer.blocks(markdown, segment.range),
+        }
+    }
+    renderer.end_line();
+    renderer.out
+}
+
+/// A `:::kind` notice, or the Markdown between notices
+struct Segment<'a> {
+    notice: Option<&'a str>,
+    range: Range<usize>,
+}
+
+/// Split Markdown at the `:::kind` and `:::` lines around notices
+///
+/// Notices are cut out before parsing since CommonMark would read their fences as
+/// paragraph text. Fences inside code blocks are left alone.
+fn segments(markdown: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut notice: Option<&str> = None;
+    let mut fence: Option<&str> = None;
+    let mut start = 0;
+    let mut offset = 0;
+
+    for line in markdown.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|marker| trimmed.starts_with(marker)) {
+            fence = Some(marker);
+            continue;
+        }
+
+        match (notice, trimmed.strip_prefix(":::")) {
+            (None, Some(kind)) if !kind.trim().is_empty() => {
+                segments.push(Segment { notice: None, range: start..line_start });
+                notice = Some(kind.trim());
+                start = offset;
+            }
+            (Some(kind), Some(rest)) if rest.trim().is_empty() => {
+                segments.push(Segment { notice: Some(kind), range: start..line_start });
+                notice = None;
+                start = offset;
+            }
+            _ => {}
+        }
+    }
+
+    // A notice that isn't closed runs to the end
+    segments.push(Segment { notice, range: start..markdown.len() });
+    segments
+}
+
+/// Prefix drawn at the start of each line inside a block quote, notice or list item
+struct Container {
+    prefix: Span<'static>,
+    /// Drawn instead of `prefix` on the first line, e.g. a list bullet
+    first: Option<Span<'static>>,
+    /// Whether this is a ticked checklist item, whose text is struck through
+    checked: bool,
+}
+
+impl Container {
+    fn new(prefix: Span<'static>) -> Self {
+        Self { prefix, first: None, checked: false }
+    }
+}
+
+/// A link being rendered
+struct LinkState {
+    offset: usize,
+    line: usize,
+    span: usize,
+    /// The mentioned user, for mentions
+    user: Option<String>,
+    /// Link text of a mention, shown if the user's name isn't known
+    text: String,
+}
+
+/// A table being collected, drawn once its column widths are known
+#[derive(Default)]
+struct Table {
+    alignments: Vec<Alignment>,
+    /// Rows of cells, the header row first
+    rows: Vec<Vec<Vec<Span<'static>>>>,
+    /// Links in the table, by row
+    links: Vec<(usize, usize)>,
+}
+
+impl Table {
+    /// The cell being filled
+    fn current_cell(&mut self) -> Option<&mut Vec<Span<'static>>> {
+        self.rows.last_mut().and_then(|row| row.last_mut())
+    }
+
+    fn push(&mut self, span: Span<'static>) {
+        if let Some(cell) = self.current_cell() {
+            cell.push(span);
+        }
+    }
+}
+
+struct Renderer<'a> {
+    width: usize,
+    theme: Theme,
+    users: &'a HashMap<String, String>,
+    images: &'a HashMap<String, (u32, u32)>,
+    out: Rendered,
+    /// Spans of the line being built, `None` until something is put on it
+    line: Option<Vec<Span<'static>>>,
+    containers: Vec<Container>,
+    styles: Vec<Style>,
+    /// Next number of each enclosing list, `None` for bullet lists
+    lists: Vec<Option<u64>>,
+    /// Whether a blank line goes before the next block
+    separate: bool,
+    /// Level, title and line of the heading being rendered
+    heading: Option<(usize, String, usize)>,
+    /// Language and text of the code block being collected
+    code: Option<(String, String)>,
+    link: Option<LinkState>,
+    /// Link and alt text of the image being rendered
+    image: Option<(String, String)>,
+    table: Option<Table>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(
+        width: usize,
+        theme: Theme,
+        users: &'a HashMap<String, String>,
+        images: &'a HashMap<String, (u32, u32)>,
+    ) -> Self {
+        Self {
+            width,
+            theme,
+            users,
+            images,
+            out: Rendered::default(),
+            line: None,
+            containers: Vec::new(),
+            styles: Vec::new(),
+            lists: Vec::new(),
+            separate: false,
+            heading: None,
+            code: None,
+            link: None,
+            image: None,
+            table: None,
+        }
+    }
+
+    /// Render a stretch of ordinary Markdown
+    fn blocks(&mut self, markdown: &str, range: Range<usize>) {
+        let options = Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH;
+        let base = range.start;
+        for (event, span) in Parser::new_ext(&markdown[range], options).into_offset_iter() {
+            self.event(event, base + span.start..base + span.end);
+        }
+        self.end_line();
+    }
+
+    /// Render a notice as a coloured panel with a title line
+    fn notice(&mut self, kind: &str, markdown: &str, range: Range<usize>) {
+        let (title, color) = match kind {
+            "warning" => ("Warning", self.theme.highlight),
+            "tip" => ("Tip", self.theme.success),
+            "success" => ("Success", self.theme.success),
+            _ => ("Info", self.theme.link),
+        };
+
+        self.start_block();
+        self.containers.push(Container::new(Span::styled("▌ ", Style::default().fg(color))));
+        self.current_line()
+            .push(Span::styled(title, Style::default().fg(color).add_modifier(Modifier::BOLD)));
+        self.end_line();
+        self.blocks(markdown, range);
+        self.containers.pop();
+        self.separate = true;
+    }
+
+    fn event(&mut self, event: Event, range: Range<usize>) {
+        match event {
+            Event::Start(tag) => self.start(tag, range),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => self.text(&text),
+            Event::Code(code) => {
+                if let Some((_, title, _)) = &mut self.heading {
+                    title.push_str(&code);
+                }
+                let style = self.style().fg(self.theme.accent);
+                self.push(Span::styled(code.into_string(), style));
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                let style = Style::default().fg(self.theme.dim);
+                self.text_with_style(&html, style);
+            }
+            Event::SoftBreak | Event::HardBreak => match &mut self.table {
+                Some(table) => table.push(Span::raw(" ")),
+                None => self.end_line(),
+            },
+            Event::Rule => {
+                self.start_block();
+                let rule = "─".repeat(self.width.saturating_sub(self.indent()).max(1));
+                let rule = Span::styled(rule, Style::default().fg(self.theme.dim));
+                self.current_line().push(rule);
+                self.end_block();
+            }
+            Event::TaskListMarker(checked) => self.task(checked, range),
+            Event::FootnoteReference(name) => self.text(&format!("[^{}]", name)),
+            Event::InlineMath(math) | Event::DisplayMath(math) => self.text(&math),
+        }
+    }
+
+    fn start(&mut self, tag: Tag, range: Range<usize>) {
+        match tag {
+            Tag::Paragraph | Tag::HtmlBlock => self.start_block(),
+            Tag::Heading { level, .. } => {
+                self.start_block();
+                let style = match level {
+                    HeadingLevel::H1 => Style::default()
+                        .fg(self.theme.accent)
+                        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                    HeadingLevel::H2 => Style::default().fg(self.theme.accent).add_modifier(Modifier::BOLD),
+                    _ => Style::default().fg(self.theme.text).add_modifier(Modifier::BOLD),
+                };
+                self.push_style(style);
+                self.heading = Some((level as usize, String::new(), self.line_index()));
+            }
+            Tag::BlockQuote(_) => {
+                self.start_block();
+                self.containers
+                    .push(Container::new(Span::styled("│ ", Style::default().fg(self.theme.dim))));
+                self.push_style(Style::default().fg(self.theme.muted));
+            }
+            Tag::CodeBlock(kind) => {
+                self.start_block();
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or("").to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((language, String::new()));
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.start_block();
+                } else {
+                    self.end_line();
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.end_line();
+                let depth = self.lists.len();
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ if depth.is_multiple_of(2) => "◦ ".to_string(),
+                    _ => "• ".to_string(),
+                };
+                let mut item = Container::new(Span::raw(" ".repeat(marker.chars().count())));
+                item.first = Some(Span::styled(marker, Style::default().fg(self.theme.accent)));
+                self.containers.push(item);
+            }
+            Tag::Table(alignments) => {
+                self.start_block();
+                self.table = Some(Table { alignments, ..Table::default() });
+            }
+            Tag::TableHead | Tag::TableRow => {
+                if let Some(table) = &mut self.table {
+                    table.rows.push(Vec::new());
+                }
+            }
+            Tag::TableCell => {
+                if let Some(row) = self.table.as_mut().and_then(|table| table.rows.last_mut()) {
+                    row.push(Vec::new());
+                }
+            }
+            Tag::Emphasis => self.push_style(Style::default().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self.push_style(Style::default().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => self.push_style(Style::default().add_modifier(Modifier::CROSSED_OUT)),
+            Tag::Link { dest_url, .. } => {
+                let link = Link { text: String::new(), url: dest_url.to_string(), line: 0, range: range.clone() };
+                let user = match link.target("") {
+                    LinkTarget::User(id) => Some(id),
+                    _ => None,
+                };
+                if user.is_none() {
+                    self.push_style(Style::default().fg(self.theme.link).add_modifier(Modifier::UNDERLINED));
+                }
+                if let Some(table) = &mut self.table {
+                    table.links.push((table.rows.len().saturating_sub(1), range.start));
+                }
+                self.link = Some(LinkState {
+                    offset: range.start,
+                    line: self.line_index(),
+                    span: self.span_index(),
+                    user,
+                    text: String::new(),
+                });
+            }
+            Tag::Image { dest_url, .. } => self.image = Some((dest_url.to_string(), String::new())),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph | TagEnd::HtmlBlock => self.end_block(),
+            TagEnd::Heading(_) => {
+                self.styles.pop();
+                if let Some((level, title, line)) = self.heading.take() {
+                    self.out.headings.push(TocEntry { level, title: title.trim().to_string(), line });
+                }
+                self.end_block();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.end_line();
+                self.containers.pop();
+                self.styles.pop();
+                self.separate = true;
+            }
+            TagEnd::CodeBlock => {
+                if let Some((language, code)) = self.code.take() {
+                    self.code_block(&language, &code);
+                }
+                self.end_block();
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.end_block();
+                } else {
+                    self.end_line();
+                }
+            }
+            TagEnd::Item => {
+                self.end_line();
+                if self.containers.pop().is_some_and(|item| item.checked) {
+                    self.styles.pop();
+                }
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.draw_table(table);
+                }
+                self.end_block();
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => {
+                self.styles.pop();
+            }
+            TagEnd::Link => self.end_link(),
+            TagEnd::Image => {
+                if let Some((url, alt)) = self.image.take() {
+                    self.image(&url, &alt);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end_link(&mut self) {
+        let Some(link) = self.link.take() else {
+            return;
+        };
+        let start = match link.user {
+            Some(id) => {
+                // Outline writes the `@` before the link
+                self.strip_at();
+                let name = self.users.get(&id).cloned().unwrap_or(link.text);
+                let style = Style::default().fg(self.theme.mention).add_modifier(Modifier::BOLD);
+                self.push(Span::styled(format!("@{}", name), style));
+                self.span_index().saturating_sub(1)
+            }
+            None => {
+                self.styles.pop();
+                link.span
+            }
+        };
+
+        // Links in tables are placed when the table is drawn
+        if self.table.is_none() {
+            let end = if self.line_index() == link.line { self.span_index() } else { start };
+            self.out.links.push(RenderedLink { offset: link.offset, line: link.line, spans: start..end });
+        }
+    }
+
+    /// Leave blank lines for an attached image of known size, or show its alt text
+    fn image(&mut self, url: &str, alt: &str) {
+        let id = attachment_id(url).filter(|_| url.contains("attachments.redirect"));
+        // Images inside tables, headings and links stay on their line
+        let inline = self.table.is_some() || self.heading.is_some() || self.link.is_some();
+        let size = id.filter(|_| !inline).and_then(|id| self.images.get(id)).copied();
+
+        let Some((id, (width, height))) = id.zip(size) else {
+            let label = if alt.is_empty() { "[image]".to_string() } else { format!("[image: {}]", alt) };
+            let style = Style::default().fg(self.theme.dim).add_modifier(Modifier::ITALIC);
+            self.push(Span::styled(label, style));
+            if let Some(id) = id.filter(|_| !inline) {
+                let line = self.line_index();
+                self.out.images.push(RenderedImage { id: id.to_string(), line, column: 0, cols: 0, rows: 0 });
+            }
+            return;
+        };
+
+        // Keep the image's shape in cells, within the pane's width and MAX_IMAGE_ROWS
+        let (cell_width, cell_height) = graphics::cell_size();
+        let (cell_width, cell_height) = (cell_width as usize, cell_height as usize);
+        let (width, height) = (width as usize, height as usize);
+        let available = self.width.saturating_sub(self.indent()).max(1);
+        let mut cols = width.div_ceil(cell_width).clamp(1, available);
+        let mut rows = (cols * cell_width * height).div_ceil(width * cell_height).max(1);
+        if rows > MAX_IMAGE_ROWS {
+            rows = MAX_IMAGE_ROWS;
+            cols = (rows * cell_height * width).div_ceil(height * cell_width).clamp(1, available);
+        }
+
+        // Text before the image keeps its line
+        if self.line.as_ref().is_some_and(|line| line.len() > self.containers.len()) {
+            self.end_line();
+        }
+        let line = self.line_index();
+        let column = self.indent();
+        for _ in 0..rows {
+            self.current_line();
+            self.end_line();
+        }
+        self.out.images.push(RenderedImage { id: id.to_string(), line, column, cols, rows });
+    }
+
+    /// Turn the bullet of the current list item into a checkbox
+    fn task(&mut self, checked: bool, range: Range<usize>) {
+        let column = self.containers.iter().rev().skip(1).map(|c| c.prefix.width()).sum();
+        let line = self.line_index();
+        let (checkbox, style) = if checked {
+            ("☑ ", Style::default().fg(self.theme.success))
+        } else {
+            ("☐ ", Style::default().fg(self.theme.accent))
+        };
+
+        let Some(item) = self.containers.last_mut() else {
+            return;
+        };
+        item.first = Some(Span::styled(checkbox, style));
+        item.checked = checked;
+        if checked {
+            self.push_style(Style::default().fg(self.theme.dim).add_modifier(Modifier::CROSSED_OUT));
+        }
+        self.out.tasks.push(Task { line, column, marker: range, checked });
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some((_, code)) = &mut self.code {
+            code.push_str(text);
+            return;
+        }
+        if let Some((_, alt)) = &mut self.image {
+            alt.push_str(text);
+            return;
+        }
+        if let Some((_, title, _)) = &mut self.heading {
+            title.push_str(text);
+        }
+        if let Some(link) = self.link.as_mut().filter(|link| link.user.is_some()) {
+            link.text.push_str(text);
+            return;
+        }
+        self.text_with_style(text, self.style());
+    }
+
+    fn text_with_style(&mut self, text: &str, style: Style) {
+        for (i, piece) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.end_line();
+            }
+            if !piece.is_empty() {
+                self.push(Span::styled(piece.to_string(), style));
+            }
+        }
+    }
+
+    /// Highlight a code block with syntect, or show it plain for unknown languages
+    fn code_block(&mut self, language: &str, code: &str) {
+        let syntax = (!language.is_empty())
+            .then(|| SYNTAXES.find_syntax_by_token(language))
+            .flatten();
+        let code_theme = CODE_THEMES.themes.get(self.theme.code);
+        let plain = Style::default().fg(self.theme.muted);
+
+        let mut highlighter = syntax.zip(code_theme).map(|(syntax, theme)| HighlightLines::new(syntax, theme));
+        for line in LinesWithEndings::from(code) {
+            let highlighted = highlighter.as_mut().and_then(|h| h.highlight_line(line, &SYNTAXES).ok());
+            let spans: Vec<Span<'static>> = match highlighted {
+                Some(ranges) => ranges
+                    .into_iter()
+                    .map(|(style, text)| {
+                        let color = Color::Rgb(style.foreground.r, style.foreground.g, style.foreground.b);
+                        Span::styled(text.trim_end_matches(['\n', '\r']).to_string(), Style::default().fg(color))
+                    })
+                    .collect(),
+                None => vec![Span::styled(line.trim_end_matches(['\n', '\r']).to_string(), plain)],
+            };
+            let current = self.current_line();
+            current.push(Span::raw("  "));
+            current.extend(spans);
+            self.end_line();
+        }
+    }
+
+    /// Draw a table with box-drawing borders, shrinking the widest columns to fit
+    fn draw_table(&mut self, table: Table) {
+        let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        let natural: Vec<usize> = (0..columns)
+            .map(|c| {
+                let widths = table.rows.iter().filter_map(|row| row.get(c)).map(|cell| cell_width(cell));
+                widths.max().unwrap_or(0).max(1)
+            })
+            .collect();
+        // A border before each column and after the last, and a space either side of the text
+        let available = self.width.saturating_sub(self.indent() + 3 * columns + 1);
+        let widths = fit_columns(&natural, available);
+        let border = Style::default().fg(self.theme.dim);
+
+        self.table_rule(&widths, ['┌', '┬', '┐']);
+        let mut row_lines = Vec::new();
+        let rows = table.rows.len();
+        for (i, row) in table.rows.into_iter().enumerate() {
+            row_lines.push(self.line_index());
+            let mut cells = row.into_iter();
+            let mut spans = vec![Span::styled("│", border)];
+            for (c, &width) in widths.iter().enumerate() {
+                let mut cell = cells.next().unwrap_or_default();
+                if i == 0 {
+                    for span in &mut cell {
+                        span.style = span.style.add_modifier(Modifier::BOLD);
+                    }
+                }
+                let alignment = table.alignments.get(c).copied().unwrap_or(Alignment::None);
+                spans.push(Span::raw(" "));
+                spans.extend(fit_cell(cell, width, alignment));
+                spans.push(Span::styled(" │", border));
+            }
+            self.current_line().extend(spans);
+            self.end_line();
+            if i == 0 && rows > 1 {
+                self.table_rule(&widths, ['├', '┼', '┤']);
+            }
+        }
+        self.table_rule(&widths, ['└', '┴', '┘']);
+
+        for (row, offset) in table.links {
+            if let Some(&line) = row_lines.get(row) {
+                self.out.links.push(RenderedLink { offset, line, spans: 0..0 });
+            }
+        }
+    }
+
+    fn table_rule(&mut self, widths: &[usize], [left, middle, right]: [char; 3]) {
+        let mut rule = String::from(left);
+        for (i, width) in widths.iter().enumerate() {
+            if i > 0 {
+                rule.push(middle);
+            }
+            rule.push_str(&"─".repeat(width + 2));
+        }
+        rule.push(right);
+        let style = Style::default().fg(self.theme.dim);
+        self.current_line().push(Span::styled(rule, style));
+        self.end_line();
+    }
+
+    /// Remove an `@` just before the cursor
+    fn strip_at(&mut self) {
+        let spans = match &mut self.table {
+            Some(table) => table.current_cell(),
+            None => self.line.as_mut(),
+        };
+        let Some(spans) = spans else {
+            return;
+        };
+        if let Some(last) = spans.last_mut().filter(|span| span.content.ends_with('@')) {
+            let content = &last.content[..last.content.len() - 1];
+            if content.is_empty() {
+                spans.pop();
+            } else {
+                last.content = content.to_string().into();
+            }
+        }
+    }
+
+    fn style(&self) -> Style {
+        self.styles.last().copied().unwrap_or_default()
+    }
+
+    fn push_style(&mut self, style: Style) {
+        self.styles.push(self.style().patch(style));
+    }
+
+    fn push(&mut self, span: Span<'static>) {
+        match &mut self.table {
+            Some(table) => table.push(span),
+            None => self.current_line().push(span),
+        }
+    }
+
+    /// The line being built, started with the prefixes of the enclosing blocks if need be
+    fn current_line(&mut self) -> &mut Vec<Span<'static>> {
+        if self.line.is_none() {
+            let prefix = self
+                .containers
+                .iter_mut()
+                .map(|container| container.first.take().unwrap_or_else(|| container.prefix.clone()))
+                .collect();
+            self.line = Some(prefix);
+        }
+        self.line.get_or_insert_with(Vec::new)
+    }
+
+    fn end_line(&mut self) {
+        if let Some(spans) = self.line.take() {
+            self.out.text.lines.push(Line::from(spans));
+        }
+    }
+
+    /// Index of the line being built (or the next one)
+    fn line_index(&self) -> usize {
+        self.out.text.lines.len()
+    }
+
+    /// Index the next span on the current line will get
+    fn span_index(&self) -> usize {
+        self.line.as_ref().map_or(self.containers.len(), Vec::len)
+    }
+
+    /// Width taken by the prefixes of the enclosing blocks
+    fn indent(&self) -> usize {
+        self.containers.iter().map(|container| container.prefix.width()).sum()
+    }
+
+    fn start_block(&mut self) {
+        self.end_line();
+        if self.separate {
+            // Blank lines keep the bars of enclosing quotes and notices, but not list bullets
+            let prefix = self.containers.iter().map(|container| container.prefix.clone()).collect::<Vec<_>>();
+            self.out.text.lines.push(Line::from(prefix));
+        }
+        self.separate = false;
+    }
+
+    fn end_block(&mut self) {
+        self.end_line();
+        self.separate = true;
+    }
+}
+
+fn cell_width(cell: &[Span]) -> usize {
+    cell.iter().map(Span::width).sum()
+}
+
+/// Shrink column widths until they fit, taking from the widest column each time
+fn fit_columns(natural: &[usize], available: usize) -> Vec<usize> {
+    let mut widths = natural.to_vec();
+    let mut total: usize = widths.iter().sum();
+    while total > available {
+        let Some((widest, &width)) = widths.iter().enumerate().max_by_key(|(_, width)| **width) else {
+            break;
+        };
+        if width <= MIN_COLUMN_WIDTH {
+            break;
+        }
+        widths[widest] -= 1;
+        total -= 1;
+    }
+    widths
+}
+
+/// Pad or cut a cell's text to a column width
+fn fit_cell(cell: Vec<Span<'static>>, width: usize, alignment: Alignment) -> Vec<Span<'static>> {
+    let mut spans = truncate(cell, width);
+    let pad = width.saturating_sub(cell_width(&spans));
+    let (left, right) = match alignment {
+        Alignment::Right => (pad, 0),
+        Alignment::Center => (pad / 2, pad - pad / 2),
+        Alignment::Left | Alignment::None => (0, pad),
+    };
+    if left > 0 {
+        spans.insert(0, Span::raw(" ".repeat(left)));
+    }
+    if right > 0 {
+        spans.push(Span::raw(" ".repeat(right)));
+    }
+    spans
+}
+
+/// Cut spans down to a display width, ending with `…` where something was cut
+fn truncate(spans: Vec<Span<'static>>, width: usize) -> Vec<Span<'static>> {
+    if cell_width(&spans) <= width {
+        return spans;
+    }
+
+    let mut room = width.saturating_sub(1);
+    let mut cut = Vec::new();
+    for span in spans {
+        let mut content = String::new();
+        let mut whole = true;
+        for ch in span.content.chars() {
+            let char_width = ch.width().unwrap_or(0);
+            if char_width > room {
+                whole = false;
+                break;
+            }
+            room -= char_width;
+            content.push(ch);
+        }
+        cut.push(Span::styled(content, span.style));
+        if !whole {
+            cut.push(Span::styled("…", span.style));
+            break;
+        }
+    }
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unicode_width::UnicodeWidthStr;
+
+    fn render_with(markdown: &str, width: usize, users: &[(&str, &str)]) -> Rendered {
+        let users = users.iter().map(|(id, name)| (id.to_string(), name.to_string())).collect();
+        render(markdown, width, &Theme::default(), &users, &HashMap::new())
+    }
+
+    fn lines(rendered: &Rendered) -> Vec<String> {
+        rendered
+            .text
+            .lines
+            .iter()
+            .map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect())
+            .collect()
+    }
+
+    fn segment_parts(markdown: &str) -> Vec<(Option<&str>, &str)> {
+        segments(markdown).into_iter().map(|s| (s.notice, &markdown[s.range])).collect()
+    }
+
+    #[test]
+    fn test_segments() {
+        assert_eq!(segment_parts("Just text\n"), vec![(None, "Just text\n")]);
+
+        let markdown = "Before\n:::info\nHello\n:::\nAfter\n";
+        assert_eq!(
+            segment_parts(markdown),
+            vec![(None, "Before\n"), (Some("info"), "Hello\n"), (None, "After\n")]
+        );
+
+        // Kinds are trimmed, and a bare `:::` outside a notice is ordinary text
+        assert_eq!(
+            segment_parts("::: warning \nCareful\n:::\n:::\n"),
+            vec![(None, ""), (Some("warning"), "Careful\n"), (None, ":::\n")]
+        );
+
+        // An unclosed notice runs to the end
+        assert_eq!(segment_parts(":::tip\nNo end"), vec![(None, ""), (Some("tip"), "No end")]);
+    }
+
+    #[test]
+    fn test_segments_skip_code_fences() {
+        let markdown = "```\n:::info\n```\n~~~md\n:::\n~~~\n";
+        assert_eq!(segment_parts(markdown), vec![(None, markdown)]);
+
+        // A fence inside a notice doesn't end it early
+        let markdown = ":::info\n```\n:::\n```\n:::\n";
+        assert_eq!(segment_parts(markdown), vec![(None, ""), (Some("info"), "```\n:::\n```\n"), (None, "")]);
+    }
+
+    #[test]
+    fn test_notices() {
+        let rendered = render_with(":::warning\nCareful **now**\n:::\nAfter\n", 40, &[]);
+        assert_eq!(lines(&rendered), vec!["▌ Warning", "▌ Careful now", "", "After"]);
+
+        // Unknown kinds fall back to an info panel; blank lines inside keep the bar
+        let rendered = render_with(":::note\nOne\n\nTwo\n:::\n", 40, &[]);
+        assert_eq!(lines(&rendered), vec!["▌ Info", "▌ One", "▌ ", "▌ Two"]);
+
+        let rendered = render_with(":::tip\n- [ ] inside\n:::\n", 40, &[]);
+        assert_eq!(lines(&rendered), vec!["▌ Tip", "▌ ☐ inside"]);
+    }
+
+    #[test]
+    fn test_table_columns_with_wide_characters() {
+        let markdown = "| 名前 | Note |\n|---|:-:|\n| 日本語テキスト | ok |\n| a | 絵文字🙂 |\n";
+
+        for width in [80, 24, 16] {
+            let rendered = render_with(markdown, width, &[]);
+            let shown = lines(&rendered);
+            assert_eq!(shown.len(), 6, "{}: {:#?}", width, shown);
+
+            // Every row lines up with the borders, within the pane
+            let table_width = shown[0].width();
+            for line in &shown {
+                assert_eq!(line.width(), table_width, "{}: {:#?}", width, shown);
+            }
+            assert!(table_width <= width, "{}: {:#?}", width, shown);
+        }
+
+        let shown = lines(&render_with(markdown, 80, &[]));
+        assert_eq!(shown[0], "┌────────────────┬──────────┐");
+        assert_eq!(shown[3], "│ 日本語テキスト │    ok    │");
+
+        // Shrunk columns cut at a character boundary and mark the cut
+        let shown = lines(&render_with(markdown, 16, &[]));
+        assert!(shown[3].contains('…'), "{:#?}", shown);
+    }
+
+    #[test]
+    fn test_fit_columns() {
+        assert_eq!(fit_columns(&[4, 6], 20), vec![4, 6]);
+        assert_eq!(fit_columns(&[10, 4], 8), vec![4, 4]);
+        // Columns aren't shrunk below the minimum, even if the table doesn't fit
+        assert_eq!(fit_columns(&[5, 5], 2), vec![MIN_COLUMN_WIDTH, MIN_COLUMN_WIDTH]);
+    }
+
+    #[test]
+    fn test_truncate_and_align_cells() {
+        let text = |spans: &[Span]| spans.iter().map(|span| span.content.as_ref()).collect::<String>();
+
+        // A wide character that doesn't fit is left out rather than split
+        let cut = truncate(vec![Span::raw("日本語")], 4);
+        assert_eq!(text(&cut), "日…");
+        assert_eq!(truncate(vec![Span::raw("abc")], 3), vec![Span::raw("abc")]);
+
+        let cut = truncate(vec![Span::raw("ab"), Span::raw("cdef")], 4);
+        assert_eq!(text(&cut), "abc…");
+
+        assert_eq!(text(&fit_cell(vec![Span::raw("ab")], 5, Alignment::Right)), "   ab");
+        assert_eq!(text(&fit_cell(vec![Span::raw("ab")], 5, Alignment::Center)), " ab  ");
+        assert_eq!(text(&fit_cell(vec![Span::raw("名")], 5, Alignment::None)), "名   ");
+    }
+
+    #[test]
+    fn test_toggle_task_maps_to_its_source_line() {
+        let markdown = "Intro\n\n- [ ] one\n- [x] two\n  - [ ] nested\n\n:::info\n- [ ] in a notice\n:::\n";
+        let rendered = render_with(markdown, 40, &[]);
+        let shown = lines(&rendered);
+        assert_eq!(rendered.tasks.len(), 4);
+
+        let expected = [
+            ("☐ one", "- [x] one"),
+            ("☑ two", "- [ ] two"),
+            ("☐ nested", "  - [x] nested"),
+            ("☐ in a notice", "- [x] in a notice"),
+        ];
+        for (task, (label, toggled)) in rendered.tasks.iter().zip(expected) {
+            assert!(shown[task.line].ends_with(label), "{:?} {:#?}", task, shown);
+            assert_eq!(shown[task.line].chars().nth(task.column), label.chars().next());
+            assert_eq!(&markdown[task.marker.clone()], if task.checked { "[x]" } else { "[ ]" });
+
+            // Only the item's own line changes
+            let after = task.toggle(markdown);
+            let changed: Vec<(&str, &str)> = markdown.lines().zip(after.lines()).filter(|(a, b)| a != b).collect();
+            assert_eq!(changed.len(), 1, "{:?}", changed);
+            assert_eq!(changed[0].1, toggled);
+        }
+    }
+
+    #[test]
+    fn test_mentions_show_current_names() {
+        let markdown = "Ask @[Jane](mention://m1/user/u1) or @[Old](mention://m2/user/u2).\n";
+        let rendered = render_with(markdown, 80, &[("u1", "Jane Doe")]);
+        assert_eq!(lines(&rendered), vec!["Ask @Jane Doe or @Old."]);
+
+        // Each mention is one span, found by its offset in the Markdown
+        assert_eq!(rendered.links.len(), 2);
+        let line = &rendered.text.lines[0];
+        for (link, (name, source)) in rendered.links.iter().zip([("@Jane Doe", "[Jane]"), ("@Old", "[Old]")]) {
+            assert_eq!(link.spans.len(), 1);
+            assert_eq!(line.spans[link.spans.start].content, name);
+            assert!(markdown[link.offset..].starts_with(source), "{}", &markdown[link.offset..]);
+        }
+    }
+
+    #[test]
+    fn test_mentions_in_tables() {
+        let markdown = "| Owner |\n|---|\n| @[Jane](mention://m1/user/u1) |\n";
+        let rendered = render_with(markdown, 40, &[("u1", "Jane Doe")]);
+        let shown = lines(&rendered);
+        assert!(shown[3].contains("@Jane Doe"), "{:#?}", shown);
+        assert!(!shown[3].contains("@@"), "{:#?}", shown);
+        assert_eq!(rendered.links.len(), 1);
+        assert_eq!(rendered.links[0].line, 3);
+    }
+}
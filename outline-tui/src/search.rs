@@ -0,0 +1,596 @@
+use outline_api::{DateFilter, OutlineClient, SearchDocumentsRequest, SearchResult, User};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Results fetched per `documents.search` request
+const PAGE_SIZE: u32 = 25;
+
+/// Fetch the next page when the selection gets this close to the end
+const LOAD_MORE_THRESHOLD: usize = 5;
+
+/// Queries kept in the search history
+pub const MAX_HISTORY: usize = 50;
+
+/// Date ranges the date filter cycles through
+const DATE_FILTERS: [Option<DateFilter>; 5] = [
+    None,
+    Some(DateFilter::Day),
+    Some(DateFilter::Week),
+    Some(DateFilter::Month),
+    Some(DateFilter::Year),
+];
+
+/// Which part of the search view has the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchFocus {
+    Query,
+    Results,
+}
+
+/// Filters applied to the search
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilters {
+    /// Collection ID and name
+    pub collection: Option<(String, String)>,
+    pub author: Option<User>,
+    /// Index into `DATE_FILTERS`
+    date: usize,
+    pub include_drafts: bool,
+    pub include_archived: bool,
+}
+
+impl SearchFilters {
+    pub fn date_filter(&self) -> Option<DateFilter> {
+        DATE_FILTERS[self.date]
+    }
+
+    pub fn date_label(&self) -> &'static str {
+        match self.date_filter() {
+            None => "Any time",
+            Some(DateFilter::Day) => "Past day",
+            Some(DateFilter::Week) => "Past week",
+            Some(DateFilter::Month) => "Past month",
+            Some(DateFilter::Year) => "Past year",
+        }
+    }
+
+    pub fn cycle_date(&mut self) {
+        self.date = (self.date + 1) % DATE_FILTERS.len();
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.collection.is_none()
+            && self.author.is_none()
+            && self.date == 0
+            && !self.include_drafts
+            && !self.include_archived
+    }
+}
+
+/// A search result, as shown in the list
+#[derive(Debug, Clone)]
+pub struct SearchEntry {
+    pub id: String,
+    pub title: String,
+    pub icon: String,
+    pub collection_id: Option<String>,
+    /// Text around the match, with `<b>` marking the matched words
+    pub context: String,
+    pub author: Option<User>,
+    pub updated_at: String,
+}
+
+impl From<SearchResult> for SearchEntry {
+    fn from(result: SearchResult) -> Self {
+        let document = result.document;
+        Self {
+            icon: document.icon().to_string(),
+            id: document.id,
+            title: document.title,
+            collection_id: document.collection_id,
+            context: result.context,
+            author: document.updated_by.or(document.created_by),
+            updated_at: document.updated_at,
+        }
+    }
+}
+
+impl SearchEntry {
+    /// The first highlighted term in the context, used to find the match in the document
+    pub fn first_match(&self) -> Option<String> {
+        context_parts(&self.context)
+            .into_iter()
+            .find(|(text, highlighted)| *highlighted && !text.trim().is_empty())
+            .map(|(text, _)| text.trim().to_string())
+    }
+}
+
+/// State of the result list
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchStatus {
+    /// Nothing searched yet
+    Idle,
+    Loading,
+    /// More results can be fetched
+    More,
+    /// Every result has been fetched
+    Complete,
+    Failed(String),
+}
+
+struct SearchPage {
+    /// Which search the page belongs to
+    generation: u64,
+    result: Result<Vec<SearchResult>, String>,
+}
+
+/// The search view: query, filters and results fetched page by page
+pub struct SearchView {
+    /// Whether the view is showing; it keeps its results while hidden
+    pub open: bool,
+    pub focus: SearchFocus,
+    /// Query being typed
+    pub input: String,
+    /// Query the results are for
+    pub query: String,
+    pub filters: SearchFilters,
+    pub results: Vec<SearchEntry>,
+    pub selected: usize,
+    pub status: SearchStatus,
+    /// Authors seen in results, offered by the author filter
+    pub authors: Vec<User>,
+    /// Position while browsing the history with ↑/↓
+    history_index: Option<usize>,
+    /// Bumped for every new search so late pages of an older one are dropped
+    generation: u64,
+    client: Arc<OutlineClient>,
+    tx: mpsc::UnboundedSender<SearchPage>,
+    rx: mpsc::UnboundedReceiver<SearchPage>,
+}
+
+impl SearchView {
+    pub fn new(client: Arc<OutlineClient>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            open: true,
+            focus: SearchFocus::Query,
+            input: String::new(),
+            query: String::new(),
+            filters: SearchFilters::default(),
+            results: Vec::new(),
+            selected: 0,
+            status: SearchStatus::Idle,
+            authors: Vec::new(),
+            history_index: None,
+            generation: 0,
+            client,
+            tx,
+            rx,
+        }
+    }
+
+    /// Show the view again with the query focused
+    pub fn reopen(&mut self) {
+        self.open = true;
+        self.focus = SearchFocus::Query;
+        self.history_index = None;
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.input.push(c);
+        self.history_index = None;
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+        self.history_index = None;
+    }
+
+    /// Step back through earlier queries (most recent first)
+    pub fn history_previous(&mut self, history: &[String]) {
+        let index = self.history_index.map_or(0, |i| i + 1);
+        if let Some(query) = history.get(index) {
+            self.input = query.clone();
+            self.history_index = Some(index);
+        }
+    }
+
+    /// Step forward through the history, back to an empty query
+    pub fn history_next(&mut self, history: &[String]) {
+        match self.history_index {
+            Some(0) | None => {
+                self.history_index = None;
+                self.input.clear();
+            }
+            Some(i) => {
+                self.input = history[i - 1].clone();
+                self.history_index = Some(i - 1);
+            }
+        }
+    }
+
+    /// Search for the typed query; returns it so it can be added to the history
+    pub fn submit(&mut self) -> Option<String> {
+        let query = self.input.trim().to_string();
+        if query.is_empty() {
+            return None;
+        }
+        self.query = query.clone();
+        self.history_index = None;
+        self.focus = SearchFocus::Results;
+        self.search();
+        Some(query)
+    }
+
+    /// Run the current query from the first page, e.g. after a filter changed
+    pub fn search(&mut self) {
+        if self.query.is_empty() {
+            return;
+        }
+        self.generation += 1;
+        self.results.clear();
+        self.selected = 0;
+        self.fetch_page();
+    }
+
+    fn fetch_page(&mut self) {
+        self.status = SearchStatus::Loading;
+
+        let mut request = SearchDocumentsRequest::builder(self.query.clone())
+            .offset(self.results.len() as u32)
+            .limit(PAGE_SIZE)
+            .include_drafts(self.filters.include_drafts)
+            .include_archived(self.filters.include_archived);
+        if let Some((id, _)) = &self.filters.collection {
+            request = request.collection_id(id.clone());
+        }
+        if let Some(author) = &self.filters.author {
+            request = request.user_id(author.id.clone());
+        }
+        if let Some(date) = self.filters.date_filter() {
+            request = request.date_filter(date);
+        }
+        let request = request.build();
+
+        let client = self.client.clone();
+        let tx = self.tx.clone();
+        let generation = self.generation;
+        tokio::spawn(async move {
+            let result = client
+                .search_documents(request)
+                .await
+                .map(|response| response.data)
+                .map_err(|e| e.to_string());
+            let _ = tx.send(SearchPage { generation, result });
+        });
+    }
+
+    /// Apply fetched pages (call this regularly in event loop)
+    pub fn tick(&mut self) {
+        while let Ok(page) = self.rx.try_recv() {
+            if page.generation != self.generation {
+                continue;
+            }
+            match page.result {
+                Ok(results) => {
+                    self.status = if results.len() < PAGE_SIZE as usize {
+                        SearchStatus::Complete
+                    } else {
+                        SearchStatus::More
+                    };
+                    for result in results {
+                        let entry = SearchEntry::from(result);
+                        // Pages can overlap if documents changed between requests
+                        if self.results.iter().any(|r| r.id == entry.id) {
+                            continue;
+                        }
+                        if let Some(author) = &entry.author {
+                            if !self.authors.iter().any(|a| a.id == author.id) {
+                                self.authors.push(author.clone());
+                                self.authors.sort_by(|a, b| a.name.cmp(&b.name));
+                            }
+                        }
+                        self.results.push(entry);
+                    }
+                }
+                Err(e) => self.status = SearchStatus::Failed(e),
+            }
+        }
+
+        self.load_more_if_needed();
+    }
+
+    /// Fetch the next page once the selection nears the end of what's loaded
+    fn load_more_if_needed(&mut self) {
+        if self.status == SearchStatus::More
+            && self.selected + LOAD_MORE_THRESHOLD >= self.results.len()
+        {
+            self.fetch_page();
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.results.len() {
+            self.selected += 1;
+        }
+        self.load_more_if_needed();
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn page_down(&mut self) {
+        self.selected = (self.selected + 10).min(self.results.len().saturating_sub(1));
+        self.load_more_if_needed();
+    }
+
+    pub fn page_up(&mut self) {
+        self.selected = self.selected.saturating_sub(10);
+    }
+
+    pub fn selected_entry(&self) -> Option<&SearchEntry> {
+        self.results.get(self.selected)
+    }
+
+    /// Cycle the collection filter through all collections and back to none
+    pub fn cycle_collection(&mut self, collections: &[(String, String)]) {
+        let next = match &self.filters.collection {
+            None => collections.first(),
+            Some((id, _)) => collections
+                .iter()
+                .position(|(c, _)| c == id)
+                .and_then(|i| collections.get(i + 1)),
+        };
+        self.filters.collection = next.cloned();
+        self.search();
+    }
+
+    /// Cycle the author filter through authors seen in results and back to anyone
+    pub fn cycle_author(&mut self) {
+        let next = match &self.filters.author {
+            None => self.authors.first(),
+            Some(author) => self
+                .authors
+                .iter()
+                .position(|a| a.id == author.id)
+                .and_then(|i| self.authors.get(i + 1)),
+        };
+        self.filters.author = next.cloned();
+        self.search();
+    }
+
+    pub fn cycle_date(&mut self) {
+        self.filters.cycle_date();
+        self.search();
+    }
+
+    pub fn toggle_drafts(&mut self) {
+        self.filters.include_drafts = !self.filters.include_drafts;
+        self.search();
+    }
+
+    pub fn toggle_archived(&mut self) {
+        self.filters.include_archived = !self.filters.include_archived;
+        self.search();
+    }
+
+    pub fn clear_filters(&mut self) {
+        if !self.filters.is_default() {
+            self.filters = SearchFilters::default();
+            self.search();
+        }
+    }
+}
+
+/// Add a query to the front of the history, dropping older copies of it
+pub fn remember_query(history: &mut Vec<String>, query: String) {
+    history.retain(|q| *q != query);
+    history.insert(0, query);
+    history.truncate(MAX_HISTORY);
+}
+
+/// Split search context into plain and highlighted parts, using its `<b>` markup
+pub fn context_parts(context: &str) -> Vec<(String, bool)> {
+    let mut parts = Vec::new();
+    let mut rest = context;
+    while let Some(start) = rest.find("<b>") {
+        if start > 0 {
+            parts.push((rest[..start].to_string(), false));
+        }
+        rest = &rest[start + 3..];
+        let end = rest.find("</b>").unwrap_or(rest.len());
+        parts.push((rest[..end].to_string(), true));
+        rest = rest.get(end + 4..).unwrap_or("");
+    }
+    if !rest.is_empty() {
+        parts.push((rest.to_string(), false));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tests run on a current-thread runtime and never await, so the page requests spawned by the
+    // view never run; pages are delivered by hand instead.
+    fn view() -> SearchView {
+        SearchView::new(Arc::new(OutlineClient::new("http://localhost".to_string()).unwrap()))
+    }
+
+    fn result(id: &str) -> SearchResult {
+        serde_json::from_value(serde_json::json!({
+            "ranking": 1.0,
+            "context": format!("about <b>{}</b>", id),
+            "document": {
+                "id": id,
+                "title": id,
+                "collectionId": null,
+                "parentDocumentId": null,
+                "publishedAt": null,
+                "archivedAt": null,
+                "deletedAt": null,
+                "updatedBy": { "id": format!("author-{}", id), "name": format!("Author {}", id) },
+            },
+        }))
+        .unwrap()
+    }
+
+    /// Deliver a page for the current search, named `prefix-start` to `prefix-end`
+    fn deliver(view: &mut SearchView, prefix: &str, range: std::ops::Range<usize>) {
+        let results = range.map(|i| result(&format!("{}-{}", prefix, i))).collect();
+        view.tx.send(SearchPage { generation: view.generation, result: Ok(results) }).unwrap();
+        view.tick();
+    }
+
+    fn submit(view: &mut SearchView, query: &str) -> Option<String> {
+        view.input = query.to_string();
+        view.submit()
+    }
+
+    #[tokio::test]
+    async fn test_pages_load_as_the_selection_nears_the_end() {
+        let mut view = view();
+        assert_eq!(submit(&mut view, "  deploy "), Some("deploy".to_string()));
+        assert_eq!(view.focus, SearchFocus::Results);
+        assert_eq!(view.status, SearchStatus::Loading);
+
+        deliver(&mut view, "doc", 0..PAGE_SIZE as usize);
+        assert_eq!(view.status, SearchStatus::More);
+        assert_eq!(view.results.len(), 25);
+
+        // Nothing more is fetched while the selection is far from the end
+        view.page_down();
+        assert_eq!(view.selected, 10);
+        assert_eq!(view.status, SearchStatus::More);
+
+        view.page_down();
+        assert_eq!(view.selected, 20);
+        assert_eq!(view.status, SearchStatus::Loading);
+
+        // A short page completes the results; overlapping entries are not repeated
+        deliver(&mut view, "doc", 20..35);
+        assert_eq!(view.status, SearchStatus::Complete);
+        assert_eq!(view.results.len(), 35);
+        assert_eq!(view.results[34].id, "doc-34");
+        assert_eq!(view.selected_entry().unwrap().id, "doc-20");
+
+        // Once complete, reaching the end fetches nothing more
+        for _ in 0..20 {
+            view.select_next();
+        }
+        assert_eq!(view.selected, 34);
+        assert_eq!(view.status, SearchStatus::Complete);
+    }
+
+    #[tokio::test]
+    async fn test_a_full_page_near_the_selection_fetches_the_next_one() {
+        let mut view = view();
+        submit(&mut view, "deploy");
+        deliver(&mut view, "doc", 0..PAGE_SIZE as usize);
+        view.selected = 19;
+        view.select_next();
+        assert_eq!(view.status, SearchStatus::Loading);
+
+        // The next page arrives with the selection where it was
+        deliver(&mut view, "doc", 25..50);
+        assert_eq!(view.selected, 20);
+        assert_eq!(view.results.len(), 50);
+        assert_eq!(view.status, SearchStatus::More);
+    }
+
+    #[tokio::test]
+    async fn test_selection_is_clamped_to_loaded_results() {
+        let mut view = view();
+        submit(&mut view, "deploy");
+        deliver(&mut view, "doc", 0..7);
+        assert_eq!(view.status, SearchStatus::Complete);
+
+        view.page_down();
+        assert_eq!(view.selected, 6);
+        view.select_next();
+        assert_eq!(view.selected, 6);
+        assert_eq!(view.selected_entry().unwrap().id, "doc-6");
+
+        view.page_up();
+        assert_eq!(view.selected, 0);
+        view.select_previous();
+        assert_eq!(view.selected, 0);
+    }
+
+    #[tokio::test]
+    async fn test_new_search_drops_pages_of_the_previous_one() {
+        let mut view = view();
+        submit(&mut view, "deploy");
+        deliver(&mut view, "doc", 0..PAGE_SIZE as usize);
+        view.page_down();
+        let stale = view.generation;
+
+        // Changing a filter starts over from the first page
+        view.toggle_drafts();
+        assert!(view.results.is_empty());
+        assert_eq!(view.selected, 0);
+        assert_eq!(view.status, SearchStatus::Loading);
+
+        view.tx.send(SearchPage { generation: stale, result: Ok(vec![result("late")]) }).unwrap();
+        view.tick();
+        assert!(view.results.is_empty());
+        assert_eq!(view.status, SearchStatus::Loading);
+
+        deliver(&mut view, "draft", 0..3);
+        assert_eq!(view.results.len(), 3);
+        assert_eq!(view.selected_entry().unwrap().id, "draft-0");
+    }
+
+    #[tokio::test]
+    async fn test_empty_results() {
+        let mut view = view();
+        assert_eq!(submit(&mut view, "   "), None);
+        assert_eq!(view.status, SearchStatus::Idle);
+        assert_eq!(view.focus, SearchFocus::Query);
+
+        submit(&mut view, "nothing matches");
+        deliver(&mut view, "doc", 0..0);
+        assert_eq!(view.status, SearchStatus::Complete);
+        assert!(view.selected_entry().is_none());
+
+        view.select_next();
+        view.page_down();
+        view.page_up();
+        assert_eq!(view.selected, 0);
+        assert!(view.selected_entry().is_none());
+        assert_eq!(view.status, SearchStatus::Complete);
+    }
+
+    #[tokio::test]
+    async fn test_failed_page_keeps_loaded_results() {
+        let mut view = view();
+        submit(&mut view, "deploy");
+        deliver(&mut view, "doc", 0..PAGE_SIZE as usize);
+        view.selected = 22;
+        view.select_next();
+
+        view.tx.send(SearchPage { generation: view.generation, result: Err("timed out".to_string()) }).unwrap();
+        view.tick();
+        assert_eq!(view.status, SearchStatus::Failed("timed out".to_string()));
+        assert_eq!(view.results.len(), 25);
+        assert_eq!(view.selected, 23);
+
+        // A failed search is not retried by moving the selection
+        view.select_next();
+        assert!(matches!(view.status, SearchStatus::Failed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_authors_are_collected_for_the_author_filter() {
+        let mut view = view();
+        submit(&mut view, "deploy");
+        deliver(&mut view, "b", 0..2);
+        deliver(&mut view, "a", 0..1);
+
+        let names: Vec<&str> = view.authors.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["Author a-0", "Author b-0", "Author b-1"]);
+        assert_eq!(view.results[0].first_match().as_deref(), Some("b-0"));
+    }
+}
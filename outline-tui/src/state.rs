@@ -0,0 +1,63 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+use tracing::debug;
+
+use crate::config::Config;
+
+/// UI state kept between sessions, stored next to the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UiState {
+    /// Collections and documents expanded in the sidebar
+    #[serde(default)]
+    pub expanded: BTreeSet<String>,
+
+    /// Search queries, most recent first
+    #[serde(default)]
+    pub search_history: Vec<String>,
+
+    /// Whether the comments pane is open
+    #[serde(default)]
+    pub show_comments: bool,
+
+    /// Whether the links pane is open
+    #[serde(default)]
+    pub show_links: bool,
+
+    /// Whether the table of contents pane is open
+    #[serde(default)]
+    pub show_toc: bool,
+}
+
+impl UiState {
+    /// Load the saved state, falling back to defaults if there is none or it can't be read
+    pub fn load() -> Self {
+        let loaded = Self::file_path().and_then(|path| {
+            if !path.exists() {
+                return Ok(Self::default());
+            }
+            let contents = fs::read_to_string(&path)
+                .context(format!("Failed to read UI state: {:?}", path))?;
+            serde_json::from_str(&contents).context("Failed to parse UI state")
+        });
+
+        loaded.unwrap_or_else(|e| {
+            debug!("Ignoring saved UI state: {}", e);
+            Self::default()
+        })
+    }
+
+    /// Write the state to disk
+    pub fn save(&self) -> Result<()> {
+        let path = Self::file_path()?;
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(&path, contents).context(format!("Failed to write UI state: {:?}", path))
+    }
+
+    /// Get the state file path (`~/.outline-cli/tui-state.json`)
+    pub fn file_path() -> Result<PathBuf> {
+        Ok(Config::config_file_path()?.with_file_name("tui-state.json"))
+    }
+}
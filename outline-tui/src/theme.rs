@@ -0,0 +1,149 @@
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Built-in palettes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+/// The `[theme]` config section: a preset, and colours overriding some of its roles
+///
+/// Colours are names (`blue`, `lightcyan`), `#rrggbb` or a 0-255 palette index, e.g.
+/// `accent = "#5e81ac"`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    #[serde(default)]
+    pub preset: ThemePreset,
+    #[serde(flatten)]
+    pub colors: BTreeMap<String, String>,
+}
+
+/// Colours of the interface, by role
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    /// Document text and titles
+    pub text: Color,
+    /// Secondary text and unfocused borders
+    pub muted: Color,
+    /// Hints, placeholders and separators
+    pub dim: Color,
+    /// Focused borders, section headers and the active tab
+    pub accent: Color,
+    /// Text drawn on the accent colour
+    pub accent_text: Color,
+    /// Background of the selected row
+    pub selection: Color,
+    /// Current section, pending changes and notices
+    pub highlight: Color,
+    pub success: Color,
+    pub error: Color,
+    pub link: Color,
+    pub mention: Color,
+    /// syntect theme for code blocks
+    pub code: &'static str,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::preset(ThemePreset::Dark)
+    }
+}
+
+impl Theme {
+    pub fn preset(preset: ThemePreset) -> Self {
+        match preset {
+            ThemePreset::Dark => Self {
+                text: Color::White,
+                muted: Color::Gray,
+                dim: Color::DarkGray,
+                accent: Color::Cyan,
+                accent_text: Color::Black,
+                selection: Color::Blue,
+                highlight: Color::Yellow,
+                success: Color::Green,
+                error: Color::Red,
+                link: Color::Blue,
+                mention: Color::Magenta,
+                code: "base16-ocean.dark",
+            },
+            ThemePreset::Light => Self {
+                text: Color::Black,
+                muted: Color::DarkGray,
+                dim: Color::Gray,
+                accent: Color::Blue,
+                accent_text: Color::White,
+                selection: Color::LightBlue,
+                highlight: Color::Rgb(0x9a, 0x67, 0x00),
+                success: Color::Rgb(0x1a, 0x7f, 0x37),
+                error: Color::Red,
+                link: Color::Blue,
+                mention: Color::Magenta,
+                code: "InspiredGitHub",
+            },
+            ThemePreset::HighContrast => Self {
+                text: Color::White,
+                muted: Color::White,
+                dim: Color::Gray,
+                accent: Color::LightYellow,
+                accent_text: Color::Black,
+                selection: Color::Blue,
+                highlight: Color::LightYellow,
+                success: Color::LightGreen,
+                error: Color::LightRed,
+                link: Color::LightCyan,
+                mention: Color::LightMagenta,
+                code: "base16-eighties.dark",
+            },
+        }
+    }
+
+    /// Build the palette from the `[theme]` config section
+    ///
+    /// Unknown roles and colours that can't be parsed are skipped and described in the
+    /// returned messages.
+    pub fn from_config(config: &ThemeConfig) -> (Self, Vec<String>) {
+        let mut theme = Self::preset(config.preset);
+        let mut problems = Vec::new();
+
+        for (role, value) in &config.colors {
+            let Ok(color) = value.parse::<Color>() else {
+                problems.push(format!("theme.{}: unknown colour '{}'", role, value));
+                continue;
+            };
+            let slot = match role.as_str() {
+                "text" => &mut theme.text,
+                "muted" => &mut theme.muted,
+                "dim" => &mut theme.dim,
+                "accent" => &mut theme.accent,
+                "accent_text" => &mut theme.accent_text,
+                "selection" => &mut theme.selection,
+                "highlight" => &mut theme.highlight,
+                "success" => &mut theme.success,
+                "error" => &mut theme.error,
+                "link" => &mut theme.link,
+                "mention" => &mut theme.mention,
+                _ => {
+                    problems.push(format!("theme.{}: unknown colour role", role));
+                    continue;
+                }
+            };
+            *slot = color;
+        }
+        (theme, problems)
+    }
+
+    /// Border colour of a pane
+    pub fn border(&self, focused: bool) -> Color {
+        if focused {
+            self.accent
+        } else {
+            self.muted
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::HashSet;
+
+/// A heading of the current document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TocEntry {
+    /// Heading level (1-6)
+    pub level: usize,
+    pub title: String,
+    /// Rendered line the heading is on
+    pub line: usize,
+}
+
+/// Heading outline of the current document, shown in the table of contents pane
+#[derive(Debug, Default)]
+pub struct TableOfContents {
+    pub entries: Vec<TocEntry>,
+    /// Entries whose subsections are hidden
+    pub collapsed: HashSet<usize>,
+    /// Selected entry, by index into `entries`
+    pub selected: usize,
+}
+
+impl TableOfContents {
+    /// Replace the outline with the headings of the rendered document
+    ///
+    /// Folds are kept while the headings stay the same, e.g. while editing a paragraph.
+    pub fn set_entries(&mut self, entries: &[TocEntry]) {
+        if entries == self.entries {
+            return;
+        }
+        let same_headings = entries.len() == self.entries.len()
+            && entries.iter().zip(&self.entries).all(|(a, b)| a.level == b.level && a.title == b.title);
+        if !same_headings {
+            self.collapsed.clear();
+            self.selected = 0;
+        }
+        self.entries = entries.to_vec();
+    }
+
+    /// The heading whose section contains a rendered line
+    pub fn section_at(&self, line: usize) -> Option<usize> {
+        self.entries.iter().rposition(|entry| entry.line <= line)
+    }
+
+    /// Headings leading to an entry, outermost first, ending with the entry itself
+    pub fn path(&self, index: usize) -> Vec<usize> {
+        let mut path = vec![index];
+        let mut level = self.entries[index].level;
+        for (i, entry) in self.entries[..index].iter().enumerate().rev() {
+            if entry.level < level {
+                path.insert(0, i);
+                level = entry.level;
+            }
+        }
+        path
+    }
+
+    /// The next heading after a rendered line, or the last one before it
+    pub fn next_heading(&self, line: usize, forward: bool) -> Option<&TocEntry> {
+        if forward {
+            self.entries.iter().find(|entry| entry.line > line)
+        } else {
+            self.entries.iter().rev().find(|entry| entry.line < line)
+        }
+    }
+
+    pub fn has_children(&self, index: usize) -> bool {
+        self.entries
+            .get(index + 1)
+            .is_some_and(|next| next.level > self.entries[index].level)
+    }
+
+    /// Entries not hidden inside a collapsed heading, with their nesting depth
+    pub fn visible(&self) -> Vec<(usize, usize)> {
+        let mut visible = Vec::new();
+        let mut hidden_below: Option<usize> = None;
+        let mut parents: Vec<usize> = Vec::new();
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            if hidden_below.is_some_and(|level| entry.level > level) {
+                continue;
+            }
+            hidden_below = None;
+
+            while parents.last().is_some_and(|&level| level >= entry.level) {
+                parents.pop();
+            }
+            visible.push((i, parents.len()));
+            parents.push(entry.level);
+
+            if self.collapsed.contains(&i) {
+                hidden_below = Some(entry.level);
+            }
+        }
+        visible
+    }
+
+    /// The visible entry standing for an entry: itself, or the collapsed heading hiding it
+    pub fn visible_entry(&self, index: usize) -> usize {
+        let visible = self.visible();
+        visible
+            .iter()
+            .rev()
+            .find(|(i, _)| *i <= index)
+            .map_or(index, |(i, _)| *i)
+    }
+
+    /// Select the visible entry for the section containing a rendered line
+    pub fn follow(&mut self, line: usize) {
+        if let Some(section) = self.section_at(line) {
+            self.selected = self.visible_entry(section);
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        let visible = self.visible();
+        if let Some(pos) = visible.iter().position(|(i, _)| *i == self.selected) {
+            if let Some((next, _)) = visible.get(pos + 1) {
+                self.selected = *next;
+            }
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        let visible = self.visible();
+        if let Some(pos) = visible.iter().position(|(i, _)| *i == self.selected) {
+            if pos > 0 {
+                self.selected = visible[pos - 1].0;
+            }
+        }
+    }
+
+    /// Fold or unfold the selected heading (`None` toggles)
+    pub fn set_collapsed(&mut self, collapse: Option<bool>) {
+        if !self.has_children(self.selected) {
+            return;
+        }
+        let collapse = collapse.unwrap_or(!self.collapsed.contains(&self.selected));
+        if collapse {
+            self.collapsed.insert(self.selected);
+        } else {
+            self.collapsed.remove(&self.selected);
+        }
+    }
+
+    /// Select the heading containing the selected one
+    pub fn select_parent(&mut self) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let path = self.path(self.selected);
+        if path.len() > 1 {
+            self.selected = path[path.len() - 2];
+        }
+    }
+
+    pub fn selected_entry(&self) -> Option<&TocEntry> {
+        self.entries.get(self.selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: usize, title: &str, line: usize) -> TocEntry {
+        TocEntry { level, title: title.to_string(), line }
+    }
+
+    fn entries() -> Vec<TocEntry> {
+        vec![
+            entry(1, "Intro", 0),
+            entry(2, "Setup", 5),
+            entry(3, "Install", 10),
+            entry(3, "Configure", 20),
+            entry(2, "Usage", 30),
+            entry(1, "Reference", 40),
+            entry(3, "Flags", 50),
+        ]
+    }
+
+    fn toc() -> TableOfContents {
+        let mut toc = TableOfContents::default();
+        toc.set_entries(&entries());
+        toc
+    }
+
+    fn fold(toc: &mut TableOfContents, index: usize) {
+        toc.selected = index;
+        toc.set_collapsed(Some(true));
+    }
+
+    #[test]
+    fn test_nesting_depths() {
+        let toc = toc();
+        assert_eq!(toc.visible(), vec![(0, 0), (1, 1), (2, 2), (3, 2), (4, 1), (5, 0), (6, 1)]);
+        assert!(toc.has_children(1));
+        assert!(!toc.has_children(2));
+        assert!(toc.has_children(5));
+        assert!(!toc.has_children(6));
+    }
+
+    #[test]
+    fn test_folding_nested_headings() {
+        let mut toc = toc();
+        fold(&mut toc, 1);
+        assert_eq!(toc.visible(), vec![(0, 0), (1, 1), (4, 1), (5, 0), (6, 1)]);
+
+        // Folding the outer heading too hides everything under it; unfolding it keeps the inner fold
+        fold(&mut toc, 0);
+        assert_eq!(toc.visible(), vec![(0, 0), (5, 0), (6, 1)]);
+        toc.set_collapsed(None);
+        assert_eq!(toc.visible(), vec![(0, 0), (1, 1), (4, 1), (5, 0), (6, 1)]);
+
+        toc.selected = 1;
+        toc.set_collapsed(None);
+        assert_eq!(toc.visible().len(), 7);
+
+        // Headings without subsections can't be folded
+        fold(&mut toc, 2);
+        assert!(toc.collapsed.is_empty());
+    }
+
+    #[test]
+    fn test_selection_skips_folded_sections() {
+        let mut toc = toc();
+        fold(&mut toc, 1);
+
+        toc.select_next();
+        assert_eq!(toc.selected, 4);
+        toc.select_previous();
+        assert_eq!(toc.selected, 1);
+        toc.select_previous();
+        toc.select_previous();
+        assert_eq!(toc.selected, 0);
+
+        toc.selected = 6;
+        toc.select_next();
+        assert_eq!(toc.selected, 6);
+    }
+
+    #[test]
+    fn test_following_into_a_folded_section_selects_its_heading() {
+        let mut toc = toc();
+        fold(&mut toc, 1);
+
+        toc.follow(22);
+        assert_eq!(toc.section_at(22), Some(3));
+        assert_eq!(toc.selected, 1);
+        assert_eq!(toc.visible_entry(2), 1);
+        assert_eq!(toc.visible_entry(4), 4);
+
+        toc.follow(35);
+        assert_eq!(toc.selected, 4);
+        toc.follow(100);
+        assert_eq!(toc.selected, 6);
+    }
+
+    #[test]
+    fn test_jumping_between_headings_ignores_folds() {
+        let mut toc = toc();
+        fold(&mut toc, 1);
+
+        // Heading jumps move through the document, folded sections included
+        assert_eq!(toc.next_heading(5, true).map(|e| e.title.as_str()), Some("Install"));
+        assert_eq!(toc.next_heading(30, false).map(|e| e.title.as_str()), Some("Configure"));
+        assert_eq!(toc.next_heading(12, true).map(|e| e.title.as_str()), Some("Configure"));
+        assert!(toc.next_heading(50, true).is_none());
+        assert!(toc.next_heading(0, false).is_none());
+    }
+
+    #[test]
+    fn test_section_paths() {
+        let mut toc = toc();
+        assert_eq!(toc.path(3), vec![0, 1, 3]);
+        assert_eq!(toc.path(4), vec![0, 4]);
+        assert_eq!(toc.path(5), vec![5]);
+        // A skipped level still nests under the nearest shallower heading
+        assert_eq!(toc.path(6), vec![5, 6]);
+
+        assert_eq!(toc.section_at(0), Some(0));
+        assert_eq!(toc.section_at(19), Some(2));
+
+        toc.selected = 3;
+        toc.select_parent();
+        assert_eq!(toc.selected, 1);
+        toc.select_parent();
+        assert_eq!(toc.selected, 0);
+        toc.select_parent();
+        assert_eq!(toc.selected, 0);
+    }
+
+    #[test]
+    fn test_section_before_the_first_heading() {
+        let mut toc = TableOfContents::default();
+        toc.set_entries(&[entry(2, "Later", 10)]);
+        assert_eq!(toc.section_at(3), None);
+        toc.follow(3);
+        assert_eq!(toc.selected, 0);
+
+        let mut empty = TableOfContents::default();
+        empty.select_parent();
+        empty.select_next();
+        assert!(empty.selected_entry().is_none());
+    }
+
+    #[test]
+    fn test_folds_survive_moved_headings_but_not_new_ones() {
+        let mut toc = toc();
+        fold(&mut toc, 1);
+
+        // Editing a paragraph moves headings down without changing them
+        let moved: Vec<TocEntry> = entries().into_iter().map(|e| entry(e.level, &e.title, e.line + 2)).collect();
+        toc.set_entries(&moved);
+        assert!(toc.collapsed.contains(&1));
+        assert_eq!(toc.selected, 1);
+        assert_eq!(toc.entries[2].line, 12);
+
+        let mut renamed = moved.clone();
+        renamed[2].title = "Installation".to_string();
+        toc.set_entries(&renamed);
+        assert!(toc.collapsed.is_empty());
+        assert_eq!(toc.selected, 0);
+    }
+}
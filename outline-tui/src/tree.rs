@@ -0,0 +1,526 @@
+use anyhow::Result;
+use outline_api::{
+    Collection, CollectionDocumentsRequest, ListCollectionsRequest, NavigationNode, OutlineClient,
+};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::debug;
+
+use crate::app::{DocumentEntry, SidebarItem};
+use crate::finder::BREADCRUMB_SEPARATOR;
+
+/// Collections fetched per `collections.list` request
+const COLLECTION_PAGE_SIZE: u32 = 100;
+
+/// Result of a background sidebar load, sent to the UI over a channel
+pub enum TreeUpdate {
+    /// The list of collections (their documents are loaded separately)
+    Collections(Result<Vec<Collection>, String>),
+    /// The document tree of one collection
+    Documents {
+        collection_id: String,
+        result: Result<Vec<NavigationNode>, String>,
+    },
+}
+
+/// Loading state of a collection's documents
+#[derive(Debug, Clone)]
+pub enum Documents {
+    NotLoaded,
+    Loading,
+    Loaded(Vec<NavigationNode>),
+    Failed(String),
+}
+
+/// A collection in the sidebar tree
+#[derive(Debug, Clone)]
+pub struct CollectionNode {
+    pub collection: Collection,
+    pub documents: Documents,
+}
+
+/// Collections and their document trees, as far as they have been loaded
+///
+/// Documents are fetched per collection, the first time it is expanded.
+#[derive(Debug, Default)]
+pub struct SidebarTree {
+    pub collections: Vec<CollectionNode>,
+    /// IDs of expanded collections and documents
+    pub expanded: BTreeSet<String>,
+    /// Whether the collection list is being (re)loaded
+    pub loading_collections: bool,
+}
+
+impl SidebarTree {
+    pub fn new(expanded: BTreeSet<String>) -> Self {
+        Self {
+            expanded,
+            ..Default::default()
+        }
+    }
+
+    /// Replace the collection list, keeping documents already loaded for collections that remain
+    pub fn set_collections(&mut self, collections: Vec<Collection>) {
+        let mut previous = std::mem::take(&mut self.collections);
+        self.collections = collections
+            .into_iter()
+            .map(|collection| {
+                let documents = previous
+                    .iter()
+                    .position(|node| node.collection.id == collection.id)
+                    .map(|i| previous.swap_remove(i).documents)
+                    .unwrap_or(Documents::NotLoaded);
+                CollectionNode { collection, documents }
+            })
+            .collect();
+        self.loading_collections = false;
+    }
+
+    /// Store the result of loading a collection's documents
+    pub fn set_documents(&mut self, collection_id: &str, result: Result<Vec<NavigationNode>, String>) {
+        if let Some(node) = self.collection_mut(collection_id) {
+            node.documents = match result {
+                Ok(nodes) => Documents::Loaded(nodes),
+                Err(e) => Documents::Failed(e),
+            };
+        }
+    }
+
+    /// Mark a collection's documents as being loaded
+    pub fn mark_loading(&mut self, collection_id: &str) {
+        if let Some(node) = self.collection_mut(collection_id) {
+            node.documents = Documents::Loading;
+        }
+    }
+
+    fn collection_mut(&mut self, collection_id: &str) -> Option<&mut CollectionNode> {
+        self.collections.iter_mut().find(|node| node.collection.id == collection_id)
+    }
+
+    pub fn collection(&self, collection_id: &str) -> Option<&CollectionNode> {
+        self.collections.iter().find(|node| node.collection.id == collection_id)
+    }
+
+    /// Whether a collection's documents still have to be fetched
+    pub fn needs_documents(&self, collection_id: &str) -> bool {
+        self.collection(collection_id)
+            .is_some_and(|node| matches!(node.documents, Documents::NotLoaded | Documents::Failed(_)))
+    }
+
+    /// Collections whose documents have not been fetched yet
+    pub fn unloaded_collection_ids(&self) -> Vec<String> {
+        self.collections
+            .iter()
+            .filter(|node| matches!(node.documents, Documents::NotLoaded))
+            .map(|node| node.collection.id.clone())
+            .collect()
+    }
+
+    /// Whether anything is still loading
+    pub fn is_loading(&self) -> bool {
+        self.loading_collections
+            || self.collections.iter().any(|node| matches!(node.documents, Documents::Loading))
+    }
+
+    /// Collections whose documents are loaded or loading, so a refresh should fetch them again
+    pub fn loaded_collection_ids(&self) -> BTreeSet<String> {
+        self.collections
+            .iter()
+            .filter(|node| matches!(node.documents, Documents::Loaded(_) | Documents::Loading))
+            .map(|node| node.collection.id.clone())
+            .collect()
+    }
+
+    /// Number of documents loaded so far
+    pub fn document_count(&self) -> usize {
+        self.collections
+            .iter()
+            .map(|node| match &node.documents {
+                Documents::Loaded(nodes) => nodes.iter().map(NavigationNode::count).sum(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// The collection and parent documents above a document, as `Collection › Parent`
+    ///
+    /// Only the collection name is known until the collection's documents are loaded.
+    pub fn breadcrumb(&self, collection_id: &str, document_id: &str) -> Option<String> {
+        let node = self.collection(collection_id)?;
+        let mut parts = vec![node.collection.name.as_str()];
+        if let Documents::Loaded(documents) = &node.documents {
+            ancestors(documents, document_id, &mut parts);
+        }
+        Some(parts.join(BREADCRUMB_SEPARATOR))
+    }
+
+    pub fn is_expanded(&self, id: &str) -> bool {
+        self.expanded.contains(id)
+    }
+
+    /// Expand or collapse a collection or document; returns whether it is now expanded
+    pub fn set_expanded(&mut self, id: &str, expanded: bool) -> bool {
+        if expanded {
+            self.expanded.insert(id.to_string());
+        } else {
+            self.expanded.remove(id);
+        }
+        expanded
+    }
+
+    /// The visible rows: collections, and the documents under expanded ones
+    pub fn flatten(&self) -> Vec<SidebarItem> {
+        let mut items = Vec::new();
+
+        for node in &self.collections {
+            items.push(SidebarItem::Collection(node.collection.clone()));
+
+            if let (true, Documents::Loaded(documents)) = (self.is_expanded(&node.collection.id), &node.documents) {
+                for document in documents {
+                    self.push_document(&mut items, &node.collection.id, document, 1);
+                }
+            }
+        }
+
+        items
+    }
+
+    fn push_document(&self, items: &mut Vec<SidebarItem>, collection_id: &str, node: &NavigationNode, level: usize) {
+        let entry = DocumentEntry {
+            id: node.id.clone(),
+            title: node.title.clone(),
+            icon: node.icon().to_string(),
+            collection_id: collection_id.to_string(),
+            has_children: !node.children.is_empty(),
+        };
+        items.push(SidebarItem::Document(entry, level));
+
+        if self.is_expanded(&node.id) {
+            for child in &node.children {
+                self.push_document(items, collection_id, child, level + 1);
+            }
+        }
+    }
+}
+
+/// Push the titles of the documents above `id` onto `path`; returns whether `id` was found
+fn ancestors<'a>(nodes: &'a [NavigationNode], id: &str, path: &mut Vec<&'a str>) -> bool {
+    for node in nodes {
+        if node.id == id {
+            return true;
+        }
+        path.push(&node.title);
+        if ancestors(&node.children, id, path) {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+/// Load the collection list in the background, then the documents of the given collections
+pub fn spawn_load(client: Arc<OutlineClient>, tx: UnboundedSender<TreeUpdate>, preload: BTreeSet<String>) {
+    tokio::spawn(async move {
+        let collections = match list_all_collections(&client).await {
+            Ok(collections) => collections,
+            Err(e) => {
+                let _ = tx.send(TreeUpdate::Collections(Err(e.to_string())));
+                return;
+            }
+        };
+
+        let to_load: Vec<String> = collections
+            .iter()
+            .filter(|c| preload.contains(&c.id))
+            .map(|c| c.id.clone())
+            .collect();
+        if tx.send(TreeUpdate::Collections(Ok(collections))).is_err() {
+            return;
+        }
+
+        load_each(&client, &tx, to_load).await;
+    });
+}
+
+/// Load the documents of several collections in the background, one at a time
+pub fn spawn_load_collections(client: Arc<OutlineClient>, tx: UnboundedSender<TreeUpdate>, collection_ids: Vec<String>) {
+    tokio::spawn(async move {
+        load_each(&client, &tx, collection_ids).await;
+    });
+}
+
+async fn load_each(client: &OutlineClient, tx: &UnboundedSender<TreeUpdate>, collection_ids: Vec<String>) {
+    for collection_id in collection_ids {
+        let result = load_documents(client, &collection_id).await;
+        if tx.send(TreeUpdate::Documents { collection_id, result }).is_err() {
+            return;
+        }
+    }
+}
+
+/// Load one collection's documents in the background
+pub fn spawn_load_documents(client: Arc<OutlineClient>, tx: UnboundedSender<TreeUpdate>, collection_id: String) {
+    tokio::spawn(async move {
+        let result = load_documents(&client, &collection_id).await;
+        let _ = tx.send(TreeUpdate::Documents { collection_id, result });
+    });
+}
+
+/// Fetch every collection, following pagination
+async fn list_all_collections(client: &OutlineClient) -> Result<Vec<Collection>> {
+    let mut collections = Vec::new();
+    loop {
+        let request = ListCollectionsRequest::with_pagination(collections.len() as u32, COLLECTION_PAGE_SIZE);
+        let response = client.list_collections(request).await?;
+        let count = response.data.len() as u32;
+        collections.extend(response.data);
+        if count < COLLECTION_PAGE_SIZE {
+            return Ok(collections);
+        }
+    }
+}
+
+/// Fetch a collection's whole document tree in one `collections.documents` call
+async fn load_documents(client: &OutlineClient, collection_id: &str) -> Result<Vec<NavigationNode>, String> {
+    let request = CollectionDocumentsRequest::new(collection_id.to_string());
+    match client.list_collection_documents(request).await {
+        Ok(response) => {
+            debug!("Loaded {} documents for collection {}", response.data.iter().map(NavigationNode::count).sum::<usize>(), collection_id);
+            Ok(response.data)
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, title: &str, children: Vec<NavigationNode>) -> NavigationNode {
+        NavigationNode {
+            id: id.to_string(),
+            title: title.to_string(),
+            url: format!("/doc/{}", id),
+            icon: None,
+            children,
+        }
+    }
+
+    fn collection(id: &str, name: &str) -> Collection {
+        Collection {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: None,
+            color: None,
+            icon: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            deleted_at: None,
+            archived_at: None,
+            permission: None,
+        }
+    }
+
+    fn engineering_documents() -> Vec<NavigationNode> {
+        vec![
+            node("deploy", "Deploy", vec![node("checklist", "Checklist", vec![node("rollback", "Rollback", vec![])])]),
+            node("runbook", "Runbook", vec![]),
+        ]
+    }
+
+    /// Visible rows as `(id, level)`, with level 0 for collections
+    fn rows(tree: &SidebarTree) -> Vec<(String, usize)> {
+        tree.flatten()
+            .iter()
+            .map(|item| match item {
+                SidebarItem::Collection(c) => (c.id.clone(), 0),
+                SidebarItem::Document(entry, level) => (entry.id.clone(), *level),
+            })
+            .collect()
+    }
+
+    fn ids(rows: &[(&str, usize)]) -> Vec<(String, usize)> {
+        rows.iter().map(|(id, level)| (id.to_string(), *level)).collect()
+    }
+
+    #[test]
+    fn test_documents_load_lazily_on_expand() {
+        let mut tree = SidebarTree::new(BTreeSet::new());
+        tree.loading_collections = true;
+        tree.set_collections(vec![collection("eng", "Engineering"), collection("ops", "Operations")]);
+
+        assert!(!tree.is_loading());
+        assert!(tree.needs_documents("eng"));
+        assert!(!tree.needs_documents("missing"));
+        assert_eq!(tree.unloaded_collection_ids(), vec!["eng", "ops"]);
+        assert_eq!(rows(&tree), ids(&[("eng", 0), ("ops", 0)]));
+
+        // Expanding before the documents arrive shows nothing under the collection
+        assert!(tree.set_expanded("eng", true));
+        tree.mark_loading("eng");
+        assert!(tree.is_loading());
+        assert!(!tree.needs_documents("eng"));
+        assert_eq!(tree.unloaded_collection_ids(), vec!["ops"]);
+        assert_eq!(rows(&tree), ids(&[("eng", 0), ("ops", 0)]));
+
+        tree.set_documents("eng", Ok(engineering_documents()));
+        assert!(!tree.is_loading());
+        assert_eq!(tree.document_count(), 4);
+        assert_eq!(rows(&tree), ids(&[("eng", 0), ("deploy", 1), ("runbook", 1), ("ops", 0)]));
+
+        // Nested documents appear one level deeper as their parents expand
+        tree.set_expanded("deploy", true);
+        tree.set_expanded("checklist", true);
+        assert_eq!(
+            rows(&tree),
+            ids(&[("eng", 0), ("deploy", 1), ("checklist", 2), ("rollback", 3), ("runbook", 1), ("ops", 0)])
+        );
+
+        // Collapsing the collection hides its documents but remembers their state
+        assert!(!tree.set_expanded("eng", false));
+        assert_eq!(rows(&tree), ids(&[("eng", 0), ("ops", 0)]));
+        tree.set_expanded("eng", true);
+        assert_eq!(rows(&tree).len(), 6);
+    }
+
+    #[test]
+    fn test_document_rows_describe_their_children() {
+        let mut tree = SidebarTree::new(BTreeSet::from(["eng".to_string()]));
+        tree.set_collections(vec![collection("eng", "Engineering")]);
+        tree.set_documents("eng", Ok(engineering_documents()));
+
+        let entries: Vec<DocumentEntry> = tree
+            .flatten()
+            .into_iter()
+            .filter_map(|item| match item {
+                SidebarItem::Document(entry, _) => Some(entry),
+                SidebarItem::Collection(_) => None,
+            })
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title, "Deploy");
+        assert_eq!(entries[0].collection_id, "eng");
+        assert!(entries[0].has_children);
+        assert!(!entries[1].has_children);
+    }
+
+    #[test]
+    fn test_failed_documents_are_fetched_again() {
+        let mut tree = SidebarTree::new(BTreeSet::from(["eng".to_string()]));
+        tree.set_collections(vec![collection("eng", "Engineering")]);
+        tree.mark_loading("eng");
+        tree.set_documents("eng", Err("timed out".to_string()));
+
+        assert!(tree.needs_documents("eng"));
+        assert!(!tree.is_loading());
+        assert!(tree.loaded_collection_ids().is_empty());
+        // Only never-fetched collections are preloaded in bulk
+        assert!(tree.unloaded_collection_ids().is_empty());
+        assert_eq!(rows(&tree), ids(&[("eng", 0)]));
+
+        // Results for collections that have gone are dropped
+        tree.set_documents("gone", Ok(engineering_documents()));
+        assert_eq!(tree.document_count(), 0);
+    }
+
+    #[test]
+    fn test_reload_keeps_loaded_documents_of_remaining_collections() {
+        let mut tree = SidebarTree::new(BTreeSet::new());
+        tree.set_collections(vec![collection("eng", "Engineering"), collection("ops", "Operations")]);
+        tree.set_documents("eng", Ok(engineering_documents()));
+        tree.mark_loading("ops");
+        assert_eq!(tree.loaded_collection_ids(), BTreeSet::from(["eng".to_string(), "ops".to_string()]));
+
+        tree.loading_collections = true;
+        tree.set_collections(vec![
+            collection("hr", "People"),
+            collection("eng", "Engineering (renamed)"),
+        ]);
+
+        assert!(!tree.loading_collections);
+        assert_eq!(tree.collections.len(), 2);
+        assert_eq!(tree.collections[1].collection.name, "Engineering (renamed)");
+        assert!(matches!(tree.collections[1].documents, Documents::Loaded(_)));
+        assert!(matches!(tree.collections[0].documents, Documents::NotLoaded));
+        assert_eq!(tree.loaded_collection_ids(), BTreeSet::from(["eng".to_string()]));
+        assert_eq!(tree.unloaded_collection_ids(), vec!["hr"]);
+        assert_eq!(tree.document_count(), 4);
+    }
+
+    #[test]
+    fn test_restored_expand_state_applies_to_reloaded_tree() {
+        let mut tree = SidebarTree::new(BTreeSet::new());
+        tree.set_collections(vec![collection("eng", "Engineering"), collection("ops", "Operations")]);
+        tree.set_documents("eng", Ok(engineering_documents()));
+        tree.set_expanded("eng", true);
+        tree.set_expanded("deploy", true);
+        let expected = rows(&tree);
+
+        // The expand state is saved with the UI state and handed to the next session's tree
+        let saved = tree.expanded.clone();
+        let mut restored = SidebarTree::new(saved);
+        assert!(restored.is_expanded("eng"));
+        assert!(restored.is_expanded("deploy"));
+        assert!(!restored.is_expanded("ops"));
+
+        // Nothing shows under the expanded collection until its documents arrive
+        restored.set_collections(vec![collection("eng", "Engineering"), collection("ops", "Operations")]);
+        assert_eq!(rows(&restored), ids(&[("eng", 0), ("ops", 0)]));
+
+        restored.set_documents("eng", Ok(engineering_documents()));
+        assert_eq!(rows(&restored), expected);
+        assert_eq!(
+            rows(&restored),
+            ids(&[("eng", 0), ("deploy", 1), ("checklist", 2), ("runbook", 1), ("ops", 0)])
+        );
+    }
+
+    #[test]
+    fn test_stale_expand_state_is_ignored() {
+        let expanded = BTreeSet::from(["deleted-collection".to_string(), "deleted-doc".to_string()]);
+        let mut tree = SidebarTree::new(expanded);
+        tree.set_collections(vec![collection("eng", "Engineering")]);
+        tree.set_documents("eng", Ok(engineering_documents()));
+
+        assert_eq!(rows(&tree), ids(&[("eng", 0)]));
+    }
+
+    #[test]
+    fn test_breadcrumb() {
+        let mut tree = SidebarTree::new(BTreeSet::new());
+        tree.set_collections(vec![collection("eng", "Engineering")]);
+
+        // Only the collection is known before its documents load
+        assert_eq!(tree.breadcrumb("eng", "rollback").as_deref(), Some("Engineering"));
+
+        tree.set_documents("eng", Ok(engineering_documents()));
+        let crumb = |path: &[&str]| path.join(BREADCRUMB_SEPARATOR);
+        assert_eq!(
+            tree.breadcrumb("eng", "rollback"),
+            Some(crumb(&["Engineering", "Deploy", "Checklist"]))
+        );
+        assert_eq!(tree.breadcrumb("eng", "checklist"), Some(crumb(&["Engineering", "Deploy"])));
+        assert_eq!(tree.breadcrumb("eng", "runbook"), Some(crumb(&["Engineering"])));
+        // A document missing from the tree gets no parents from other branches
+        assert_eq!(tree.breadcrumb("eng", "missing"), Some(crumb(&["Engineering"])));
+        assert_eq!(tree.breadcrumb("ops", "runbook"), None);
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let documents = engineering_documents();
+
+        let mut path = Vec::new();
+        assert!(ancestors(&documents, "rollback", &mut path));
+        assert_eq!(path, vec!["Deploy", "Checklist"]);
+
+        let mut path = Vec::new();
+        assert!(ancestors(&documents, "runbook", &mut path));
+        assert!(path.is_empty());
+
+        let mut path = vec!["Engineering"];
+        assert!(!ancestors(&documents, "missing", &mut path));
+        assert_eq!(path, vec!["Engineering"]);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::app::App;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph, Wrap},
     Frame,
@@ -9,6 +9,8 @@ use ratatui::{
 
 /// Render the authentication setup page
 pub fn render_auth_page(f: &mut Frame, app: &App) {
+    let theme = app.theme;
+
     let area = f.area();
 
     // Create a centered layout
@@ -37,13 +39,13 @@ pub fn render_auth_page(f: &mut Frame, app: &App) {
         Line::from(""),
         Line::from(vec![
             Span::styled("Outline TUI", Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.accent)
                 .add_modifier(Modifier::BOLD)),
         ]),
         Line::from(""),
         Line::from(Span::styled(
             "Authentication Required",
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from("No authentication configured. Choose a method:"),
@@ -60,9 +62,9 @@ pub fn render_auth_page(f: &mut Frame, app: &App) {
     for (i, (title, desc)) in options.iter().enumerate() {
         let is_selected = i == app.auth_selected;
         let style = if is_selected {
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
         } else {
-            Style::default().fg(Color::White)
+            Style::default().fg(theme.text)
         };
 
         let marker = if is_selected { "  ▶ " } else { "    " };
@@ -75,7 +77,7 @@ pub fn render_auth_page(f: &mut Frame, app: &App) {
         ]));
         lines.push(Line::from(vec![
             Span::raw("      "),
-            Span::styled(*desc, Style::default().fg(Color::Gray)),
+            Span::styled(*desc, Style::default().fg(theme.muted)),
         ]));
         lines.push(Line::from(""));
     }
@@ -84,26 +86,26 @@ pub fn render_auth_page(f: &mut Frame, app: &App) {
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
         "Navigation:",
-        Style::default().fg(Color::DarkGray).add_modifier(Modifier::BOLD),
+        Style::default().fg(theme.dim).add_modifier(Modifier::BOLD),
     )));
     lines.push(Line::from(Span::styled(
         "  ↑/↓ or j/k    Navigate options",
-        Style::default().fg(Color::DarkGray),
+        Style::default().fg(theme.dim),
     )));
     lines.push(Line::from(Span::styled(
         "  Enter or 1/2  Select option",
-        Style::default().fg(Color::DarkGray),
+        Style::default().fg(theme.dim),
     )));
     lines.push(Line::from(Span::styled(
         "  q             Quit",
-        Style::default().fg(Color::DarkGray),
+        Style::default().fg(theme.dim),
     )));
 
     let paragraph = Paragraph::new(lines)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
+                .border_style(Style::default().fg(theme.accent)),
         )
         .alignment(Alignment::Left)
         .wrap(Wrap { trim: false });
@@ -113,6 +115,8 @@ pub fn render_auth_page(f: &mut Frame, app: &App) {
 
 /// Render API token input screen
 pub fn render_api_token_input(f: &mut Frame, app: &App) {
+    let theme = app.theme;
+
     let area = f.area();
 
     let outer_layout = Layout::default()
@@ -139,40 +143,40 @@ pub fn render_api_token_input(f: &mut Frame, app: &App) {
         Line::from(""),
         Line::from(vec![
             Span::styled("API Token Setup", Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.accent)
                 .add_modifier(Modifier::BOLD)),
         ]),
         Line::from(""),
         Line::from("Enter your Outline API token:"),
         Line::from(""),
         Line::from(vec![
-            Span::styled("Token: ", Style::default().fg(Color::Gray)),
-            Span::styled(&app.api_token_input, Style::default().fg(Color::White)),
-            Span::styled("_", Style::default().fg(Color::Yellow)),
+            Span::styled("Token: ", Style::default().fg(theme.muted)),
+            Span::styled(&app.api_token_input, Style::default().fg(theme.text)),
+            Span::styled("_", Style::default().fg(theme.highlight)),
         ]),
         Line::from(""),
         Line::from(""),
         Line::from(Span::styled(
             "Get your API token from Outline settings:",
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.dim),
         )),
         Line::from(Span::styled(
             "  https://app.getoutline.com/settings/tokens",
-            Style::default().fg(Color::Blue),
+            Style::default().fg(theme.link),
         )),
         Line::from(""),
         Line::from(""),
         Line::from(Span::styled(
             "Navigation:",
-            Style::default().fg(Color::DarkGray).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.dim).add_modifier(Modifier::BOLD),
         )),
         Line::from(Span::styled(
             "  Enter         Save token and continue",
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.dim),
         )),
         Line::from(Span::styled(
             "  Esc           Cancel and go back",
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.dim),
         )),
     ];
 
@@ -180,7 +184,7 @@ pub fn render_api_token_input(f: &mut Frame, app: &App) {
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
+                .border_style(Style::default().fg(theme.accent)),
         )
         .alignment(Alignment::Left)
         .wrap(Wrap { trim: false });
@@ -0,0 +1,217 @@
+use crate::app::{App, FocusedPane};
+use crate::comments::{age, CommentRow, CommentsPanel, CommentsStatus, Compose, ComposeMode};
+use crate::theme::Theme;
+use outline_api::Comment;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+/// Render the comments pane for the current document
+pub fn render_comments(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+
+    app.comments_area = Some(area);
+
+    let is_focused = app.focused_pane == FocusedPane::Comments;
+    let border_color = theme.border(is_focused);
+
+    let Some(panel) = &app.comments else {
+        let empty = Paragraph::new(Span::styled("Open a document to see its comments.", Style::default().fg(theme.dim)))
+            .block(Block::default().borders(Borders::ALL).title(" Comments ").border_style(Style::default().fg(border_color)))
+            .wrap(Wrap { trim: false });
+        f.render_widget(empty, area);
+        return;
+    };
+
+    let (open, resolved) = panel.thread_counts();
+    let mut title = format!(" Comments ({} open, {} resolved) ", open, resolved);
+    if panel.saving {
+        title.push_str("(saving…) ");
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(border_color));
+    let block = if is_focused {
+        block.title_bottom(" n:New r:Reply e:Edit d:Delete R:Resolve ")
+    } else {
+        block
+    };
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // The input box or delete prompt sits under the list
+    let footer_height = if panel.compose.is_some() {
+        6
+    } else if panel.confirm_delete.is_some() {
+        3
+    } else {
+        0
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(footer_height)])
+        .split(inner);
+
+    render_threads(f, panel, is_focused, chunks[0], theme);
+
+    if let Some(compose) = &panel.compose {
+        render_compose(f, compose, chunks[1], theme);
+    } else if panel.confirm_delete.is_some() {
+        let prompt = Paragraph::new(Line::from(vec![
+            Span::styled("Delete this comment", Style::default().fg(theme.error)),
+            Span::raw(" and its replies? Press "),
+            Span::styled("d", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" again to confirm."),
+        ]))
+        .block(Block::default().borders(Borders::TOP))
+        .wrap(Wrap { trim: false });
+        f.render_widget(prompt, chunks[1]);
+    }
+}
+
+fn render_threads(f: &mut Frame, panel: &CommentsPanel, is_focused: bool, area: Rect, theme: Theme) {
+    let rows = panel.rows();
+
+    if rows.is_empty() {
+        let message = match &panel.status {
+            CommentsStatus::Loading => Span::styled("Loading comments…", Style::default().fg(theme.highlight)),
+            CommentsStatus::Failed(e) => Span::styled(format!("Failed to load comments: {}", e), Style::default().fg(theme.error)),
+            CommentsStatus::Loaded if panel.comments.is_empty() => {
+                Span::styled("No comments yet. Press n to start a thread.", Style::default().fg(theme.dim))
+            }
+            CommentsStatus::Loaded => {
+                Span::styled("All threads are resolved. Press s to show them.", Style::default().fg(theme.dim))
+            }
+        };
+        f.render_widget(Paragraph::new(message).wrap(Wrap { trim: false }), area);
+        return;
+    }
+
+    let width = area.width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| ListItem::new(comment_text(&panel.comments[row.index], *row, i > 0, width, theme)))
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(Style::default().bg(if is_focused { theme.selection } else { theme.dim }))
+        .highlight_symbol(if is_focused { "▶" } else { " " });
+
+    let mut state = ListState::default();
+    state.select(Some(panel.selected));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+/// One comment: author, age and state, the anchored text for thread roots, then the body
+fn comment_text(comment: &Comment, row: CommentRow, spaced: bool, width: usize, theme: Theme) -> Text<'static> {
+    let indent = "  ".repeat(row.depth);
+    let mut lines = Vec::new();
+
+    // A blank line between threads
+    if spaced && row.depth == 0 {
+        lines.push(Line::from(""));
+    }
+
+    let mut header = vec![Span::raw(indent.clone())];
+    if row.depth == 0 {
+        header.push(if comment.resolved_at.is_some() {
+            Span::styled("✓ ", Style::default().fg(theme.success))
+        } else {
+            Span::styled("● ", Style::default().fg(theme.highlight))
+        });
+    } else {
+        header.push(Span::styled("└ ", Style::default().fg(theme.dim)));
+    }
+    header.push(Span::styled(
+        comment.created_by.name.clone(),
+        Style::default().fg(theme.text).add_modifier(Modifier::BOLD),
+    ));
+    header.push(Span::styled(format!(" · {}", age(&comment.created_at)), Style::default().fg(theme.dim)));
+    if comment.updated_at != comment.created_at {
+        header.push(Span::styled(" (edited)", Style::default().fg(theme.dim)));
+    }
+    if let Some(by) = &comment.resolved_by {
+        header.push(Span::styled(format!(" · resolved by {}", by.name), Style::default().fg(theme.success)));
+    }
+    lines.push(Line::from(header));
+
+    let body_indent = format!("{}  ", indent);
+    let body_width = width.saturating_sub(body_indent.len()).max(10);
+
+    if let Some(anchor) = comment.anchor_text.as_deref().filter(|a| !a.trim().is_empty()) {
+        let quote: String = anchor.split_whitespace().collect::<Vec<_>>().join(" ");
+        let quote: String = quote.chars().take(body_width.saturating_sub(2)).collect();
+        lines.push(Line::from(vec![
+            Span::raw(body_indent.clone()),
+            Span::styled(
+                format!("│ {}", quote),
+                Style::default().fg(theme.dim).add_modifier(Modifier::ITALIC),
+            ),
+        ]));
+    }
+
+    for line in comment.data.text.lines() {
+        for wrapped in wrap(line, body_width) {
+            lines.push(Line::from(vec![Span::raw(body_indent.clone()), Span::raw(wrapped)]));
+        }
+    }
+
+    Text::from(lines)
+}
+
+fn render_compose(f: &mut Frame, compose: &Compose, area: Rect, theme: Theme) {
+    let title = match compose.mode {
+        ComposeMode::New => " New comment ",
+        ComposeMode::Reply { .. } => " Reply ",
+        ComposeMode::Edit { .. } => " Edit comment ",
+    };
+
+    let mut lines: Vec<Line> = compose.text.split('\n').map(|l| Line::from(l.to_string())).collect();
+    if let Some(last) = lines.last_mut() {
+        last.spans.push(Span::styled("█", Style::default().fg(theme.accent)));
+    }
+
+    let input = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(" Enter: Send | Alt+Enter: Newline | Esc: Cancel ")
+                .border_style(Style::default().fg(theme.accent)),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(input, area);
+}
+
+/// Break text into lines of at most `width` characters, at spaces where possible
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split(' ') {
+        let needed = if current.is_empty() { word.chars().count() } else { current.chars().count() + 1 + word.chars().count() };
+        if needed > width && !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+
+        // Split words longer than the line
+        while current.chars().count() > width {
+            let head: String = current.chars().take(width).collect();
+            current = current.chars().skip(width).collect();
+            lines.push(head);
+        }
+    }
+    lines.push(current);
+    lines
+}
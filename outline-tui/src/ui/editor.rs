@@ -1,17 +1,37 @@
 use crate::app::{App, EditorMode, FocusedPane, VimMode};
+use crate::buffers::{Buffer, SplitDirection};
+use crate::render;
+use crate::theme::Theme;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, Paragraph, Wrap},
     Frame,
 };
-use tui_markdown::from_str as markdown_from_str;
+use std::collections::HashMap;
+
+/// Columns left of the text for comment marks
+const GUTTER_WIDTH: u16 = 2;
 
 /// Render the document editor/viewer pane
 pub fn render_editor(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+
     let is_focused = app.focused_pane == FocusedPane::Editor;
 
+    // With more than one document open, a tab bar goes above the title
+    let area = if app.buffers.len() > 1 {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(area);
+        render_tabs(f, app, chunks[0]);
+        chunks[1]
+    } else {
+        area
+    };
+
     // Split into title and content areas
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -23,44 +43,133 @@ pub fn render_editor(f: &mut Frame, app: &mut App, area: Rect) {
 
     // Render title
     render_document_title(f, app, is_focused, chunks[0]);
+    app.content_area = None;
+
+    // A pending merge takes over the content area until it's resolved
+    if app.merge_view.is_some() {
+        super::merge::render_merge_view(f, app, chunks[1]);
+        return;
+    }
+
+    // The other buffer of a split shares the content area
+    let mut content_area = chunks[1];
+    if let Some(split) = app.split {
+        if let Some(buffer) = app.buffers.get(split.buffer) {
+            let direction = match split.direction {
+                SplitDirection::Vertical => Direction::Horizontal,
+                SplitDirection::Horizontal => Direction::Vertical,
+            };
+            let halves = Layout::default()
+                .direction(direction)
+                .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+                .split(content_area);
+            render_split_buffer(f, buffer, halves[1], theme, app.users.names());
+            content_area = halves[0];
+        }
+    }
 
     // Render content based on mode
     match app.editor_mode {
-        EditorMode::View => render_document_content(f, app, is_focused, chunks[1]),
-        EditorMode::Edit => render_text_editor(f, app, is_focused, chunks[1]),
+        EditorMode::View => render_document_content(f, app, is_focused, content_area),
+        EditorMode::Edit => render_text_editor(f, app, is_focused, content_area),
     }
 }
 
+/// Render the tab bar of open documents, marking the active one and unsaved changes
+fn render_tabs(f: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme;
+
+    let active_title = app.current_document.as_ref().map_or("", |doc| doc.title.as_str());
+    let active = app.buffers.active();
+
+    let mut spans = Vec::new();
+    for (i, (title, dirty)) in app.buffers.tabs(active_title, app.is_dirty()).into_iter().enumerate() {
+        let style = if i == active {
+            Style::default().fg(theme.accent_text).bg(theme.accent).add_modifier(Modifier::BOLD)
+        } else if app.split.is_some_and(|split| split.buffer == i) {
+            Style::default().fg(theme.accent)
+        } else {
+            Style::default().fg(theme.muted)
+        };
+        spans.push(Span::styled(format!(" {}:{} ", i + 1, title), style));
+        if dirty {
+            spans.push(Span::styled("● ", Style::default().fg(theme.highlight)));
+        }
+        spans.push(Span::styled("│", Style::default().fg(theme.dim)));
+    }
+
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// Render the inactive buffer of a split, read-only at its own scroll position
+fn render_split_buffer(f: &mut Frame, buffer: &Buffer, area: Rect, theme: Theme, users: &HashMap<String, String>) {
+    let content_text = match buffer.editor_mode {
+        // Unsaved edits are shown as they are in the editor
+        EditorMode::Edit => Text::from(buffer.textarea.lines().join("\n")),
+        EditorMode::View if buffer.text.is_empty() => Text::from("(Empty document)"),
+        EditorMode::View => {
+            let width = area.width.saturating_sub(2) as usize;
+            render::render(&buffer.text, width, &theme, users, &HashMap::new()).text
+        }
+    };
+    let dirty = if buffer.is_dirty() { " [+]" } else { "" };
+
+    let content = Paragraph::new(content_text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} {}{} ", buffer.document.icon(), buffer.document.title, dirty))
+                .title_bottom(" Ctrl-w:Switch ")
+                .border_style(Style::default().fg(theme.dim)),
+        )
+        .style(Style::default().fg(theme.text))
+        .wrap(Wrap { trim: false })
+        .scroll((buffer.scroll_offset, 0));
+
+    f.render_widget(content, area);
+}
+
 /// Render the document title
 fn render_document_title(f: &mut Frame, app: &App, is_focused: bool, area: Rect) {
+    let theme = app.theme;
+
     let title_text = if let Some(doc) = &app.current_document {
         let icon = doc.icon();
-        vec![
+        let mut spans = vec![
             Span::raw(icon),
             Span::raw(" "),
             Span::styled(
-                &doc.title,
+                doc.title.clone(),
                 Style::default()
-                    .fg(Color::White)
+                    .fg(theme.text)
                     .add_modifier(Modifier::BOLD),
             ),
-        ]
+        ];
+
+        // Breadcrumb of the section at the top of the view
+        if let Some(section) = app.current_section().filter(|_| app.editor_mode == EditorMode::View) {
+            for index in app.toc.path(section) {
+                spans.push(Span::styled(" › ", Style::default().fg(theme.dim)));
+                spans.push(Span::styled(app.toc.entries[index].title.clone(), Style::default().fg(theme.muted)));
+            }
+        }
+        spans
     } else {
         vec![Span::styled(
             "No document selected",
-            Style::default().fg(Color::Gray),
+            Style::default().fg(theme.muted),
         )]
     };
 
     let mode_indicator = match app.editor_mode {
-        EditorMode::View => Span::styled(" [VIEW]", Style::default().fg(Color::Green)),
+        EditorMode::View => Span::styled(" [VIEW]", Style::default().fg(theme.success)),
         EditorMode::Edit => {
             let vim_mode_text = match app.vim_mode {
                 VimMode::Normal => "NORMAL",
                 VimMode::Insert => "INSERT",
                 VimMode::Visual => "VISUAL",
             };
-            Span::styled(format!(" [{}]", vim_mode_text), Style::default().fg(Color::Yellow))
+            Span::styled(format!(" [{}]", vim_mode_text), Style::default().fg(theme.highlight))
         }
     };
 
@@ -68,13 +177,13 @@ fn render_document_title(f: &mut Frame, app: &App, is_focused: bool, area: Rect)
     let collab_indicator = {
         use outline_api::collaboration::ConnectionStatus;
         match &app.collaboration_status {
-            ConnectionStatus::Connected => Span::styled(" [COLLAB]", Style::default().fg(Color::Cyan)),
-            ConnectionStatus::Connecting => Span::styled(" [CONNECTING...]", Style::default().fg(Color::Yellow)),
-            ConnectionStatus::Synced => Span::styled(" [SYNCED]", Style::default().fg(Color::Green)),
+            ConnectionStatus::Connected => Span::styled(" [COLLAB]", Style::default().fg(theme.accent)),
+            ConnectionStatus::Connecting => Span::styled(" [CONNECTING...]", Style::default().fg(theme.highlight)),
+            ConnectionStatus::Synced => Span::styled(" [SYNCED]", Style::default().fg(theme.success)),
             ConnectionStatus::Disconnected => Span::raw(""),
             ConnectionStatus::Error(e) => Span::styled(
                 format!(" [ERROR: {}]", e),
-                Style::default().fg(Color::Red)
+                Style::default().fg(theme.error)
             ),
         }
     };
@@ -83,11 +192,7 @@ fn render_document_title(f: &mut Frame, app: &App, is_focused: bool, area: Rect)
     title_line.push(mode_indicator);
     title_line.push(collab_indicator);
 
-    let border_color = if is_focused {
-        Color::Cyan
-    } else {
-        Color::Gray
-    };
+    let border_color = theme.border(is_focused);
 
     let title = Paragraph::new(Line::from(title_line)).block(
         Block::default()
@@ -99,30 +204,39 @@ fn render_document_title(f: &mut Frame, app: &App, is_focused: bool, area: Rect)
 }
 
 /// Render the document content
-fn render_document_content(f: &mut Frame, app: &App, is_focused: bool, area: Rect) {
-    let border_color = if is_focused {
-        Color::Cyan
-    } else {
-        Color::Gray
-    };
+fn render_document_content(f: &mut Frame, app: &mut App, is_focused: bool, area: Rect) {
+    let theme = app.theme;
+
+    let border_color = theme.border(is_focused);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Content ")
+        .border_style(Style::default().fg(border_color));
+
+    // The text is rendered for this width before the next frame (see `App::sync_rendered`)
+    let mut text_area = block.inner(area);
+    let gutter = if app.comments.is_some() { GUTTER_WIDTH } else { 0 };
+    text_area.x += gutter;
+    text_area.width = text_area.width.saturating_sub(gutter);
+    app.render_width = text_area.width;
 
     let content_text = if app.current_document.is_none() {
         Text::from("Select a document from the sidebar to view its content.")
     } else if app.document_text.is_empty() {
         Text::from("(Empty document - press 'e' to edit)")
     } else {
-        // Parse markdown and convert to ratatui Text
-        markdown_from_str(&app.document_text)
+        app.content_area = Some(text_area);
+        let mut text = app.rendered.text.clone();
+        draw_images(app, &mut text, text_area);
+        highlight_link_cursor(app, &mut text);
+        highlight_task_cursor(app, &mut text);
+        add_comment_gutter(app, &mut text);
+        text
     };
 
     let content = Paragraph::new(content_text)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" Content ")
-                .border_style(Style::default().fg(border_color)),
-        )
-        .style(Style::default().fg(Color::White))
+        .block(block)
+        .style(Style::default().fg(theme.text))
         .wrap(Wrap { trim: false })
         .scroll((app.scroll_offset, 0));
 
@@ -131,11 +245,9 @@ fn render_document_content(f: &mut Frame, app: &App, is_focused: bool, area: Rec
 
 /// Render the text editor (edit mode)
 fn render_text_editor(f: &mut Frame, app: &mut App, is_focused: bool, area: Rect) {
-    let border_color = if is_focused {
-        Color::Cyan
-    } else {
-        Color::Gray
-    };
+    let theme = app.theme;
+
+    let border_color = theme.border(is_focused);
 
     let mut textarea = app.textarea.clone();
     textarea.set_block(
@@ -147,11 +259,118 @@ fn render_text_editor(f: &mut Frame, app: &mut App, is_focused: bool, area: Rect
 
     // Set cursor style based on vim mode
     let cursor_style = match app.vim_mode {
-        VimMode::Normal => Style::default().bg(Color::Gray),
-        VimMode::Insert => Style::default().bg(Color::Green),
-        VimMode::Visual => Style::default().bg(Color::Blue),
+        VimMode::Normal => Style::default().bg(theme.muted),
+        VimMode::Insert => Style::default().bg(theme.success),
+        VimMode::Visual => Style::default().bg(theme.selection),
     };
     textarea.set_cursor_style(cursor_style);
 
     f.render_widget(&textarea, area);
 }
+
+/// Draw attached images into the lines left for them: with the terminal's graphics
+/// protocol when the whole image is in view, as half blocks otherwise
+fn draw_images(app: &mut App, text: &mut Text<'static>, area: Rect) {
+    let Some(last) = app.rendered.images.iter().filter(|image| image.rows > 0).map(|image| image.line).max() else {
+        return;
+    };
+
+    // Screen row of each line before scrolling, as long lines wrap
+    let mut rows = Vec::with_capacity(last + 1);
+    let mut row = 0;
+    for line in text.lines.iter().take(last + 1) {
+        rows.push(row);
+        row += Paragraph::new(line.clone()).wrap(Wrap { trim: false }).line_count(area.width).max(1);
+    }
+
+    for image in app.rendered.images.clone() {
+        let Some(attachment) = app.attachments.get(&image.id).cloned() else {
+            continue;
+        };
+        let Some(&row) = rows.get(image.line).filter(|_| image.rows > 0) else {
+            continue;
+        };
+        let (cols, height) = (image.cols as u16, image.rows as u16);
+        let top = row as i64 - app.scroll_offset as i64;
+        let in_view = top >= 0 && top + height as i64 <= area.height as i64;
+
+        if in_view && app.image_layer.can_place(&attachment) {
+            let area = Rect::new(area.x + image.column as u16, area.y + top as u16, cols, height);
+            app.image_layer.place(&image.id, area);
+        } else if let Some(decoded) = &attachment.image {
+            let blocks = app.image_layer.half_blocks(&image.id, decoded, cols, height);
+            for (line, block) in text.lines.iter_mut().skip(image.line).zip(blocks) {
+                line.spans.extend(block.spans.iter().cloned());
+            }
+        }
+    }
+}
+
+/// Highlight the link picked with `]l`/`[l`
+fn highlight_link_cursor(app: &App, text: &mut Text) {
+    let Some(link) = app.links.as_ref().and_then(|panel| panel.cursor_link()) else {
+        return;
+    };
+    let Some(rendered) = app.rendered.links.iter().find(|r| r.offset == link.range.start) else {
+        return;
+    };
+    if let Some(line) = text.lines.get_mut(rendered.line) {
+        for span in line.spans.iter_mut().take(rendered.spans.end).skip(rendered.spans.start) {
+            span.style = span.style.add_modifier(Modifier::REVERSED);
+        }
+    }
+}
+
+/// Highlight the checkbox picked with `]t`/`[t`
+fn highlight_task_cursor(app: &App, text: &mut Text) {
+    let Some(task) = app.task_cursor.and_then(|i| app.rendered.tasks.get(i)) else {
+        return;
+    };
+    let Some(line) = text.lines.get_mut(task.line) else {
+        return;
+    };
+    let mut column = 0;
+    for span in line.spans.iter_mut() {
+        if column == task.column {
+            span.style = span.style.add_modifier(Modifier::REVERSED);
+            break;
+        }
+        column += span.width();
+    }
+}
+
+/// Mark lines that comment threads are anchored to in a gutter left of the text
+fn add_comment_gutter(app: &App, text: &mut Text) {
+    let theme = app.theme;
+
+    let Some(panel) = &app.comments else {
+        return;
+    };
+    let anchors: Vec<(usize, bool, bool)> = panel
+        .anchors(&app.rendered.text)
+        .into_iter()
+        .map(|(line, comment)| {
+            let selected = app.focused_pane == FocusedPane::Comments
+                && panel.selected_thread().is_some_and(|thread| thread.id == comment.id);
+            (line, comment.resolved_at.is_some(), selected)
+        })
+        .collect();
+
+    for (i, line) in text.lines.iter_mut().enumerate() {
+        let here: Vec<_> = anchors.iter().filter(|(anchor, _, _)| *anchor == i).collect();
+        let style = if here.iter().any(|(_, _, selected)| *selected) {
+            Some(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))
+        } else if here.iter().any(|(_, resolved, _)| !resolved) {
+            Some(Style::default().fg(theme.highlight))
+        } else if !here.is_empty() {
+            Some(Style::default().fg(theme.dim))
+        } else {
+            None
+        };
+        let mark = match style {
+            Some(style) => Span::styled("▍ ", style),
+            None => Span::raw("  "),
+        };
+        line.spans.insert(0, mark);
+    }
+}
@@ -0,0 +1,200 @@
+use crate::app::App;
+use crate::finder::{Finder, FinderMatch, Preview, SearchHit, SearchStatus};
+use crate::render;
+use crate::theme::Theme;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+use std::collections::HashMap;
+
+use super::modal::centered_rect;
+use super::search::context_line;
+
+/// Render the "go to document" finder: query, results and a preview of the highlighted document
+pub fn render_finder(f: &mut Frame, app: &App) {
+    let theme = app.theme;
+
+    let Some(finder) = &app.finder else {
+        return;
+    };
+
+    let area = centered_rect(90, 85, f.area());
+    f.render_widget(Clear, area);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(columns[0]);
+
+    render_query(f, app, finder, rows[0]);
+    render_results(f, finder, rows[1], theme);
+    render_preview(f, finder, columns[1], theme, app.users.names());
+}
+
+fn render_query(f: &mut Frame, app: &App, finder: &Finder, area: Rect) {
+    let theme = app.theme;
+
+    let loading = if app.sidebar_tree.is_loading() { ", loading more…" } else { "" };
+    let title = format!(" Go to document ({} loaded{}) ", finder.candidate_count(), loading);
+
+    let input = Paragraph::new(Line::from(vec![
+        Span::styled("> ", Style::default().fg(theme.accent)),
+        Span::raw(finder.query.as_str()),
+        Span::styled("█", Style::default().fg(theme.accent)),
+    ]))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(theme.accent)),
+    );
+
+    f.render_widget(input, area);
+}
+
+fn render_results(f: &mut Frame, finder: &Finder, area: Rect, theme: Theme) {
+    let width = area.width.saturating_sub(4) as usize;
+    let mut items = Vec::new();
+    let mut selected_row = None;
+
+    items.push(section_header(format!("Documents ({})", finder.matches.len()), theme));
+    for (i, m) in finder.matches.iter().enumerate() {
+        if i == finder.selected {
+            selected_row = Some(items.len());
+        }
+        items.push(ListItem::new(match_line(m, theme)));
+    }
+    if finder.matches.is_empty() {
+        items.push(hint("No matching documents", theme));
+    }
+
+    items.push(ListItem::new(Line::from("")));
+    items.push(section_header("Full-text results".to_string(), theme));
+    let hits: Vec<&SearchHit> = finder.visible_hits().collect();
+    for (i, hit) in hits.iter().enumerate() {
+        if finder.matches.len() + i == finder.selected {
+            selected_row = Some(items.len());
+        }
+        items.push(ListItem::new(hit_lines(hit, width, theme)));
+    }
+    match &finder.search {
+        SearchStatus::Idle => items.push(hint("Type to search document contents", theme)),
+        SearchStatus::Waiting(_) | SearchStatus::Searching => items.push(hint("Searching…", theme)),
+        SearchStatus::Done if hits.is_empty() => {
+            let message = if finder.hits.is_empty() { "No results" } else { "No other results" };
+            items.push(hint(message, theme));
+        }
+        SearchStatus::Done => {}
+        SearchStatus::Failed(e) => items.push(ListItem::new(Line::from(Span::styled(
+            format!("  Search failed: {}", e),
+            Style::default().fg(theme.error),
+        )))),
+    }
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" ↑/↓ Ctrl-n/p: Select | Enter: Open | Ctrl-u: Clear | Esc: Close ")
+                .border_style(Style::default().fg(theme.accent)),
+        )
+        .highlight_style(Style::default().bg(theme.selection).add_modifier(Modifier::BOLD))
+        .highlight_symbol("▶ ");
+
+    let mut state = ListState::default();
+    state.select(selected_row);
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn render_preview(f: &mut Frame, finder: &Finder, area: Rect, theme: Theme, users: &HashMap<String, String>) {
+    let title = finder
+        .selected_title()
+        .map(|title| format!(" {} ", title))
+        .unwrap_or_else(|| " Preview ".to_string());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(theme.muted));
+
+    let preview = match finder.selected_preview() {
+        Some(Preview::Loaded(text)) if text.trim().is_empty() => {
+            Paragraph::new(Span::styled("(empty document)", Style::default().fg(theme.dim)))
+        }
+        Some(Preview::Loaded(text)) => {
+            Paragraph::new(render::render(text, area.width.saturating_sub(2) as usize, &theme, users, &HashMap::new()).text)
+        }
+        Some(Preview::Failed(e)) => Paragraph::new(Span::styled(
+            format!("Failed to load preview: {}", e),
+            Style::default().fg(theme.error),
+        )),
+        Some(Preview::Loading) => Paragraph::new(Span::styled("Loading…", Style::default().fg(theme.highlight))),
+        None => Paragraph::new(Text::default()),
+    };
+
+    f.render_widget(preview.block(block).wrap(Wrap { trim: false }), area);
+}
+
+/// A local match: icon, then breadcrumb and title with the matched characters highlighted
+fn match_line(m: &FinderMatch, theme: Theme) -> Line<'static> {
+    let mut spans = vec![Span::raw(m.candidate.icon.clone()), Span::raw("  ")];
+
+    let mut matched = m.indices.iter().peekable();
+    let mut run = String::new();
+    let mut run_style = None;
+    for (i, c) in m.candidate.path.chars().enumerate() {
+        let is_match = matched.next_if(|&&index| index as usize == i).is_some();
+        let style = if is_match {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else if i >= m.candidate.title_start {
+            Style::default().fg(theme.text)
+        } else {
+            Style::default().fg(theme.dim)
+        };
+
+        if run_style.is_some_and(|s| s != style) {
+            spans.push(Span::styled(std::mem::take(&mut run), run_style.unwrap_or_default()));
+        }
+        run_style = Some(style);
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(Span::styled(run, run_style.unwrap_or_default()));
+    }
+
+    Line::from(spans)
+}
+
+/// A full-text result: icon and title, then the context with the matched words highlighted
+fn hit_lines(hit: &SearchHit, width: usize, theme: Theme) -> Text<'static> {
+    let title = Line::from(vec![
+        Span::raw(hit.icon.clone()),
+        Span::raw("  "),
+        Span::styled(hit.title.clone(), Style::default().fg(theme.text)),
+    ]);
+
+    // Keep the context to one line so every result takes the same space
+    Text::from(vec![title, context_line(&hit.context, width, theme)])
+}
+
+fn section_header(title: String, theme: Theme) -> ListItem<'static> {
+    ListItem::new(Line::from(Span::styled(
+        title,
+        Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
+    )))
+}
+
+fn hint(message: &str, theme: Theme) -> ListItem<'static> {
+    ListItem::new(Line::from(Span::styled(
+        format!("  {}", message),
+        Style::default().fg(theme.dim),
+    )))
+}
@@ -0,0 +1,130 @@
+use crate::app::{App, FocusedPane};
+use crate::links::{BacklinksStatus, LinksPanel};
+use crate::theme::Theme;
+use outline_api::markdown::LinkTarget;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+/// Render the links pane: backlinks, then links and mentions in the current document
+pub fn render_links(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+
+    app.links_area = Some(area);
+
+    let is_focused = app.focused_pane == FocusedPane::Links;
+    let border_color = theme.border(is_focused);
+
+    let Some(panel) = &app.links else {
+        let empty = Paragraph::new(Span::styled("Open a document to see its links.", Style::default().fg(theme.dim)))
+            .block(Block::default().borders(Borders::ALL).title(" Links ").border_style(Style::default().fg(border_color)))
+            .wrap(Wrap { trim: false });
+        f.render_widget(empty, area);
+        return;
+    };
+
+    let mut title = format!(" Links ({} in, {} out) ", panel.backlinks.len(), panel.outgoing.len());
+    if app.history.can_go_back() || app.history.can_go_forward() {
+        let back = if app.history.can_go_back() { "◀" } else { "◁" };
+        let forward = if app.history.can_go_forward() { "▶" } else { "▷" };
+        title.push_str(&format!("{}{} ", back, forward));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(border_color));
+    let block = if is_focused {
+        block.title_bottom(" Enter:Follow H/L:Back/Forward ")
+    } else {
+        block
+    };
+
+    let (items, selected_item) = link_items(app, panel);
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(if is_focused { theme.selection } else { theme.dim }))
+        .highlight_symbol(if is_focused { "▶ " } else { "  " });
+
+    let mut state = ListState::default();
+    state.select(selected_item);
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+/// List items under "Backlinks" and "Outgoing" headers, and which item is selected
+fn link_items(app: &App, panel: &LinksPanel) -> (Vec<ListItem<'static>>, Option<usize>) {
+    let theme = app.theme;
+
+    let mut items = Vec::new();
+    let mut selected = None;
+
+    items.push(section_header(format!("Backlinks ({})", panel.backlinks.len()), theme));
+    for (i, backlink) in panel.backlinks.iter().enumerate() {
+        if i == panel.selected {
+            selected = Some(items.len());
+        }
+        let mut line = vec![
+            Span::raw(backlink.icon.clone()),
+            Span::raw(" "),
+            Span::styled(backlink.title.clone(), Style::default().fg(theme.text)),
+        ];
+        let collection = backlink
+            .collection_id
+            .as_deref()
+            .and_then(|id| app.sidebar_tree.collection(id));
+        if let Some(node) = collection {
+            line.push(Span::styled(format!(" · {}", node.collection.name), Style::default().fg(theme.dim)));
+        }
+        items.push(ListItem::new(Line::from(line)));
+    }
+    match &panel.status {
+        BacklinksStatus::Loading => items.push(hint("Loading…", theme.highlight)),
+        BacklinksStatus::Failed(e) => items.push(hint(&format!("Failed to load backlinks: {}", e), theme.error)),
+        BacklinksStatus::Loaded if panel.backlinks.is_empty() => {
+            items.push(hint("No documents link here", theme.dim))
+        }
+        BacklinksStatus::Loaded => {}
+    }
+
+    items.push(ListItem::new(Line::from("")));
+    items.push(section_header(format!("Outgoing ({})", panel.outgoing.len()), theme));
+    for (i, link) in panel.outgoing.iter().enumerate() {
+        if panel.backlinks.len() + i == panel.selected {
+            selected = Some(items.len());
+        }
+        let (marker, color) = match panel.target(link) {
+            LinkTarget::Document(_) => ("→ ", theme.text),
+            LinkTarget::User(_) => ("@", theme.mention),
+            LinkTarget::External(_) => ("↗ ", theme.link),
+            LinkTarget::Other(_) => ("  ", theme.dim),
+        };
+        let mut line = vec![
+            Span::styled(marker, Style::default().fg(color)),
+            Span::styled(link.text.clone(), Style::default().fg(color)),
+        ];
+        if panel.cursor == Some(i) {
+            line.push(Span::styled(" ◀", Style::default().fg(theme.accent).add_modifier(Modifier::BOLD)));
+        }
+        items.push(ListItem::new(Line::from(line)));
+    }
+    if panel.outgoing.is_empty() {
+        items.push(hint("No links in this document", theme.dim));
+    }
+
+    (items, selected)
+}
+
+fn section_header(title: String, theme: Theme) -> ListItem<'static> {
+    ListItem::new(Line::from(Span::styled(
+        title,
+        Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
+    )))
+}
+
+fn hint(message: &str, color: Color) -> ListItem<'static> {
+    ListItem::new(Line::from(Span::styled(format!("  {}", message), Style::default().fg(color))))
+}
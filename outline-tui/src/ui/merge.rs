@@ -0,0 +1,159 @@
+use crate::actions::Action;
+use crate::app::{App, MergeView};
+use crate::keymap::KeyContext;
+use crate::theme::Theme;
+use outline_api::merge::ChunkKind;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+/// Which version a merge column shows
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Base,
+    Mine,
+    Theirs,
+}
+
+/// Render the three-way merge view (base / mine / theirs side by side)
+pub fn render_merge_view(f: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme;
+
+    let Some(view) = &app.merge_view else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4),  // Summary and keys
+            Constraint::Min(0),     // Versions
+        ])
+        .split(area);
+
+    render_merge_summary(f, app, view, chunks[0]);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+        ])
+        .split(chunks[1]);
+
+    let base_revision = app.current_document.as_ref().map(|d| d.revision).unwrap_or_default();
+    let theirs_author = view.latest.updated_by.as_ref()
+        .map(|u| format!(", {}", u.name))
+        .unwrap_or_default();
+
+    let titles = [
+        format!(" Base (rev {}) ", base_revision),
+        " Mine ".to_string(),
+        format!(" Theirs (rev {}{}) ", view.latest.revision, theirs_author),
+    ];
+
+    for ((side, title), column) in [Side::Base, Side::Mine, Side::Theirs]
+        .into_iter()
+        .zip(titles)
+        .zip(columns.iter())
+    {
+        let paragraph = Paragraph::new(column_lines(view, side, theme))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(Style::default().fg(theme.highlight)),
+            )
+            .scroll((view.scroll, 0));
+        f.render_widget(paragraph, *column);
+    }
+}
+
+fn render_merge_summary(f: &mut Frame, app: &App, view: &MergeView, area: Rect) {
+    let theme = app.theme;
+    let conflicts = view.merge.conflict_count();
+    let summary = if conflicts == 0 {
+        Span::styled(
+            "Changed on the server since you opened it - changes merge cleanly",
+            Style::default().fg(theme.success),
+        )
+    } else {
+        Span::styled(
+            format!("Changed on the server since you opened it - {} conflicting section(s)", conflicts),
+            Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
+        )
+    };
+
+    let keys = if conflicts == 0 {
+        super::key_hints(app, KeyContext::Merge, &[
+            (Action::SaveMerge, "Save merged"),
+            (Action::EditDocument, "Edit merged"),
+            (Action::KeepMine, "Keep mine"),
+            (Action::TakeTheirs, "Take theirs"),
+            (Action::Cancel, "Cancel"),
+        ])
+    } else {
+        super::key_hints(app, KeyContext::Merge, &[
+            (Action::NextConflict, "Next conflict"),
+            (Action::EditDocument, "Edit with markers"),
+            (Action::KeepMine, "Keep mine"),
+            (Action::TakeTheirs, "Take theirs"),
+            (Action::Cancel, "Cancel"),
+        ])
+    };
+
+    let paragraph = Paragraph::new(vec![
+        Line::from(summary),
+        Line::from(Span::styled(keys, Style::default().fg(theme.muted))),
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Merge ")
+            .border_style(Style::default().fg(theme.highlight)),
+    )
+    .wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, area);
+}
+
+/// Lines of one version, padded so chunks line up across the three columns
+fn column_lines(view: &MergeView, side: Side, theme: Theme) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+
+    for chunk in &view.merge.chunks {
+        let text = match side {
+            Side::Base => &chunk.base,
+            Side::Mine => &chunk.mine,
+            Side::Theirs => &chunk.theirs,
+        };
+
+        let changed = match chunk.kind {
+            ChunkKind::Unchanged => false,
+            ChunkKind::Mine => side == Side::Mine,
+            ChunkKind::Theirs => side == Side::Theirs,
+            ChunkKind::Both => side != Side::Base,
+            ChunkKind::Conflict => true,
+        };
+        let style = match (chunk.kind, changed) {
+            (_, false) => Style::default().fg(theme.muted),
+            (ChunkKind::Conflict, true) => Style::default().fg(theme.error),
+            (_, true) if side == Side::Mine => Style::default().fg(theme.highlight),
+            (_, true) => Style::default().fg(theme.accent),
+        };
+
+        for line in text {
+            lines.push(Line::from(Span::styled(line.as_str(), style)));
+        }
+        for _ in text.len()..MergeView::chunk_height(chunk) {
+            lines.push(Line::from(Span::styled("~", Style::default().fg(theme.dim))));
+        }
+    }
+
+    lines
+}
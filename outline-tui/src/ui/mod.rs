@@ -1,12 +1,21 @@
 pub mod sidebar;
 pub mod editor;
+pub mod merge;
 pub mod modal;
 pub mod auth;
+pub mod comments;
+pub mod finder;
+pub mod links;
+pub mod search;
+pub mod toc;
 
+use crate::actions::Action;
 use crate::app::{App, AppView, FocusedPane};
+use crate::keymap::KeyContext;
+use crate::theme::Theme;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
     Frame,
@@ -19,6 +28,9 @@ pub use auth::{render_auth_page, render_api_token_input};
 
 /// Render the entire UI
 pub fn render(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    app.image_layer.clear();
+
     match app.view {
         AppView::AuthSetup => {
             // Show auth setup page (full screen)
@@ -43,50 +55,98 @@ pub fn render(f: &mut Frame, app: &mut App) {
                 .split(size);
 
             // Render header
-            render_header(f, chunks[0]);
-
-            // Split content into sidebar and editor
-            let content_chunks = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([
+            render_header(f, chunks[0], theme);
+
+            // Split content into sidebar and editor, and a column for the comments, links and
+            // contents panes when any of them are open
+            let side_panes = [app.show_comments, app.show_links, app.show_toc]
+                .into_iter()
+                .filter(|open| *open)
+                .count() as u32;
+            let constraints = if side_panes > 0 {
+                vec![
+                    Constraint::Percentage(25),  // Sidebar
+                    Constraint::Percentage(45),  // Editor
+                    Constraint::Percentage(30),  // Side panes
+                ]
+            } else {
+                vec![
                     Constraint::Percentage(25),  // Sidebar
                     Constraint::Percentage(75),  // Editor
-                ])
+                ]
+            };
+            let content_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(constraints)
                 .split(chunks[1]);
 
             // Render sidebar and editor
             render_sidebar(f, app, content_chunks[0]);
             render_editor(f, app, content_chunks[1]);
+            app.comments_area = None;
+            app.links_area = None;
+            app.toc_area = None;
+            if side_panes > 0 {
+                // Open panes share the column: contents, comments, then links
+                let panes = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints(vec![Constraint::Ratio(1, side_panes); side_panes as usize])
+                    .split(content_chunks[2]);
+                let mut panes = panes.iter();
+                if app.show_toc {
+                    if let Some(area) = panes.next() {
+                        toc::render_toc(f, app, *area);
+                    }
+                }
+                if app.show_comments {
+                    if let Some(area) = panes.next() {
+                        comments::render_comments(f, app, *area);
+                    }
+                }
+                if app.show_links {
+                    if let Some(area) = panes.next() {
+                        links::render_links(f, app, *area);
+                    }
+                }
+            }
 
             // Render footer
             render_footer(f, app, chunks[2]);
 
-            // Render modal on top of everything
+            // Render the finder and modals on top of everything
+            finder::render_finder(f, app);
+            search::render_search(f, app);
             render_modal(f, app);
+
+            // Images would be drawn over them, so they're hidden while anything is on top
+            let search_open = app.search_view.as_ref().is_some_and(|view| view.open);
+            if app.finder.is_some() || search_open || app.modal.is_open() {
+                app.image_layer.clear();
+            }
         }
     }
 }
 
 /// Render the header
-fn render_header(f: &mut Frame, area: Rect) {
+fn render_header(f: &mut Frame, area: Rect, theme: Theme) {
     let title = Paragraph::new(Line::from(vec![
-        Span::styled("Outline ", Style::default().fg(Color::Cyan)),
+        Span::styled("Outline ", Style::default().fg(theme.accent)),
         Span::styled(
             "TUI",
             Style::default()
-                .fg(Color::Yellow)
+                .fg(theme.highlight)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw(" - "),
         Span::styled(
             "Team Knowledge Base",
-            Style::default().fg(Color::Gray),
+            Style::default().fg(theme.muted),
         ),
     ]))
     .block(
         Block::default()
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Cyan)),
+            .border_style(Style::default().fg(theme.accent)),
     );
 
     f.render_widget(title, area);
@@ -94,30 +154,85 @@ fn render_header(f: &mut Frame, area: Rect) {
 
 /// Render the footer with help text and status
 fn render_footer(f: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme;
+
     let help_text = match app.focused_pane {
-        FocusedPane::Sidebar => {
-            "↑/↓: Navigate | Enter: Open | Tab: Switch pane | q: Quit"
-        }
-        FocusedPane::Editor => {
-            "Tab: Switch pane | e: Edit mode | Esc: View mode | ↑/↓: Scroll | q: Quit"
-        }
+        FocusedPane::Sidebar => key_hints(app, KeyContext::Sidebar, &[
+            (Action::MoveDown, "Navigate"),
+            (Action::Open, "Open"),
+            (Action::GoToDocument, "Go to"),
+            (Action::ToggleFocus, "Switch pane"),
+            (Action::ShowHelp, "Help"),
+            (Action::Quit, "Quit"),
+        ]),
+        FocusedPane::Editor => key_hints(app, KeyContext::View, &[
+            (Action::ToggleFocus, "Switch pane"),
+            (Action::EditDocument, "Edit mode"),
+            (Action::NextTab, "Next tab"),
+            (Action::EnterCommandMode, "Command"),
+            (Action::ToggleToc, "Contents"),
+            (Action::NextHeading, "Next heading"),
+            (Action::ToggleComments, "Comments"),
+            (Action::ToggleLinks, "Links"),
+            (Action::NextLink, "Pick link"),
+            (Action::Open, "Follow"),
+            (Action::HistoryBack, "Back"),
+            (Action::Quit, "Quit"),
+        ]),
+        FocusedPane::Comments => key_hints(app, KeyContext::Comments, &[
+            (Action::CreateComment, "New"),
+            (Action::ReplyToComment, "Reply"),
+            (Action::UpdateComment, "Edit"),
+            (Action::DeleteComment, "Delete"),
+            (Action::ResolveComment, "Resolve"),
+            (Action::Open, "Show in text"),
+            (Action::ToggleResolved, "Show resolved"),
+            (Action::Cancel, "Document"),
+        ]),
+        FocusedPane::Links => key_hints(app, KeyContext::Links, &[
+            (Action::MoveDown, "Select"),
+            (Action::Open, "Follow"),
+            (Action::HistoryBack, "Back"),
+            (Action::HistoryForward, "Forward"),
+            (Action::ToggleLinks, "Hide"),
+            (Action::Cancel, "Document"),
+        ]),
+        FocusedPane::Toc => key_hints(app, KeyContext::Toc, &[
+            (Action::MoveDown, "Select heading"),
+            (Action::Open, "Go to"),
+            (Action::ToggleExpand, "Fold"),
+            (Action::ToggleToc, "Hide"),
+            (Action::Cancel, "Document"),
+        ]),
     };
 
     let status_text = app
         .status_message
-        .as_ref()
-        .map(|s| s.as_str())
+        .as_deref()
         .unwrap_or("");
 
     let footer_text = if !status_text.is_empty() {
         format!("{} | {}", help_text, status_text)
     } else {
-        help_text.to_string()
+        help_text
     };
 
     let footer = Paragraph::new(footer_text)
         .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::Gray));
+        .style(Style::default().fg(theme.muted));
 
     f.render_widget(footer, area);
 }
+
+/// Footer hints with the keys currently bound to each action; unbound actions are left out
+fn key_hints(app: &App, context: KeyContext, hints: &[(Action, &str)]) -> String {
+    hints
+        .iter()
+        .filter_map(|(action, label)| {
+            app.keymap
+                .keys_for(context, action)
+                .map(|keys| format!("{}: {}", keys, label))
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
@@ -1,8 +1,10 @@
 use crate::app::App;
+use crate::keymap::KeyContext;
 use crate::modals::{ModalType, get_action_list};
+use crate::theme::Theme;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
     Frame,
@@ -10,6 +12,8 @@ use ratatui::{
 
 /// Render modal dialogs
 pub fn render_modal(f: &mut Frame, app: &App) {
+    let theme = app.theme;
+
     if !app.modal.is_open() {
         return;
     }
@@ -17,30 +21,35 @@ pub fn render_modal(f: &mut Frame, app: &App) {
     match &app.modal.modal_type {
         ModalType::None => {}
         ModalType::ActionMenu => render_action_menu(f, app),
-        ModalType::Help => render_help(f),
-        ModalType::CommandInput => render_command_input(f),
+        ModalType::Help { scroll } => render_help(f, app, *scroll),
+        ModalType::CommandInput { value } => render_command_input(f, value, theme),
         ModalType::TextInput { title, prompt, value, .. } => {
-            render_text_input(f, title, prompt, value);
+            render_text_input(f, title, prompt, value, theme);
         }
         ModalType::MultiInput { title, fields, current_field, .. } => {
-            render_multi_input(f, title, fields, *current_field);
+            render_multi_input(f, title, fields, *current_field, theme);
         }
         ModalType::Confirmation { title, message, .. } => {
-            render_confirmation(f, title, message);
+            render_confirmation(f, title, message, theme);
         }
         ModalType::List { title, items, state } => {
-            render_list(f, title, items, state);
+            render_list(f, title, items, state, theme);
         }
         ModalType::Message { title, message } => {
-            render_message(f, title, message);
+            render_message(f, title, message, theme);
+        }
+        ModalType::Attachment { id, name } => {
+            render_attachment(f, app, id, name);
         }
         ModalType::AuthSetup { selected } => {
-            render_auth_setup(f, *selected);
+            render_auth_setup(f, *selected, theme);
         }
     }
 }
 
 fn render_action_menu(f: &mut Frame, app: &App) {
+    let theme = app.theme;
+
     let area = centered_rect(60, 70, f.area());
 
     // Clear the area
@@ -52,8 +61,8 @@ fn render_action_menu(f: &mut Frame, app: &App) {
 
     for action in &actions {
         let category = action.category().to_string();
-        let entry = format!("{}", action.description());
-        categories.entry(category).or_insert_with(Vec::new).push(entry);
+        let entry = action.description().to_string();
+        categories.entry(category).or_default().push(entry);
     }
 
     // Build list items
@@ -61,7 +70,7 @@ fn render_action_menu(f: &mut Frame, app: &App) {
     for (category, items) in categories {
         list_items.push(ListItem::new(Line::from(Span::styled(
             format!("─── {} ───", category),
-            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
         ))));
         for item in items {
             list_items.push(ListItem::new(format!("  {}", item)));
@@ -73,95 +82,120 @@ fn render_action_menu(f: &mut Frame, app: &App) {
             Block::default()
                 .title(" Actions (↑/↓ navigate, Enter select, Esc close) ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow)),
+                .border_style(Style::default().fg(theme.highlight)),
         )
-        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD))
+        .highlight_style(Style::default().bg(theme.dim).add_modifier(Modifier::BOLD))
         .highlight_symbol(">> ");
 
     f.render_stateful_widget(list, area, &mut app.modal.menu_state.clone());
 }
 
-fn render_help(f: &mut Frame) {
+/// Render the help, listing the effective key bindings
+fn render_help(f: &mut Frame, app: &App, scroll: u16) {
+    let theme = app.theme;
+
     let area = centered_rect(80, 80, f.area());
 
     f.render_widget(Clear, area);
 
-    let help_text = vec![
+    let header = |title: &str| Line::from(Span::styled(title.to_string(), Style::default().fg(theme.accent)));
+    let entry = |keys: &str, description: &str| {
+        Line::from(vec![
+            Span::styled(format!("  {:<16}", keys), Style::default().fg(theme.text)),
+            Span::styled(format!("- {}", description), Style::default().fg(theme.muted)),
+        ])
+    };
+
+    let mut help_text = vec![
         Line::from(Span::styled("Outline TUI - Help", Style::default().add_modifier(Modifier::BOLD))),
         Line::from(""),
-        Line::from(Span::styled("Navigation", Style::default().fg(Color::Cyan))),
-        Line::from("  Tab          - Switch between sidebar and editor"),
-        Line::from("  ↑/↓ or j/k   - Navigate lists"),
-        Line::from("  PageUp/Down  - Jump by page"),
-        Line::from("  Home/End     - Jump to start/end"),
-        Line::from("  Enter        - Open selected document"),
-        Line::from("  r            - Refresh data"),
-        Line::from("  q            - Quit application"),
-        Line::from(""),
-        Line::from(Span::styled("Actions", Style::default().fg(Color::Cyan))),
-        Line::from("  m or :       - Open action menu"),
-        Line::from("  h or ?       - Show this help"),
-        Line::from("  Esc          - Close modal/menu"),
+    ];
+    for context in KeyContext::ALL {
+        help_text.push(header(context.title()));
+        for (keys, action) in app.keymap.help(context) {
+            help_text.push(entry(&keys, action.description()));
+        }
+        help_text.push(Line::from(""));
+    }
+
+    // Keys of the panes and modes the keymap doesn't cover
+    help_text.extend([
+        header("Edit Mode (insert and visual)"),
+        entry("Esc", "Back to normal mode"),
+        entry("y", "Copy the selection (visual)"),
+        entry("d, x", "Cut the selection (visual)"),
         Line::from(""),
-        Line::from(Span::styled("Document Actions", Style::default().fg(Color::Cyan))),
-        Line::from("  c            - Create document (as child of selected)"),
-        Line::from("  e            - Edit document (in vim mode)"),
-        Line::from("  u            - Update document"),
-        Line::from("  d            - Delete document"),
-        Line::from("  /            - Search documents"),
-        Line::from("  a            - Archive document"),
-        Line::from("  s            - Star document"),
-        Line::from("  x            - Export document"),
+        header("Writing a Comment"),
+        entry("Enter", "Send"),
+        entry("Alt+Enter", "New line"),
+        entry("Esc", "Cancel"),
         Line::from(""),
-        Line::from(Span::styled("Vim Editing (in Edit mode)", Style::default().fg(Color::Cyan))),
-        Line::from("  i/a          - Insert mode"),
-        Line::from("  v            - Visual mode"),
-        Line::from("  hjkl         - Movement"),
-        Line::from("  u            - Undo | Ctrl+r - Redo"),
-        Line::from("  Esc          - Save & exit to view"),
+        header("Command Line (:)"),
+        entry(":ls, :b N", "List buffers, switch to buffer N"),
+        entry(":bn, :bp, :bd", "Next, previous, close buffer"),
+        entry(":sp, :vs, :only", "Split, vertical split, close the split"),
+        entry(":w, :q, :q!", "Save, quit, quit without saving"),
         Line::from(""),
-        Line::from(Span::styled("Press Esc to close", Style::default().fg(Color::Gray))),
-    ];
+        Line::from(Span::styled(
+            "Change keys in the [keymap] section of ~/.outline-cli/config.toml",
+            Style::default().fg(theme.dim),
+        )),
+        Line::from(Span::styled("j/k to scroll, Esc to close", Style::default().fg(theme.dim))),
+    ]);
+
+    let visible = area.height.saturating_sub(2);
+    let scroll = scroll.min((help_text.len() as u16).saturating_sub(visible));
 
     let paragraph = Paragraph::new(help_text)
         .block(
             Block::default()
                 .title(" Help ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow)),
+                .border_style(Style::default().fg(theme.highlight)),
         )
-        .wrap(Wrap { trim: true });
+        .scroll((scroll, 0));
 
     f.render_widget(paragraph, area);
 }
 
-fn render_command_input(f: &mut Frame) {
-    let area = centered_rect(60, 10, f.area());
+/// The command line, drawn over the footer like vim's
+fn render_command_input(f: &mut Frame, value: &str, theme: Theme) {
+    let size = f.area();
+    let area = Rect {
+        x: size.x,
+        y: size.y + size.height.saturating_sub(3),
+        width: size.width,
+        height: size.height.min(3),
+    };
 
     f.render_widget(Clear, area);
 
-    let paragraph = Paragraph::new("Command input not yet implemented")
-        .block(
-            Block::default()
-                .title(" Command ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow)),
-        );
+    let paragraph = Paragraph::new(Line::from(vec![
+        Span::styled(":", Style::default().fg(theme.highlight)),
+        Span::raw(value),
+        Span::styled("█", Style::default().fg(theme.highlight)),
+    ]))
+    .block(
+        Block::default()
+            .title(" Command (:ls :b N :bn :bp :bd :sp :vs :only :w :q) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.highlight)),
+    );
 
     f.render_widget(paragraph, area);
 }
 
-fn render_text_input(f: &mut Frame, title: &str, prompt: &str, value: &str) {
+fn render_text_input(f: &mut Frame, title: &str, prompt: &str, value: &str, theme: Theme) {
     let area = centered_rect(60, 20, f.area());
 
     f.render_widget(Clear, area);
 
     let text = vec![
-        Line::from(Span::styled(prompt, Style::default().fg(Color::Gray))),
+        Line::from(Span::styled(prompt, Style::default().fg(theme.muted))),
         Line::from(""),
         Line::from(Span::raw(value)),
         Line::from(""),
-        Line::from(Span::styled("Press Enter to submit, Esc to cancel", Style::default().fg(Color::Gray))),
+        Line::from(Span::styled("Press Enter to submit, Esc to cancel", Style::default().fg(theme.muted))),
     ];
 
     let paragraph = Paragraph::new(text)
@@ -169,13 +203,13 @@ fn render_text_input(f: &mut Frame, title: &str, prompt: &str, value: &str) {
             Block::default()
                 .title(format!(" {} ", title))
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow)),
+                .border_style(Style::default().fg(theme.highlight)),
         );
 
     f.render_widget(paragraph, area);
 }
 
-fn render_multi_input(f: &mut Frame, title: &str, fields: &[crate::modals::InputField], current_field: usize) {
+fn render_multi_input(f: &mut Frame, title: &str, fields: &[crate::modals::InputField], current_field: usize, theme: Theme) {
     let area = centered_rect(70, 30 + fields.len() as u16 * 3, f.area());
 
     f.render_widget(Clear, area);
@@ -184,9 +218,9 @@ fn render_multi_input(f: &mut Frame, title: &str, fields: &[crate::modals::Input
     for (i, field) in fields.iter().enumerate() {
         let is_current = i == current_field;
         let style = if is_current {
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
         } else {
-            Style::default().fg(Color::Gray)
+            Style::default().fg(theme.muted)
         };
 
         lines.push(Line::from(Span::styled(&field.label, style)));
@@ -201,7 +235,7 @@ fn render_multi_input(f: &mut Frame, title: &str, fields: &[crate::modals::Input
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
         "Tab: Next field | Shift+Tab: Previous | Enter: Submit | Esc: Cancel",
-        Style::default().fg(Color::Gray),
+        Style::default().fg(theme.muted),
     )));
 
     let paragraph = Paragraph::new(lines)
@@ -209,13 +243,13 @@ fn render_multi_input(f: &mut Frame, title: &str, fields: &[crate::modals::Input
             Block::default()
                 .title(format!(" {} ", title))
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow)),
+                .border_style(Style::default().fg(theme.highlight)),
         );
 
     f.render_widget(paragraph, area);
 }
 
-fn render_confirmation(f: &mut Frame, title: &str, message: &str) {
+fn render_confirmation(f: &mut Frame, title: &str, message: &str, theme: Theme) {
     let area = centered_rect(50, 15, f.area());
 
     f.render_widget(Clear, area);
@@ -223,7 +257,7 @@ fn render_confirmation(f: &mut Frame, title: &str, message: &str) {
     let text = vec![
         Line::from(Span::raw(message)),
         Line::from(""),
-        Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(Color::Gray))),
+        Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(theme.muted))),
     ];
 
     let paragraph = Paragraph::new(text)
@@ -231,14 +265,14 @@ fn render_confirmation(f: &mut Frame, title: &str, message: &str) {
             Block::default()
                 .title(format!(" {} ", title))
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Red)),
+                .border_style(Style::default().fg(theme.error)),
         )
         .alignment(Alignment::Center);
 
     f.render_widget(paragraph, area);
 }
 
-fn render_list(f: &mut Frame, title: &str, items: &[String], state: &ratatui::widgets::ListState) {
+fn render_list(f: &mut Frame, title: &str, items: &[String], state: &ratatui::widgets::ListState, theme: Theme) {
     let area = centered_rect(70, 70, f.area());
 
     f.render_widget(Clear, area);
@@ -253,15 +287,15 @@ fn render_list(f: &mut Frame, title: &str, items: &[String], state: &ratatui::wi
             Block::default()
                 .title(format!(" {} ", title))
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow)),
+                .border_style(Style::default().fg(theme.highlight)),
         )
-        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD))
+        .highlight_style(Style::default().bg(theme.dim).add_modifier(Modifier::BOLD))
         .highlight_symbol(">> ");
 
     f.render_stateful_widget(list, area, &mut state.clone());
 }
 
-fn render_message(f: &mut Frame, title: &str, message: &str) {
+fn render_message(f: &mut Frame, title: &str, message: &str, theme: Theme) {
     let area = centered_rect(60, 30, f.area());
 
     f.render_widget(Clear, area);
@@ -271,28 +305,60 @@ fn render_message(f: &mut Frame, title: &str, message: &str) {
             Block::default()
                 .title(format!(" {} ", title))
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
+                .border_style(Style::default().fg(theme.accent)),
         )
         .wrap(Wrap { trim: true });
 
     f.render_widget(paragraph, area);
 }
 
-fn render_auth_setup(f: &mut Frame, selected: usize) {
+/// Render the choice of opening or saving an attachment
+fn render_attachment(f: &mut Frame, app: &App, id: &str, name: &str) {
+    let theme = app.theme;
+    let area = centered_rect(50, 20, f.area());
+
+    f.render_widget(Clear, area);
+
+    let details = match app.attachments.get(id) {
+        Some(attachment) => format!("{}, {} bytes", attachment.content_type, attachment.data.len()),
+        None => "Downloaded when opened or saved".to_string(),
+    };
+    let text = vec![
+        Line::from(Span::styled(name, Style::default().fg(theme.text).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(details, Style::default().fg(theme.muted))),
+        Line::from(""),
+        Line::from(Span::styled(
+            "o: Open  s: Save to Downloads  Esc: Cancel",
+            Style::default().fg(theme.muted),
+        )),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(" Attachment ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.accent)),
+        )
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, area);
+}
+
+fn render_auth_setup(f: &mut Frame, selected: usize, theme: Theme) {
     let area = centered_rect(70, 50, f.area());
 
     f.render_widget(Clear, area);
 
-    let options = vec![
-        ("1", "OAuth2 (Recommended)", "Automatic refresh, more secure"),
+    let options = [("1", "OAuth2 (Recommended)", "Automatic refresh, more secure"),
         ("2", "API Token", "Simple setup with manual token"),
-        ("Q", "Exit", "Configure authentication via CLI"),
-    ];
+        ("Q", "Exit", "Configure authentication via CLI")];
 
     let mut lines = vec![
         Line::from(Span::styled(
             "Authentication Required",
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from("No authentication configured. Choose a method:"),
@@ -301,9 +367,9 @@ fn render_auth_setup(f: &mut Frame, selected: usize) {
 
     for (i, (key, title, desc)) in options.iter().enumerate() {
         let style = if i == selected {
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
         } else {
-            Style::default().fg(Color::White)
+            Style::default().fg(theme.text)
         };
 
         let marker = if i == selected { ">> " } else { "   " };
@@ -316,7 +382,7 @@ fn render_auth_setup(f: &mut Frame, selected: usize) {
         ]));
         lines.push(Line::from(Span::styled(
             format!("     {}", desc),
-            Style::default().fg(Color::Gray),
+            Style::default().fg(theme.muted),
         )));
         lines.push(Line::from(""));
     }
@@ -324,7 +390,7 @@ fn render_auth_setup(f: &mut Frame, selected: usize) {
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
         "↑/↓: Navigate | Enter/1/2: Select | Q: Quit",
-        Style::default().fg(Color::DarkGray),
+        Style::default().fg(theme.dim),
     )));
 
     let paragraph = Paragraph::new(lines)
@@ -332,7 +398,7 @@ fn render_auth_setup(f: &mut Frame, selected: usize) {
             Block::default()
                 .title(" Outline TUI - Setup ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow)),
+                .border_style(Style::default().fg(theme.highlight)),
         )
         .alignment(Alignment::Left);
 
@@ -340,7 +406,7 @@ fn render_auth_setup(f: &mut Frame, selected: usize) {
 }
 
 /// Helper function to create a centered rect
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+pub(super) fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
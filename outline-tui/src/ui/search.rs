@@ -0,0 +1,220 @@
+use crate::actions::Action;
+use crate::app::App;
+use crate::keymap::KeyContext;
+use crate::search::{context_parts, SearchEntry, SearchFocus, SearchStatus, SearchView};
+use crate::theme::Theme;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::modal::centered_rect;
+
+/// Render the search view: query, filters and results
+pub fn render_search(f: &mut Frame, app: &App) {
+    let Some(view) = app.search_view.as_ref().filter(|view| view.open) else {
+        return;
+    };
+
+    let area = centered_rect(90, 90, f.area());
+    f.render_widget(Clear, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Query
+            Constraint::Length(3), // Filters
+            Constraint::Min(0),    // Results
+        ])
+        .split(area);
+
+    render_query(f, app, view, rows[0]);
+    render_filters(f, app, view, rows[1]);
+    render_results(f, app, view, rows[2]);
+}
+
+fn render_query(f: &mut Frame, app: &App, view: &SearchView, area: Rect) {
+    let theme = app.theme;
+
+    let focused = view.focus == SearchFocus::Query;
+    let mut spans = vec![
+        Span::styled("Search: ", Style::default().fg(theme.accent)),
+        Span::raw(view.input.as_str()),
+    ];
+    if focused {
+        spans.push(Span::styled("█", Style::default().fg(theme.accent)));
+    }
+
+    let title = if focused && !app.ui_state.search_history.is_empty() {
+        " Search (Enter: Search | ↑/↓: History | Tab: Results | Esc: Close) ".to_string()
+    } else if focused {
+        " Search (Enter: Search | Tab: Results | Esc: Close) ".to_string()
+    } else {
+        format!(" Search ({}) ", super::key_hints(app, KeyContext::Search, &[(Action::FocusQuery, "Edit query")]))
+    };
+
+    let input = Paragraph::new(Line::from(spans)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(theme.border(focused))),
+    );
+    f.render_widget(input, area);
+}
+
+fn render_filters(f: &mut Frame, app: &App, view: &SearchView, area: Rect) {
+    let theme = app.theme;
+    let filters = &view.filters;
+    let collection = filters
+        .collection
+        .as_ref()
+        .map_or("All", |(_, name)| name.as_str());
+    let author = filters.author.as_ref().map_or("Anyone", |user| user.name.as_str());
+    let on_off = |on: bool| if on { "on" } else { "off" };
+
+    let filter = |action: Action, label: &'static str, value: String, active: bool| {
+        let key = app.keymap.keys_for(KeyContext::Search, &action).map_or(String::new(), |keys| format!("[{}] ", keys));
+        vec![
+            Span::styled(key, Style::default().fg(theme.dim)),
+            Span::raw(format!("{}: ", label)),
+            Span::styled(
+                value,
+                if active {
+                    Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme.muted)
+                },
+            ),
+            Span::raw("   "),
+        ]
+    };
+
+    let mut spans = Vec::new();
+    spans.extend(filter(Action::FilterCollection, "Collection", collection.to_string(), filters.collection.is_some()));
+    spans.extend(filter(Action::FilterAuthor, "Author", author.to_string(), filters.author.is_some()));
+    spans.extend(filter(Action::FilterDate, "Date", filters.date_label().to_string(), filters.date_filter().is_some()));
+    spans.extend(filter(Action::ToggleDrafts, "Drafts", on_off(filters.include_drafts).to_string(), filters.include_drafts));
+    spans.extend(filter(Action::ToggleArchived, "Archived", on_off(filters.include_archived).to_string(), filters.include_archived));
+    if let Some(keys) = app.keymap.keys_for(KeyContext::Search, &Action::ClearFilters).filter(|_| !filters.is_default()) {
+        spans.push(Span::styled(format!("[{}] Clear", keys), Style::default().fg(theme.dim)));
+    }
+
+    let bar = Paragraph::new(Line::from(spans)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Filters ")
+            .border_style(Style::default().fg(theme.muted)),
+    );
+    f.render_widget(bar, area);
+}
+
+fn render_results(f: &mut Frame, app: &App, view: &SearchView, area: Rect) {
+    let theme = app.theme;
+
+    let focused = view.focus == SearchFocus::Results;
+    let width = area.width.saturating_sub(4) as usize;
+
+    let mut items: Vec<ListItem> = view
+        .results
+        .iter()
+        .map(|entry| ListItem::new(result_lines(app, entry, width)))
+        .collect();
+
+    let status = match &view.status {
+        SearchStatus::Idle => Some(Span::styled("  Type a query and press Enter", Style::default().fg(theme.dim))),
+        SearchStatus::Loading => Some(Span::styled("  Searching…", Style::default().fg(theme.highlight))),
+        SearchStatus::Complete if view.results.is_empty() => {
+            Some(Span::styled("  No results", Style::default().fg(theme.dim)))
+        }
+        SearchStatus::Complete | SearchStatus::More => None,
+        SearchStatus::Failed(e) => Some(Span::styled(format!("  Search failed: {}", e), Style::default().fg(theme.error))),
+    };
+    if let Some(status) = status {
+        items.push(ListItem::new(Line::from(status)));
+    }
+
+    let count = match view.status {
+        SearchStatus::More | SearchStatus::Loading if !view.results.is_empty() => format!("{}+ results", view.results.len()),
+        _ if view.query.is_empty() => "Results".to_string(),
+        _ => format!("{} results", view.results.len()),
+    };
+    let title = if focused {
+        let keys = super::key_hints(app, KeyContext::Search, &[
+            (Action::Open, "Open"),
+            (Action::MoveDown, "Move"),
+            (Action::Cancel, "Close"),
+        ]);
+        format!(" {} for \"{}\" ({}) ", count, view.query, keys)
+    } else {
+        format!(" {} ", count)
+    };
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(theme.border(focused))),
+        )
+        .highlight_style(Style::default().bg(if focused { theme.selection } else { theme.dim }))
+        .highlight_symbol(if focused { "▶ " } else { "  " });
+
+    let mut state = ListState::default();
+    if !view.results.is_empty() {
+        state.select(Some(view.selected));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+/// A result: title and author, breadcrumb, then the context around the match
+fn result_lines(app: &App, entry: &SearchEntry, width: usize) -> Text<'static> {
+    let theme = app.theme;
+
+    let mut title = vec![
+        Span::raw(entry.icon.clone()),
+        Span::raw("  "),
+        Span::styled(entry.title.clone(), Style::default().fg(theme.text).add_modifier(Modifier::BOLD)),
+    ];
+    let updated = entry.updated_at.get(..10).unwrap_or(&entry.updated_at);
+    let byline = match &entry.author {
+        Some(author) => format!("  {} · {}", author.name, updated),
+        None => format!("  {}", updated),
+    };
+    title.push(Span::styled(byline, Style::default().fg(theme.dim)));
+
+    let breadcrumb = entry
+        .collection_id
+        .as_deref()
+        .and_then(|collection_id| app.sidebar_tree.breadcrumb(collection_id, &entry.id))
+        .unwrap_or_default();
+
+    Text::from(vec![
+        Line::from(title),
+        Line::from(Span::styled(format!("    {}", breadcrumb), Style::default().fg(theme.dim))),
+        context_line(&entry.context, width, theme),
+        Line::from(""),
+    ])
+}
+
+/// Search context on one line, with the matched words highlighted
+pub(super) fn context_line(context: &str, width: usize, theme: Theme) -> Line<'static> {
+    let mut spans = vec![Span::raw("    ")];
+    let mut remaining = width.saturating_sub(4);
+    for (text, highlighted) in context_parts(context) {
+        if remaining == 0 {
+            break;
+        }
+        let text: String = text.replace('\n', " ").chars().take(remaining).collect();
+        remaining -= text.chars().count();
+        let style = if highlighted {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.muted)
+        };
+        spans.push(Span::styled(text, style));
+    }
+    Line::from(spans)
+}
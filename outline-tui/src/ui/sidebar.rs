@@ -1,7 +1,8 @@
-use crate::app::{App, FocusedPane};
+use crate::app::{App, FocusedPane, SidebarItem};
+use crate::tree::Documents;
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem},
     Frame,
@@ -9,20 +10,22 @@ use ratatui::{
 
 /// Render the sidebar with collections and documents
 pub fn render_sidebar(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+
     // Store the sidebar area for mouse click detection
     app.sidebar_area = Some(area);
 
     let is_focused = app.focused_pane == FocusedPane::Sidebar;
 
     // Build list items from sidebar items with tree indicators
-    let items: Vec<ListItem> = if app.is_loading && app.sidebar_items.is_empty() {
+    let items: Vec<ListItem> = if app.sidebar_tree.loading_collections && app.sidebar_items.is_empty() {
         // Show loading indicator when no items yet
         vec![
             ListItem::new(Line::from("")),
-            ListItem::new(Line::from(Span::styled("Loading data...", Style::default().fg(Color::Yellow)))),
+            ListItem::new(Line::from(Span::styled("Loading data...", Style::default().fg(theme.highlight)))),
             ListItem::new(Line::from("")),
-            ListItem::new(Line::from(Span::styled("Fetching collections and documents", Style::default().fg(Color::Gray)))),
-            ListItem::new(Line::from(Span::styled("This may take a moment...", Style::default().fg(Color::DarkGray)))),
+            ListItem::new(Line::from(Span::styled("Fetching collections", Style::default().fg(theme.muted)))),
+            ListItem::new(Line::from(Span::styled("Documents load when a collection is expanded", Style::default().fg(theme.dim)))),
         ]
     } else {
         app.sidebar_items
@@ -32,50 +35,71 @@ pub fn render_sidebar(f: &mut Frame, app: &mut App, area: Rect) {
             let icon = item.icon();
             let title = item.title();
 
+            // Expansion marker for collections and documents with children
+            let marker = if !item.is_expandable() {
+                "  "
+            } else if app.sidebar_tree.is_expanded(item.id()) {
+                "▾ "
+            } else {
+                "▸ "
+            };
+
             // Build display line with simple spacing
-            let line = if indent_level == 0 {
-                // Collections: icon + space + title
-                Line::from(vec![
+            let mut spans = if indent_level == 0 {
+                // Collections: marker + icon + space + title
+                vec![
+                    Span::styled(marker, Style::default().fg(theme.dim)),
                     Span::raw(icon),
                     Span::raw("  "),
                     Span::raw(title),
-                ])
+                ]
             } else if indent_level == 1 {
-                // Root documents: indent + icon + space + title
-                Line::from(vec![
+                // Root documents: indent + marker + icon + space + title
+                vec![
                     Span::raw("  "),
+                    Span::styled(marker, Style::default().fg(theme.dim)),
                     Span::raw(icon),
                     Span::raw("  "),
                     Span::raw(title),
-                ])
+                ]
             } else {
-                // Child documents: indent + tree + icon + space + title
+                // Child documents: indent + tree + marker + icon + space + title
                 let base_indent = "  ".repeat(indent_level - 1);
-                Line::from(vec![
+                vec![
                     Span::raw(base_indent),
-                    Span::styled("└─ ", Style::default().fg(Color::DarkGray)),
+                    Span::styled("└─", Style::default().fg(theme.dim)),
+                    Span::styled(marker, Style::default().fg(theme.dim)),
                     Span::raw(icon),
                     Span::raw("  "),
                     Span::raw(title),
-                ])
+                ]
             };
 
-            ListItem::new(line)
+            // Show the load state of a collection's documents
+            if let SidebarItem::Collection(collection) = item {
+                match app.sidebar_tree.collection(&collection.id).map(|node| &node.documents) {
+                    Some(Documents::Loading) => {
+                        spans.push(Span::styled(" …", Style::default().fg(theme.highlight)));
+                    }
+                    Some(Documents::Failed(e)) => {
+                        spans.push(Span::styled(format!(" (failed: {})", e), Style::default().fg(theme.error)));
+                    }
+                    _ => {}
+                }
+            }
+
+            ListItem::new(Line::from(spans))
             })
             .collect()
     };
 
     // Determine border color based on focus
-    let border_color = if is_focused {
-        Color::Cyan
-    } else {
-        Color::Gray
-    };
+    let border_color = theme.border(is_focused);
 
     let title = if app.is_loading {
-        " Collections & Documents (Loading...) "
+        " Collections & Documents (Loading...) ".to_string()
     } else {
-        " Collections & Documents "
+        format!(" Collections & Documents ({} loaded) ", app.sidebar_tree.document_count())
     };
 
     // Create the list widget
@@ -88,7 +112,7 @@ pub fn render_sidebar(f: &mut Frame, app: &mut App, area: Rect) {
         )
         .highlight_style(
             Style::default()
-                .bg(if is_focused { Color::Blue } else { Color::DarkGray })
+                .bg(if is_focused { theme.selection } else { theme.dim })
                 .add_modifier(Modifier::BOLD),
         )
         .highlight_symbol(if is_focused { "▶ " } else { "  " });
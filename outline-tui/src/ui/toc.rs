@@ -0,0 +1,87 @@
+use crate::app::{App, FocusedPane};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+/// Render the table of contents pane: the document's headings, foldable like the sidebar
+pub fn render_toc(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+
+    app.toc_area = Some(area);
+
+    let is_focused = app.focused_pane == FocusedPane::Toc;
+    let border_color = theme.border(is_focused);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Contents ({}) ", app.toc.entries.len()))
+        .border_style(Style::default().fg(border_color));
+    let block = if is_focused {
+        block.title_bottom(" Enter:Go Space:Fold ")
+    } else {
+        block
+    };
+
+    if app.toc.entries.is_empty() {
+        let message = if app.current_document.is_none() {
+            "Open a document to see its contents."
+        } else {
+            "This document has no headings."
+        };
+        let empty = Paragraph::new(Span::styled(message, Style::default().fg(theme.dim)))
+            .block(block)
+            .wrap(Wrap { trim: false });
+        f.render_widget(empty, area);
+        return;
+    }
+
+    let current = app.current_section();
+    let visible = app.toc.visible();
+    let mut selected = None;
+    let items: Vec<ListItem> = visible
+        .iter()
+        .enumerate()
+        .map(|(row, &(index, depth))| {
+            if index == app.toc.selected {
+                selected = Some(row);
+            }
+            let entry = &app.toc.entries[index];
+            let marker = if !app.toc.has_children(index) {
+                "  "
+            } else if app.toc.collapsed.contains(&index) {
+                "▸ "
+            } else {
+                "▾ "
+            };
+
+            // The section at the top of the view, or the folded heading containing it
+            let is_current = current.is_some_and(|c| app.toc.visible_entry(c) == index);
+            let style = if is_current {
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+            } else if entry.level == 1 {
+                Style::default().fg(theme.text).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.muted)
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::raw("  ".repeat(depth)),
+                Span::styled(marker, Style::default().fg(theme.dim)),
+                Span::styled(entry.title.clone(), style),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(if is_focused { theme.selection } else { theme.dim }))
+        .highlight_symbol(if is_focused { "▶ " } else { "  " });
+
+    let mut state = ListState::default();
+    state.select(selected);
+    f.render_stateful_widget(list, area, &mut state);
+}
@@ -0,0 +1,79 @@
+use outline_api::markdown::{self, LinkTarget};
+use outline_api::{OutlineClient, UserInfoRequest};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Names of users mentioned in documents, looked up in the background
+///
+/// Mentions show the name written into the text until the lookup finishes, and keep
+/// showing it if the user can't be found.
+pub struct UserNames {
+    client: Option<Arc<OutlineClient>>,
+    names: HashMap<String, String>,
+    /// Users looked up already or being looked up
+    requested: HashSet<String>,
+    tx: mpsc::UnboundedSender<(String, Option<String>)>,
+    rx: mpsc::UnboundedReceiver<(String, Option<String>)>,
+    /// Hash of the text mentions were last collected from
+    parsed: u64,
+}
+
+impl UserNames {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            client: None,
+            names: HashMap::new(),
+            requested: HashSet::new(),
+            tx,
+            rx,
+            parsed: 0,
+        }
+    }
+
+    /// Use this client for lookups from now on
+    pub fn set_client(&mut self, client: Arc<OutlineClient>) {
+        self.client = Some(client);
+    }
+
+    /// Names found so far, by user ID
+    pub fn names(&self) -> &HashMap<String, String> {
+        &self.names
+    }
+
+    /// Look up users mentioned in the text if it changed, and apply finished lookups
+    /// (call this regularly in event loop)
+    pub fn tick(&mut self, text: &str) {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        let hash = hasher.finish();
+        if hash != self.parsed {
+            if let Some(client) = &self.client {
+                self.parsed = hash;
+                for link in markdown::links(text) {
+                    let LinkTarget::User(id) = link.target("") else {
+                        continue;
+                    };
+                    if self.requested.insert(id.clone()) {
+                        let client = client.clone();
+                        let tx = self.tx.clone();
+                        tokio::spawn(async move {
+                            let request = UserInfoRequest::for_user(id.clone());
+                            let name = client.get_user(request).await.ok().map(|user| user.name);
+                            let _ = tx.send((id, name));
+                        });
+                    }
+                }
+            }
+        }
+
+        while let Ok((id, name)) = self.rx.try_recv() {
+            if let Some(name) = name {
+                self.names.insert(id, name);
+            }
+        }
+    }
+}